@@ -30,7 +30,7 @@ fn apply_position_deltas(app: &mut AnimateApp, deltas: &[(uuid::Uuid, [f32; 2])]
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Align");
 
     for layer in &mut app.project.layers {
         let has_match = tween::resolve_frame(layer, app.current_frame)
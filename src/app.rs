@@ -41,6 +41,7 @@ pub enum PropertiesTab {
     #[default]
     Properties,
     Library,
+    History,
 }
 
 pub struct EasingEditorState {
@@ -76,11 +77,20 @@ pub struct AnimateApp {
     pub snap_to_guides: bool,
     pub grid_size: f32,
     pub easing_editor: Option<EasingEditorState>,
+    pub graph_editor: Option<crate::graph_editor::GraphEditorState>,
     pub editing_symbol: Option<uuid::Uuid>,
     pub properties_tab: PropertiesTab,
     pub ik_drag_bone: Option<(usize, usize)>,
     pub expanded_property_layers: HashSet<uuid::Uuid>,
     #[cfg(not(target_arch = "wasm32"))]
+    pub export_current_scene_only: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub video_export: Option<crate::export::VideoExportJob>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub import_warnings: Vec<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub lip_sync: Option<crate::lipsync::LipSyncState>,
+    #[cfg(not(target_arch = "wasm32"))]
     pub image_textures: HashMap<uuid::Uuid, egui::TextureHandle>,
     #[cfg(target_arch = "wasm32")]
     pub pending_project_load: std::rc::Rc<std::cell::RefCell<Option<Vec<u8>>>>,
@@ -112,11 +122,20 @@ impl Default for AnimateApp {
             snap_to_guides: false,
             grid_size: 10.0,
             easing_editor: None,
+            graph_editor: None,
             editing_symbol: None,
             properties_tab: PropertiesTab::default(),
             ik_drag_bone: None,
             expanded_property_layers: HashSet::new(),
             #[cfg(not(target_arch = "wasm32"))]
+            export_current_scene_only: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            video_export: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            import_warnings: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            lip_sync: None,
+            #[cfg(not(target_arch = "wasm32"))]
             image_textures: HashMap::new(),
             #[cfg(target_arch = "wasm32")]
             pending_project_load: std::rc::Rc::new(std::cell::RefCell::new(None)),
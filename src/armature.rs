@@ -2,13 +2,38 @@ use nightshade::prelude::*;
 
 use crate::app::AnimateApp;
 use crate::canvas::CanvasView;
-use crate::project::{Armature, Bone};
+use crate::hierarchy;
+use crate::project::{
+    AnimObject, Armature, Bone, BonePose, BoneWeight, Layer, PropertyKey, Shape, TweenType,
+};
+use crate::tween;
+
+const MAX_BONE_INFLUENCES: usize = 4;
+const MIN_BONE_WEIGHT: f32 = 0.01;
+
+#[derive(Clone, Copy)]
+pub struct BoneTransform {
+    pub start: [f32; 2],
+    pub rotation: f32,
+    pub length: f32,
+    pub scale: [f32; 2],
+}
+
+impl BoneTransform {
+    pub fn end(&self) -> [f32; 2] {
+        [
+            self.start[0] + self.length * self.rotation.cos(),
+            self.start[1] + self.length * self.rotation.sin(),
+        ]
+    }
+}
 
 pub fn draw_bone_overlay(app: &AnimateApp, view: &CanvasView, painter: &egui::Painter) {
     for armature in &app.project.armatures {
         for bone in &armature.bones {
-            let resolved_rotation = resolve_bone_rotation(app, bone);
-            let (start, end) = bone_endpoints(bone, armature, resolved_rotation);
+            let transform = compute_bone_transform(bone, armature, Some(app.current_frame));
+            let start = transform.start;
+            let end = transform.end();
             let screen_start = view.canvas_to_screen(egui::pos2(start[0], start[1]));
             let screen_end = view.canvas_to_screen(egui::pos2(end[0], end[1]));
 
@@ -52,38 +77,239 @@ fn draw_bone_shape(painter: &egui::Painter, start: egui::Pos2, end: egui::Pos2,
     painter.circle_filled(end, joint_radius, egui::Color32::from_rgb(80, 100, 180));
 }
 
-fn bone_endpoints(
+pub fn compute_bone_transform(
     bone: &Bone,
     armature: &Armature,
-    resolved_rotation: f32,
-) -> ([f32; 2], [f32; 2]) {
-    let start = compute_bone_world_position(bone, armature);
-    let end = [
-        start[0] + bone.length * resolved_rotation.cos(),
-        start[1] + bone.length * resolved_rotation.sin(),
-    ];
-    (start, end)
+    frame: Option<u32>,
+) -> BoneTransform {
+    let pose = frame
+        .map(|frame| tween::resolve_bone_pose(bone, frame))
+        .unwrap_or_default();
+
+    let (origin, inherited_rotation) = match bone
+        .parent_bone_id
+        .and_then(|parent_id| armature.bones.iter().find(|b| b.id == parent_id))
+    {
+        Some(parent) => {
+            let parent_transform = compute_bone_transform(parent, armature, frame);
+            (
+                parent_transform.end(),
+                parent_transform.rotation - parent.rotation,
+            )
+        }
+        None => (bone.position, 0.0),
+    };
+
+    BoneTransform {
+        start: [
+            origin[0] + pose.translation[0],
+            origin[1] + pose.translation[1],
+        ],
+        rotation: bone.rotation + inherited_rotation + pose.rotation,
+        length: bone.length * pose.scale[0],
+        scale: pose.scale,
+    }
 }
 
-fn compute_bone_world_position(bone: &Bone, armature: &Armature) -> [f32; 2] {
-    if let Some(parent_id) = bone.parent_bone_id {
-        if let Some(parent) = armature.bones.iter().find(|b| b.id == parent_id) {
-            let parent_pos = compute_bone_world_position(parent, armature);
-            let parent_rot = parent.rotation;
-            [
-                parent_pos[0] + parent.length * parent_rot.cos(),
-                parent_pos[1] + parent.length * parent_rot.sin(),
-            ]
-        } else {
-            bone.position
+pub fn apply_bone_bindings(objects: &mut [AnimObject], armatures: &[Armature], frame: u32) {
+    if armatures.is_empty() {
+        return;
+    }
+
+    let transforms: Vec<(uuid::Uuid, BoneTransform, BoneTransform)> = armatures
+        .iter()
+        .flat_map(|armature| {
+            armature.bones.iter().map(move |bone| {
+                (
+                    bone.id,
+                    compute_bone_transform(bone, armature, None),
+                    compute_bone_transform(bone, armature, Some(frame)),
+                )
+            })
+        })
+        .collect();
+
+    for object in objects.iter_mut() {
+        skin_path_points(object, &transforms);
+    }
+
+    for armature in armatures {
+        for bone in &armature.bones {
+            if bone.bound_object_ids.is_empty() {
+                continue;
+            }
+            let Some((_, rest, posed)) = transforms.iter().find(|(id, _, _)| *id == bone.id) else {
+                continue;
+            };
+            for object in objects.iter_mut() {
+                if bone.bound_object_ids.contains(&object.id) {
+                    apply_bone_transform(object, rest, posed);
+                }
+            }
         }
-    } else {
-        bone.position
     }
 }
 
-fn resolve_bone_rotation(_app: &AnimateApp, bone: &Bone) -> f32 {
-    bone.rotation
+fn apply_bone_transform(object: &mut AnimObject, rest: &BoneTransform, posed: &BoneTransform) {
+    object.position = transform_point_by_bone(object.position, rest, posed);
+    object.rotation += posed.rotation - rest.rotation;
+    object.scale[0] *= posed.scale[0];
+    object.scale[1] *= posed.scale[1];
+}
+
+fn transform_point_by_bone(
+    point: [f32; 2],
+    rest: &BoneTransform,
+    posed: &BoneTransform,
+) -> [f32; 2] {
+    let dx = point[0] - rest.start[0];
+    let dy = point[1] - rest.start[1];
+    let (rest_sin, rest_cos) = (-rest.rotation).sin_cos();
+    let local_x = (dx * rest_cos - dy * rest_sin) * posed.scale[0];
+    let local_y = (dx * rest_sin + dy * rest_cos) * posed.scale[1];
+    let (posed_sin, posed_cos) = posed.rotation.sin_cos();
+    [
+        posed.start[0] + local_x * posed_cos - local_y * posed_sin,
+        posed.start[1] + local_x * posed_sin + local_y * posed_cos,
+    ]
+}
+
+fn skin_path_points(
+    object: &mut AnimObject,
+    transforms: &[(uuid::Uuid, BoneTransform, BoneTransform)],
+) {
+    if object.scale[0].abs() < 1e-6 || object.scale[1].abs() < 1e-6 {
+        return;
+    }
+    let mut shape = std::mem::replace(&mut object.shape, Shape::Null);
+    if let Shape::Path { points, .. } = &mut shape {
+        for point in points.iter_mut() {
+            if point.weights.is_empty() {
+                continue;
+            }
+            point.position = skin_local_point(point.position, &point.weights, object, transforms);
+            if let Some(control) = point.control_in.as_mut() {
+                *control = skin_local_point(*control, &point.weights, object, transforms);
+            }
+            if let Some(control) = point.control_out.as_mut() {
+                *control = skin_local_point(*control, &point.weights, object, transforms);
+            }
+        }
+    }
+    object.shape = shape;
+}
+
+fn skin_local_point(
+    local: [f32; 2],
+    weights: &[BoneWeight],
+    object: &AnimObject,
+    transforms: &[(uuid::Uuid, BoneTransform, BoneTransform)],
+) -> [f32; 2] {
+    let world = hierarchy::to_world_point(object, local);
+
+    let mut blended = [0.0, 0.0];
+    let mut total_weight = 0.0;
+    for bone_weight in weights {
+        if let Some((_, rest, posed)) = transforms
+            .iter()
+            .find(|(id, _, _)| *id == bone_weight.bone_id)
+        {
+            let skinned = transform_point_by_bone(world, rest, posed);
+            blended[0] += skinned[0] * bone_weight.weight;
+            blended[1] += skinned[1] * bone_weight.weight;
+            total_weight += bone_weight.weight;
+        }
+    }
+
+    if total_weight <= 0.0 {
+        return local;
+    }
+
+    let remaining = (1.0 - total_weight).max(0.0);
+    blended[0] += world[0] * remaining;
+    blended[1] += world[1] * remaining;
+    total_weight += remaining;
+
+    hierarchy::to_local_point(
+        object,
+        [blended[0] / total_weight, blended[1] / total_weight],
+    )
+}
+
+pub fn auto_weight_objects(layer: &mut Layer, object_ids: &[uuid::Uuid], armature: &Armature) {
+    let segments: Vec<(uuid::Uuid, [f32; 2], [f32; 2])> = armature
+        .bones
+        .iter()
+        .map(|bone| {
+            let rest = compute_bone_transform(bone, armature, None);
+            (bone.id, rest.start, rest.end())
+        })
+        .collect();
+    if segments.is_empty() {
+        return;
+    }
+
+    for keyframe in layer.keyframes.values_mut() {
+        for object in &mut keyframe.objects {
+            if !object_ids.contains(&object.id) {
+                continue;
+            }
+            let mut shape = std::mem::replace(&mut object.shape, Shape::Null);
+            if let Shape::Path { points, .. } = &mut shape {
+                for point in points.iter_mut() {
+                    let world = hierarchy::to_world_point(object, point.position);
+                    point.weights = compute_auto_weights(world, &segments);
+                }
+            }
+            object.shape = shape;
+        }
+    }
+}
+
+fn compute_auto_weights(
+    point: [f32; 2],
+    segments: &[(uuid::Uuid, [f32; 2], [f32; 2])],
+) -> Vec<BoneWeight> {
+    let mut candidates: Vec<(uuid::Uuid, f32)> = segments
+        .iter()
+        .map(|(bone_id, start, end)| {
+            let distance = distance_to_segment(point, *start, *end);
+            (*bone_id, 1.0 / (distance + 1.0).powi(2))
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    candidates.truncate(MAX_BONE_INFLUENCES);
+
+    let total: f32 = candidates.iter().map(|(_, weight)| weight).sum();
+    let mut weights: Vec<BoneWeight> = candidates
+        .into_iter()
+        .map(|(bone_id, weight)| BoneWeight {
+            bone_id,
+            weight: weight / total,
+        })
+        .filter(|bone_weight| bone_weight.weight >= MIN_BONE_WEIGHT)
+        .collect();
+
+    let kept_total: f32 = weights.iter().map(|bone_weight| bone_weight.weight).sum();
+    for bone_weight in &mut weights {
+        bone_weight.weight /= kept_total;
+    }
+    weights
+}
+
+fn distance_to_segment(point: [f32; 2], start: [f32; 2], end: [f32; 2]) -> f32 {
+    let segment_x = end[0] - start[0];
+    let segment_y = end[1] - start[1];
+    let length_sq = segment_x * segment_x + segment_y * segment_y;
+    let t = if length_sq < 1e-6 {
+        0.0
+    } else {
+        (((point[0] - start[0]) * segment_x + (point[1] - start[1]) * segment_y) / length_sq)
+            .clamp(0.0, 1.0)
+    };
+    let closest_x = start[0] + segment_x * t;
+    let closest_y = start[1] + segment_y * t;
+    ((point[0] - closest_x).powi(2) + (point[1] - closest_y).powi(2)).sqrt()
 }
 
 pub fn handle_bone_tool(
@@ -127,9 +353,9 @@ fn place_bone(app: &mut AnimateApp, canvas_pos: egui::Pos2) {
         let parent_bone = &armature.bones[bone_index];
 
         if is_end {
-            app.history.push(app.project.clone());
+            app.history.push(app.project.clone(), "Add Bone");
             let parent_id = parent_bone.id;
-            let parent_end = bone_end_position(parent_bone, armature);
+            let parent_end = compute_bone_transform(parent_bone, armature, None).end();
             let dx = click_pos[0] - parent_end[0];
             let dy = click_pos[1] - parent_end[1];
             let length = (dx * dx + dy * dy).sqrt().max(10.0);
@@ -143,13 +369,14 @@ fn place_bone(app: &mut AnimateApp, canvas_pos: egui::Pos2) {
                 length,
                 rotation,
                 bound_object_ids: Vec::new(),
+                pose_keys: std::collections::BTreeMap::new(),
             };
             app.project.armatures[armature_index].bones.push(new_bone);
             return;
         }
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Add Bone");
 
     let new_bone = Bone {
         id: uuid::Uuid::new_v4(),
@@ -159,6 +386,7 @@ fn place_bone(app: &mut AnimateApp, canvas_pos: egui::Pos2) {
         length: 80.0,
         rotation: 0.0,
         bound_object_ids: Vec::new(),
+        pose_keys: std::collections::BTreeMap::new(),
     };
 
     let armature = Armature {
@@ -170,22 +398,15 @@ fn place_bone(app: &mut AnimateApp, canvas_pos: egui::Pos2) {
     app.project.armatures.push(armature);
 }
 
-fn bone_end_position(bone: &Bone, armature: &Armature) -> [f32; 2] {
-    let start = compute_bone_world_position(bone, armature);
-    [
-        start[0] + bone.length * bone.rotation.cos(),
-        start[1] + bone.length * bone.rotation.sin(),
-    ]
-}
-
 fn find_nearest_joint(app: &AnimateApp, pos: [f32; 2]) -> Option<(usize, usize, bool)> {
     let threshold = 15.0 / app.canvas_view.zoom;
     let threshold_sq = threshold * threshold;
 
     for (armature_index, armature) in app.project.armatures.iter().enumerate() {
         for (bone_index, bone) in armature.bones.iter().enumerate() {
-            let start = compute_bone_world_position(bone, armature);
-            let end = bone_end_position(bone, armature);
+            let transform = compute_bone_transform(bone, armature, Some(app.current_frame));
+            let start = transform.start;
+            let end = transform.end();
 
             let end_dx = pos[0] - end[0];
             let end_dy = pos[1] - end[1];
@@ -206,6 +427,7 @@ fn find_nearest_joint(app: &AnimateApp, pos: [f32; 2]) -> Option<(usize, usize,
 fn try_start_ik_drag(app: &mut AnimateApp, canvas_pos: egui::Pos2) {
     let click_pos = [canvas_pos.x, canvas_pos.y];
     if let Some((armature_index, bone_index, true)) = find_nearest_joint(app, click_pos) {
+        app.history.push(app.project.clone(), "Pose Bones");
         app.ik_drag_bone = Some((armature_index, bone_index));
     }
 }
@@ -225,10 +447,11 @@ fn update_ik_drag(app: &mut AnimateApp, canvas_pos: egui::Pos2) {
         &mut app.project.armatures[armature_index],
         bone_index,
         target,
+        app.current_frame,
     );
 }
 
-fn fabrik_solve(armature: &mut Armature, end_bone_index: usize, target: [f32; 2]) {
+fn fabrik_solve(armature: &mut Armature, end_bone_index: usize, target: [f32; 2], frame: u32) {
     let chain = build_bone_chain(armature, end_bone_index);
     if chain.is_empty() {
         return;
@@ -238,14 +461,13 @@ fn fabrik_solve(armature: &mut Armature, end_bone_index: usize, target: [f32; 2]
     let mut lengths: Vec<f32> = Vec::new();
 
     for &bone_index in &chain {
-        let bone = &armature.bones[bone_index];
-        let start = compute_bone_world_position(bone, armature);
-        positions.push(start);
-        lengths.push(bone.length);
+        let transform = compute_bone_transform(&armature.bones[bone_index], armature, Some(frame));
+        positions.push(transform.start);
+        lengths.push(transform.length);
     }
 
     let last_bone = &armature.bones[*chain.last().unwrap()];
-    let last_end = bone_end_position(last_bone, armature);
+    let last_end = compute_bone_transform(last_bone, armature, Some(frame)).end();
     positions.push(last_end);
 
     let root = positions[0];
@@ -275,15 +497,39 @@ fn fabrik_solve(armature: &mut Armature, end_bone_index: usize, target: [f32; 2]
         }
     }
 
+    let mut inherited_rotation = 0.0;
     for (chain_index, &bone_index) in chain.iter().enumerate() {
         let start = positions[chain_index];
         let end = positions[chain_index + 1];
         let dx = end[0] - start[0];
         let dy = end[1] - start[1];
-        armature.bones[bone_index].rotation = dy.atan2(dx);
+        let world_rotation = dy.atan2(dx);
+
+        let bone = &mut armature.bones[bone_index];
+        let mut pose = tween::resolve_bone_pose(bone, frame);
+        pose.rotation = normalize_angle(world_rotation - bone.rotation - inherited_rotation);
+        set_pose_key(bone, frame, pose);
+
+        inherited_rotation = world_rotation - bone.rotation;
     }
 }
 
+fn normalize_angle(angle: f32) -> f32 {
+    let wrapped = (angle + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU);
+    wrapped - std::f32::consts::PI
+}
+
+fn set_pose_key(bone: &mut Bone, frame: u32, pose: BonePose) {
+    bone.pose_keys
+        .entry(frame)
+        .and_modify(|key| key.value = pose)
+        .or_insert(PropertyKey {
+            value: pose,
+            tween: TweenType::Linear,
+            tangents: None,
+        });
+}
+
 fn build_bone_chain(armature: &Armature, end_bone_index: usize) -> Vec<usize> {
     let mut chain = vec![end_bone_index];
     let mut current = end_bone_index;
@@ -324,10 +570,325 @@ pub fn draw_bone_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
                 delete_armature = Some(armature_index);
             }
         });
+
+        for bone_index in 0..bone_count {
+            draw_bone_pose_editor(app, ui, armature_index, bone_index);
+        }
     }
 
     if let Some(index) = delete_armature {
-        app.history.push(app.project.clone());
+        app.history.push(app.project.clone(), "Delete Armature");
         app.project.armatures.remove(index);
     }
 }
+
+fn draw_bone_pose_editor(
+    app: &mut AnimateApp,
+    ui: &mut egui::Ui,
+    armature_index: usize,
+    bone_index: usize,
+) {
+    let frame = app.current_frame;
+    let bone = &app.project.armatures[armature_index].bones[bone_index];
+    let bone_id = bone.id;
+    let bone_name = bone.name.clone();
+    let bound_count = bone.bound_object_ids.len();
+    let existing_key = bone.pose_keys.get(&frame).map(|key| key.tween);
+    let pose = tween::resolve_bone_pose(bone, frame);
+
+    let mut translation = pose.translation;
+    let mut rotation_degrees = pose.rotation.to_degrees();
+    let mut scale = pose.scale;
+    let mut changed = false;
+
+    egui::CollapsingHeader::new(format!("{} ({} bound)", bone_name, bound_count))
+        .id_salt(bone_id)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("X:");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut translation[0]).speed(1.0))
+                    .changed();
+                ui.label("Y:");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut translation[1]).speed(1.0))
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Rotation:");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut rotation_degrees)
+                            .speed(1.0)
+                            .suffix("°"),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Scale:");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut scale[0])
+                            .speed(0.01)
+                            .range(0.01..=100.0),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut scale[1])
+                            .speed(0.01)
+                            .range(0.01..=100.0),
+                    )
+                    .changed();
+            });
+
+            if changed {
+                app.history.push(app.project.clone(), "Edit Bone Pose");
+                let bone = &mut app.project.armatures[armature_index].bones[bone_index];
+                set_pose_key(
+                    bone,
+                    frame,
+                    BonePose {
+                        translation,
+                        rotation: rotation_degrees.to_radians(),
+                        scale,
+                    },
+                );
+            }
+
+            if let Some(mut tween) = existing_key {
+                ui.horizontal(|ui| {
+                    ui.label("Tween:");
+                    egui::ComboBox::from_id_salt(("bone_pose_tween", bone_id))
+                        .selected_text(tween_name(tween))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut tween, TweenType::None, "None");
+                            ui.selectable_value(&mut tween, TweenType::Linear, "Linear");
+                            ui.selectable_value(&mut tween, TweenType::EaseIn, "Ease In");
+                            ui.selectable_value(&mut tween, TweenType::EaseOut, "Ease Out");
+                            ui.selectable_value(&mut tween, TweenType::EaseInOut, "Ease In/Out");
+                        });
+                });
+                if Some(tween) != existing_key {
+                    app.history.push(app.project.clone(), "Set Pose Tween");
+                    let bone = &mut app.project.armatures[armature_index].bones[bone_index];
+                    if let Some(key) = bone.pose_keys.get_mut(&frame) {
+                        key.tween = tween;
+                    }
+                }
+            }
+
+            ui.horizontal(|ui| {
+                if existing_key.is_some() {
+                    if ui.small_button("Remove Pose Key").clicked() {
+                        app.history.push(app.project.clone(), "Remove Pose Key");
+                        app.project.armatures[armature_index].bones[bone_index]
+                            .pose_keys
+                            .remove(&frame);
+                    }
+                } else if ui.small_button("Add Pose Key").clicked() {
+                    app.history.push(app.project.clone(), "Add Pose Key");
+                    let bone = &mut app.project.armatures[armature_index].bones[bone_index];
+                    set_pose_key(bone, frame, pose);
+                }
+            });
+
+            if bound_count > 0 && ui.small_button("Unbind All").clicked() {
+                app.history.push(app.project.clone(), "Unbind Objects");
+                app.project.armatures[armature_index].bones[bone_index]
+                    .bound_object_ids
+                    .clear();
+            }
+        });
+}
+
+pub fn draw_object_bone_binding(app: &mut AnimateApp, ui: &mut egui::Ui) {
+    if app.project.armatures.is_empty() {
+        return;
+    }
+
+    let selected = app.selection.selected_objects.clone();
+    let current_binding = app.project.armatures.iter().find_map(|armature| {
+        armature
+            .bones
+            .iter()
+            .find(|bone| bone.bound_object_ids.contains(&selected[0]))
+            .map(|bone| bone.id)
+    });
+
+    let bone_names: Vec<(uuid::Uuid, String)> = app
+        .project
+        .armatures
+        .iter()
+        .flat_map(|armature| {
+            armature
+                .bones
+                .iter()
+                .map(|bone| (bone.id, format!("{} / {}", armature.name, bone.name)))
+        })
+        .collect();
+
+    let selected_text = current_binding
+        .and_then(|bone_id| bone_names.iter().find(|(id, _)| *id == bone_id))
+        .map(|(_, name)| name.clone())
+        .unwrap_or_else(|| "None".to_string());
+
+    let mut binding = current_binding;
+    ui.horizontal(|ui| {
+        ui.label("Bone:");
+        egui::ComboBox::from_id_salt("object_bone_binding")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut binding, None, "None");
+                for (bone_id, name) in &bone_names {
+                    ui.selectable_value(&mut binding, Some(*bone_id), name);
+                }
+            });
+    });
+
+    if binding != current_binding {
+        app.history.push(app.project.clone(), "Bind Objects");
+        for armature in &mut app.project.armatures {
+            for bone in &mut armature.bones {
+                bone.bound_object_ids.retain(|id| !selected.contains(id));
+                if Some(bone.id) == binding {
+                    bone.bound_object_ids.extend(selected.iter().copied());
+                }
+            }
+        }
+    }
+
+    let selected_paths: Vec<uuid::Uuid> = app
+        .project
+        .layers
+        .iter()
+        .filter_map(|layer| tween::resolve_frame(layer, app.current_frame))
+        .flatten()
+        .filter(|object| {
+            selected.contains(&object.id) && matches!(object.shape, Shape::Path { .. })
+        })
+        .map(|object| object.id)
+        .collect();
+    if selected_paths.is_empty() {
+        return;
+    }
+
+    let mut auto_weight_armature = None;
+    let mut clear_weights = false;
+    ui.horizontal_wrapped(|ui| {
+        for (armature_index, armature) in app.project.armatures.iter().enumerate() {
+            if ui
+                .small_button(format!("Auto Weight to {}", armature.name))
+                .clicked()
+            {
+                auto_weight_armature = Some(armature_index);
+            }
+        }
+        if ui.small_button("Clear Weights").clicked() {
+            clear_weights = true;
+        }
+    });
+
+    if let Some(armature_index) = auto_weight_armature {
+        app.history.push(app.project.clone(), "Auto Weights");
+        let armature = app.project.armatures[armature_index].clone();
+        for layer in &mut app.project.layers {
+            auto_weight_objects(layer, &selected_paths, &armature);
+        }
+        for armature in &mut app.project.armatures {
+            for bone in &mut armature.bones {
+                bone.bound_object_ids
+                    .retain(|id| !selected_paths.contains(id));
+            }
+        }
+    }
+
+    if clear_weights {
+        app.history.push(app.project.clone(), "Clear Weights");
+        for layer in &mut app.project.layers {
+            for keyframe in layer.keyframes.values_mut() {
+                for object in &mut keyframe.objects {
+                    if !selected_paths.contains(&object.id) {
+                        continue;
+                    }
+                    if let Shape::Path { points, .. } = &mut object.shape {
+                        for point in points.iter_mut() {
+                            point.weights.clear();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn tween_name(tween: TweenType) -> &'static str {
+    match tween {
+        TweenType::None => "None",
+        TweenType::Linear => "Linear",
+        TweenType::EaseIn => "Ease In",
+        TweenType::EaseOut => "Ease Out",
+        TweenType::EaseInOut => "Ease In/Out",
+        TweenType::CubicBezier { .. } => "Custom",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paint::Paint;
+    use crate::project::PathPoint;
+
+    #[test]
+    fn skinned_rotated_path_is_drawn_where_the_bone_moves_it() {
+        let bone_id = uuid::Uuid::new_v4();
+        let rest = BoneTransform {
+            start: [0.0, 0.0],
+            rotation: 0.0,
+            length: 10.0,
+            scale: [1.0, 1.0],
+        };
+        let posed = BoneTransform {
+            start: [10.0, 0.0],
+            ..rest
+        };
+        let point = |position| PathPoint {
+            position,
+            control_in: None,
+            control_out: None,
+            pressure: 1.0,
+            weights: vec![BoneWeight {
+                bone_id,
+                weight: 1.0,
+            }],
+        };
+        let mut object = AnimObject::new(
+            Shape::Path {
+                points: vec![point([0.0, 0.0]), point([5.0, 0.0])],
+                closed: false,
+            },
+            [20.0, 20.0],
+            Paint::default(),
+            Paint::default(),
+            1.0,
+        );
+        object.rotation = std::f32::consts::FRAC_PI_2;
+        let drawn_before: Vec<[f32; 2]> = [[0.0, 0.0], [5.0, 0.0]]
+            .into_iter()
+            .map(|local| hierarchy::to_world_point(&object, local))
+            .collect();
+
+        skin_path_points(&mut object, &[(bone_id, rest, posed)]);
+
+        let Shape::Path { points, .. } = &object.shape else {
+            panic!("expected a path");
+        };
+        for (point, before) in points.iter().zip(drawn_before) {
+            let drawn = hierarchy::to_world_point(&object, point.position);
+            assert!((drawn[0] - (before[0] + 10.0)).abs() < 1e-3);
+            assert!((drawn[1] - before[1]).abs() < 1e-3);
+        }
+    }
+}
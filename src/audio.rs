@@ -5,14 +5,21 @@ use nightshade::prelude::*;
 use rodio::Source;
 
 use crate::app::AnimateApp;
-use crate::project::AudioTrack;
+use crate::project::{AudioTrack, Project};
 
-struct AudioPlaybackState {
-    _stream: rodio::OutputStream,
-    sink: rodio::Sink,
+pub const MIXDOWN_SAMPLE_RATE: u32 = 44_100;
+
+const MIN_SCRUB_SECONDS: f64 = 0.06;
+#[cfg(feature = "scripting")]
+const ENVELOPE_SAMPLE_RATE: u32 = 8_000;
+
+struct AudioOutput {
+    stream: rodio::OutputStream,
+    sinks: Vec<rodio::Sink>,
+    playing: bool,
 }
 
-static AUDIO_STATE: Mutex<Option<AudioPlaybackState>> = Mutex::new(None);
+static AUDIO_OUTPUT: Mutex<Option<AudioOutput>> = Mutex::new(None);
 
 pub fn import_audio(app: &mut AnimateApp) {
     let Some(path) = rfd::FileDialog::new()
@@ -32,71 +39,218 @@ pub fn import_audio(app: &mut AnimateApp) {
         .map(|os_str| os_str.to_string_lossy().to_string())
         .unwrap_or_else(|| "audio".to_string());
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Import Audio");
 
     app.project.audio_tracks.push(AudioTrack {
         id: uuid::Uuid::new_v4(),
         name,
-        data,
+        data: data.into(),
         start_frame: 0,
         volume: 1.0,
+        muted: false,
+        solo: false,
     });
 }
 
+pub fn audible_tracks(project: &Project) -> impl Iterator<Item = &AudioTrack> {
+    let any_solo = project.audio_tracks.iter().any(|track| track.solo);
+    project
+        .audio_tracks
+        .iter()
+        .filter(move |track| !track.muted && (track.solo || !any_solo))
+}
+
 pub fn start_audio_playback(app: &AnimateApp) {
-    stop_audio_playback();
+    play_tracks(&app.project, app.current_frame, None);
+}
 
-    if app.project.audio_tracks.is_empty() {
+pub fn scrub_audio(app: &AnimateApp) {
+    if app.playback.playing {
+        start_audio_playback(app);
         return;
     }
+    let frame_seconds = 1.0 / app.project.frame_rate.max(1) as f64;
+    let length = std::time::Duration::from_secs_f64(frame_seconds.max(MIN_SCRUB_SECONDS));
+    play_tracks(&app.project, app.current_frame, Some(length));
+}
 
-    let track = &app.project.audio_tracks[0];
-
-    let Ok(stream) = rodio::OutputStreamBuilder::open_default_stream() else {
+fn play_tracks(project: &Project, frame: u32, length: Option<std::time::Duration>) {
+    let Ok(mut guard) = AUDIO_OUTPUT.lock() else {
         return;
     };
-
-    let sink = rodio::Sink::connect_new(stream.mixer());
-
-    let cursor = Cursor::new(track.data.clone());
-    let Ok(source) = rodio::Decoder::new(cursor) else {
+    if let Some(output) = guard.as_mut() {
+        for sink in output.sinks.drain(..) {
+            sink.stop();
+        }
+        output.playing = false;
+    }
+    if audible_tracks(project).next().is_none() {
+        return;
+    }
+    if guard.is_none() {
+        let Ok(mut stream) = rodio::OutputStreamBuilder::open_default_stream() else {
+            return;
+        };
+        stream.log_on_drop(false);
+        *guard = Some(AudioOutput {
+            stream,
+            sinks: Vec::new(),
+            playing: false,
+        });
+    }
+    let Some(output) = guard.as_mut() else {
         return;
     };
+    output.playing = length.is_none();
 
-    let frame_offset = app.current_frame.saturating_sub(track.start_frame);
-    let seconds_offset = frame_offset as f64 / app.project.frame_rate as f64;
+    let frame_rate = project.frame_rate.max(1) as f64;
+    for track in audible_tracks(project) {
+        let Ok(bytes) = track.data.bytes() else {
+            continue;
+        };
+        let Ok(mut source) = rodio::Decoder::new(Cursor::new(bytes.to_vec())) else {
+            continue;
+        };
+        let offset_seconds = (frame as f64 - track.start_frame as f64) / frame_rate;
+        let delay = std::time::Duration::from_secs_f64((-offset_seconds).max(0.0));
+        if length.is_some_and(|length| delay >= length) {
+            continue;
+        }
+        if offset_seconds > 0.0
+            && source
+                .try_seek(std::time::Duration::from_secs_f64(offset_seconds))
+                .is_err()
+        {
+            continue;
+        }
 
-    sink.set_volume(track.volume);
-    sink.append(source);
+        let sink = rodio::Sink::connect_new(output.stream.mixer());
+        sink.set_volume(track.volume);
+        let source = source.delay(delay);
+        match length {
+            Some(length) => sink.append(source.take_duration(length)),
+            None => sink.append(source),
+        }
+        output.sinks.push(sink);
+    }
+}
+
+pub fn mixdown_sample_count(project: &Project, frame_count: u32, sample_rate: u32) -> usize {
+    (frame_count as u64 * sample_rate as u64 / project.frame_rate.max(1) as u64) as usize
+}
 
-    if seconds_offset > 0.0 {
-        sink.try_seek(std::time::Duration::from_secs_f64(seconds_offset))
-            .ok();
+pub fn mix_audio_tracks(
+    project: &Project,
+    start_frame: u32,
+    frame_count: u32,
+    sample_rate: u32,
+) -> Vec<i16> {
+    let frame_rate = project.frame_rate.max(1) as f64;
+    let sample_count = mixdown_sample_count(project, frame_count, sample_rate);
+    let mut mix = vec![0.0_f32; sample_count * 2];
+
+    for track in audible_tracks(project) {
+        let Some((channels, track_sample_rate, samples)) = decode_track(track) else {
+            continue;
+        };
+        let source_length = samples.len() / channels;
+        if source_length < 2 {
+            continue;
+        }
+        let offset_seconds = (track.start_frame as f64 - start_frame as f64) / frame_rate;
+        let rate_ratio = track_sample_rate as f64 / sample_rate as f64;
+        let first_output = (offset_seconds * sample_rate as f64).ceil().max(0.0) as usize;
+
+        for output_index in first_output..sample_count {
+            let position =
+                (output_index as f64 - offset_seconds * sample_rate as f64).max(0.0) * rate_ratio;
+            let index = position as usize;
+            if index + 1 >= source_length {
+                break;
+            }
+            let fraction = (position - index as f64) as f32;
+            for channel in 0..2 {
+                let source_channel = channel.min(channels - 1);
+                let current = samples[index * channels + source_channel];
+                let next = samples[(index + 1) * channels + source_channel];
+                mix[output_index * 2 + channel] +=
+                    (current + (next - current) * fraction) * track.volume;
+            }
+        }
     }
 
-    let state = AudioPlaybackState {
-        _stream: stream,
-        sink,
-    };
+    mix.into_iter()
+        .map(|sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16)
+        .collect()
+}
+
+#[cfg(feature = "scripting")]
+pub fn amplitude_envelope(project: &Project) -> Vec<f32> {
+    let mix = mix_audio_tracks(project, 0, project.total_frames, ENVELOPE_SAMPLE_RATE);
+    (0..project.total_frames)
+        .map(|frame| {
+            let start = mixdown_sample_count(project, frame, ENVELOPE_SAMPLE_RATE) * 2;
+            let end =
+                (mixdown_sample_count(project, frame + 1, ENVELOPE_SAMPLE_RATE) * 2).min(mix.len());
+            let window = &mix[start.min(end)..end];
+            if window.is_empty() {
+                return 0.0;
+            }
+            let energy = window
+                .iter()
+                .map(|sample| (*sample as f32 / i16::MAX as f32).powi(2))
+                .sum::<f32>();
+            (energy / window.len() as f32).sqrt()
+        })
+        .collect()
+}
+
+fn decode_track(track: &AudioTrack) -> Option<(usize, u32, Vec<f32>)> {
+    let bytes = track.data.bytes().ok()?;
+    let decoder = rodio::Decoder::new(Cursor::new(bytes.to_vec())).ok()?;
+    let channels = decoder.channels().max(1) as usize;
+    let sample_rate = decoder.sample_rate().max(1);
+    Some((channels, sample_rate, decoder.collect()))
+}
 
-    if let Ok(mut guard) = AUDIO_STATE.lock() {
-        *guard = Some(state);
+pub fn write_wav(path: &std::path::Path, sample_rate: u32, samples: &[i16]) -> Result<(), String> {
+    let data_size = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + samples.len() * 2);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16_u32.to_le_bytes());
+    bytes.extend_from_slice(&1_u16.to_le_bytes());
+    bytes.extend_from_slice(&2_u16.to_le_bytes());
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&(sample_rate * 4).to_le_bytes());
+    bytes.extend_from_slice(&4_u16.to_le_bytes());
+    bytes.extend_from_slice(&16_u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
     }
+    std::fs::write(path, bytes)
+        .map_err(|error| format!("Failed to write {}: {}", path.display(), error))
 }
 
 pub fn stop_audio_playback() {
-    if let Ok(mut guard) = AUDIO_STATE.lock()
-        && let Some(state) = guard.take()
+    if let Ok(mut guard) = AUDIO_OUTPUT.lock()
+        && let Some(output) = guard.as_mut()
     {
-        state.sink.stop();
+        for sink in output.sinks.drain(..) {
+            sink.stop();
+        }
+        output.playing = false;
     }
 }
 
 pub fn sync_playback_state(playing: bool, app: &AnimateApp) {
     if playing {
-        let has_audio = AUDIO_STATE
+        let has_audio = AUDIO_OUTPUT
             .lock()
-            .map(|guard| guard.is_some())
+            .map(|guard| guard.as_ref().is_some_and(|output| output.playing))
             .unwrap_or(false);
         if !has_audio {
             start_audio_playback(app);
@@ -114,13 +268,44 @@ pub fn draw_audio_tracks(app: &mut AnimateApp, ui: &mut egui::Ui) {
     ui.separator();
 
     let mut remove_index = None;
+    let mut mix_changed = false;
 
     for track_index in 0..app.project.audio_tracks.len() {
         let track_name = app.project.audio_tracks[track_index].name.clone();
         let track_volume = app.project.audio_tracks[track_index].volume;
         let track_start = app.project.audio_tracks[track_index].start_frame;
+        let track_muted = app.project.audio_tracks[track_index].muted;
+        let track_solo = app.project.audio_tracks[track_index].solo;
 
         ui.horizontal(|ui| {
+            if ui
+                .selectable_label(track_muted, "M")
+                .on_hover_text("Mute track")
+                .clicked()
+            {
+                let name = if track_muted {
+                    "Unmute Audio Track"
+                } else {
+                    "Mute Audio Track"
+                };
+                app.history.push(app.project.clone(), name);
+                app.project.audio_tracks[track_index].muted = !track_muted;
+                mix_changed = true;
+            }
+            if ui
+                .selectable_label(track_solo, "S")
+                .on_hover_text("Solo track")
+                .clicked()
+            {
+                let name = if track_solo {
+                    "Unsolo Audio Track"
+                } else {
+                    "Solo Audio Track"
+                };
+                app.history.push(app.project.clone(), name);
+                app.project.audio_tracks[track_index].solo = !track_solo;
+                mix_changed = true;
+            }
             ui.label(&track_name);
 
             let mut volume = track_volume;
@@ -156,8 +341,13 @@ pub fn draw_audio_tracks(app: &mut AnimateApp, ui: &mut egui::Ui) {
     }
 
     if let Some(index) = remove_index {
-        app.history.push(app.project.clone());
+        app.history.push(app.project.clone(), "Remove Audio Track");
         app.project.audio_tracks.remove(index);
+        mix_changed = true;
+    }
+
+    if mix_changed && app.playback.playing {
+        start_audio_playback(app);
     }
 }
 
@@ -168,20 +358,27 @@ pub fn draw_audio_waveform(
     frame_cell_width: f32,
     scroll_offset: f32,
 ) {
+    let audible: Vec<uuid::Uuid> = audible_tracks(&app.project).map(|track| track.id).collect();
     for track in &app.project.audio_tracks {
         let waveform_height = 20.0;
         let waveform_top = timeline_rect.max.y - waveform_height - 2.0;
 
-        let samples = extract_waveform_samples(&track.data, 1024);
+        let Some((sample_rate, mono)) = decode_mono(track) else {
+            continue;
+        };
+        let samples = extract_waveform_samples(&mono, 1024);
         if samples.is_empty() {
             continue;
         }
 
-        let sample_rate = estimate_sample_rate(&track.data);
-        let total_duration_seconds = samples.len() as f32 / sample_rate;
+        let total_duration_seconds = mono.len() as f32 / sample_rate as f32;
         let total_frames = total_duration_seconds * app.project.frame_rate as f32;
 
-        let waveform_color = egui::Color32::from_rgba_unmultiplied(100, 150, 255, 120);
+        let waveform_color = if audible.contains(&track.id) {
+            egui::Color32::from_rgba_unmultiplied(100, 150, 255, 120)
+        } else {
+            egui::Color32::from_rgba_unmultiplied(120, 120, 130, 50)
+        };
 
         for sample_index in 0..samples.len() {
             let frame_pos = track.start_frame as f32
@@ -204,20 +401,22 @@ pub fn draw_audio_waveform(
     }
 }
 
-fn extract_waveform_samples(data: &[u8], target_count: usize) -> Vec<f32> {
-    let cursor = Cursor::new(data.to_vec());
-    let Ok(source) = rodio::Decoder::new(cursor) else {
-        return Vec::new();
-    };
-
-    let all_samples: Vec<f32> = source.map(|sample| sample / i16::MAX as f32).collect();
+pub fn decode_mono(track: &AudioTrack) -> Option<(u32, Vec<f32>)> {
+    let (channels, sample_rate, samples) = decode_track(track)?;
+    let mono = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+    Some((sample_rate, mono))
+}
 
-    if all_samples.is_empty() {
+fn extract_waveform_samples(samples: &[f32], target_count: usize) -> Vec<f32> {
+    if samples.is_empty() {
         return Vec::new();
     }
 
-    let chunk_size = (all_samples.len() / target_count).max(1);
-    all_samples
+    let chunk_size = (samples.len() / target_count).max(1);
+    samples
         .chunks(chunk_size)
         .map(|chunk| {
             chunk
@@ -226,12 +425,3 @@ fn extract_waveform_samples(data: &[u8], target_count: usize) -> Vec<f32> {
         })
         .collect()
 }
-
-fn estimate_sample_rate(data: &[u8]) -> f32 {
-    let cursor = Cursor::new(data.to_vec());
-    if let Ok(source) = rodio::Decoder::new(cursor) {
-        source.sample_rate() as f32
-    } else {
-        44100.0
-    }
-}
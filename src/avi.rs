@@ -0,0 +1,271 @@
+use std::io::{Seek, SeekFrom, Write};
+
+const AVIF_HASINDEX: u32 = 0x10;
+const AVIF_ISINTERLEAVED: u32 = 0x100;
+const AVIIF_KEYFRAME: u32 = 0x10;
+const AUDIO_BLOCK_ALIGN: u32 = 4;
+
+pub struct AviAudioFormat {
+    pub sample_rate: u32,
+    pub sample_frames: u32,
+}
+
+struct IndexEntry {
+    chunk_id: [u8; 4],
+    offset: u32,
+    size: u32,
+}
+
+pub struct AviWriter {
+    file: std::io::BufWriter<std::fs::File>,
+    movi_list_size_position: u64,
+    movi_position: u64,
+    main_buffer_size_position: u64,
+    video_buffer_size_position: u64,
+    largest_chunk: u32,
+    index: Vec<IndexEntry>,
+}
+
+impl AviWriter {
+    pub fn create(
+        path: &std::path::Path,
+        width: u32,
+        height: u32,
+        frame_rate: u32,
+        frame_count: u32,
+        audio: Option<AviAudioFormat>,
+    ) -> Result<Self, String> {
+        let file = std::fs::File::create(path)
+            .map_err(|error| format!("Failed to create {}: {}", path.display(), error))?;
+
+        let frame_rate = frame_rate.max(1);
+        let stream_count = if audio.is_some() { 2 } else { 1 };
+        let mut header = Vec::new();
+
+        header.extend_from_slice(b"RIFF");
+        push_u32(&mut header, 0);
+        header.extend_from_slice(b"AVI ");
+
+        let hdrl_start = begin_list(&mut header, b"hdrl");
+        header.extend_from_slice(b"avih");
+        push_u32(&mut header, 56);
+        push_u32(&mut header, 1_000_000 / frame_rate);
+        push_u32(&mut header, 0);
+        push_u32(&mut header, 0);
+        push_u32(&mut header, AVIF_HASINDEX | AVIF_ISINTERLEAVED);
+        push_u32(&mut header, frame_count);
+        push_u32(&mut header, 0);
+        push_u32(&mut header, stream_count);
+        let main_buffer_size_position = header.len() as u64;
+        push_u32(&mut header, 0);
+        push_u32(&mut header, width);
+        push_u32(&mut header, height);
+        for _ in 0..4 {
+            push_u32(&mut header, 0);
+        }
+
+        let video_list_start = begin_list(&mut header, b"strl");
+        header.extend_from_slice(b"strh");
+        push_u32(&mut header, 56);
+        header.extend_from_slice(b"vids");
+        header.extend_from_slice(b"MJPG");
+        push_u32(&mut header, 0);
+        push_u16(&mut header, 0);
+        push_u16(&mut header, 0);
+        push_u32(&mut header, 0);
+        push_u32(&mut header, 1);
+        push_u32(&mut header, frame_rate);
+        push_u32(&mut header, 0);
+        push_u32(&mut header, frame_count);
+        let video_buffer_size_position = header.len() as u64;
+        push_u32(&mut header, 0);
+        push_u32(&mut header, u32::MAX);
+        push_u32(&mut header, 0);
+        push_u16(&mut header, 0);
+        push_u16(&mut header, 0);
+        push_u16(&mut header, width as u16);
+        push_u16(&mut header, height as u16);
+
+        header.extend_from_slice(b"strf");
+        push_u32(&mut header, 40);
+        push_u32(&mut header, 40);
+        push_u32(&mut header, width);
+        push_u32(&mut header, height);
+        push_u16(&mut header, 1);
+        push_u16(&mut header, 24);
+        header.extend_from_slice(b"MJPG");
+        push_u32(&mut header, width * height * 3);
+        for _ in 0..4 {
+            push_u32(&mut header, 0);
+        }
+        end_list(&mut header, video_list_start);
+
+        if let Some(audio) = &audio {
+            let byte_rate = audio.sample_rate * AUDIO_BLOCK_ALIGN;
+            let audio_list_start = begin_list(&mut header, b"strl");
+            header.extend_from_slice(b"strh");
+            push_u32(&mut header, 56);
+            header.extend_from_slice(b"auds");
+            push_u32(&mut header, 0);
+            push_u32(&mut header, 0);
+            push_u16(&mut header, 0);
+            push_u16(&mut header, 0);
+            push_u32(&mut header, 0);
+            push_u32(&mut header, AUDIO_BLOCK_ALIGN);
+            push_u32(&mut header, byte_rate);
+            push_u32(&mut header, 0);
+            push_u32(&mut header, audio.sample_frames);
+            push_u32(&mut header, byte_rate / frame_rate + AUDIO_BLOCK_ALIGN);
+            push_u32(&mut header, u32::MAX);
+            push_u32(&mut header, AUDIO_BLOCK_ALIGN);
+            for _ in 0..4 {
+                push_u16(&mut header, 0);
+            }
+
+            header.extend_from_slice(b"strf");
+            push_u32(&mut header, 18);
+            push_u16(&mut header, 1);
+            push_u16(&mut header, 2);
+            push_u32(&mut header, audio.sample_rate);
+            push_u32(&mut header, byte_rate);
+            push_u16(&mut header, AUDIO_BLOCK_ALIGN as u16);
+            push_u16(&mut header, 16);
+            push_u16(&mut header, 0);
+            end_list(&mut header, audio_list_start);
+        }
+        end_list(&mut header, hdrl_start);
+
+        header.extend_from_slice(b"LIST");
+        let movi_list_size_position = header.len() as u64;
+        push_u32(&mut header, 0);
+        let movi_position = header.len() as u64;
+        header.extend_from_slice(b"movi");
+
+        let mut file = std::io::BufWriter::new(file);
+        file.write_all(&header)
+            .map_err(|error| format!("Failed to write AVI header: {}", error))?;
+
+        Ok(Self {
+            file,
+            movi_list_size_position,
+            movi_position,
+            main_buffer_size_position,
+            video_buffer_size_position,
+            largest_chunk: 0,
+            index: Vec::new(),
+        })
+    }
+
+    pub fn write_video_frame(&mut self, jpeg: &[u8]) -> Result<(), String> {
+        self.write_chunk(*b"00dc", jpeg)
+    }
+
+    pub fn write_audio(&mut self, samples: &[i16]) -> Result<(), String> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        let bytes: Vec<u8> = samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        self.write_chunk(*b"01wb", &bytes)
+    }
+
+    pub fn finish(mut self) -> Result<(), String> {
+        let movi_end = self.position()?;
+
+        let mut index = Vec::with_capacity(8 + self.index.len() * 16);
+        index.extend_from_slice(b"idx1");
+        push_u32(&mut index, (self.index.len() * 16) as u32);
+        for entry in &self.index {
+            index.extend_from_slice(&entry.chunk_id);
+            push_u32(&mut index, AVIIF_KEYFRAME);
+            push_u32(&mut index, entry.offset);
+            push_u32(&mut index, entry.size);
+        }
+        self.file
+            .write_all(&index)
+            .map_err(|error| format!("Failed to write AVI index: {}", error))?;
+
+        let file_end = self.position()?;
+        if file_end > u32::MAX as u64 {
+            return Err("AVI output exceeds the 4 GB RIFF limit".to_string());
+        }
+
+        let movi_size = (movi_end - self.movi_list_size_position - 4) as u32;
+        let largest_chunk = self.largest_chunk;
+        self.patch_u32(4, file_end as u32 - 8)?;
+        self.patch_u32(self.movi_list_size_position, movi_size)?;
+        self.patch_u32(self.main_buffer_size_position, largest_chunk)?;
+        self.patch_u32(self.video_buffer_size_position, largest_chunk)?;
+        self.file
+            .flush()
+            .map_err(|error| format!("Failed to finish AVI file: {}", error))
+    }
+
+    fn write_chunk(&mut self, chunk_id: [u8; 4], data: &[u8]) -> Result<(), String> {
+        let offset = self.position()? - self.movi_position;
+        if offset + data.len() as u64 > u32::MAX as u64 {
+            return Err("AVI output exceeds the 4 GB RIFF limit".to_string());
+        }
+
+        let size = data.len() as u32;
+        let mut chunk_header = Vec::with_capacity(8);
+        chunk_header.extend_from_slice(&chunk_id);
+        push_u32(&mut chunk_header, size);
+        self.file
+            .write_all(&chunk_header)
+            .and_then(|_| self.file.write_all(data))
+            .and_then(|_| {
+                if size % 2 == 1 {
+                    self.file.write_all(&[0])
+                } else {
+                    Ok(())
+                }
+            })
+            .map_err(|error| format!("Failed to write AVI chunk: {}", error))?;
+
+        self.largest_chunk = self.largest_chunk.max(size);
+        self.index.push(IndexEntry {
+            chunk_id,
+            offset: offset as u32,
+            size,
+        });
+        Ok(())
+    }
+
+    fn position(&mut self) -> Result<u64, String> {
+        self.file
+            .stream_position()
+            .map_err(|error| format!("Failed to query AVI position: {}", error))
+    }
+
+    fn patch_u32(&mut self, position: u64, value: u32) -> Result<(), String> {
+        self.file
+            .seek(SeekFrom::Start(position))
+            .and_then(|_| self.file.write_all(&value.to_le_bytes()))
+            .and_then(|_| self.file.seek(SeekFrom::End(0)).map(|_| ()))
+            .map_err(|error| format!("Failed to update AVI header: {}", error))
+    }
+}
+
+fn push_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn begin_list(buffer: &mut Vec<u8>, list_type: &[u8; 4]) -> usize {
+    buffer.extend_from_slice(b"LIST");
+    let size_position = buffer.len();
+    push_u32(buffer, 0);
+    buffer.extend_from_slice(list_type);
+    size_position
+}
+
+fn end_list(buffer: &mut [u8], size_position: usize) {
+    let size = (buffer.len() - size_position - 4) as u32;
+    buffer[size_position..size_position + 4].copy_from_slice(&size.to_le_bytes());
+}
@@ -3,6 +3,7 @@ use i_overlay::core::overlay_rule::OverlayRule;
 use i_overlay::float::single::SingleFloatOverlay;
 
 use crate::app::AnimateApp;
+use crate::hierarchy;
 use crate::project::{AnimObject, PathPoint, Shape};
 use crate::tween;
 
@@ -73,6 +74,7 @@ pub fn apply_boolean_operation(app: &mut AnimateApp, operation: BooleanOp) {
             control_in: None,
             control_out: None,
             pressure: 1.0,
+            weights: Vec::new(),
         })
         .collect();
 
@@ -80,7 +82,7 @@ pub fn apply_boolean_operation(app: &mut AnimateApp, operation: BooleanOp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Boolean Operation");
 
     let result_object = AnimObject::new(
         Shape::Path {
@@ -188,8 +190,7 @@ fn object_to_polygon(object: &AnimObject) -> Vec<[f64; 2]> {
             let mut polygon = Vec::new();
             for point_index in 0..points.len() {
                 let point = &points[point_index];
-                let world_x = (object.position[0] + point.position[0] * object.scale[0]) as f64;
-                let world_y = (object.position[1] + point.position[1] * object.scale[1]) as f64;
+                let [world_x, world_y] = hierarchy::to_world_point(object, point.position);
 
                 if point_index > 0 {
                     let prev = &points[point_index - 1];
@@ -207,15 +208,13 @@ fn object_to_polygon(object: &AnimObject) -> Vec<[f64; 2]> {
                                 + 3.0 * omt * omt * t * ctrl_out[1]
                                 + 3.0 * omt * t * t * ctrl_in[1]
                                 + t * t * t * point.position[1];
-                            polygon.push([
-                                (object.position[0] + bx * object.scale[0]) as f64,
-                                (object.position[1] + by * object.scale[1]) as f64,
-                            ]);
+                            let [world_x, world_y] = hierarchy::to_world_point(object, [bx, by]);
+                            polygon.push([world_x as f64, world_y as f64]);
                         }
                         continue;
                     }
                 }
-                polygon.push([world_x, world_y]);
+                polygon.push([world_x as f64, world_y as f64]);
             }
             polygon
         }
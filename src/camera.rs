@@ -158,7 +158,7 @@ pub fn draw_camera_properties(app: &mut crate::app::AnimateApp, ui: &mut egui::U
     });
 
     if changed {
-        app.history.push(app.project.clone());
+        app.history.push(app.project.clone(), "Edit Camera");
         app.project.camera_keyframes.insert(
             frame,
             CameraKeyframe {
@@ -172,11 +172,12 @@ pub fn draw_camera_properties(app: &mut crate::app::AnimateApp, ui: &mut egui::U
     ui.horizontal(|ui| {
         if has_keyframe {
             if ui.small_button("Remove Camera Keyframe").clicked() {
-                app.history.push(app.project.clone());
+                app.history
+                    .push(app.project.clone(), "Remove Camera Keyframe");
                 app.project.camera_keyframes.remove(&frame);
             }
         } else if ui.small_button("Add Camera Keyframe").clicked() {
-            app.history.push(app.project.clone());
+            app.history.push(app.project.clone(), "Add Camera Keyframe");
             app.project.camera_keyframes.insert(
                 frame,
                 CameraKeyframe {
@@ -203,7 +204,7 @@ pub fn draw_camera_properties(app: &mut crate::app::AnimateApp, ui: &mut egui::U
     });
 
     if ui.small_button("Reset Camera").clicked() {
-        app.history.push(app.project.clone());
+        app.history.push(app.project.clone(), "Reset Camera");
         app.project.camera_keyframes.clear();
         app.project.camera_tween = TweenType::None;
     }
@@ -6,6 +6,7 @@ use crate::app::AnimateApp;
 use crate::armature;
 use crate::clipboard;
 use crate::guides;
+use crate::hierarchy;
 use crate::library;
 use crate::menu;
 use crate::node_edit;
@@ -18,6 +19,8 @@ use crate::transform;
 use crate::tween;
 use crate::z_order;
 
+pub const NULL_SIZE: f32 = 20.0;
+
 #[derive(Clone)]
 pub struct CanvasView {
     pub pan: egui::Vec2,
@@ -79,6 +82,7 @@ pub fn draw_canvas(app: &mut AnimateApp, ui_context: &egui::Context) {
         draw_frame_objects(app, &clipped_painter);
 
         selection::draw_selection_indicators(app, &app.canvas_view.clone(), &clipped_painter);
+        draw_group_hint(app, &clipped_painter);
         transform::draw_transform_handles(app, &app.canvas_view.clone(), &clipped_painter);
         node_edit::draw_node_edit_overlay(app, &app.canvas_view.clone(), &clipped_painter);
 
@@ -110,6 +114,19 @@ pub fn draw_canvas(app: &mut AnimateApp, ui_context: &egui::Context) {
     });
 }
 
+fn draw_group_hint(app: &AnimateApp, painter: &egui::Painter) {
+    if app.selection.entered_group.is_none() {
+        return;
+    }
+    painter.text(
+        app.canvas_view.panel_rect.left_top() + egui::vec2(28.0, 28.0),
+        egui::Align2::LEFT_TOP,
+        "Editing group (double-click empty space or press Esc to exit)",
+        egui::FontId::proportional(13.0),
+        egui::Color32::from_rgb(230, 120, 30),
+    );
+}
+
 fn draw_context_menu(app: &mut AnimateApp, response: &egui::Response) {
     response.context_menu(|ui| {
         if !app.selection.selected_objects.is_empty() {
@@ -132,6 +149,15 @@ fn draw_context_menu(app: &mut AnimateApp, response: &egui::Response) {
                 ui.close();
             }
             ui.separator();
+            if ui.button("Group (Ctrl+G)").clicked() {
+                crate::group::group_selected(app);
+                ui.close();
+            }
+            if ui.button("Ungroup (Ctrl+Shift+G)").clicked() {
+                crate::group::ungroup_selected(app);
+                ui.close();
+            }
+            ui.separator();
             ui.menu_button("Arrange", |ui| {
                 if ui.button("Bring to Front").clicked() {
                     z_order::bring_to_front(app);
@@ -230,7 +256,10 @@ fn ensure_image_textures(app: &mut AnimateApp, ui_context: &egui::Context) {
         if app.image_textures.contains_key(&asset.id) {
             continue;
         }
-        let Ok(dynamic_image) = image::load_from_memory(&asset.data) else {
+        let Ok(bytes) = asset.data.bytes() else {
+            continue;
+        };
+        let Ok(dynamic_image) = image::load_from_memory(bytes) else {
             continue;
         };
         let rgba = dynamic_image.to_rgba8();
@@ -265,7 +294,12 @@ fn draw_frame_objects(app: &AnimateApp, painter: &egui::Painter) {
             continue;
         }
 
-        if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
+        if let Some(objects) = tween::resolve_posed_frame(
+            layer,
+            app.current_frame,
+            &app.project.layers,
+            &app.project.armatures,
+        ) {
             for object in &objects {
                 if layer.layer_type == LayerType::Guide {
                     render_object(
@@ -414,11 +448,8 @@ pub fn render_object(
             }
         }
         Shape::Line { end_x, end_y } => {
-            let end_canvas = egui::pos2(
-                object.position[0] + end_x * object.scale[0],
-                object.position[1] + end_y * object.scale[1],
-            );
-            let screen_end = view.canvas_to_screen(end_canvas);
+            let [end_x, end_y] = hierarchy::to_world_point(object, [*end_x, *end_y]);
+            let screen_end = view.canvas_to_screen(egui::pos2(end_x, end_y));
             painter.line_segment([screen_pos, screen_end], stroke);
         }
         Shape::Path { points, closed } => {
@@ -438,10 +469,8 @@ pub fn render_object(
             let mut screen_points = Vec::new();
             for path_point_index in 0..points.len() {
                 let point = &points[path_point_index];
-                let canvas_pt = egui::pos2(
-                    object.position[0] + point.position[0] * object.scale[0],
-                    object.position[1] + point.position[1] * object.scale[1],
-                );
+                let [canvas_x, canvas_y] = hierarchy::to_world_point(object, point.position);
+                let canvas_pt = egui::pos2(canvas_x, canvas_y);
 
                 if path_point_index > 0 {
                     let prev = &points[path_point_index - 1];
@@ -457,10 +486,8 @@ pub fn render_object(
                                 point.position,
                                 t,
                             );
-                            let canvas_bezier = egui::pos2(
-                                object.position[0] + bezier[0] * object.scale[0],
-                                object.position[1] + bezier[1] * object.scale[1],
-                            );
+                            let [bezier_x, bezier_y] = hierarchy::to_world_point(object, bezier);
+                            let canvas_bezier = egui::pos2(bezier_x, bezier_y);
                             screen_points.push(view.canvas_to_screen(canvas_bezier));
                         }
                         continue;
@@ -479,10 +506,8 @@ pub fn render_object(
                         let t = step as f32 / 16.0;
                         let bezier =
                             cubic_bezier(last.position, control_out, control_in, first.position, t);
-                        let canvas_bezier = egui::pos2(
-                            object.position[0] + bezier[0] * object.scale[0],
-                            object.position[1] + bezier[1] * object.scale[1],
-                        );
+                        let [bezier_x, bezier_y] = hierarchy::to_world_point(object, bezier);
+                        let canvas_bezier = egui::pos2(bezier_x, bezier_y);
                         screen_points.push(view.canvas_to_screen(canvas_bezier));
                     }
                 }
@@ -542,6 +567,29 @@ pub fn render_object(
             }
         }
         Shape::SymbolInstance { .. } => {}
+        Shape::Null => {
+            let color =
+                egui::Color32::from_rgba_unmultiplied(230, 120, 30, (layer_opacity * 255.0) as u8);
+            let null_stroke = egui::Stroke::new(1.5, color);
+            let size = NULL_SIZE * view.zoom;
+            let (sin_r, cos_r) = object.rotation.sin_cos();
+            let axis_x = egui::vec2(cos_r, sin_r) * size;
+            let axis_y = egui::vec2(-sin_r, cos_r) * size;
+            painter.circle_stroke(screen_pos, size * 0.5, null_stroke);
+            painter.line_segment([screen_pos - axis_x, screen_pos + axis_x], null_stroke);
+            painter.line_segment([screen_pos - axis_y, screen_pos + axis_y], null_stroke);
+        }
+        Shape::Group { children } => {
+            for child in children {
+                render_object(
+                    &library::compose_instance_transform(object, child),
+                    view,
+                    painter,
+                    layer_opacity,
+                    image_textures,
+                );
+            }
+        }
     }
 }
 
@@ -556,44 +604,29 @@ pub fn render_symbol_instances(
             continue;
         }
 
-        if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
-            for object in &objects {
-                if let Shape::SymbolInstance { symbol_id } = &object.shape
-                    && let Some(symbol) = app
-                        .project
-                        .library
-                        .symbols
-                        .iter()
-                        .find(|symbol| symbol.id == *symbol_id)
-                {
-                    for symbol_layer in symbol.layers.iter().rev() {
-                        if !symbol_layer.visible {
-                            continue;
-                        }
-                        if let Some(symbol_objects) = tween::resolve_frame(symbol_layer, 0) {
-                            for symbol_object in &symbol_objects {
-                                let mut transformed = symbol_object.clone();
-                                let cos_r = object.rotation.cos();
-                                let sin_r = object.rotation.sin();
-                                let local_x = symbol_object.position[0] * object.scale[0];
-                                let local_y = symbol_object.position[1] * object.scale[1];
-                                transformed.position[0] =
-                                    object.position[0] + local_x * cos_r - local_y * sin_r;
-                                transformed.position[1] =
-                                    object.position[1] + local_x * sin_r + local_y * cos_r;
-                                transformed.rotation += object.rotation;
-                                transformed.scale[0] *= object.scale[0];
-                                transformed.scale[1] *= object.scale[1];
-                                render_object(
-                                    &transformed,
-                                    &app.canvas_view,
-                                    painter,
-                                    layer.opacity,
-                                    Some(image_textures),
-                                );
-                            }
-                        }
-                    }
+        if let Some(objects) = tween::resolve_posed_frame(
+            layer,
+            app.current_frame,
+            &app.project.layers,
+            &app.project.armatures,
+        ) {
+            for object in &crate::group::flatten_groups(objects) {
+                if !matches!(object.shape, Shape::SymbolInstance { .. }) {
+                    continue;
+                }
+                for transformed in library::flatten_symbol_instance(
+                    &app.project.library.symbols,
+                    layer,
+                    app.current_frame,
+                    object,
+                ) {
+                    render_object(
+                        &transformed,
+                        &app.canvas_view,
+                        painter,
+                        layer.opacity,
+                        Some(image_textures),
+                    );
                 }
             }
         }
@@ -614,14 +647,10 @@ fn render_variable_width_path(
         let prev = &points[index - 1];
         let curr = &points[index];
 
-        let prev_screen = view.canvas_to_screen(egui::pos2(
-            object.position[0] + prev.position[0] * object.scale[0],
-            object.position[1] + prev.position[1] * object.scale[1],
-        ));
-        let curr_screen = view.canvas_to_screen(egui::pos2(
-            object.position[0] + curr.position[0] * object.scale[0],
-            object.position[1] + curr.position[1] * object.scale[1],
-        ));
+        let [prev_x, prev_y] = hierarchy::to_world_point(object, prev.position);
+        let [curr_x, curr_y] = hierarchy::to_world_point(object, curr.position);
+        let prev_screen = view.canvas_to_screen(egui::pos2(prev_x, prev_y));
+        let curr_screen = view.canvas_to_screen(egui::pos2(curr_x, curr_y));
 
         let avg_pressure = (prev.pressure + curr.pressure) / 2.0;
         let width = (base_width * avg_pressure).max(0.5);
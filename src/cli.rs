@@ -0,0 +1,258 @@
+use crate::export::{self, RenderSettings, VideoFormat};
+use crate::io;
+use crate::lottie;
+use crate::scenes;
+use crate::svg;
+
+const RENDER_USAGE: &str = "Usage: framekey render <project.anim> --output <path> [options]
+
+Options:
+  -o, --output <path>      Output file, or folder for png and multi-frame svg
+  -f, --format <format>    gif, png, sprite, avi, mp4, webm, wav, lottie, svg
+                           or animated-svg
+                           (inferred from the output extension if omitted)
+      --frames <range>     Frame range such as 1-24 or 12 (default: all frames)
+      --scale <factor>     Output scale factor (default: 1.0)
+      --scene <scene>      Render one scene by number or name (default: all scenes in order)
+  -h, --help               Print this help";
+
+#[derive(Clone, Copy)]
+enum RenderFormat {
+    Gif,
+    PngSequence,
+    SpriteSheet,
+    Avi,
+    Mp4,
+    WebM,
+    Wav,
+    Lottie,
+    Svg,
+    AnimatedSvg,
+}
+
+impl RenderFormat {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "gif" => Some(Self::Gif),
+            "png" => Some(Self::PngSequence),
+            "sprite" | "spritesheet" => Some(Self::SpriteSheet),
+            "avi" => Some(Self::Avi),
+            "mp4" => Some(Self::Mp4),
+            "webm" => Some(Self::WebM),
+            "wav" => Some(Self::Wav),
+            "lottie" | "json" => Some(Self::Lottie),
+            "svg" => Some(Self::Svg),
+            "animated-svg" => Some(Self::AnimatedSvg),
+            _ => None,
+        }
+    }
+}
+
+struct RenderArgs {
+    input: std::path::PathBuf,
+    output: std::path::PathBuf,
+    format: RenderFormat,
+    frames: Option<(u32, u32)>,
+    scale: f32,
+    scene: Option<String>,
+}
+
+pub fn run_render(args: &[String]) -> Result<(), String> {
+    if args
+        .iter()
+        .any(|argument| argument == "-h" || argument == "--help")
+    {
+        println!("{}", RENDER_USAGE);
+        return Ok(());
+    }
+
+    let render_args = parse_render_args(args)?;
+    let project = io::load_project(&render_args.input)
+        .map_err(|error| format!("Failed to load {}: {}", render_args.input.display(), error))?;
+    #[cfg(feature = "scripting")]
+    crate::expressions::update_context(&project);
+
+    let settings = resolve_settings(&project, &render_args)?;
+    let output = render_args.output.as_path();
+
+    match render_args.format {
+        RenderFormat::Gif => export::export_gif(&project, output, &settings),
+        RenderFormat::PngSequence => {
+            std::fs::create_dir_all(output)
+                .map_err(|error| format!("Failed to create {}: {}", output.display(), error))?;
+            export::export_png_sequence(&project, output, &settings)
+        }
+        RenderFormat::SpriteSheet => export::export_sprite_sheet(&project, output, &settings),
+        RenderFormat::Avi => render_video(&project, output, VideoFormat::Avi, &settings),
+        RenderFormat::Mp4 => render_video(&project, output, VideoFormat::Mp4, &settings),
+        RenderFormat::WebM => render_video(&project, output, VideoFormat::WebM, &settings),
+        RenderFormat::Wav => export::export_wav(&project, output, &settings),
+        RenderFormat::Lottie => lottie::export_lottie(&project, output, &settings),
+        RenderFormat::Svg => {
+            if settings.end_frame - settings.start_frame == 1 {
+                return svg::export_svg(&project, settings.start_frame, output, &settings);
+            }
+            std::fs::create_dir_all(output)
+                .map_err(|error| format!("Failed to create {}: {}", output.display(), error))?;
+            for frame in settings.frames() {
+                let path = output.join(format!("frame_{:04}.svg", frame + 1));
+                svg::export_svg(&project, frame, &path, &settings)?;
+            }
+            Ok(())
+        }
+        RenderFormat::AnimatedSvg => svg::export_animated_svg(&project, output, &settings),
+    }
+}
+
+fn render_video(
+    project: &crate::project::Project,
+    output: &std::path::Path,
+    format: VideoFormat,
+    settings: &RenderSettings,
+) -> Result<(), String> {
+    let result = export::export_video(
+        project,
+        output,
+        format,
+        settings,
+        &mut |completed, total| {
+            eprint!("\rRendering frame {}/{}", completed, total);
+            true
+        },
+    );
+    eprintln!();
+    result
+}
+
+fn parse_render_args(args: &[String]) -> Result<RenderArgs, String> {
+    let mut input = None;
+    let mut output = None;
+    let mut format = None;
+    let mut frames = None;
+    let mut scale = 1.0;
+    let mut scene = None;
+
+    let mut arguments = args.iter();
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "-o" | "--output" => {
+                output = Some(std::path::PathBuf::from(next_value(
+                    &mut arguments,
+                    argument,
+                )?));
+            }
+            "-f" | "--format" => {
+                let value = next_value(&mut arguments, argument)?;
+                format = Some(
+                    RenderFormat::parse(value)
+                        .ok_or_else(|| format!("Unknown format '{}'", value))?,
+                );
+            }
+            "--frames" => {
+                frames = Some(parse_frame_range(next_value(&mut arguments, argument)?)?);
+            }
+            "--scale" => {
+                let value = next_value(&mut arguments, argument)?;
+                scale = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|scale| scale.is_finite() && *scale > 0.0)
+                    .ok_or_else(|| format!("Invalid scale '{}'", value))?;
+            }
+            "--scene" => {
+                scene = Some(next_value(&mut arguments, argument)?.to_string());
+            }
+            _ if argument.starts_with('-') => {
+                return Err(format!("Unknown option '{}'\n\n{}", argument, RENDER_USAGE));
+            }
+            _ => {
+                if input.is_some() {
+                    return Err(format!("Unexpected argument '{}'", argument));
+                }
+                input = Some(std::path::PathBuf::from(argument));
+            }
+        }
+    }
+
+    let input = input.ok_or_else(|| format!("Missing project file\n\n{}", RENDER_USAGE))?;
+    let output = output.ok_or_else(|| format!("Missing --output\n\n{}", RENDER_USAGE))?;
+
+    let format = match format {
+        Some(format) => format,
+        None => output
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(RenderFormat::parse)
+            .ok_or_else(|| {
+                format!(
+                    "Cannot infer format from '{}', pass --format",
+                    output.display()
+                )
+            })?,
+    };
+
+    Ok(RenderArgs {
+        input,
+        output,
+        format,
+        frames,
+        scale,
+        scene,
+    })
+}
+
+fn next_value<'a>(
+    arguments: &mut std::slice::Iter<'a, String>,
+    flag: &str,
+) -> Result<&'a str, String> {
+    arguments
+        .next()
+        .map(String::as_str)
+        .ok_or_else(|| format!("Missing value for {}", flag))
+}
+
+fn parse_frame_range(value: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("Invalid frame range '{}'", value);
+    let (first, last) = match value.split_once('-') {
+        Some((first, last)) => (first.trim(), last.trim()),
+        None => (value.trim(), value.trim()),
+    };
+    let first = first.parse::<u32>().map_err(|_| invalid())?;
+    let last = last.parse::<u32>().map_err(|_| invalid())?;
+    if first == 0 || last < first {
+        return Err(invalid());
+    }
+    Ok((first, last))
+}
+
+fn resolve_settings(
+    project: &crate::project::Project,
+    render_args: &RenderArgs,
+) -> Result<RenderSettings, String> {
+    let mut settings = match &render_args.scene {
+        Some(scene) => {
+            let scene_index = scenes::find_scene(project, scene)
+                .ok_or_else(|| format!("Unknown scene '{}'", scene))?;
+            RenderSettings::scene(project, scene_index)
+        }
+        None => RenderSettings::full(project),
+    };
+    settings.scale = render_args.scale;
+
+    if let Some((first, last)) = render_args.frames {
+        if last > settings.end_frame {
+            return Err(format!(
+                "Frame range {}-{} is outside the {} frames being rendered",
+                first, last, settings.end_frame
+            ));
+        }
+        settings.start_frame = first - 1;
+        settings.end_frame = last;
+    }
+
+    if settings.end_frame <= settings.start_frame {
+        return Err("Project has no frames to render".to_string());
+    }
+
+    Ok(settings)
+}
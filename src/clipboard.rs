@@ -1,4 +1,5 @@
 use crate::app::AnimateApp;
+use crate::group;
 use crate::tween;
 
 pub fn copy_selected(app: &mut AnimateApp) {
@@ -10,7 +11,7 @@ pub fn copy_selected(app: &mut AnimateApp) {
     app.clipboard.objects.clear();
     for layer in &app.project.layers {
         if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
-            for object in &objects {
+            for object in &group::container_objects(objects, app.selection.entered_group) {
                 if selected.contains(&object.id) {
                     app.clipboard.objects.push(object.clone());
                 }
@@ -35,20 +36,23 @@ pub fn paste(app: &mut AnimateApp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Paste");
 
     let layer = &mut app.project.layers[app.active_layer];
     tween::ensure_keyframe_at(layer, app.current_frame);
 
     let mut new_ids = Vec::new();
-    if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame) {
+    if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame)
+        && let Some(container) =
+            group::container_mut(&mut keyframe.objects, app.selection.entered_group)
+    {
         for original in &app.clipboard.objects {
             let mut pasted = original.clone();
-            pasted.id = uuid::Uuid::new_v4();
+            group::assign_new_ids(&mut pasted);
             pasted.position[0] += 10.0;
             pasted.position[1] += 10.0;
             new_ids.push(pasted.id);
-            keyframe.objects.push(pasted);
+            container.push(pasted);
         }
     }
 
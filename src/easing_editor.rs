@@ -179,7 +179,7 @@ pub fn draw_easing_editor(app: &mut AnimateApp, ui_context: &egui::Context) {
     }
 
     if applied {
-        app.history.push(app.project.clone());
+        app.history.push(app.project.clone(), "Edit Easing");
         if layer_index < app.project.layers.len()
             && let Some(keyframe) = app.project.layers[layer_index].keyframes.get_mut(&frame)
         {
@@ -1,48 +1,129 @@
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, OnceLock};
 
 use nightshade::prelude::*;
+use rayon::prelude::*;
 
+use crate::audio;
+use crate::avi;
 use crate::camera;
+use crate::hierarchy;
+use crate::library;
 use crate::paint::Paint;
-use crate::project::{AnimObject, BlendMode, LayerType, PathPoint, Project, Shape};
+use crate::project::{AnimObject, BlendMode, Layer, LayerType, PathPoint, Project, Shape};
+use crate::raster;
+use crate::scenes;
 use crate::tween;
 
-pub fn export_gif(project: &Project, path: &std::path::Path) {
-    let width = project.canvas_width as u16;
-    let height = project.canvas_height as u16;
-    let delay = ((100 + project.frame_rate / 2) / project.frame_rate) as u16;
-
-    let Ok(file) = std::fs::File::create(path) else {
-        return;
-    };
+#[derive(Clone, Copy)]
+pub struct RenderSettings {
+    pub start_frame: u32,
+    pub end_frame: u32,
+    pub scale: f32,
+    pub scene: Option<usize>,
+}
 
-    let Ok(mut encoder) = gif::Encoder::new(file, width, height, &[]) else {
-        return;
-    };
+impl RenderSettings {
+    pub fn full(project: &Project) -> Self {
+        Self {
+            start_frame: 0,
+            end_frame: scenes::sequence_total_frames(project),
+            scale: 1.0,
+            scene: None,
+        }
+    }
 
-    let _ = encoder.set_repeat(gif::Repeat::Infinite);
+    pub fn scene(project: &Project, scene_index: usize) -> Self {
+        Self {
+            start_frame: 0,
+            end_frame: scenes::scene_layers(project, scene_index).1,
+            scale: 1.0,
+            scene: Some(scene_index),
+        }
+    }
 
-    for frame_index in 0..project.total_frames {
-        let rgba_image = rasterize_frame(project, frame_index);
-        let mut pixels = rgba_image.into_raw();
+    pub fn resolve_frame<'a>(&self, project: &'a Project, frame: u32) -> (&'a [Layer], u32) {
+        match self.scene {
+            Some(scene_index) => (scenes::scene_layers(project, scene_index).0, frame),
+            None => {
+                let (scene_index, local_frame) = scenes::locate_sequence_frame(project, frame);
+                (scenes::scene_layers(project, scene_index).0, local_frame)
+            }
+        }
+    }
 
-        let mut gif_frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
-        gif_frame.delay = delay;
+    pub fn frames(&self) -> std::ops::Range<u32> {
+        self.start_frame..self.end_frame
+    }
 
-        let _ = encoder.write_frame(&gif_frame);
+    pub fn output_size(&self, project: &Project) -> (u32, u32) {
+        (
+            ((project.canvas_width as f32 * self.scale).round() as u32).max(1),
+            ((project.canvas_height as f32 * self.scale).round() as u32).max(1),
+        )
     }
 }
 
-pub fn export_png_sequence(project: &Project, folder: &std::path::Path) {
-    for frame in 0..project.total_frames {
-        let image = rasterize_frame(project, frame);
-        let filename = format!("frame_{:04}.png", frame + 1);
-        let path = folder.join(filename);
-        let _ = image.save(path);
+pub fn export_gif(
+    project: &Project,
+    path: &std::path::Path,
+    settings: &RenderSettings,
+) -> Result<(), String> {
+    project.load_assets()?;
+    let (width, height) = settings.output_size(project);
+    if width > u16::MAX as u32 || height > u16::MAX as u32 {
+        return Err(format!("GIF size {}x{} is too large", width, height));
     }
+    let delay = ((100 + project.frame_rate / 2) / project.frame_rate) as u16;
+
+    let file = std::fs::File::create(path)
+        .map_err(|error| format!("Failed to create {}: {}", path.display(), error))?;
+
+    let mut encoder = gif::Encoder::new(file, width as u16, height as u16, &[])
+        .map_err(|error| format!("Failed to create GIF encoder: {}", error))?;
+
+    let _ = encoder.set_repeat(gif::Repeat::Infinite);
+
+    render_frames(
+        project,
+        settings,
+        |_, rgba_image| {
+            let mut pixels = rgba_image.into_raw();
+            let mut gif_frame =
+                gif::Frame::from_rgba_speed(width as u16, height as u16, &mut pixels, 10);
+            gif_frame.delay = delay;
+            gif_frame
+        },
+        |_, frame_index, gif_frame| {
+            encoder
+                .write_frame(&gif_frame)
+                .map_err(|error| format!("Failed to write frame {}: {}", frame_index, error))
+        },
+    )
 }
 
+pub fn export_png_sequence(
+    project: &Project,
+    folder: &std::path::Path,
+    settings: &RenderSettings,
+) -> Result<(), String> {
+    project.load_assets()?;
+    render_frames(
+        project,
+        settings,
+        |frame, image| {
+            let path = folder.join(format!("frame_{:04}.png", frame + 1));
+            image
+                .save(&path)
+                .map_err(|error| format!("Failed to save {}: {}", path.display(), error))
+        },
+        |_, _, result| result,
+    )
+}
+
+#[derive(Clone, Copy)]
 pub enum VideoFormat {
+    Avi,
     Mp4,
     WebM,
 }
@@ -51,31 +132,184 @@ pub fn export_video(
     project: &Project,
     path: &std::path::Path,
     format: VideoFormat,
+    settings: &RenderSettings,
+    progress: &mut dyn FnMut(u32, u32) -> bool,
+) -> Result<(), String> {
+    project.load_assets()?;
+    let frame_count = settings.end_frame.saturating_sub(settings.start_frame);
+    if frame_count == 0 {
+        return Err("No frames to export".to_string());
+    }
+    let audio = (!project.audio_tracks.is_empty()).then(|| mixdown_audio(project, settings));
+
+    match format {
+        VideoFormat::Avi => {
+            let result = export_avi(project, path, settings, audio.as_deref(), progress);
+            if result.is_err() {
+                let _ = std::fs::remove_file(path);
+            }
+            result
+        }
+        VideoFormat::Mp4 | VideoFormat::WebM => {
+            export_ffmpeg_video(project, path, format, settings, audio.as_deref(), progress)
+        }
+    }
+}
+
+pub fn export_wav(
+    project: &Project,
+    path: &std::path::Path,
+    settings: &RenderSettings,
+) -> Result<(), String> {
+    project.load_assets()?;
+    if project.audio_tracks.is_empty() {
+        return Err("Project has no audio tracks".to_string());
+    }
+    if settings.end_frame <= settings.start_frame {
+        return Err("No frames to export".to_string());
+    }
+    audio::write_wav(
+        path,
+        audio::MIXDOWN_SAMPLE_RATE,
+        &mixdown_audio(project, settings),
+    )
+}
+
+fn mixdown_audio(project: &Project, settings: &RenderSettings) -> Vec<i16> {
+    let audio_start = settings.start_frame
+        + settings
+            .scene
+            .map(|scene_index| scenes::scene_start_frame(project, scene_index))
+            .unwrap_or(0);
+    audio::mix_audio_tracks(
+        project,
+        audio_start,
+        settings.end_frame.saturating_sub(settings.start_frame),
+        audio::MIXDOWN_SAMPLE_RATE,
+    )
+}
+
+fn check_progress(
+    progress: &mut dyn FnMut(u32, u32) -> bool,
+    completed: usize,
+    total: u32,
+) -> Result<(), String> {
+    if progress(completed as u32, total) {
+        Ok(())
+    } else {
+        Err("Export cancelled".to_string())
+    }
+}
+
+fn export_avi(
+    project: &Project,
+    path: &std::path::Path,
+    settings: &RenderSettings,
+    audio: Option<&[i16]>,
+    progress: &mut dyn FnMut(u32, u32) -> bool,
+) -> Result<(), String> {
+    let (width, height) = settings.output_size(project);
+    let frame_count = settings.end_frame - settings.start_frame;
+    let audio_format = audio.map(|samples| avi::AviAudioFormat {
+        sample_rate: audio::MIXDOWN_SAMPLE_RATE,
+        sample_frames: (samples.len() / 2) as u32,
+    });
+    let mut writer = avi::AviWriter::create(
+        path,
+        width,
+        height,
+        project.frame_rate,
+        frame_count,
+        audio_format,
+    )?;
+
+    render_frames(
+        project,
+        settings,
+        |frame, image| {
+            let rgb = image::DynamicImage::ImageRgba8(image).to_rgb8();
+            let mut jpeg = Vec::new();
+            image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 90)
+                .encode_image(&rgb)
+                .map(|_| jpeg)
+                .map_err(|error| format!("Failed to encode frame {}: {}", frame + 1, error))
+        },
+        |index, _, jpeg| {
+            writer.write_video_frame(&jpeg?)?;
+            if let Some(samples) = audio {
+                let start =
+                    audio::mixdown_sample_count(project, index as u32, audio::MIXDOWN_SAMPLE_RATE);
+                let end = audio::mixdown_sample_count(
+                    project,
+                    index as u32 + 1,
+                    audio::MIXDOWN_SAMPLE_RATE,
+                );
+                writer.write_audio(&samples[start * 2..end * 2])?;
+            }
+            check_progress(progress, index + 1, frame_count)
+        },
+    )?;
+
+    writer.finish()
+}
+
+fn export_ffmpeg_video(
+    project: &Project,
+    path: &std::path::Path,
+    format: VideoFormat,
+    settings: &RenderSettings,
+    audio: Option<&[i16]>,
+    progress: &mut dyn FnMut(u32, u32) -> bool,
 ) -> Result<(), String> {
     let temp_dir = std::env::temp_dir().join(format!("framekey_export_{}", uuid::Uuid::new_v4()));
     std::fs::create_dir_all(&temp_dir)
         .map_err(|error| format!("Failed to create temp directory: {}", error))?;
 
-    for frame in 0..project.total_frames {
-        let image = rasterize_frame(project, frame);
-        let filename = format!("frame_{:05}.png", frame);
-        let frame_path = temp_dir.join(&filename);
-        image
-            .save(&frame_path)
-            .map_err(|error| format!("Failed to save frame {}: {}", frame, error))?;
-    }
+    let result = encode_ffmpeg_video(project, path, format, settings, audio, progress, &temp_dir);
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    result
+}
 
-    let input_pattern = temp_dir.join("frame_%05d.png");
-    let input_pattern_str = input_pattern.to_string_lossy().to_string();
-    let output_str = path.to_string_lossy().to_string();
+fn encode_ffmpeg_video(
+    project: &Project,
+    path: &std::path::Path,
+    format: VideoFormat,
+    settings: &RenderSettings,
+    audio: Option<&[i16]>,
+    progress: &mut dyn FnMut(u32, u32) -> bool,
+    temp_dir: &std::path::Path,
+) -> Result<(), String> {
+    let first_frame = settings.start_frame;
+    let frame_count = settings.end_frame - settings.start_frame;
+    render_frames(
+        project,
+        settings,
+        |frame, image| {
+            let frame_path = temp_dir.join(format!("frame_{:05}.png", frame - first_frame));
+            image
+                .save(&frame_path)
+                .map_err(|error| format!("Failed to save frame {}: {}", frame, error))
+        },
+        |index, _, result| {
+            result?;
+            check_progress(progress, index + 1, frame_count)
+        },
+    )?;
 
+    let input_pattern = temp_dir.join("frame_%05d.png");
     let mut command = std::process::Command::new("ffmpeg");
     command
         .arg("-y")
         .arg("-framerate")
         .arg(project.frame_rate.to_string())
         .arg("-i")
-        .arg(&input_pattern_str);
+        .arg(&input_pattern);
+
+    if let Some(samples) = audio {
+        let audio_path = temp_dir.join("audio.wav");
+        audio::write_wav(&audio_path, audio::MIXDOWN_SAMPLE_RATE, samples)?;
+        command.arg("-i").arg(&audio_path).arg("-shortest");
+    }
 
     match format {
         VideoFormat::Mp4 => {
@@ -87,7 +321,9 @@ pub fn export_video(
                 .arg("-crf")
                 .arg("18")
                 .arg("-preset")
-                .arg("medium");
+                .arg("medium")
+                .arg("-c:a")
+                .arg("aac");
         }
         VideoFormat::WebM => {
             command
@@ -98,18 +334,19 @@ pub fn export_video(
                 .arg("-b:v")
                 .arg("0")
                 .arg("-pix_fmt")
-                .arg("yuva420p");
+                .arg("yuva420p")
+                .arg("-c:a")
+                .arg("libopus");
         }
+        VideoFormat::Avi => {}
     }
 
-    command.arg(&output_str);
+    command.arg(path);
 
     let output = command
         .output()
         .map_err(|error| format!("Failed to run ffmpeg (is it installed?): {}", error))?;
 
-    let _ = std::fs::remove_dir_all(&temp_dir);
-
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(format!("ffmpeg failed: {}", stderr));
@@ -118,31 +355,141 @@ pub fn export_video(
     Ok(())
 }
 
-pub fn export_sprite_sheet(project: &Project, path: &std::path::Path) {
-    let columns = (project.total_frames as f64).sqrt().ceil() as u32;
-    let rows = project.total_frames.div_ceil(columns);
+pub struct VideoExportJob {
+    pub path: std::path::PathBuf,
+    pub total_frames: u32,
+    completed_frames: Arc<AtomicU32>,
+    cancel_requested: Arc<AtomicBool>,
+    handle: Option<std::thread::JoinHandle<Result<(), String>>>,
+}
+
+impl VideoExportJob {
+    pub fn spawn(
+        project: Project,
+        path: std::path::PathBuf,
+        format: VideoFormat,
+        settings: RenderSettings,
+    ) -> Self {
+        let completed_frames = Arc::new(AtomicU32::new(0));
+        let cancel_requested = Arc::new(AtomicBool::new(false));
+        let thread_completed = completed_frames.clone();
+        let thread_cancel = cancel_requested.clone();
+        let thread_path = path.clone();
+        let handle = std::thread::spawn(move || {
+            export_video(
+                &project,
+                &thread_path,
+                format,
+                &settings,
+                &mut |completed, _| {
+                    thread_completed.store(completed, Ordering::Relaxed);
+                    !thread_cancel.load(Ordering::Relaxed)
+                },
+            )
+        });
+
+        Self {
+            path,
+            total_frames: settings.end_frame.saturating_sub(settings.start_frame),
+            completed_frames,
+            cancel_requested,
+            handle: Some(handle),
+        }
+    }
 
-    let sheet_width = columns * project.canvas_width;
-    let sheet_height = rows * project.canvas_height;
+    pub fn completed_frames(&self) -> u32 {
+        self.completed_frames.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel(&self) {
+        self.cancel_requested.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_requested.load(Ordering::Relaxed)
+    }
+
+    pub fn poll(&mut self) -> Option<Result<(), String>> {
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        let handle = self.handle.take()?;
+        Some(
+            handle
+                .join()
+                .unwrap_or_else(|_| Err("Video export thread panicked".to_string())),
+        )
+    }
+}
+
+pub fn export_sprite_sheet(
+    project: &Project,
+    path: &std::path::Path,
+    settings: &RenderSettings,
+) -> Result<(), String> {
+    project.load_assets()?;
+    let frame_count = settings.end_frame.saturating_sub(settings.start_frame);
+    if frame_count == 0 {
+        return Err("No frames to export".to_string());
+    }
+    let (frame_width, frame_height) = settings.output_size(project);
+    let columns = (frame_count as f64).sqrt().ceil() as u32;
+    let rows = frame_count.div_ceil(columns);
+
+    let sheet_width = columns * frame_width;
+    let sheet_height = rows * frame_height;
 
     let mut sheet: image::RgbaImage = image::ImageBuffer::new(sheet_width, sheet_height);
 
-    for frame in 0..project.total_frames {
-        let frame_image = rasterize_frame(project, frame);
-        let column = frame % columns;
-        let row = frame / columns;
-        let offset_x = column * project.canvas_width;
-        let offset_y = row * project.canvas_height;
-
-        for y in 0..project.canvas_height {
-            for x in 0..project.canvas_width {
-                let pixel = frame_image.get_pixel(x, y);
-                sheet.put_pixel(offset_x + x, offset_y + y, *pixel);
-            }
+    render_frames(
+        project,
+        settings,
+        |_, frame_image| frame_image,
+        |index, _, frame_image| {
+            let column = index as u32 % columns;
+            let row = index as u32 / columns;
+            image::imageops::replace(
+                &mut sheet,
+                &frame_image,
+                (column * frame_width) as i64,
+                (row * frame_height) as i64,
+            );
+            Ok(())
+        },
+    )?;
+
+    sheet
+        .save(path)
+        .map_err(|error| format!("Failed to save {}: {}", path.display(), error))
+}
+
+pub fn render_thumbnail(project: &Project, max_size: u32) -> image::RgbaImage {
+    let mut settings = RenderSettings::full(project);
+    let longest_side = project.canvas_width.max(project.canvas_height).max(1);
+    settings.scale = (max_size as f32 / longest_side as f32).min(1.0);
+    rasterize_frame(project, 0, &settings)
+}
+
+fn render_frames<T: Send>(
+    project: &Project,
+    settings: &RenderSettings,
+    prepare: impl Fn(u32, image::RgbaImage) -> T + Sync,
+    mut consume: impl FnMut(usize, u32, T) -> Result<(), String>,
+) -> Result<(), String> {
+    let frames: Vec<u32> = settings.frames().collect();
+    let batch_size = rayon::current_num_threads().max(1) * 2;
+
+    for (batch_index, batch) in frames.chunks(batch_size).enumerate() {
+        let prepared: Vec<T> = batch
+            .par_iter()
+            .map(|frame| prepare(*frame, rasterize_frame(project, *frame, settings)))
+            .collect();
+        for (offset, (frame, value)) in batch.iter().zip(prepared).enumerate() {
+            consume(batch_index * batch_size + offset, *frame, value)?;
         }
     }
 
-    let _ = sheet.save(path);
+    Ok(())
 }
 
 fn apply_camera_to_object(
@@ -160,9 +507,32 @@ fn apply_camera_to_object(
     transformed
 }
 
-fn rasterize_frame(project: &Project, frame: u32) -> image::RgbaImage {
-    let width = project.canvas_width;
-    let height = project.canvas_height;
+fn scale_object(object: &mut AnimObject, factor: f32) {
+    object.position[0] *= factor;
+    object.position[1] *= factor;
+    object.scale[0] *= factor;
+    object.scale[1] *= factor;
+    object.stroke_width *= factor;
+}
+
+fn resolve_render_objects(
+    project: &Project,
+    layers: &[Layer],
+    layer: &Layer,
+    frame: u32,
+) -> Option<Vec<AnimObject>> {
+    tween::resolve_posed_frame(layer, frame, layers, &project.armatures).map(|objects| {
+        library::expand_symbol_instances(&project.library.symbols, layer, frame, objects)
+    })
+}
+
+fn rasterize_frame(project: &Project, frame: u32, settings: &RenderSettings) -> image::RgbaImage {
+    let (layers, frame) = settings.resolve_frame(project, frame);
+    let scale = settings.scale;
+    let canvas_width = project.canvas_width as f32;
+    let canvas_height = project.canvas_height as f32;
+    let width = ((canvas_width * scale).round() as u32).max(1);
+    let height = ((canvas_height * scale).round() as u32).max(1);
 
     let cam = camera::resolve_camera(project, frame);
     let has_camera = !project.camera_keyframes.is_empty();
@@ -178,8 +548,8 @@ fn rasterize_frame(project: &Project, frame: u32) -> image::RgbaImage {
     let mut image_buffer: image::RgbaImage =
         image::ImageBuffer::from_pixel(width, height, bg_pixel);
 
-    for layer_index in (0..project.layers.len()).rev() {
-        let layer = &project.layers[layer_index];
+    for layer_index in (0..layers.len()).rev() {
+        let layer = &layers[layer_index];
         if !layer.visible {
             continue;
         }
@@ -191,22 +561,25 @@ fn rasterize_frame(project: &Project, frame: u32) -> image::RgbaImage {
         }
 
         let is_masked = layer_index > 0 && {
-            let above = &project.layers[layer_index - 1];
+            let above = &layers[layer_index - 1];
             above.layer_type == LayerType::Mask && above.visible
         };
 
         if is_masked {
-            let mask_layer = &project.layers[layer_index - 1];
+            let mask_layer = &layers[layer_index - 1];
 
             let mut layer_buffer: image::RgbaImage =
                 image::ImageBuffer::from_pixel(width, height, image::Rgba([0, 0, 0, 0]));
-            if let Some(objects) = tween::resolve_frame(layer, frame) {
+            if let Some(objects) = resolve_render_objects(project, layers, layer, frame) {
                 for object in &objects {
-                    let render_obj = if has_camera {
-                        apply_camera_to_object(object, &cam, width as f32, height as f32)
+                    let mut render_obj = if has_camera {
+                        apply_camera_to_object(object, &cam, canvas_width, canvas_height)
                     } else {
                         object.clone()
                     };
+                    if scale != 1.0 {
+                        scale_object(&mut render_obj, scale);
+                    }
                     rasterize_object_with_assets(
                         &mut layer_buffer,
                         &render_obj,
@@ -218,13 +591,16 @@ fn rasterize_frame(project: &Project, frame: u32) -> image::RgbaImage {
 
             let mut mask_buffer: image::RgbaImage =
                 image::ImageBuffer::from_pixel(width, height, image::Rgba([0, 0, 0, 0]));
-            if let Some(objects) = tween::resolve_frame(mask_layer, frame) {
+            if let Some(objects) = resolve_render_objects(project, layers, mask_layer, frame) {
                 for object in &objects {
-                    let render_obj = if has_camera {
-                        apply_camera_to_object(object, &cam, width as f32, height as f32)
+                    let mut render_obj = if has_camera {
+                        apply_camera_to_object(object, &cam, canvas_width, canvas_height)
                     } else {
                         object.clone()
                     };
+                    if scale != 1.0 {
+                        scale_object(&mut render_obj, scale);
+                    }
                     rasterize_object_with_assets(
                         &mut mask_buffer,
                         &render_obj,
@@ -234,32 +610,25 @@ fn rasterize_frame(project: &Project, frame: u32) -> image::RgbaImage {
                 }
             }
 
-            for y in 0..height {
-                for x in 0..width {
-                    let layer_pixel = layer_buffer.get_pixel(x, y);
-                    let mask_pixel = mask_buffer.get_pixel(x, y);
-                    let mask_alpha = mask_pixel[3] as f32 / 255.0;
-                    let result = image::Rgba([
-                        layer_pixel[0],
-                        layer_pixel[1],
-                        layer_pixel[2],
-                        (layer_pixel[3] as f32 * mask_alpha) as u8,
-                    ]);
-                    layer_buffer.put_pixel(x, y, result);
-                }
+            for (layer_pixel, mask_pixel) in layer_buffer.pixels_mut().zip(mask_buffer.pixels()) {
+                let mask_alpha = mask_pixel[3] as f32 / 255.0;
+                layer_pixel[3] = (layer_pixel[3] as f32 * mask_alpha).round() as u8;
             }
 
             composite_layer(&mut image_buffer, &layer_buffer, layer.blend_mode);
         } else if layer.blend_mode != BlendMode::Normal {
             let mut layer_buffer: image::RgbaImage =
                 image::ImageBuffer::from_pixel(width, height, image::Rgba([0, 0, 0, 0]));
-            if let Some(objects) = tween::resolve_frame(layer, frame) {
+            if let Some(objects) = resolve_render_objects(project, layers, layer, frame) {
                 for object in &objects {
-                    let render_obj = if has_camera {
-                        apply_camera_to_object(object, &cam, width as f32, height as f32)
+                    let mut render_obj = if has_camera {
+                        apply_camera_to_object(object, &cam, canvas_width, canvas_height)
                     } else {
                         object.clone()
                     };
+                    if scale != 1.0 {
+                        scale_object(&mut render_obj, scale);
+                    }
                     rasterize_object_with_assets(
                         &mut layer_buffer,
                         &render_obj,
@@ -269,13 +638,16 @@ fn rasterize_frame(project: &Project, frame: u32) -> image::RgbaImage {
                 }
             }
             composite_layer(&mut image_buffer, &layer_buffer, layer.blend_mode);
-        } else if let Some(objects) = tween::resolve_frame(layer, frame) {
+        } else if let Some(objects) = resolve_render_objects(project, layers, layer, frame) {
             for object in &objects {
-                let render_obj = if has_camera {
-                    apply_camera_to_object(object, &cam, width as f32, height as f32)
+                let mut render_obj = if has_camera {
+                    apply_camera_to_object(object, &cam, canvas_width, canvas_height)
                 } else {
                     object.clone()
                 };
+                if scale != 1.0 {
+                    scale_object(&mut render_obj, scale);
+                }
                 rasterize_object_with_assets(
                     &mut image_buffer,
                     &render_obj,
@@ -290,17 +662,11 @@ fn rasterize_frame(project: &Project, frame: u32) -> image::RgbaImage {
 }
 
 fn composite_layer(dst: &mut image::RgbaImage, src: &image::RgbaImage, blend_mode: BlendMode) {
-    let (width, height) = dst.dimensions();
-    for y in 0..height {
-        for x in 0..width {
-            let src_pixel = src.get_pixel(x, y);
-            if src_pixel[3] == 0 {
-                continue;
-            }
-            let dst_pixel = *dst.get_pixel(x, y);
-            let blended = blend_pixel_with_mode(&dst_pixel, src_pixel, blend_mode);
-            dst.put_pixel(x, y, blended);
+    for (dst_pixel, src_pixel) in dst.pixels_mut().zip(src.pixels()) {
+        if src_pixel[3] == 0 {
+            continue;
         }
+        *dst_pixel = blend_pixel_with_mode(dst_pixel, src_pixel, blend_mode);
     }
 }
 
@@ -403,10 +769,6 @@ fn rasterize_object_with_assets(
     layer_opacity: f32,
     image_assets: &[crate::project::ImageAsset],
 ) {
-    let (width, height) = image_buffer.dimensions();
-    let fill = sample_paint_solid(&object.fill);
-    let stroke = sample_paint_solid(&object.stroke);
-
     match &object.shape {
         Shape::Rectangle {
             width: rect_width,
@@ -415,172 +777,59 @@ fn rasterize_object_with_assets(
         } => {
             let half_w = rect_width * object.scale[0] / 2.0;
             let half_h = rect_height * object.scale[1] / 2.0;
-            let radius = corner_radius.min(half_w).min(half_h);
-            let diagonal = (half_w * half_w + half_h * half_h).sqrt();
-            let bound_w = if object.rotation.abs() > 0.001 {
-                diagonal
-            } else {
-                half_w
-            };
-            let bound_h = if object.rotation.abs() > 0.001 {
-                diagonal
-            } else {
-                half_h
-            };
-            let min_x = ((object.position[0] - bound_w).floor() as i32).max(0) as u32;
-            let min_y = ((object.position[1] - bound_h).floor() as i32).max(0) as u32;
-            let max_x = ((object.position[0] + bound_w).ceil() as u32).min(width - 1);
-            let max_y = ((object.position[1] + bound_h).ceil() as u32).min(height - 1);
-
-            for y in min_y..=max_y {
-                for x in min_x..=max_x {
-                    let local_x = x as f32 - object.position[0];
-                    let local_y = y as f32 - object.position[1];
-                    let cos_r = (-object.rotation).cos();
-                    let sin_r = (-object.rotation).sin();
-                    let unrotated_x = local_x * cos_r - local_y * sin_r;
-                    let unrotated_y = local_x * sin_r + local_y * cos_r;
-
-                    let dist = rounded_rect_dist(unrotated_x, unrotated_y, half_w, half_h, radius);
-                    if dist <= 0.0 {
-                        let inner_dist = if object.stroke_width > 0.0 {
-                            rounded_rect_dist(
-                                unrotated_x,
-                                unrotated_y,
-                                (half_w - object.stroke_width).max(0.0),
-                                (half_h - object.stroke_width).max(0.0),
-                                (radius - object.stroke_width).max(0.0),
-                            )
-                        } else {
-                            -1.0
-                        };
-
-                        let color = if inner_dist > 0.0 {
-                            sample_paint_at_local(
-                                &object.stroke,
-                                unrotated_x,
-                                unrotated_y,
-                                half_w,
-                                half_h,
-                            )
-                        } else {
-                            sample_paint_at_local(
-                                &object.fill,
-                                unrotated_x,
-                                unrotated_y,
-                                half_w,
-                                half_h,
-                            )
-                        };
-
-                        blend_pixel(image_buffer, x, y, color, layer_opacity);
-                    }
-                }
-            }
+            let outline = raster::rounded_rect_outline(
+                object.position,
+                half_w,
+                half_h,
+                *corner_radius,
+                object.rotation,
+            );
+            let area = PaintArea::new(object.position, object.rotation, half_w, half_h);
+            fill_polygon(image_buffer, &outline, &object.fill, &area, layer_opacity);
+            stroke_polyline(
+                image_buffer,
+                &outline,
+                &vec![object.stroke_width / 2.0; outline.len()],
+                true,
+                &object.stroke,
+                &area,
+                layer_opacity,
+            );
         }
         Shape::Ellipse { radius_x, radius_y } => {
             let scaled_rx = radius_x * object.scale[0];
             let scaled_ry = radius_y * object.scale[1];
-            let max_radius = scaled_rx.max(scaled_ry);
-            let bound_x = if object.rotation.abs() > 0.001 {
-                max_radius
-            } else {
-                scaled_rx
-            };
-            let bound_y = if object.rotation.abs() > 0.001 {
-                max_radius
-            } else {
-                scaled_ry
-            };
-            let min_x =
-                ((object.position[0] - bound_x - object.stroke_width).floor() as i32).max(0) as u32;
-            let min_y =
-                ((object.position[1] - bound_y - object.stroke_width).floor() as i32).max(0) as u32;
-            let max_x =
-                ((object.position[0] + bound_x + object.stroke_width).ceil() as u32).min(width - 1);
-            let max_y = ((object.position[1] + bound_y + object.stroke_width).ceil() as u32)
-                .min(height - 1);
-
-            for y in min_y..=max_y {
-                for x in min_x..=max_x {
-                    let local_x = x as f32 - object.position[0];
-                    let local_y = y as f32 - object.position[1];
-                    let cos_r = (-object.rotation).cos();
-                    let sin_r = (-object.rotation).sin();
-                    let unrotated_x = local_x * cos_r - local_y * sin_r;
-                    let unrotated_y = local_x * sin_r + local_y * cos_r;
-
-                    if scaled_rx > 0.001 && scaled_ry > 0.001 {
-                        let dist =
-                            (unrotated_x / scaled_rx).powi(2) + (unrotated_y / scaled_ry).powi(2);
-                        if dist <= 1.0 {
-                            let inner_rx = (scaled_rx - object.stroke_width).max(0.0);
-                            let inner_ry = (scaled_ry - object.stroke_width).max(0.0);
-                            let inner_dist = if inner_rx > 0.0 && inner_ry > 0.0 {
-                                (unrotated_x / inner_rx).powi(2) + (unrotated_y / inner_ry).powi(2)
-                            } else {
-                                2.0
-                            };
-
-                            let color = if inner_dist > 1.0 && object.stroke_width > 0.0 {
-                                sample_paint_at_local(
-                                    &object.stroke,
-                                    unrotated_x,
-                                    unrotated_y,
-                                    scaled_rx,
-                                    scaled_ry,
-                                )
-                            } else {
-                                sample_paint_at_local(
-                                    &object.fill,
-                                    unrotated_x,
-                                    unrotated_y,
-                                    scaled_rx,
-                                    scaled_ry,
-                                )
-                            };
-
-                            blend_pixel(image_buffer, x, y, color, layer_opacity);
-                        }
-                    }
-                }
+            if scaled_rx <= 0.001 || scaled_ry <= 0.001 {
+                return;
             }
+            let outline =
+                raster::ellipse_outline(object.position, scaled_rx, scaled_ry, object.rotation);
+            let area = PaintArea::new(object.position, object.rotation, scaled_rx, scaled_ry);
+            fill_polygon(image_buffer, &outline, &object.fill, &area, layer_opacity);
+            stroke_polyline(
+                image_buffer,
+                &outline,
+                &vec![object.stroke_width / 2.0; outline.len()],
+                true,
+                &object.stroke,
+                &area,
+                layer_opacity,
+            );
         }
         Shape::Line { end_x, end_y } => {
-            let start_x = object.position[0];
-            let start_y = object.position[1];
-            let line_end_x = start_x + end_x * object.scale[0];
-            let line_end_y = start_y + end_y * object.scale[1];
-
-            let thickness = object.stroke_width.max(1.0);
-            let min_px = ((start_x.min(line_end_x) - thickness).floor() as i32).max(0) as u32;
-            let min_py = ((start_y.min(line_end_y) - thickness).floor() as i32).max(0) as u32;
-            let max_px = ((start_x.max(line_end_x) + thickness).ceil() as u32).min(width - 1);
-            let max_py = ((start_y.max(line_end_y) + thickness).ceil() as u32).min(height - 1);
-
-            let dx = line_end_x - start_x;
-            let dy = line_end_y - start_y;
-            let line_len_sq = dx * dx + dy * dy;
-
-            for y in min_py..=max_py {
-                for x in min_px..=max_px {
-                    let px = x as f32 - start_x;
-                    let py = y as f32 - start_y;
-
-                    if line_len_sq < 0.001 {
-                        continue;
-                    }
-
-                    let t = ((px * dx + py * dy) / line_len_sq).clamp(0.0, 1.0);
-                    let closest_x = t * dx;
-                    let closest_y = t * dy;
-                    let dist = ((px - closest_x).powi(2) + (py - closest_y).powi(2)).sqrt();
-
-                    if dist <= thickness / 2.0 {
-                        blend_pixel(image_buffer, x, y, stroke, layer_opacity);
-                    }
-                }
-            }
+            let start = object.position;
+            let end = hierarchy::to_world_point(object, [*end_x, *end_y]);
+            let half_thickness = object.stroke_width.max(1.0) / 2.0;
+            let area = PaintArea::from_points(&[start, end]);
+            stroke_polyline(
+                image_buffer,
+                &[start, end],
+                &[half_thickness, half_thickness],
+                false,
+                &object.stroke,
+                &area,
+                layer_opacity,
+            );
         }
         Shape::Path { points, closed } => {
             if points.len() < 2 {
@@ -597,25 +846,27 @@ fn rasterize_object_with_assets(
             }
 
             let canvas_points = build_path_points(object, points, *closed);
+            let area = PaintArea::from_points(&canvas_points);
+            let is_filled = *closed && canvas_points.len() >= 3;
 
-            if *closed && canvas_points.len() >= 3 {
-                rasterize_closed_path(
-                    image_buffer,
-                    &canvas_points,
-                    fill,
-                    stroke,
-                    object.stroke_width,
-                    layer_opacity,
-                );
-            } else {
-                rasterize_open_path(
+            if is_filled {
+                fill_polygon(
                     image_buffer,
                     &canvas_points,
-                    stroke,
-                    object.stroke_width,
+                    &object.fill,
+                    &area,
                     layer_opacity,
                 );
             }
+            stroke_polyline(
+                image_buffer,
+                &canvas_points,
+                &vec![object.stroke_width / 2.0; canvas_points.len()],
+                is_filled,
+                &object.stroke,
+                &area,
+                layer_opacity,
+            );
         }
         Shape::Text {
             content, font_size, ..
@@ -631,7 +882,10 @@ fn rasterize_object_with_assets(
             let Some(asset) = image_assets.iter().find(|asset| asset.id == *image_id) else {
                 return;
             };
-            let Ok(source_image) = image::load_from_memory(&asset.data) else {
+            let Ok(bytes) = asset.data.bytes() else {
+                return;
+            };
+            let Ok(source_image) = image::load_from_memory(bytes) else {
                 return;
             };
             let scaled = image::imageops::resize(
@@ -640,42 +894,158 @@ fn rasterize_object_with_assets(
                 (display_height * object.scale[1]) as u32,
                 image::imageops::FilterType::Lanczos3,
             );
+            let (width, height) = image_buffer.dimensions();
             let (scaled_w, scaled_h) = scaled.dimensions();
-            let half_w = scaled_w as f32 / 2.0;
-            let half_h = scaled_h as f32 / 2.0;
-            let origin_x = object.position[0] - half_w;
-            let origin_y = object.position[1] - half_h;
-
-            for src_y in 0..scaled_h {
-                for src_x in 0..scaled_w {
-                    let dst_x = (origin_x + src_x as f32) as i32;
-                    let dst_y = (origin_y + src_y as f32) as i32;
-                    if dst_x >= 0 && dst_y >= 0 && (dst_x as u32) < width && (dst_y as u32) < height
-                    {
-                        let src_pixel = scaled.get_pixel(src_x, src_y);
-                        let color = [
-                            src_pixel[0] as f32 / 255.0,
-                            src_pixel[1] as f32 / 255.0,
-                            src_pixel[2] as f32 / 255.0,
-                            src_pixel[3] as f32 / 255.0,
-                        ];
-                        blend_pixel(
-                            image_buffer,
-                            dst_x as u32,
-                            dst_y as u32,
-                            color,
-                            layer_opacity,
-                        );
-                    }
+            let origin_x = (object.position[0] - scaled_w as f32 / 2.0) as i32;
+            let origin_y = (object.position[1] - scaled_h as f32 / 2.0) as i32;
+
+            for (src_x, src_y, src_pixel) in scaled.enumerate_pixels() {
+                let dst_x = origin_x + src_x as i32;
+                let dst_y = origin_y + src_y as i32;
+                if dst_x >= 0 && dst_y >= 0 && (dst_x as u32) < width && (dst_y as u32) < height {
+                    let color = [
+                        src_pixel[0] as f32 / 255.0,
+                        src_pixel[1] as f32 / 255.0,
+                        src_pixel[2] as f32 / 255.0,
+                        src_pixel[3] as f32 / 255.0,
+                    ];
+                    blend_pixel(
+                        image_buffer,
+                        dst_x as u32,
+                        dst_y as u32,
+                        color,
+                        layer_opacity,
+                    );
                 }
             }
         }
-        Shape::SymbolInstance { .. } => {}
+        Shape::SymbolInstance { .. } | Shape::Null => {}
+        Shape::Group { children } => {
+            for child in children {
+                rasterize_object_with_assets(
+                    image_buffer,
+                    &library::compose_instance_transform(object, child),
+                    layer_opacity,
+                    image_assets,
+                );
+            }
+        }
+    }
+}
+
+struct PaintArea {
+    center: [f32; 2],
+    rotation: f32,
+    half_w: f32,
+    half_h: f32,
+}
+
+impl PaintArea {
+    fn new(center: [f32; 2], rotation: f32, half_w: f32, half_h: f32) -> Self {
+        Self {
+            center,
+            rotation,
+            half_w,
+            half_h,
+        }
+    }
+
+    fn from_points(points: &[[f32; 2]]) -> Self {
+        let bounds = raster::polygon_bounds(points);
+        Self::new(
+            [(bounds[0] + bounds[2]) / 2.0, (bounds[1] + bounds[3]) / 2.0],
+            0.0,
+            (bounds[2] - bounds[0]) / 2.0,
+            (bounds[3] - bounds[1]) / 2.0,
+        )
+    }
+
+    fn sample(&self, paint: &Paint, x: f32, y: f32) -> [f32; 4] {
+        if let Paint::Solid(color) = paint {
+            return *color;
+        }
+        let local_x = x - self.center[0];
+        let local_y = y - self.center[1];
+        let cos_r = (-self.rotation).cos();
+        let sin_r = (-self.rotation).sin();
+        sample_paint_at_local(
+            paint,
+            local_x * cos_r - local_y * sin_r,
+            local_x * sin_r + local_y * cos_r,
+            self.half_w,
+            self.half_h,
+        )
     }
 }
 
-fn sample_paint_solid(paint: &Paint) -> [f32; 4] {
-    paint.as_solid()
+fn is_paint_visible(paint: &Paint) -> bool {
+    match paint {
+        Paint::Solid(color) => color[3] > 0.001,
+        Paint::LinearGradient { stops, .. } | Paint::RadialGradient { stops, .. } => {
+            stops.iter().any(|stop| stop.color[3] > 0.001)
+        }
+    }
+}
+
+fn fill_polygon(
+    image_buffer: &mut image::RgbaImage,
+    polygon: &[[f32; 2]],
+    paint: &Paint,
+    area: &PaintArea,
+    layer_opacity: f32,
+) {
+    if !is_paint_visible(paint) {
+        return;
+    }
+    let (width, height) = image_buffer.dimensions();
+    let Some(mut rasterizer) =
+        raster::Rasterizer::new(width, height, raster::polygon_bounds(polygon))
+    else {
+        return;
+    };
+    rasterizer.fill_polygon(polygon);
+    paint_coverage(image_buffer, &rasterizer, paint, area, layer_opacity);
+}
+
+fn stroke_polyline(
+    image_buffer: &mut image::RgbaImage,
+    points: &[[f32; 2]],
+    half_widths: &[f32],
+    closed: bool,
+    paint: &Paint,
+    area: &PaintArea,
+    layer_opacity: f32,
+) {
+    if !is_paint_visible(paint) || half_widths.iter().all(|half_width| *half_width < 0.05) {
+        return;
+    }
+    let (width, height) = image_buffer.dimensions();
+    let Some(mut rasterizer) =
+        raster::Rasterizer::new(width, height, raster::polyline_bounds(points, half_widths))
+    else {
+        return;
+    };
+    raster::stroke_polyline(&mut rasterizer, points, half_widths, closed);
+    paint_coverage(image_buffer, &rasterizer, paint, area, layer_opacity);
+}
+
+fn paint_coverage(
+    image_buffer: &mut image::RgbaImage,
+    rasterizer: &raster::Rasterizer,
+    paint: &Paint,
+    area: &PaintArea,
+    layer_opacity: f32,
+) {
+    rasterizer.for_each_pixel(|x, y, coverage| {
+        let color = area.sample(paint, x as f32 + 0.5, y as f32 + 0.5);
+        blend_pixel(
+            image_buffer,
+            x,
+            y,
+            [color[0], color[1], color[2], color[3] * coverage],
+            layer_opacity,
+        );
+    });
 }
 
 fn sample_paint_at_local(
@@ -739,55 +1109,24 @@ fn rasterize_variable_width_path(
     points: &[PathPoint],
     layer_opacity: f32,
 ) {
-    let stroke_color = object.stroke.as_solid();
-    if stroke_color[3] < 0.001 {
-        return;
-    }
-
-    let (img_w, img_h) = image_buffer.dimensions();
-
-    for index in 1..points.len() {
-        let prev = &points[index - 1];
-        let curr = &points[index];
-
-        let ax = object.position[0] + prev.position[0] * object.scale[0];
-        let ay = object.position[1] + prev.position[1] * object.scale[1];
-        let bx = object.position[0] + curr.position[0] * object.scale[0];
-        let by = object.position[1] + curr.position[1] * object.scale[1];
-
-        let avg_pressure = (prev.pressure + curr.pressure) / 2.0;
-        let half_thick = (object.stroke_width * avg_pressure / 2.0).max(0.25);
-
-        let seg_min_x = ((ax.min(bx) - half_thick).floor() as i32).max(0) as u32;
-        let seg_min_y = ((ay.min(by) - half_thick).floor() as i32).max(0) as u32;
-        let seg_max_x = ((ax.max(bx) + half_thick).ceil() as u32).min(img_w - 1);
-        let seg_max_y = ((ay.max(by) + half_thick).ceil() as u32).min(img_h - 1);
-
-        let dx = bx - ax;
-        let dy = by - ay;
-        let len_sq = dx * dx + dy * dy;
-        if len_sq < 0.001 {
-            continue;
-        }
-
-        for y in seg_min_y..=seg_max_y {
-            for x in seg_min_x..=seg_max_x {
-                let px = x as f32 + 0.5 - ax;
-                let py = y as f32 + 0.5 - ay;
-                let t = ((px * dx + py * dy) / len_sq).clamp(0.0, 1.0);
-                let cx = t * dx;
-                let cy = t * dy;
-                let dist = ((px - cx).powi(2) + (py - cy).powi(2)).sqrt();
-
-                let interp_pressure = prev.pressure + (curr.pressure - prev.pressure) * t;
-                let local_half_thick = (object.stroke_width * interp_pressure / 2.0).max(0.25);
-
-                if dist <= local_half_thick {
-                    blend_pixel(image_buffer, x, y, stroke_color, layer_opacity);
-                }
-            }
-        }
-    }
+    let canvas_points: Vec<[f32; 2]> = points
+        .iter()
+        .map(|point| hierarchy::to_world_point(object, point.position))
+        .collect();
+    let half_widths: Vec<f32> = points
+        .iter()
+        .map(|point| (object.stroke_width * point.pressure / 2.0).max(0.25))
+        .collect();
+    let area = PaintArea::from_points(&canvas_points);
+    stroke_polyline(
+        image_buffer,
+        &canvas_points,
+        &half_widths,
+        false,
+        &object.stroke,
+        &area,
+        layer_opacity,
+    );
 }
 
 fn rasterize_text(
@@ -879,30 +1218,20 @@ fn blend_pixel(
         return;
     }
 
-    let existing = image_buffer.get_pixel(x, y);
-    let src_r = (color[0] * 255.0) as u8;
-    let src_g = (color[1] * 255.0) as u8;
-    let src_b = (color[2] * 255.0) as u8;
-    let src_a = (alpha * 255.0) as u8;
-
-    let alpha_f = alpha;
-    let inv_alpha = 1.0 - alpha_f;
-
-    let result = image::Rgba([
-        (src_r as f32 * alpha_f + existing[0] as f32 * inv_alpha) as u8,
-        (src_g as f32 * alpha_f + existing[1] as f32 * inv_alpha) as u8,
-        (src_b as f32 * alpha_f + existing[2] as f32 * inv_alpha) as u8,
-        (src_a as f32 + existing[3] as f32 * inv_alpha).min(255.0) as u8,
-    ]);
-
-    image_buffer.put_pixel(x, y, result);
-}
+    let existing = image_buffer.get_pixel_mut(x, y);
+    let existing_alpha = existing[3] as f32 / 255.0;
+    let result_alpha = alpha + existing_alpha * (1.0 - alpha);
+    if result_alpha <= 0.0 {
+        return;
+    }
 
-fn rounded_rect_dist(px: f32, py: f32, half_w: f32, half_h: f32, radius: f32) -> f32 {
-    let r = radius.min(half_w).min(half_h);
-    let dx = px.abs() - half_w + r;
-    let dy = py.abs() - half_h + r;
-    (dx.max(0.0).powi(2) + dy.max(0.0).powi(2)).sqrt() + dx.max(dy).min(0.0) - r
+    let existing_weight = existing_alpha * (1.0 - alpha);
+    for channel in 0..3 {
+        let blended = (color[channel] * 255.0 * alpha + existing[channel] as f32 * existing_weight)
+            / result_alpha;
+        existing[channel] = blended.round().clamp(0.0, 255.0) as u8;
+    }
+    existing[3] = (result_alpha * 255.0).round().clamp(0.0, 255.0) as u8;
 }
 
 fn cubic_bezier(p0: [f32; 2], p1: [f32; 2], p2: [f32; 2], p3: [f32; 2], t: f32) -> [f32; 2] {
@@ -922,10 +1251,7 @@ fn build_path_points(object: &AnimObject, points: &[PathPoint], closed: bool) ->
 
     for index in 0..points.len() {
         let point = &points[index];
-        let canvas_pt = [
-            object.position[0] + point.position[0] * object.scale[0],
-            object.position[1] + point.position[1] * object.scale[1],
-        ];
+        let canvas_pt = hierarchy::to_world_point(object, point.position);
 
         if index > 0 {
             let prev = &points[index - 1];
@@ -935,10 +1261,7 @@ fn build_path_points(object: &AnimObject, points: &[PathPoint], closed: bool) ->
                 for step in 1..=16 {
                     let t = step as f32 / 16.0;
                     let b = cubic_bezier(prev.position, ctrl_out, ctrl_in, point.position, t);
-                    result.push([
-                        object.position[0] + b[0] * object.scale[0],
-                        object.position[1] + b[1] * object.scale[1],
-                    ]);
+                    result.push(hierarchy::to_world_point(object, b));
                 }
                 continue;
             }
@@ -955,10 +1278,7 @@ fn build_path_points(object: &AnimObject, points: &[PathPoint], closed: bool) ->
             for step in 1..=16 {
                 let t = step as f32 / 16.0;
                 let b = cubic_bezier(last.position, ctrl_out, ctrl_in, first.position, t);
-                result.push([
-                    object.position[0] + b[0] * object.scale[0],
-                    object.position[1] + b[1] * object.scale[1],
-                ]);
+                result.push(hierarchy::to_world_point(object, b));
             }
         }
     }
@@ -966,144 +1286,171 @@ fn build_path_points(object: &AnimObject, points: &[PathPoint], closed: bool) ->
     result
 }
 
-fn point_in_polygon(px: f32, py: f32, polygon: &[[f32; 2]]) -> bool {
-    let mut inside = false;
-    let n = polygon.len();
-    let mut j = n - 1;
-    for i in 0..n {
-        let (xi, yi) = (polygon[i][0], polygon[i][1]);
-        let (xj, yj) = (polygon[j][0], polygon[j][1]);
-        if ((yi > py) != (yj > py)) && (px < (xj - xi) * (py - yi) / (yj - yi) + xi) {
-            inside = !inside;
-        }
-        j = i;
-    }
-    inside
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::Keyframe;
 
-fn dist_to_segment(px: f32, py: f32, a: [f32; 2], b: [f32; 2]) -> f32 {
-    let dx = b[0] - a[0];
-    let dy = b[1] - a[1];
-    let len_sq = dx * dx + dy * dy;
-    if len_sq < 0.001 {
-        return ((px - a[0]).powi(2) + (py - a[1]).powi(2)).sqrt();
-    }
-    let t = ((px - a[0]) * dx + (py - a[1]) * dy) / len_sq;
-    let t = t.clamp(0.0, 1.0);
-    let cx = a[0] + t * dx;
-    let cy = a[1] + t * dy;
-    ((px - cx).powi(2) + (py - cy).powi(2)).sqrt()
-}
+    const QUARTER_TURN: f32 = std::f32::consts::FRAC_PI_2;
 
-fn min_dist_to_edges(px: f32, py: f32, polygon: &[[f32; 2]]) -> f32 {
-    let n = polygon.len();
-    let mut min_d = f32::MAX;
-    for i in 0..n {
-        let j = (i + 1) % n;
-        let d = dist_to_segment(px, py, polygon[i], polygon[j]);
-        if d < min_d {
-            min_d = d;
-        }
+    fn stroke_path(position: [f32; 2]) -> AnimObject {
+        let point = |position| PathPoint {
+            position,
+            control_in: None,
+            control_out: None,
+            pressure: 1.0,
+            weights: Vec::new(),
+        };
+        AnimObject::new(
+            Shape::Path {
+                points: vec![point([0.0, 0.0]), point([60.0, 0.0])],
+                closed: false,
+            },
+            position,
+            Paint::Solid([0.0, 0.0, 0.0, 0.0]),
+            Paint::Solid([1.0, 0.0, 0.0, 1.0]),
+            6.0,
+        )
     }
-    min_d
-}
 
-fn rasterize_closed_path(
-    image_buffer: &mut image::RgbaImage,
-    points: &[[f32; 2]],
-    fill: [f32; 4],
-    stroke: [f32; 4],
-    stroke_width: f32,
-    layer_opacity: f32,
-) {
-    let (img_w, img_h) = image_buffer.dimensions();
-
-    let mut min_x = f32::MAX;
-    let mut min_y = f32::MAX;
-    let mut max_x = f32::MIN;
-    let mut max_y = f32::MIN;
-    for p in points {
-        min_x = min_x.min(p[0]);
-        min_y = min_y.min(p[1]);
-        max_x = max_x.max(p[0]);
-        max_y = max_y.max(p[1]);
+    fn render_objects(objects: Vec<AnimObject>) -> image::RgbaImage {
+        let mut project = Project {
+            canvas_width: 200,
+            canvas_height: 200,
+            ..Project::default()
+        };
+        project.layers[0].keyframes.insert(
+            0,
+            Keyframe {
+                objects,
+                ..Keyframe::default()
+            },
+        );
+        let settings = RenderSettings {
+            start_frame: 0,
+            end_frame: 1,
+            scale: 1.0,
+            scene: None,
+        };
+        rasterize_frame(&project, 0, &settings)
     }
 
-    let pad = stroke_width + 1.0;
-    let px_min_x = ((min_x - pad).floor() as i32).max(0) as u32;
-    let px_min_y = ((min_y - pad).floor() as i32).max(0) as u32;
-    let px_max_x = ((max_x + pad).ceil() as u32).min(img_w - 1);
-    let px_max_y = ((max_y + pad).ceil() as u32).min(img_h - 1);
-
-    for y in px_min_y..=px_max_y {
-        for x in px_min_x..=px_max_x {
-            let fx = x as f32 + 0.5;
-            let fy = y as f32 + 0.5;
-
-            let inside = point_in_polygon(fx, fy, points);
-            if !inside {
-                continue;
-            }
-
-            let color = if stroke_width > 0.0 && stroke[3] > 0.001 {
-                let edge_dist = min_dist_to_edges(fx, fy, points);
-                if edge_dist <= stroke_width {
-                    stroke
-                } else {
-                    fill
-                }
-            } else {
-                fill
-            };
-
-            blend_pixel(image_buffer, x, y, color, layer_opacity);
-        }
+    fn assert_vertical_stroke(image: &image::RgbaImage, x: u32, from_y: u32, to_y: u32) {
+        let is_red = |x, y| {
+            let pixel = image.get_pixel(x, y);
+            pixel[0] > 200 && pixel[1] < 60
+        };
+        assert!(is_red(x, (from_y + to_y) / 2));
+        assert!(!is_red(x + 30, from_y));
     }
-}
 
-fn rasterize_open_path(
-    image_buffer: &mut image::RgbaImage,
-    points: &[[f32; 2]],
-    stroke: [f32; 4],
-    stroke_width: f32,
-    layer_opacity: f32,
-) {
-    if stroke[3] < 0.001 || stroke_width < 0.1 {
-        return;
+    fn assert_same_pixels(left: &image::RgbaImage, right: &image::RgbaImage) {
+        let difference = left
+            .as_raw()
+            .iter()
+            .zip(right.as_raw())
+            .map(|(left, right)| left.abs_diff(*right))
+            .max()
+            .unwrap_or(0);
+        assert!(difference <= 2, "pixels differ by {}", difference);
     }
 
-    let (img_w, img_h) = image_buffer.dimensions();
-    let half_thick = stroke_width / 2.0;
-
-    for i in 0..points.len() - 1 {
-        let a = points[i];
-        let b = points[i + 1];
+    #[test]
+    fn parented_path_matches_directly_rotated_path() {
+        let mut parent = AnimObject::new(
+            Shape::Null,
+            [100.0, 100.0],
+            Paint::default(),
+            Paint::default(),
+            0.0,
+        );
+        parent.rotation = QUARTER_TURN;
+        let mut child = stroke_path([20.0, 0.0]);
+        child.parent_id = Some(parent.id);
+        let parented = render_objects(vec![parent, child]);
+
+        let mut direct = stroke_path([100.0, 120.0]);
+        direct.rotation = QUARTER_TURN;
+        let direct = render_objects(vec![direct]);
+
+        assert_vertical_stroke(&direct, 100, 120, 180);
+        assert_same_pixels(&parented, &direct);
+    }
 
-        let seg_min_x = ((a[0].min(b[0]) - half_thick).floor() as i32).max(0) as u32;
-        let seg_min_y = ((a[1].min(b[1]) - half_thick).floor() as i32).max(0) as u32;
-        let seg_max_x = ((a[0].max(b[0]) + half_thick).ceil() as u32).min(img_w - 1);
-        let seg_max_y = ((a[1].max(b[1]) + half_thick).ceil() as u32).min(img_h - 1);
+    #[test]
+    fn rotated_group_keeps_path_members_with_their_siblings() {
+        let rectangle = |position| {
+            AnimObject::new(
+                Shape::Rectangle {
+                    width: 20.0,
+                    height: 10.0,
+                    corner_radius: 0.0,
+                },
+                position,
+                Paint::Solid([0.0, 0.0, 1.0, 1.0]),
+                Paint::Solid([0.0, 0.0, 0.0, 0.0]),
+                0.0,
+            )
+        };
+        let mut group = AnimObject::new(
+            Shape::Group {
+                children: vec![rectangle([0.0, 0.0]), stroke_path([20.0, 0.0])],
+            },
+            [100.0, 100.0],
+            Paint::default(),
+            Paint::default(),
+            0.0,
+        );
+        group.rotation = QUARTER_TURN;
+        let grouped = render_objects(vec![group]);
+
+        let mut direct_rectangle = rectangle([100.0, 100.0]);
+        direct_rectangle.rotation = QUARTER_TURN;
+        let mut direct_path = stroke_path([100.0, 120.0]);
+        direct_path.rotation = QUARTER_TURN;
+        let direct = render_objects(vec![direct_rectangle, direct_path]);
+
+        assert_vertical_stroke(&grouped, 100, 120, 180);
+        assert_same_pixels(&grouped, &direct);
+    }
 
-        let dx = b[0] - a[0];
-        let dy = b[1] - a[1];
-        let len_sq = dx * dx + dy * dy;
-        if len_sq < 0.001 {
-            continue;
-        }
+    #[test]
+    fn parallel_rendering_matches_serial_rendering() {
+        let project = crate::test_animation::generate_showcase_animation();
+        let settings = RenderSettings {
+            start_frame: 0,
+            end_frame: 12,
+            scale: 0.25,
+            scene: None,
+        };
 
-        for y in seg_min_y..=seg_max_y {
-            for x in seg_min_x..=seg_max_x {
-                let px = x as f32 + 0.5 - a[0];
-                let py = y as f32 + 0.5 - a[1];
-                let t = ((px * dx + py * dy) / len_sq).clamp(0.0, 1.0);
-                let cx = t * dx;
-                let cy = t * dy;
-                let dist = ((px - cx).powi(2) + (py - cy).powi(2)).sqrt();
-
-                if dist <= half_thick {
-                    blend_pixel(image_buffer, x, y, stroke, layer_opacity);
-                }
-            }
+        let serial: Vec<Vec<u8>> = settings
+            .frames()
+            .map(|frame| rasterize_frame(&project, frame, &settings).into_raw())
+            .collect();
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let mut parallel = Vec::new();
+        pool.install(|| {
+            render_frames(
+                &project,
+                &settings,
+                |_, image| image.into_raw(),
+                |index, _, bytes| {
+                    assert_eq!(index, parallel.len());
+                    parallel.push(bytes);
+                    Ok(())
+                },
+            )
+        })
+        .unwrap();
+
+        assert_eq!(serial.len(), parallel.len());
+        for (frame, (serial, parallel)) in serial.iter().zip(&parallel).enumerate() {
+            assert!(serial == parallel, "frame {} differs", frame);
         }
     }
 }
@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use crate::project::{AnimObject, Layer, Project, PropertyChannel};
+
+const DEFAULT_FRAME_RATE: u32 = 24;
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_CACHED_SCRIPTS: usize = 256;
+
+static ERRORS: Mutex<BTreeMap<(uuid::Uuid, PropertyChannel), String>> = Mutex::new(BTreeMap::new());
+
+struct ExpressionContext {
+    frame_rate: u32,
+    total_frames: u32,
+    layers: Vec<Layer>,
+    audio: Vec<(uuid::Uuid, u32, u32)>,
+    amplitude: Arc<Vec<f32>>,
+    objects: Mutex<BTreeMap<u32, rhai::Map>>,
+}
+
+static CONTEXT: RwLock<Option<Arc<ExpressionContext>>> = RwLock::new(None);
+
+static SCRIPTS: Mutex<BTreeMap<String, Result<Arc<rhai::AST>, String>>> =
+    Mutex::new(BTreeMap::new());
+
+pub fn expression_error(object_id: uuid::Uuid, channel: PropertyChannel) -> Option<String> {
+    ERRORS.lock().ok()?.get(&(object_id, channel)).cloned()
+}
+
+fn has_expressions(project: &Project) -> bool {
+    project
+        .layers
+        .iter()
+        .chain(project.scenes.iter().flat_map(|scene| &scene.layers))
+        .chain(
+            project
+                .library
+                .symbols
+                .iter()
+                .flat_map(|symbol| &symbol.layers),
+        )
+        .any(|layer| !layer.expressions.is_empty())
+}
+
+fn context_layers(project: &Project) -> impl Iterator<Item = &Layer> {
+    project
+        .layers
+        .iter()
+        .chain(project.scenes.iter().flat_map(|scene| &scene.layers))
+}
+
+fn audio_signature(project: &Project) -> Vec<(uuid::Uuid, u32, u32)> {
+    crate::audio::audible_tracks(project)
+        .map(|track| (track.id, track.start_frame, track.volume.to_bits()))
+        .collect()
+}
+
+pub fn update_context(project: &Project) {
+    let current = CONTEXT.read().ok().and_then(|context| context.clone());
+    if !has_expressions(project) {
+        if current.is_some()
+            && let Ok(mut context) = CONTEXT.write()
+        {
+            *context = None;
+        }
+        return;
+    }
+
+    let frame_rate = project.frame_rate.max(1);
+    let audio = audio_signature(project);
+    let same_audio = current.as_ref().filter(|context| {
+        context.frame_rate == frame_rate
+            && context.total_frames == project.total_frames
+            && context.audio == audio
+    });
+    if let Some(context) = same_audio
+        && context.layers.iter().eq(context_layers(project))
+    {
+        return;
+    }
+
+    let amplitude = match same_audio {
+        Some(context) => context.amplitude.clone(),
+        None => Arc::new(crate::audio::amplitude_envelope(project)),
+    };
+    let context = Arc::new(ExpressionContext {
+        frame_rate,
+        total_frames: project.total_frames,
+        layers: context_layers(project).cloned().collect(),
+        audio,
+        amplitude,
+        objects: Mutex::new(BTreeMap::new()),
+    });
+    if let Ok(mut current) = CONTEXT.write() {
+        *current = Some(context);
+    }
+}
+
+pub fn apply_expressions(
+    mut objects: Vec<AnimObject>,
+    layer: &Layer,
+    frame: u32,
+) -> Vec<AnimObject> {
+    if layer.expressions.is_empty() {
+        return objects;
+    }
+    let context = CONTEXT.read().ok().and_then(|context| context.clone());
+
+    for object in &mut objects {
+        let Some(expressions) = layer.expressions.get(&object.id) else {
+            continue;
+        };
+        for (channel, source) in expressions {
+            if source.trim().is_empty() {
+                continue;
+            }
+            let result = evaluate(source, object, *channel, frame, context.as_deref());
+            let Ok(mut errors) = ERRORS.lock() else {
+                continue;
+            };
+            match result {
+                Ok(value) => {
+                    channel.set_value(object, value);
+                    errors.remove(&(object.id, *channel));
+                }
+                Err(error) => {
+                    errors.insert((object.id, *channel), error);
+                }
+            }
+        }
+    }
+    objects
+}
+
+fn evaluate(
+    source: &str,
+    object: &AnimObject,
+    channel: PropertyChannel,
+    frame: u32,
+    context: Option<&ExpressionContext>,
+) -> Result<f32, String> {
+    let ast = compile(source)?;
+    let frame_rate = context.map_or(DEFAULT_FRAME_RATE, |context| context.frame_rate);
+    let amplitude = context
+        .and_then(|context| context.amplitude.get(frame as usize).copied())
+        .unwrap_or(0.0);
+
+    let mut scope = rhai::Scope::new();
+    scope.push_constant("frame", frame as f64);
+    scope.push_constant("fps", frame_rate as f64);
+    scope.push_constant("time", frame as f64 / frame_rate as f64);
+    scope.push_constant("amplitude", amplitude as f64);
+    scope.push_constant("value", channel.value(object) as f64);
+    scope.push_constant("position", vector_map(object.position));
+    scope.push_constant("rotation", object.rotation.to_degrees() as f64);
+    scope.push_constant("scale", vector_map(object.scale));
+    scope.push_constant("stroke_width", object.stroke_width as f64);
+    scope.push_constant(
+        "objects",
+        context
+            .map(|context| context.objects_at(frame))
+            .unwrap_or_default(),
+    );
+
+    let result = engine()
+        .eval_ast_with_scope::<rhai::Dynamic>(&mut scope, &ast)
+        .map_err(|error| error.to_string())?;
+    let value = result
+        .as_float()
+        .or_else(|_| result.as_int().map(|value| value as f64))
+        .map_err(|type_name| format!("Expression returned {} instead of a number", type_name))?;
+    if !value.is_finite() {
+        return Err(format!("Expression returned {}", value));
+    }
+    Ok(value as f32)
+}
+
+impl ExpressionContext {
+    fn objects_at(&self, frame: u32) -> rhai::Map {
+        let Ok(mut cache) = self.objects.lock() else {
+            return rhai::Map::new();
+        };
+        cache
+            .entry(frame)
+            .or_insert_with(|| {
+                let mut objects = rhai::Map::new();
+                for layer in &self.layers {
+                    let Some(resolved) = crate::tween::resolve_tracked_frame(layer, frame) else {
+                        continue;
+                    };
+                    for (index, object) in resolved.iter().enumerate() {
+                        let value = rhai::Dynamic::from_map(object_map(object));
+                        if index == 0 {
+                            objects
+                                .entry(layer.name.as_str().into())
+                                .or_insert_with(|| value.clone());
+                        }
+                        objects.entry(object.id.to_string().into()).or_insert(value);
+                    }
+                }
+                objects
+            })
+            .clone()
+    }
+}
+
+fn engine() -> &'static rhai::Engine {
+    static ENGINE: OnceLock<rhai::Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut engine = rhai::Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine
+    })
+}
+
+fn compile(source: &str) -> Result<Arc<rhai::AST>, String> {
+    let mut scripts = SCRIPTS
+        .lock()
+        .map_err(|_| "Expression cache is unavailable".to_string())?;
+    if let Some(compiled) = scripts.get(source) {
+        return compiled.clone();
+    }
+    if scripts.len() >= MAX_CACHED_SCRIPTS {
+        scripts.clear();
+    }
+    let compiled = engine()
+        .compile(source)
+        .map(Arc::new)
+        .map_err(|error| error.to_string());
+    scripts.insert(source.to_string(), compiled.clone());
+    compiled
+}
+
+fn vector_map(vector: [f32; 2]) -> rhai::Map {
+    let mut map = rhai::Map::new();
+    map.insert("x".into(), rhai::Dynamic::from_float(vector[0] as f64));
+    map.insert("y".into(), rhai::Dynamic::from_float(vector[1] as f64));
+    map
+}
+
+fn object_map(object: &AnimObject) -> rhai::Map {
+    let mut map = rhai::Map::new();
+    map.insert("id".into(), object.id.to_string().into());
+    map.insert("position".into(), vector_map(object.position).into());
+    map.insert(
+        "rotation".into(),
+        rhai::Dynamic::from_float(object.rotation.to_degrees() as f64),
+    );
+    map.insert("scale".into(), vector_map(object.scale).into());
+    map.insert(
+        "stroke_width".into(),
+        rhai::Dynamic::from_float(object.stroke_width as f64),
+    );
+    map
+}
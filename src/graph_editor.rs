@@ -0,0 +1,906 @@
+use std::collections::BTreeMap;
+
+use nightshade::prelude::*;
+
+use crate::app::AnimateApp;
+use crate::project::{KeyTangents, PropertyKey, PropertyTracks};
+use crate::tween;
+
+const CHANNEL_COUNT: usize = 6;
+const PLOT_MARGIN: f32 = 24.0;
+const KEY_RADIUS: f32 = 4.0;
+const HANDLE_RADIUS: f32 = 3.5;
+const HIT_RADIUS: f32 = 7.0;
+const SLOPE_EPSILON: f32 = 0.01;
+const SAMPLES_PER_FRAME: u32 = 4;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CurveChannel {
+    PositionX,
+    PositionY,
+    Rotation,
+    ScaleX,
+    ScaleY,
+    StrokeWidth,
+}
+
+impl CurveChannel {
+    pub const ALL: [CurveChannel; CHANNEL_COUNT] = [
+        CurveChannel::PositionX,
+        CurveChannel::PositionY,
+        CurveChannel::Rotation,
+        CurveChannel::ScaleX,
+        CurveChannel::ScaleY,
+        CurveChannel::StrokeWidth,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            CurveChannel::PositionX => "X",
+            CurveChannel::PositionY => "Y",
+            CurveChannel::Rotation => "Rotation",
+            CurveChannel::ScaleX => "Scale X",
+            CurveChannel::ScaleY => "Scale Y",
+            CurveChannel::StrokeWidth => "Stroke W",
+        }
+    }
+
+    fn color(self) -> egui::Color32 {
+        match self {
+            CurveChannel::PositionX => egui::Color32::from_rgb(230, 90, 90),
+            CurveChannel::PositionY => egui::Color32::from_rgb(110, 200, 90),
+            CurveChannel::Rotation => egui::Color32::from_rgb(90, 150, 240),
+            CurveChannel::ScaleX => egui::Color32::from_rgb(230, 170, 60),
+            CurveChannel::ScaleY => egui::Color32::from_rgb(200, 100, 220),
+            CurveChannel::StrokeWidth => egui::Color32::from_rgb(80, 200, 200),
+        }
+    }
+
+    fn display_scale(self) -> f32 {
+        match self {
+            CurveChannel::Rotation => 180.0 / std::f32::consts::PI,
+            _ => 1.0,
+        }
+    }
+
+    fn component(self) -> usize {
+        match self {
+            CurveChannel::PositionY | CurveChannel::ScaleY => 1,
+            _ => 0,
+        }
+    }
+
+    fn siblings(self) -> &'static [CurveChannel] {
+        match self {
+            CurveChannel::PositionX | CurveChannel::PositionY => {
+                &[CurveChannel::PositionX, CurveChannel::PositionY]
+            }
+            CurveChannel::ScaleX | CurveChannel::ScaleY => {
+                &[CurveChannel::ScaleX, CurveChannel::ScaleY]
+            }
+            CurveChannel::Rotation => &[CurveChannel::Rotation],
+            CurveChannel::StrokeWidth => &[CurveChannel::StrokeWidth],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TangentSide {
+    In,
+    Out,
+}
+
+#[derive(Clone, Copy)]
+enum TangentPreset {
+    Smooth,
+    Flat,
+    Linear,
+}
+
+#[derive(Clone, Copy)]
+enum DragTarget {
+    Keys,
+    Tangent {
+        channel: CurveChannel,
+        frame: u32,
+        side: TangentSide,
+    },
+}
+
+#[derive(Clone, Copy)]
+struct GraphView {
+    rect: egui::Rect,
+    last_frame: f32,
+    min_value: f32,
+    max_value: f32,
+}
+
+impl GraphView {
+    fn screen_pos(&self, frame: f32, value: f32) -> egui::Pos2 {
+        egui::pos2(
+            self.rect.min.x + frame / self.last_frame * self.rect.width(),
+            self.rect.max.y
+                - (value - self.min_value) / (self.max_value - self.min_value) * self.rect.height(),
+        )
+    }
+
+    fn graph_point(&self, pos: egui::Pos2) -> (f32, f32) {
+        (
+            (pos.x - self.rect.min.x) / self.rect.width() * self.last_frame,
+            self.min_value
+                + (self.rect.max.y - pos.y) / self.rect.height()
+                    * (self.max_value - self.min_value),
+        )
+    }
+}
+
+struct GraphDrag {
+    original: PropertyTracks,
+    original_selection: Vec<(CurveChannel, u32)>,
+    start: (f32, f32),
+    target: DragTarget,
+    view: GraphView,
+}
+
+pub struct GraphEditorState {
+    visible: [bool; CHANNEL_COUNT],
+    selected: Vec<(CurveChannel, u32)>,
+    object_id: Option<uuid::Uuid>,
+    drag: Option<GraphDrag>,
+}
+
+impl Default for GraphEditorState {
+    fn default() -> Self {
+        Self {
+            visible: [true; CHANNEL_COUNT],
+            selected: Vec::new(),
+            object_id: None,
+            drag: None,
+        }
+    }
+}
+
+pub fn draw_graph_editor(app: &mut AnimateApp, ui_context: &egui::Context) {
+    let Some(mut state) = app.graph_editor.take() else {
+        return;
+    };
+
+    let mut open = true;
+    egui::Window::new("Graph Editor")
+        .open(&mut open)
+        .resizable(true)
+        .default_size(egui::vec2(560.0, 320.0))
+        .show(ui_context, |ui| {
+            draw_graph_contents(app, ui, &mut state);
+        });
+
+    if open {
+        app.graph_editor = Some(state);
+    }
+}
+
+fn draw_graph_contents(app: &mut AnimateApp, ui: &mut egui::Ui, state: &mut GraphEditorState) {
+    let object_id = match app.selection.selected_objects.as_slice() {
+        [object_id] => Some(*object_id),
+        _ => None,
+    };
+    if state.object_id != object_id {
+        state.object_id = object_id;
+        state.selected.clear();
+        state.drag = None;
+    }
+    let Some(object_id) = object_id else {
+        ui.label("Select a single object to edit its property curves.");
+        return;
+    };
+    let Some(layer_index) = app
+        .project
+        .layers
+        .iter()
+        .position(|layer| layer.property_tracks.contains_key(&object_id))
+    else {
+        ui.label("The selected object has no property keyframes.");
+        return;
+    };
+
+    let mut tracks = app.project.layers[layer_index].property_tracks[&object_id].clone();
+    let mut changed = false;
+
+    ui.horizontal_wrapped(|ui| {
+        for channel in CurveChannel::ALL {
+            let text = egui::RichText::new(channel.name()).color(channel.color());
+            ui.checkbox(&mut state.visible[channel as usize], text);
+        }
+    });
+    ui.horizontal(|ui| {
+        let has_selection = !state.selected.is_empty();
+        for (preset, label) in [
+            (TangentPreset::Smooth, "Smooth"),
+            (TangentPreset::Flat, "Flat"),
+            (TangentPreset::Linear, "Linear"),
+        ] {
+            if ui
+                .add_enabled(has_selection, egui::Button::new(label))
+                .clicked()
+            {
+                app.history.push(app.project.clone(), "Set Tangents");
+                for &(channel, frame) in &state.selected {
+                    apply_tangent_preset(&mut tracks, channel, frame, preset);
+                }
+                changed = true;
+            }
+        }
+        if ui
+            .add_enabled(has_selection, egui::Button::new("Reset"))
+            .on_hover_text("Remove custom tangents and use the key's easing")
+            .clicked()
+        {
+            app.history.push(app.project.clone(), "Reset Tangents");
+            for &(channel, frame) in &state.selected {
+                if let Some((_, tangents)) = key_mut(&mut tracks, channel, frame) {
+                    *tangents = None;
+                }
+            }
+            changed = true;
+        }
+    });
+
+    let size = ui.available_size().max(egui::vec2(320.0, 160.0));
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+    let plot_rect = rect.shrink(PLOT_MARGIN);
+    let view = match &state.drag {
+        Some(drag) => drag.view,
+        None => fit_view(&tracks, &state.visible, plot_rect, app.project.total_frames),
+    };
+
+    let pointer = response
+        .interact_pointer_pos()
+        .or_else(|| ui.ctx().input(|input| input.pointer.latest_pos()));
+    let shift = ui.ctx().input(|input| input.modifiers.shift);
+
+    if response.drag_started()
+        && let Some(pos) = ui
+            .ctx()
+            .input(|input| input.pointer.press_origin())
+            .or(pointer)
+    {
+        let handle = hit_handle(&tracks, &state.selected, &view, pos);
+        let key = hit_key(&tracks, &state.visible, &view, pos);
+        let target = match (handle, key) {
+            (Some((channel, frame, side)), _) => Some(DragTarget::Tangent {
+                channel,
+                frame,
+                side,
+            }),
+            (None, Some(key)) => {
+                if !state.selected.contains(&key) {
+                    if !shift {
+                        state.selected.clear();
+                    }
+                    state.selected.push(key);
+                }
+                Some(DragTarget::Keys)
+            }
+            (None, None) => None,
+        };
+        if let Some(target) = target {
+            app.history.push(app.project.clone(), "Edit Curves");
+            state.drag = Some(GraphDrag {
+                original: tracks.clone(),
+                original_selection: state.selected.clone(),
+                start: view.graph_point(pos),
+                target,
+                view,
+            });
+        }
+    }
+
+    if response.dragged()
+        && let Some(pos) = pointer
+        && let Some(drag) = &state.drag
+    {
+        let (frame, value) = drag.view.graph_point(pos);
+        tracks = drag.original.clone();
+        match drag.target {
+            DragTarget::Keys => {
+                let frame_offset = (frame - drag.start.0).round() as i64;
+                let value_offset = value - drag.start.1;
+                state.selected = drag_keys(
+                    &mut tracks,
+                    &drag.original_selection,
+                    frame_offset,
+                    value_offset,
+                );
+            }
+            DragTarget::Tangent {
+                channel,
+                frame: key_frame,
+                side,
+            } => {
+                drag_tangent(&mut tracks, channel, key_frame, side, (frame, value));
+            }
+        }
+        changed = true;
+    }
+
+    if response.drag_stopped() {
+        state.drag = None;
+    }
+
+    if response.clicked()
+        && let Some(pos) = pointer
+    {
+        match hit_key(&tracks, &state.visible, &view, pos) {
+            Some(key) if shift => {
+                if let Some(index) = state.selected.iter().position(|selected| *selected == key) {
+                    state.selected.remove(index);
+                } else {
+                    state.selected.push(key);
+                }
+            }
+            Some(key) => {
+                state.selected.clear();
+                state.selected.push(key);
+            }
+            None => state.selected.clear(),
+        }
+    }
+
+    let painter = ui.painter_at(rect);
+    draw_graph(&painter, rect, &view, app.current_frame, &tracks, state);
+
+    if changed {
+        app.project.layers[layer_index]
+            .property_tracks
+            .insert(object_id, tracks);
+    }
+}
+
+fn draw_graph(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    view: &GraphView,
+    current_frame: u32,
+    tracks: &PropertyTracks,
+    state: &GraphEditorState,
+) {
+    painter.rect_filled(rect, 4.0, egui::Color32::from_rgb(30, 30, 30));
+    let grid_stroke = egui::Stroke::new(0.5, egui::Color32::from_rgb(55, 55, 60));
+    let label_color = egui::Color32::from_rgb(140, 140, 140);
+    let font = egui::FontId::proportional(10.0);
+
+    let frame_step = grid_step(view.last_frame, view.rect.width() / 40.0);
+    let mut frame = 0.0;
+    while frame <= view.last_frame {
+        let top = view.screen_pos(frame, view.max_value);
+        let bottom = view.screen_pos(frame, view.min_value);
+        painter.line_segment([top, bottom], grid_stroke);
+        painter.text(
+            egui::pos2(bottom.x, rect.max.y - 2.0),
+            egui::Align2::CENTER_BOTTOM,
+            format!("{}", frame as u32 + 1),
+            font.clone(),
+            label_color,
+        );
+        frame += frame_step;
+    }
+
+    let value_range = view.max_value - view.min_value;
+    let value_step = grid_step(value_range, view.rect.height() / 30.0);
+    let mut value = (view.min_value / value_step).ceil() * value_step;
+    while value <= view.max_value {
+        let left = view.screen_pos(0.0, value);
+        let right = view.screen_pos(view.last_frame, value);
+        painter.line_segment([left, right], grid_stroke);
+        painter.text(
+            egui::pos2(rect.min.x + 2.0, left.y),
+            egui::Align2::LEFT_CENTER,
+            format_value(value, value_step),
+            font.clone(),
+            label_color,
+        );
+        value += value_step;
+    }
+
+    let playhead_top = view.screen_pos(current_frame as f32, view.max_value);
+    let playhead_bottom = view.screen_pos(current_frame as f32, view.min_value);
+    painter.line_segment(
+        [playhead_top, playhead_bottom],
+        egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 50, 50)),
+    );
+
+    for channel in CurveChannel::ALL {
+        if !state.visible[channel as usize] {
+            continue;
+        }
+        let keys = channel_keys(tracks, channel);
+        let (Some(first), Some(last)) = (keys.first(), keys.last()) else {
+            continue;
+        };
+        let points: Vec<egui::Pos2> = (first.0 * SAMPLES_PER_FRAME..=last.0 * SAMPLES_PER_FRAME)
+            .filter_map(|step| {
+                let frame = step as f32 / SAMPLES_PER_FRAME as f32;
+                let value = channel_value(tracks, channel, frame)? * channel.display_scale();
+                Some(view.screen_pos(frame, value))
+            })
+            .collect();
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new(1.5, channel.color()),
+        ));
+
+        for (frame, value) in keys {
+            let center = view.screen_pos(frame as f32, value);
+            let selected = state.selected.contains(&(channel, frame));
+            if selected {
+                let (in_handle, out_handle) = handle_positions(tracks, channel, frame);
+                for handle in [in_handle, out_handle] {
+                    let handle = view.screen_pos(handle.0, handle.1);
+                    painter.line_segment(
+                        [center, handle],
+                        egui::Stroke::new(1.0, egui::Color32::from_rgb(200, 200, 200)),
+                    );
+                    painter.circle_filled(handle, HANDLE_RADIUS, egui::Color32::WHITE);
+                }
+            }
+            let fill = if selected {
+                egui::Color32::from_rgb(255, 220, 80)
+            } else {
+                channel.color()
+            };
+            painter.rect_filled(
+                egui::Rect::from_center_size(center, egui::vec2(KEY_RADIUS, KEY_RADIUS) * 2.0),
+                0.0,
+                fill,
+            );
+        }
+    }
+}
+
+fn grid_step(range: f32, max_lines: f32) -> f32 {
+    let raw = range / max_lines.max(1.0);
+    let magnitude = 10.0_f32.powf(raw.max(1e-6).log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= raw)
+        .unwrap_or(10.0 * magnitude)
+}
+
+fn format_value(value: f32, step: f32) -> String {
+    if step >= 1.0 {
+        format!("{:.0}", value)
+    } else if step >= 0.1 {
+        format!("{:.1}", value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+fn fit_view(
+    tracks: &PropertyTracks,
+    visible: &[bool; CHANNEL_COUNT],
+    rect: egui::Rect,
+    total_frames: u32,
+) -> GraphView {
+    let mut min_value = f32::MAX;
+    let mut max_value = f32::MIN;
+    let mut last_frame = total_frames.saturating_sub(1).max(1) as f32;
+
+    for channel in CurveChannel::ALL {
+        if !visible[channel as usize] {
+            continue;
+        }
+        let keys = channel_keys(tracks, channel);
+        let (Some(first), Some(last)) = (keys.first(), keys.last()) else {
+            continue;
+        };
+        last_frame = last_frame.max(last.0 as f32);
+        for step in first.0 * SAMPLES_PER_FRAME..=last.0 * SAMPLES_PER_FRAME {
+            if let Some(value) =
+                channel_value(tracks, channel, step as f32 / SAMPLES_PER_FRAME as f32)
+            {
+                let value = value * channel.display_scale();
+                min_value = min_value.min(value);
+                max_value = max_value.max(value);
+            }
+        }
+        for frame in keys.iter().map(|key| key.0) {
+            for handle in <[(f32, f32); 2]>::from(handle_positions(tracks, channel, frame)) {
+                min_value = min_value.min(handle.1);
+                max_value = max_value.max(handle.1);
+            }
+        }
+    }
+
+    if min_value > max_value {
+        min_value = 0.0;
+        max_value = 1.0;
+    }
+    let padding = ((max_value - min_value) * 0.1).max(1.0);
+    GraphView {
+        rect,
+        last_frame,
+        min_value: min_value - padding,
+        max_value: max_value + padding,
+    }
+}
+
+fn hit_key(
+    tracks: &PropertyTracks,
+    visible: &[bool; CHANNEL_COUNT],
+    view: &GraphView,
+    pos: egui::Pos2,
+) -> Option<(CurveChannel, u32)> {
+    CurveChannel::ALL
+        .into_iter()
+        .filter(|channel| visible[*channel as usize])
+        .flat_map(|channel| {
+            channel_keys(tracks, channel)
+                .into_iter()
+                .map(move |(frame, value)| (channel, frame, value))
+        })
+        .map(|(channel, frame, value)| {
+            let distance = view.screen_pos(frame as f32, value).distance(pos);
+            (channel, frame, distance)
+        })
+        .filter(|(_, _, distance)| *distance <= HIT_RADIUS)
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(channel, frame, _)| (channel, frame))
+}
+
+fn hit_handle(
+    tracks: &PropertyTracks,
+    selected: &[(CurveChannel, u32)],
+    view: &GraphView,
+    pos: egui::Pos2,
+) -> Option<(CurveChannel, u32, TangentSide)> {
+    selected.iter().find_map(|&(channel, frame)| {
+        let (in_handle, out_handle) = handle_positions(tracks, channel, frame);
+        [(in_handle, TangentSide::In), (out_handle, TangentSide::Out)]
+            .into_iter()
+            .find(|(handle, _)| view.screen_pos(handle.0, handle.1).distance(pos) <= HIT_RADIUS)
+            .map(|(_, side)| (channel, frame, side))
+    })
+}
+
+fn channel_keys(tracks: &PropertyTracks, channel: CurveChannel) -> Vec<(u32, f32)> {
+    let scale = channel.display_scale();
+    let component = channel.component();
+    match channel {
+        CurveChannel::PositionX | CurveChannel::PositionY => {
+            component_keys(&tracks.position, component, scale)
+        }
+        CurveChannel::Rotation => component_keys(&tracks.rotation, component, scale),
+        CurveChannel::ScaleX | CurveChannel::ScaleY => {
+            component_keys(&tracks.scale, component, scale)
+        }
+        CurveChannel::StrokeWidth => component_keys(&tracks.stroke_width, component, scale),
+    }
+}
+
+fn component_keys<T: tween::CurveValue>(
+    track: &BTreeMap<u32, PropertyKey<T>>,
+    component: usize,
+    scale: f32,
+) -> Vec<(u32, f32)> {
+    track
+        .iter()
+        .map(|(frame, key)| (*frame, key.value.component(component) * scale))
+        .collect()
+}
+
+fn channel_value(tracks: &PropertyTracks, channel: CurveChannel, frame: f32) -> Option<f32> {
+    let component = channel.component();
+    match channel {
+        CurveChannel::PositionX | CurveChannel::PositionY => {
+            tween::sample_track(&tracks.position, frame, false).map(|value| value[component])
+        }
+        CurveChannel::Rotation => tween::sample_track(&tracks.rotation, frame, true),
+        CurveChannel::ScaleX | CurveChannel::ScaleY => {
+            tween::sample_track(&tracks.scale, frame, false).map(|value| value[component])
+        }
+        CurveChannel::StrokeWidth => tween::sample_track(&tracks.stroke_width, frame, false),
+    }
+}
+
+fn key_mut(
+    tracks: &mut PropertyTracks,
+    channel: CurveChannel,
+    frame: u32,
+) -> Option<(&mut f32, &mut Option<KeyTangents>)> {
+    let component = channel.component();
+    match channel {
+        CurveChannel::PositionX | CurveChannel::PositionY => tracks
+            .position
+            .get_mut(&frame)
+            .map(|key| (&mut key.value[component], &mut key.tangents)),
+        CurveChannel::Rotation => tracks
+            .rotation
+            .get_mut(&frame)
+            .map(|key| (&mut key.value, &mut key.tangents)),
+        CurveChannel::ScaleX | CurveChannel::ScaleY => tracks
+            .scale
+            .get_mut(&frame)
+            .map(|key| (&mut key.value[component], &mut key.tangents)),
+        CurveChannel::StrokeWidth => tracks
+            .stroke_width
+            .get_mut(&frame)
+            .map(|key| (&mut key.value, &mut key.tangents)),
+    }
+}
+
+fn move_key(tracks: &mut PropertyTracks, channel: CurveChannel, from: u32, to: u32) -> bool {
+    match channel {
+        CurveChannel::PositionX | CurveChannel::PositionY => {
+            move_track_key(&mut tracks.position, from, to)
+        }
+        CurveChannel::Rotation => move_track_key(&mut tracks.rotation, from, to),
+        CurveChannel::ScaleX | CurveChannel::ScaleY => move_track_key(&mut tracks.scale, from, to),
+        CurveChannel::StrokeWidth => move_track_key(&mut tracks.stroke_width, from, to),
+    }
+}
+
+fn move_track_key<T>(track: &mut BTreeMap<u32, PropertyKey<T>>, from: u32, to: u32) -> bool {
+    if from == to {
+        return true;
+    }
+    if track.contains_key(&to) {
+        return false;
+    }
+    match track.remove(&from) {
+        Some(key) => {
+            track.insert(to, key);
+            true
+        }
+        None => false,
+    }
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI
+}
+
+fn numeric_slopes(tracks: &PropertyTracks, channel: CurveChannel, frame: u32) -> (f32, f32) {
+    let difference = |a: f32, b: f32| {
+        if channel == CurveChannel::Rotation {
+            wrap_angle(a - b)
+        } else {
+            a - b
+        }
+    };
+    let frame = frame as f32;
+    let value = channel_value(tracks, channel, frame);
+    let before = channel_value(tracks, channel, frame - SLOPE_EPSILON);
+    let after = channel_value(tracks, channel, frame + SLOPE_EPSILON);
+    let in_slope = value
+        .zip(before)
+        .map(|(value, before)| difference(value, before) / SLOPE_EPSILON);
+    let out_slope = value
+        .zip(after)
+        .map(|(value, after)| difference(after, value) / SLOPE_EPSILON);
+    (
+        in_slope.or(out_slope).unwrap_or(0.0),
+        out_slope.or(in_slope).unwrap_or(0.0),
+    )
+}
+
+fn key_slopes(tracks: &PropertyTracks, channel: CurveChannel, frame: u32) -> (f32, f32) {
+    let component = channel.component();
+    let tangents = match channel {
+        CurveChannel::PositionX | CurveChannel::PositionY => {
+            tracks.position.get(&frame).and_then(|key| key.tangents)
+        }
+        CurveChannel::Rotation => tracks.rotation.get(&frame).and_then(|key| key.tangents),
+        CurveChannel::ScaleX | CurveChannel::ScaleY => {
+            tracks.scale.get(&frame).and_then(|key| key.tangents)
+        }
+        CurveChannel::StrokeWidth => tracks.stroke_width.get(&frame).and_then(|key| key.tangents),
+    };
+    match tangents {
+        Some(tangents) => (tangents.in_slope[component], tangents.out_slope[component]),
+        None => numeric_slopes(tracks, channel, frame),
+    }
+}
+
+fn neighbor_spans(tracks: &PropertyTracks, channel: CurveChannel, frame: u32) -> (f32, f32) {
+    let keys = channel_keys(tracks, channel);
+    let previous = keys
+        .iter()
+        .rev()
+        .find(|key| key.0 < frame)
+        .map(|key| (frame - key.0) as f32);
+    let next = keys
+        .iter()
+        .find(|key| key.0 > frame)
+        .map(|key| (key.0 - frame) as f32);
+    (
+        previous.or(next).unwrap_or(3.0),
+        next.or(previous).unwrap_or(3.0),
+    )
+}
+
+fn handle_positions(
+    tracks: &PropertyTracks,
+    channel: CurveChannel,
+    frame: u32,
+) -> ((f32, f32), (f32, f32)) {
+    let scale = channel.display_scale();
+    let value = channel_keys(tracks, channel)
+        .into_iter()
+        .find(|key| key.0 == frame)
+        .map_or(0.0, |key| key.1);
+    let (in_slope, out_slope) = key_slopes(tracks, channel, frame);
+    let (in_span, out_span) = neighbor_spans(tracks, channel, frame);
+    let in_length = in_span / 3.0;
+    let out_length = out_span / 3.0;
+    (
+        (
+            frame as f32 - in_length,
+            value - in_slope * scale * in_length,
+        ),
+        (
+            frame as f32 + out_length,
+            value + out_slope * scale * out_length,
+        ),
+    )
+}
+
+fn ensure_tangents(tracks: &mut PropertyTracks, channel: CurveChannel, frame: u32) {
+    let has_tangents =
+        key_mut(tracks, channel, frame).is_some_and(|(_, tangents)| tangents.is_some());
+    if has_tangents {
+        return;
+    }
+    let mut initial = KeyTangents::default();
+    for sibling in channel.siblings() {
+        let (in_slope, out_slope) = numeric_slopes(tracks, *sibling, frame);
+        initial.in_slope[sibling.component()] = in_slope;
+        initial.out_slope[sibling.component()] = out_slope;
+    }
+    if let Some((_, tangents)) = key_mut(tracks, channel, frame) {
+        *tangents = Some(initial);
+    }
+}
+
+fn set_slopes(
+    tracks: &mut PropertyTracks,
+    channel: CurveChannel,
+    frame: u32,
+    in_slope: Option<f32>,
+    out_slope: Option<f32>,
+) {
+    ensure_tangents(tracks, channel, frame);
+    let component = channel.component();
+    if let Some((_, Some(tangents))) = key_mut(tracks, channel, frame) {
+        if let Some(slope) = in_slope {
+            tangents.in_slope[component] = slope;
+        }
+        if let Some(slope) = out_slope {
+            tangents.out_slope[component] = slope;
+        }
+    }
+}
+
+fn drag_tangent(
+    tracks: &mut PropertyTracks,
+    channel: CurveChannel,
+    frame: u32,
+    side: TangentSide,
+    pointer: (f32, f32),
+) {
+    let scale = channel.display_scale();
+    let Some(value) = channel_keys(tracks, channel)
+        .into_iter()
+        .find(|key| key.0 == frame)
+        .map(|key| key.1)
+    else {
+        return;
+    };
+    let value_offset = (pointer.1 - value) / scale;
+    match side {
+        TangentSide::In => {
+            let span = (frame as f32 - pointer.0).max(SLOPE_EPSILON);
+            set_slopes(tracks, channel, frame, Some(-value_offset / span), None);
+        }
+        TangentSide::Out => {
+            let span = (pointer.0 - frame as f32).max(SLOPE_EPSILON);
+            set_slopes(tracks, channel, frame, None, Some(value_offset / span));
+        }
+    }
+}
+
+fn drag_keys(
+    tracks: &mut PropertyTracks,
+    selection: &[(CurveChannel, u32)],
+    frame_offset: i64,
+    value_offset: f32,
+) -> Vec<(CurveChannel, u32)> {
+    for &(channel, frame) in selection {
+        if let Some((value, _)) = key_mut(tracks, channel, frame) {
+            *value += value_offset / channel.display_scale();
+        }
+    }
+
+    let earliest = selection.iter().map(|key| key.1).min().unwrap_or(0) as i64;
+    let frame_offset = frame_offset.max(-earliest);
+    if frame_offset == 0 {
+        return selection.to_vec();
+    }
+
+    let mut moves: Vec<(CurveChannel, u32)> = Vec::new();
+    for &(channel, frame) in selection {
+        let primary = channel.siblings()[0];
+        if !moves.contains(&(primary, frame)) {
+            moves.push((primary, frame));
+        }
+    }
+    if frame_offset > 0 {
+        moves.sort_by(|a, b| b.1.cmp(&a.1));
+    } else {
+        moves.sort_by(|a, b| a.1.cmp(&b.1));
+    }
+
+    let mut moved: Vec<(CurveChannel, u32, u32)> = Vec::new();
+    for (channel, frame) in moves {
+        let target = (frame as i64 + frame_offset) as u32;
+        let destination = if move_key(tracks, channel, frame, target) {
+            target
+        } else {
+            frame
+        };
+        moved.push((channel, frame, destination));
+    }
+
+    selection
+        .iter()
+        .map(|&(channel, frame)| {
+            let primary = channel.siblings()[0];
+            let destination = moved
+                .iter()
+                .find(|(moved_channel, from, _)| *moved_channel == primary && *from == frame)
+                .map_or(frame, |(_, _, to)| *to);
+            (channel, destination)
+        })
+        .collect()
+}
+
+fn apply_tangent_preset(
+    tracks: &mut PropertyTracks,
+    channel: CurveChannel,
+    frame: u32,
+    preset: TangentPreset,
+) {
+    let scale = channel.display_scale();
+    let keys = channel_keys(tracks, channel);
+    let Some(index) = keys.iter().position(|key| key.0 == frame) else {
+        return;
+    };
+    let current = keys[index];
+    let previous = index.checked_sub(1).map(|index| keys[index]);
+    let next = keys.get(index + 1).copied();
+    let slope =
+        |from: (u32, f32), to: (u32, f32)| (to.1 - from.1) / (to.0 as f32 - from.0 as f32) / scale;
+
+    let (in_slope, out_slope) = match preset {
+        TangentPreset::Flat => (0.0, 0.0),
+        TangentPreset::Smooth => {
+            let smooth = match (previous, next) {
+                (Some(previous), Some(next)) => slope(previous, next),
+                (Some(previous), None) => slope(previous, current),
+                (None, Some(next)) => slope(current, next),
+                (None, None) => 0.0,
+            };
+            (smooth, smooth)
+        }
+        TangentPreset::Linear => {
+            let in_slope = previous.map(|previous| slope(previous, current));
+            let out_slope = next.map(|next| slope(current, next));
+            (
+                in_slope.or(out_slope).unwrap_or(0.0),
+                out_slope.or(in_slope).unwrap_or(0.0),
+            )
+        }
+    };
+    set_slopes(tracks, channel, frame, Some(in_slope), Some(out_slope));
+}
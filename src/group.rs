@@ -0,0 +1,314 @@
+use crate::app::AnimateApp;
+use crate::hierarchy;
+use crate::library::compose_instance_transform;
+use crate::paint::Paint;
+use crate::project::{AnimObject, Layer, Shape};
+use crate::selection;
+use crate::tween;
+
+pub fn group_selected(app: &mut AnimateApp) {
+    let selected = app.selection.selected_objects.clone();
+    let entered = app.selection.entered_group;
+    let frame = app.current_frame;
+    let Some(layer_index) = app.project.layers.iter().position(|layer| {
+        tween::resolve_frame(layer, frame).is_some_and(|objects| {
+            container_objects(objects, entered)
+                .iter()
+                .any(|object| selected.contains(&object.id))
+        })
+    }) else {
+        return;
+    };
+
+    let parents: Vec<(uuid::Uuid, AnimObject)> = match entered {
+        Some(_) => Vec::new(),
+        None => tween::resolve_frame(&app.project.layers[layer_index], frame)
+            .unwrap_or_default()
+            .iter()
+            .filter(|object| selected.contains(&object.id))
+            .filter_map(|object| {
+                hierarchy::parent_transform(
+                    &app.project.layers[layer_index],
+                    object,
+                    &app.project.layers,
+                    frame,
+                )
+                .map(|parent| (object.id, parent))
+            })
+            .collect(),
+    };
+
+    app.history.push(app.project.clone(), "Group");
+    let layer = &mut app.project.layers[layer_index];
+    tween::ensure_keyframe_at(layer, frame);
+    let Some(container) = layer
+        .keyframes
+        .get_mut(&frame)
+        .and_then(|keyframe| container_mut(&mut keyframe.objects, entered))
+    else {
+        return;
+    };
+
+    let Some(top_index) = container
+        .iter()
+        .rposition(|object| selected.contains(&object.id))
+    else {
+        return;
+    };
+    let mut members = Vec::new();
+    let mut insert_at = top_index;
+    let mut index = 0;
+    while index < container.len() {
+        if selected.contains(&container[index].id) {
+            let mut member = container.remove(index);
+            if let Some((_, parent)) = parents.iter().find(|(id, _)| *id == member.id) {
+                member = compose_instance_transform(parent, &member);
+            }
+            member.parent_id = None;
+            members.push(member);
+            if index < insert_at {
+                insert_at -= 1;
+            }
+        } else {
+            index += 1;
+        }
+    }
+
+    let Some((min, max)) = selection::objects_extent(&members) else {
+        return;
+    };
+    let center = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0];
+    for member in &mut members {
+        member.position[0] -= center[0];
+        member.position[1] -= center[1];
+    }
+
+    let group = AnimObject::new(
+        Shape::Group { children: members },
+        center,
+        Paint::Solid([0.0, 0.0, 0.0, 0.0]),
+        Paint::Solid([0.0, 0.0, 0.0, 0.0]),
+        0.0,
+    );
+    let group_id = group.id;
+    container.insert(insert_at.min(container.len()), group);
+    app.selection.selected_objects = vec![group_id];
+}
+
+pub fn ungroup_selected(app: &mut AnimateApp) {
+    let selected = app.selection.selected_objects.clone();
+    let entered = app.selection.entered_group;
+    let frame = app.current_frame;
+    let is_selected_group = |object: &AnimObject| selected.contains(&object.id) && is_group(object);
+    if !app.project.layers.iter().any(|layer| {
+        tween::resolve_frame(layer, frame).is_some_and(|objects| {
+            container_objects(objects, entered)
+                .iter()
+                .any(is_selected_group)
+        })
+    }) {
+        return;
+    }
+
+    app.history.push(app.project.clone(), "Ungroup");
+    let mut released = Vec::new();
+    for layer in &mut app.project.layers {
+        let has_group = tween::resolve_frame(layer, frame).is_some_and(|objects| {
+            container_objects(objects, entered)
+                .iter()
+                .any(is_selected_group)
+        });
+        if !has_group {
+            continue;
+        }
+        tween::ensure_keyframe_at(layer, frame);
+        let Some(container) = layer
+            .keyframes
+            .get_mut(&frame)
+            .and_then(|keyframe| container_mut(&mut keyframe.objects, entered))
+        else {
+            continue;
+        };
+        let mut index = 0;
+        while index < container.len() {
+            if !is_selected_group(&container[index]) {
+                index += 1;
+                continue;
+            }
+            let group = container.remove(index);
+            let Shape::Group { children } = &group.shape else {
+                continue;
+            };
+            for child in children {
+                let mut child = compose_instance_transform(&group, child);
+                child.parent_id = group.parent_id;
+                released.push(child.id);
+                container.insert(index, child);
+                index += 1;
+            }
+        }
+    }
+    app.selection.selected_objects = released;
+}
+
+pub fn enter_group(app: &mut AnimateApp, group_id: uuid::Uuid) {
+    app.selection.entered_group = Some(group_id);
+    app.selection.selected_objects.clear();
+}
+
+pub fn exit_group(app: &mut AnimateApp) {
+    let Some(group_id) = app.selection.entered_group else {
+        return;
+    };
+    app.selection.entered_group = app
+        .project
+        .layers
+        .iter()
+        .find_map(|layer| {
+            tween::resolve_frame(layer, app.current_frame)
+                .and_then(|objects| containing_group(&objects, group_id, None))
+        })
+        .flatten();
+    app.selection.selected_objects = vec![group_id];
+}
+
+pub fn validate_entered_group(app: &mut AnimateApp) {
+    let Some(group_id) = app.selection.entered_group else {
+        return;
+    };
+    let exists = app.project.layers.iter().any(|layer| {
+        tween::resolve_frame(layer, app.current_frame)
+            .is_some_and(|objects| find_group_world(&objects, group_id).is_some())
+    });
+    if !exists {
+        app.selection.entered_group = None;
+    }
+}
+
+pub fn assign_new_ids(object: &mut AnimObject) {
+    object.id = uuid::Uuid::new_v4();
+    if let Shape::Group { children } = &mut object.shape {
+        for child in children {
+            assign_new_ids(child);
+        }
+    }
+}
+
+pub fn contains_object(objects: &[AnimObject], object_id: uuid::Uuid) -> bool {
+    objects.iter().any(|object| {
+        object.id == object_id
+            || matches!(&object.shape, Shape::Group { children } if contains_object(children, object_id))
+    })
+}
+
+pub fn is_group(object: &AnimObject) -> bool {
+    matches!(object.shape, Shape::Group { .. })
+}
+
+fn containing_group(
+    objects: &[AnimObject],
+    group_id: uuid::Uuid,
+    parent: Option<uuid::Uuid>,
+) -> Option<Option<uuid::Uuid>> {
+    objects.iter().find_map(|object| {
+        if object.id == group_id {
+            return Some(parent);
+        }
+        match &object.shape {
+            Shape::Group { children } => containing_group(children, group_id, Some(object.id)),
+            _ => None,
+        }
+    })
+}
+
+fn find_group_world(objects: &[AnimObject], group_id: uuid::Uuid) -> Option<AnimObject> {
+    objects.iter().find_map(|object| {
+        let Shape::Group { children } = &object.shape else {
+            return None;
+        };
+        if object.id == group_id {
+            return Some(object.clone());
+        }
+        find_group_world(children, group_id)
+            .map(|nested| compose_instance_transform(object, &nested))
+    })
+}
+
+pub fn container_objects(objects: Vec<AnimObject>, entered: Option<uuid::Uuid>) -> Vec<AnimObject> {
+    let Some(group_id) = entered else {
+        return objects;
+    };
+    match find_group_world(&objects, group_id).map(|group| group.shape) {
+        Some(Shape::Group { children }) => children,
+        _ => Vec::new(),
+    }
+}
+
+pub fn editable_objects(objects: Vec<AnimObject>, entered: Option<uuid::Uuid>) -> Vec<AnimObject> {
+    let Some(group_id) = entered else {
+        return objects;
+    };
+    let Some(group) = find_group_world(&objects, group_id) else {
+        return Vec::new();
+    };
+    let Shape::Group { children } = &group.shape else {
+        return Vec::new();
+    };
+    children
+        .iter()
+        .map(|child| compose_instance_transform(&group, child))
+        .collect()
+}
+
+pub fn container_mut(
+    objects: &mut Vec<AnimObject>,
+    entered: Option<uuid::Uuid>,
+) -> Option<&mut Vec<AnimObject>> {
+    let Some(group_id) = entered else {
+        return Some(objects);
+    };
+    for object in objects.iter_mut() {
+        let is_target = object.id == group_id;
+        if let Shape::Group { children } = &mut object.shape {
+            if is_target {
+                return Some(children);
+            }
+            if let Some(container) = container_mut(children, entered) {
+                return Some(container);
+            }
+        }
+    }
+    None
+}
+
+pub fn editing_parent(
+    layer: &Layer,
+    object: &AnimObject,
+    layers: &[Layer],
+    frame: u32,
+    entered: Option<uuid::Uuid>,
+) -> Option<AnimObject> {
+    match entered {
+        None => hierarchy::parent_transform(layer, object, layers, frame),
+        Some(group_id) => {
+            let objects = tween::resolve_world_frame(layer, frame, layers)?;
+            find_group_world(&objects, group_id)
+        }
+    }
+}
+
+pub fn flatten_groups(objects: Vec<AnimObject>) -> Vec<AnimObject> {
+    let mut flattened = Vec::with_capacity(objects.len());
+    for object in objects {
+        match &object.shape {
+            Shape::Group { children } => flattened.extend(flatten_groups(
+                children
+                    .iter()
+                    .map(|child| compose_instance_transform(&object, child))
+                    .collect(),
+            )),
+            _ => flattened.push(object),
+        }
+    }
+    flattened
+}
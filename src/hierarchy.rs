@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use crate::app::AnimateApp;
+use crate::library::compose_instance_transform;
+use crate::paint::Paint;
+use crate::project::{AnimObject, Layer, Shape};
+use crate::tween;
+
+const MAX_PARENT_DEPTH: usize = 16;
+
+struct ParentResolver<'a> {
+    layers: &'a [Layer],
+    frame: u32,
+    resolved: HashMap<usize, Vec<AnimObject>>,
+    world: HashMap<uuid::Uuid, AnimObject>,
+}
+
+impl<'a> ParentResolver<'a> {
+    fn new(layers: &'a [Layer], frame: u32) -> Self {
+        Self {
+            layers,
+            frame,
+            resolved: HashMap::new(),
+            world: HashMap::new(),
+        }
+    }
+
+    fn local_object(&mut self, object_id: uuid::Uuid) -> Option<(usize, AnimObject)> {
+        for (index, layer) in self.layers.iter().enumerate() {
+            let objects = self.resolved.entry(index).or_insert_with(|| {
+                let mut objects = tween::resolve_frame(layer, self.frame).unwrap_or_default();
+                crate::motion_guide::apply_motion_guide(
+                    &mut objects,
+                    layer,
+                    self.layers,
+                    self.frame,
+                );
+                objects
+            });
+            if let Some(object) = objects.iter().find(|object| object.id == object_id) {
+                return Some((index, object.clone()));
+            }
+        }
+        None
+    }
+
+    fn world_object(&mut self, object_id: uuid::Uuid, depth: usize) -> Option<AnimObject> {
+        if depth >= MAX_PARENT_DEPTH {
+            return None;
+        }
+        if let Some(object) = self.world.get(&object_id) {
+            return Some(object.clone());
+        }
+        let (layer_index, mut object) = self.local_object(object_id)?;
+        if let Some(parent_id) = parent_of(&self.layers[layer_index], &object)
+            && let Some(parent) = self.world_object(parent_id, depth + 1)
+        {
+            object = compose_instance_transform(&parent, &object);
+        }
+        self.world.insert(object_id, object.clone());
+        Some(object)
+    }
+}
+
+pub fn insert_null_object(app: &mut AnimateApp) {
+    app.history.push(app.project.clone(), "Add Null Object");
+
+    tween::ensure_keyframe_at(&mut app.project.layers[app.active_layer], app.current_frame);
+
+    let null_object = AnimObject::new(
+        Shape::Null,
+        [
+            app.project.canvas_width as f32 / 2.0,
+            app.project.canvas_height as f32 / 2.0,
+        ],
+        Paint::Solid([0.0, 0.0, 0.0, 0.0]),
+        Paint::Solid([0.0, 0.0, 0.0, 0.0]),
+        0.0,
+    );
+
+    let null_id = null_object.id;
+    if let Some(keyframe) = app.project.layers[app.active_layer]
+        .keyframes
+        .get_mut(&app.current_frame)
+    {
+        keyframe.objects.push(null_object);
+    }
+
+    app.selection.selected_objects.clear();
+    app.selection.selected_objects.push(null_id);
+}
+
+pub fn parent_of(layer: &Layer, object: &AnimObject) -> Option<uuid::Uuid> {
+    object
+        .parent_id
+        .or(layer.transform_parent_id)
+        .filter(|parent_id| *parent_id != object.id)
+}
+
+pub fn apply_parenting(objects: &mut [AnimObject], layer: &Layer, layers: &[Layer], frame: u32) {
+    if !objects
+        .iter()
+        .any(|object| parent_of(layer, object).is_some())
+    {
+        return;
+    }
+    let mut resolver = ParentResolver::new(layers, frame);
+    for object in objects {
+        if let Some(parent_id) = parent_of(layer, object)
+            && let Some(parent) = resolver.world_object(parent_id, 0)
+        {
+            *object = compose_instance_transform(&parent, object);
+        }
+    }
+}
+
+pub fn parent_transform(
+    layer: &Layer,
+    object: &AnimObject,
+    layers: &[Layer],
+    frame: u32,
+) -> Option<AnimObject> {
+    let parent_id = parent_of(layer, object)?;
+    ParentResolver::new(layers, frame).world_object(parent_id, 0)
+}
+
+pub fn to_local(parent: &AnimObject, object: &AnimObject) -> AnimObject {
+    let mut local = object.clone();
+    local.position = to_local_offset(
+        parent,
+        [
+            object.position[0] - parent.position[0],
+            object.position[1] - parent.position[1],
+        ],
+    );
+    local.rotation -= parent.rotation;
+    local.scale[0] /= safe_scale(parent.scale[0]);
+    local.scale[1] /= safe_scale(parent.scale[1]);
+    local
+}
+
+pub fn to_world_point(object: &AnimObject, local: [f32; 2]) -> [f32; 2] {
+    let x = local[0] * object.scale[0];
+    let y = local[1] * object.scale[1];
+    let (sin_r, cos_r) = object.rotation.sin_cos();
+    [
+        object.position[0] + x * cos_r - y * sin_r,
+        object.position[1] + x * sin_r + y * cos_r,
+    ]
+}
+
+pub fn to_local_point(object: &AnimObject, world: [f32; 2]) -> [f32; 2] {
+    to_local_offset(
+        object,
+        [world[0] - object.position[0], world[1] - object.position[1]],
+    )
+}
+
+pub fn to_local_offset(parent: &AnimObject, offset: [f32; 2]) -> [f32; 2] {
+    let cos_r = parent.rotation.cos();
+    let sin_r = parent.rotation.sin();
+    [
+        (offset[0] * cos_r + offset[1] * sin_r) / safe_scale(parent.scale[0]),
+        (-offset[0] * sin_r + offset[1] * cos_r) / safe_scale(parent.scale[1]),
+    ]
+}
+
+pub fn reparent(layers: &mut [Layer], layer_index: usize, reassign: impl FnOnce(&mut Layer)) {
+    let before = keyframe_parents(layers, layer_index);
+    reassign(&mut layers[layer_index]);
+    let after = keyframe_parents(layers, layer_index);
+
+    for (frame, keyframe) in &mut layers[layer_index].keyframes {
+        for object in &mut keyframe.objects {
+            let key = (*frame, object.id);
+            let world = match before.get(&key) {
+                Some(parent) => compose_instance_transform(parent, object),
+                None => object.clone(),
+            };
+            *object = match after.get(&key) {
+                Some(parent) => to_local(parent, &world),
+                None => world,
+            };
+        }
+    }
+}
+
+fn keyframe_parents(
+    layers: &[Layer],
+    layer_index: usize,
+) -> HashMap<(u32, uuid::Uuid), AnimObject> {
+    let layer = &layers[layer_index];
+    let mut parents = HashMap::new();
+    for (frame, keyframe) in &layer.keyframes {
+        for object in &keyframe.objects {
+            if let Some(parent) = parent_transform(layer, object, layers, *frame) {
+                parents.insert((*frame, object.id), parent);
+            }
+        }
+    }
+    parents
+}
+
+pub fn has_selected_ancestor(
+    object_id: uuid::Uuid,
+    selected: &[uuid::Uuid],
+    layers: &[Layer],
+    frame: u32,
+) -> bool {
+    selected.iter().any(|selected_id| {
+        *selected_id != object_id && is_ancestor(*selected_id, object_id, layers, frame)
+    })
+}
+
+pub fn is_ancestor(
+    ancestor_id: uuid::Uuid,
+    object_id: uuid::Uuid,
+    layers: &[Layer],
+    frame: u32,
+) -> bool {
+    let mut current = object_id;
+    for _ in 0..MAX_PARENT_DEPTH {
+        let Some(parent_id) = find_parent(layers, current, frame) else {
+            return false;
+        };
+        if parent_id == ancestor_id {
+            return true;
+        }
+        current = parent_id;
+    }
+    true
+}
+
+fn find_parent(layers: &[Layer], object_id: uuid::Uuid, frame: u32) -> Option<uuid::Uuid> {
+    layers.iter().find_map(|layer| {
+        tween::resolve_frame(layer, frame)?
+            .iter()
+            .find(|object| object.id == object_id)
+            .and_then(|object| parent_of(layer, object))
+    })
+}
+
+pub fn parent_choices(layers: &[Layer], frame: u32) -> Vec<(uuid::Uuid, String)> {
+    let mut choices = Vec::new();
+    for layer in layers {
+        let Some(objects) = tween::resolve_frame(layer, frame) else {
+            continue;
+        };
+        for (index, object) in objects.iter().enumerate() {
+            choices.push((
+                object.id,
+                format!("{}: {} {}", layer.name, object.shape.name(), index + 1),
+            ));
+        }
+    }
+    choices
+}
+
+fn safe_scale(scale: f32) -> f32 {
+    if scale.abs() < 0.0001 {
+        0.0001_f32.copysign(scale)
+    } else {
+        scale
+    }
+}
@@ -1,10 +1,72 @@
-use crate::project::Project;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use nightshade::prelude::*;
+
+use crate::app::AnimateApp;
+use crate::project::{Layer, Project};
 
 const MAX_HISTORY: usize = 100;
 
+pub struct HistoryEntry {
+    pub name: String,
+    snapshot: Snapshot,
+}
+
+struct Snapshot {
+    project: Project,
+    layers: Vec<Vec<Arc<Layer>>>,
+}
+
+impl Snapshot {
+    fn new(mut project: Project, previous: Option<&Snapshot>) -> Self {
+        let shared: HashMap<uuid::Uuid, &Arc<Layer>> = previous
+            .into_iter()
+            .flat_map(|snapshot| snapshot.layers.iter().flatten())
+            .map(|layer| (layer.id, layer))
+            .collect();
+        let layers = layer_sets(&mut project)
+            .map(|set| {
+                std::mem::take(set)
+                    .into_iter()
+                    .map(|layer| match shared.get(&layer.id) {
+                        Some(existing) if ***existing == layer => Arc::clone(existing),
+                        _ => Arc::new(layer),
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { project, layers }
+    }
+
+    fn restore(self) -> Project {
+        let Self {
+            mut project,
+            layers,
+        } = self;
+        for (set, shared) in layer_sets(&mut project).zip(layers) {
+            *set = shared.into_iter().map(Arc::unwrap_or_clone).collect();
+        }
+        project
+    }
+}
+
+fn layer_sets(project: &mut Project) -> impl Iterator<Item = &mut Vec<Layer>> {
+    std::iter::once(&mut project.layers)
+        .chain(project.scenes.iter_mut().map(|scene| &mut scene.layers))
+        .chain(
+            project
+                .library
+                .symbols
+                .iter_mut()
+                .map(|symbol| &mut symbol.layers),
+        )
+}
+
 pub struct History {
-    pub undo_stack: Vec<Project>,
-    pub redo_stack: Vec<Project>,
+    pub undo_stack: Vec<HistoryEntry>,
+    pub redo_stack: Vec<HistoryEntry>,
+    base_name: String,
 }
 
 impl History {
@@ -12,21 +74,30 @@ impl History {
         Self {
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            base_name: "Initial State".to_string(),
         }
     }
 
-    pub fn push(&mut self, project: Project) {
-        self.undo_stack.push(project);
+    pub fn push(&mut self, project: Project, name: &str) {
+        let snapshot = Snapshot::new(project, self.undo_stack.last().map(|entry| &entry.snapshot));
+        self.undo_stack.push(HistoryEntry {
+            name: name.to_string(),
+            snapshot,
+        });
         self.redo_stack.clear();
         if self.undo_stack.len() > MAX_HISTORY {
-            self.undo_stack.remove(0);
+            self.base_name = self.undo_stack.remove(0).name;
         }
     }
 
     pub fn undo(&mut self, current: &mut Project) -> bool {
         if let Some(previous) = self.undo_stack.pop() {
-            self.redo_stack.push(current.clone());
-            *current = previous;
+            let snapshot = Snapshot::new(std::mem::take(current), Some(&previous.snapshot));
+            *current = previous.snapshot.restore();
+            self.redo_stack.push(HistoryEntry {
+                name: previous.name,
+                snapshot,
+            });
             true
         } else {
             false
@@ -35,11 +106,121 @@ impl History {
 
     pub fn redo(&mut self, current: &mut Project) -> bool {
         if let Some(next) = self.redo_stack.pop() {
-            self.undo_stack.push(current.clone());
-            *current = next;
+            let snapshot = Snapshot::new(std::mem::take(current), Some(&next.snapshot));
+            *current = next.snapshot.restore();
+            self.undo_stack.push(HistoryEntry {
+                name: next.name,
+                snapshot,
+            });
             true
         } else {
             false
         }
     }
+
+    pub fn base_name(&self) -> &str {
+        &self.base_name
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.undo_stack.len() + self.redo_stack.len()
+    }
+
+    pub fn position(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    pub fn step_name(&self, step: usize) -> &str {
+        if step < self.undo_stack.len() {
+            &self.undo_stack[step].name
+        } else {
+            &self.redo_stack[self.step_count() - 1 - step].name
+        }
+    }
+
+    pub fn go_to(&mut self, position: usize, current: &mut Project) -> bool {
+        let mut moved = false;
+        while self.position() > position && self.undo(current) {
+            moved = true;
+        }
+        while self.position() < position && self.redo(current) {
+            moved = true;
+        }
+        moved
+    }
+}
+
+pub fn draw_history_panel(app: &mut AnimateApp, ui: &mut egui::Ui) {
+    let position = app.history.position();
+    let mut target = None;
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        if ui
+            .selectable_label(position == 0, app.history.base_name())
+            .clicked()
+        {
+            target = Some(0);
+        }
+        for step in 0..app.history.step_count() {
+            let name = app.history.step_name(step);
+            let text = if step < position {
+                egui::RichText::new(name)
+            } else {
+                egui::RichText::new(name).weak()
+            };
+            if ui.selectable_label(position == step + 1, text).clicked() {
+                target = Some(step + 1);
+            }
+        }
+    });
+
+    if let Some(target) = target
+        && app.history.go_to(target, &mut app.project)
+    {
+        app.active_layer = app
+            .active_layer
+            .min(app.project.layers.len().saturating_sub(1));
+        app.selection.selected_objects.clear();
+        crate::group::validate_entered_group(app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_layers_are_shared_between_steps() {
+        let mut project = Project::default();
+        project.layers.push(Layer::new("Layer 2".to_string()));
+        let mut history = History::new();
+        history.push(project.clone(), "First");
+        project.layers[1].name = "Renamed".to_string();
+        history.push(project.clone(), "Second");
+
+        let first = &history.undo_stack[0].snapshot.layers[0];
+        let second = &history.undo_stack[1].snapshot.layers[0];
+        assert!(Arc::ptr_eq(&first[0], &second[0]));
+        assert!(!Arc::ptr_eq(&first[1], &second[1]));
+
+        project.layers[1].name = "Final".to_string();
+        assert!(history.undo(&mut project));
+        assert_eq!(project.layers[1].name, "Renamed");
+        assert!(history.undo(&mut project));
+        assert_eq!(project.layers[1].name, "Layer 2");
+        assert!(history.redo(&mut project));
+        assert!(history.redo(&mut project));
+        assert_eq!(project.layers[1].name, "Final");
+    }
+
+    #[test]
+    fn trimmed_history_labels_the_oldest_stored_state() {
+        let mut history = History::new();
+        for step in 0..=MAX_HISTORY {
+            history.push(Project::default(), &format!("Step {}", step));
+        }
+        assert_eq!(history.undo_stack.len(), MAX_HISTORY);
+        assert_eq!(history.base_name(), "Step 0");
+        assert_eq!(history.step_name(0), "Step 1");
+    }
 }
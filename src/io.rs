@@ -1,11 +1,23 @@
+use crate::migrations;
+use crate::package;
 use crate::project::Project;
 
 pub fn save_project(project: &Project, path: &std::path::Path) -> Result<(), String> {
+    project.load_assets()?;
+    if path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(package::PACKAGE_EXTENSION))
+    {
+        return package::save_package(project, path);
+    }
     let json = serde_json::to_string_pretty(project).map_err(|error| error.to_string())?;
     std::fs::write(path, json).map_err(|error| error.to_string())
 }
 
 pub fn load_project(path: &std::path::Path) -> Result<Project, String> {
+    if package::is_package(path) {
+        return package::load_package(path);
+    }
     let data = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
-    serde_json::from_str(&data).map_err(|error| error.to_string())
+    migrations::project_from_json(&data)
 }
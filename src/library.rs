@@ -2,7 +2,9 @@ use nightshade::prelude::*;
 
 use crate::app::AnimateApp;
 use crate::paint::Paint;
-use crate::project::{AnimObject, Keyframe, Layer, Shape, Symbol, TweenType};
+use crate::project::{
+    AnimObject, Keyframe, Layer, LayerType, Shape, Symbol, SymbolPlayMode, TweenType,
+};
 use crate::tween;
 
 pub fn convert_selection_to_symbol(app: &mut AnimateApp) {
@@ -27,7 +29,7 @@ pub fn convert_selection_to_symbol(app: &mut AnimateApp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Convert to Symbol");
 
     let mut min_x = f32::MAX;
     let mut min_y = f32::MAX;
@@ -90,7 +92,11 @@ pub fn convert_selection_to_symbol(app: &mut AnimateApp) {
     }
 
     let instance = AnimObject::new(
-        Shape::SymbolInstance { symbol_id },
+        Shape::SymbolInstance {
+            symbol_id,
+            play_mode: SymbolPlayMode::Loop,
+            first_frame: 0,
+        },
         [center_x, center_y],
         Paint::Solid([1.0, 1.0, 1.0, 1.0]),
         Paint::Solid([0.0, 0.0, 0.0, 0.0]),
@@ -111,12 +117,16 @@ pub fn convert_selection_to_symbol(app: &mut AnimateApp) {
 }
 
 pub fn instantiate_symbol(app: &mut AnimateApp, symbol_id: uuid::Uuid) {
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Add Symbol Instance");
 
     tween::ensure_keyframe_at(&mut app.project.layers[app.active_layer], app.current_frame);
 
     let instance = AnimObject::new(
-        Shape::SymbolInstance { symbol_id },
+        Shape::SymbolInstance {
+            symbol_id,
+            play_mode: SymbolPlayMode::Loop,
+            first_frame: 0,
+        },
         [
             app.project.canvas_width as f32 / 2.0,
             app.project.canvas_height as f32 / 2.0,
@@ -139,6 +149,146 @@ pub fn instantiate_symbol(app: &mut AnimateApp, symbol_id: uuid::Uuid) {
     app.selection.selected_objects.push(instance_id);
 }
 
+pub const MAX_SYMBOL_DEPTH: usize = 8;
+
+pub fn find_symbol(symbols: &[Symbol], symbol_id: uuid::Uuid) -> Option<&Symbol> {
+    symbols.iter().find(|symbol| symbol.id == symbol_id)
+}
+
+pub fn compose_instance_transform(instance: &AnimObject, object: &AnimObject) -> AnimObject {
+    let mut transformed = object.clone();
+    let cos_r = instance.rotation.cos();
+    let sin_r = instance.rotation.sin();
+    let local_x = object.position[0] * instance.scale[0];
+    let local_y = object.position[1] * instance.scale[1];
+    transformed.position[0] = instance.position[0] + local_x * cos_r - local_y * sin_r;
+    transformed.position[1] = instance.position[1] + local_x * sin_r + local_y * cos_r;
+    transformed.rotation += instance.rotation;
+    transformed.scale[0] *= instance.scale[0];
+    transformed.scale[1] *= instance.scale[1];
+    transformed
+}
+
+pub fn flatten_symbol_instance(
+    symbols: &[Symbol],
+    layer: &Layer,
+    frame: u32,
+    instance: &AnimObject,
+) -> Vec<AnimObject> {
+    let mut objects = Vec::new();
+    flatten_symbol_instance_recursive(symbols, layer, frame, instance, 0, &mut objects);
+    objects
+}
+
+fn flatten_symbol_instance_recursive(
+    symbols: &[Symbol],
+    layer: &Layer,
+    frame: u32,
+    instance: &AnimObject,
+    depth: usize,
+    objects: &mut Vec<AnimObject>,
+) {
+    if depth >= MAX_SYMBOL_DEPTH {
+        return;
+    }
+    let Shape::SymbolInstance { symbol_id, .. } = &instance.shape else {
+        return;
+    };
+    let Some(symbol) = find_symbol(symbols, *symbol_id) else {
+        return;
+    };
+
+    let symbol_frame = resolve_instance_frame(layer, frame, instance, symbol);
+    for symbol_layer in symbol.layers.iter().rev() {
+        if !symbol_layer.visible
+            || symbol_layer.layer_type == LayerType::Guide
+            || symbol_layer.layer_type == LayerType::Folder
+        {
+            continue;
+        }
+        let Some(symbol_objects) =
+            tween::resolve_world_frame(symbol_layer, symbol_frame, &symbol.layers)
+        else {
+            continue;
+        };
+        for symbol_object in &crate::group::flatten_groups(symbol_objects) {
+            if matches!(symbol_object.shape, Shape::SymbolInstance { .. }) {
+                let mut nested = Vec::new();
+                flatten_symbol_instance_recursive(
+                    symbols,
+                    symbol_layer,
+                    symbol_frame,
+                    symbol_object,
+                    depth + 1,
+                    &mut nested,
+                );
+                objects.extend(
+                    nested
+                        .iter()
+                        .map(|object| compose_instance_transform(instance, object)),
+                );
+            } else {
+                objects.push(compose_instance_transform(instance, symbol_object));
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn expand_symbol_instances(
+    symbols: &[Symbol],
+    layer: &Layer,
+    frame: u32,
+    objects: Vec<AnimObject>,
+) -> Vec<AnimObject> {
+    let mut expanded = Vec::with_capacity(objects.len());
+    for object in crate::group::flatten_groups(objects) {
+        if matches!(object.shape, Shape::SymbolInstance { .. }) {
+            expanded.extend(flatten_symbol_instance(symbols, layer, frame, &object));
+        } else {
+            expanded.push(object);
+        }
+    }
+    expanded
+}
+
+pub fn resolve_instance_frame(
+    layer: &Layer,
+    frame: u32,
+    instance: &AnimObject,
+    symbol: &Symbol,
+) -> u32 {
+    let Shape::SymbolInstance {
+        play_mode,
+        first_frame,
+        ..
+    } = &instance.shape
+    else {
+        return 0;
+    };
+
+    let total_frames = symbol.total_frames.max(1);
+    let first_frame = (*first_frame).min(total_frames - 1);
+    let elapsed = frame - instance_start_frame(layer, frame, instance.id);
+
+    match play_mode {
+        SymbolPlayMode::Loop => (first_frame + elapsed) % total_frames,
+        SymbolPlayMode::PlayOnce => (first_frame + elapsed).min(total_frames - 1),
+        SymbolPlayMode::SingleFrame => first_frame,
+    }
+}
+
+fn instance_start_frame(layer: &Layer, frame: u32, object_id: uuid::Uuid) -> u32 {
+    let mut start = frame;
+    for (keyframe_frame, keyframe) in layer.keyframes.range(..=frame).rev() {
+        if !crate::group::contains_object(&keyframe.objects, object_id) {
+            break;
+        }
+        start = *keyframe_frame;
+    }
+    start
+}
+
 pub fn draw_library_panel(app: &mut AnimateApp, ui: &mut egui::Ui) {
     ui.heading("Library");
     ui.separator();
@@ -206,7 +356,7 @@ pub fn draw_library_panel(app: &mut AnimateApp, ui: &mut egui::Ui) {
                 app.editing_symbol = None;
             }
             LibraryAction::Delete(index) => {
-                app.history.push(app.project.clone());
+                app.history.push(app.project.clone(), "Delete Symbol");
                 let removed_id = app.project.library.symbols[index].id;
                 app.project.library.symbols.remove(index);
                 if app.editing_symbol == Some(removed_id) {
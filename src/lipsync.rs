@@ -0,0 +1,460 @@
+use nightshade::prelude::*;
+
+use crate::app::AnimateApp;
+use crate::audio;
+use crate::paint::Paint;
+use crate::project::{AnimObject, AudioTrack, Keyframe, Shape, SymbolPlayMode};
+use crate::tween;
+
+const VISEME_COUNT: usize = 6;
+const MIN_HOLD_FRAMES: usize = 2;
+const CLOSED_LEVEL_FACTOR: f32 = 2.5;
+const FRICATIVE_FREQUENCY: f32 = 3000.0;
+const WIDE_FREQUENCY: f32 = 1800.0;
+const ROUND_FREQUENCY: f32 = 600.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Viseme {
+    Rest,
+    Closed,
+    Open,
+    Wide,
+    Round,
+    Fricative,
+}
+
+impl Viseme {
+    pub const ALL: [Viseme; VISEME_COUNT] = [
+        Viseme::Rest,
+        Viseme::Closed,
+        Viseme::Open,
+        Viseme::Wide,
+        Viseme::Round,
+        Viseme::Fricative,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Viseme::Rest => "Rest",
+            Viseme::Closed => "Closed (M, B, P)",
+            Viseme::Open => "Open (A, I)",
+            Viseme::Wide => "Wide (E)",
+            Viseme::Round => "Round (O, U, W)",
+            Viseme::Fricative => "Fricative (F, V, S)",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MouthSource {
+    Symbols,
+    Keyframes,
+}
+
+pub struct LipSyncState {
+    pub track_index: usize,
+    pub layer_index: usize,
+    pub source: MouthSource,
+    pub symbols: [Option<uuid::Uuid>; VISEME_COUNT],
+    pub frames: [Option<u32>; VISEME_COUNT],
+    pub threshold: f32,
+}
+
+impl LipSyncState {
+    pub fn new(app: &AnimateApp) -> Self {
+        Self {
+            track_index: 0,
+            layer_index: app.active_layer,
+            source: MouthSource::Symbols,
+            symbols: [None; VISEME_COUNT],
+            frames: [None; VISEME_COUNT],
+            threshold: 0.1,
+        }
+    }
+}
+
+struct FrameFeatures {
+    level: f32,
+    crossing_frequency: f32,
+    brightness: f32,
+}
+
+pub fn analyze_track(
+    track: &AudioTrack,
+    frame_rate: u32,
+    threshold: f32,
+) -> Result<Vec<Viseme>, String> {
+    let (sample_rate, samples) = audio::decode_mono(track)
+        .ok_or_else(|| format!("Failed to decode audio track {}", track.name))?;
+    let samples_per_frame = (sample_rate as f64 / frame_rate.max(1) as f64).max(1.0);
+    let frame_count = (samples.len() as f64 / samples_per_frame).ceil() as usize;
+
+    let features: Vec<FrameFeatures> = (0..frame_count)
+        .map(|frame| {
+            let start = (frame as f64 * samples_per_frame) as usize;
+            let end = (((frame + 1) as f64 * samples_per_frame) as usize).min(samples.len());
+            frame_features(&samples[start..end], sample_rate)
+        })
+        .collect();
+
+    let peak = features
+        .iter()
+        .fold(0.0_f32, |peak, features| peak.max(features.level));
+    if peak <= 0.0 {
+        return Ok(vec![Viseme::Rest; frame_count]);
+    }
+
+    let visemes = features
+        .iter()
+        .map(|features| classify(features, features.level / peak, threshold))
+        .collect();
+    Ok(hold_visemes(visemes, MIN_HOLD_FRAMES))
+}
+
+fn frame_features(window: &[f32], sample_rate: u32) -> FrameFeatures {
+    if window.len() < 2 {
+        return FrameFeatures {
+            level: 0.0,
+            crossing_frequency: 0.0,
+            brightness: 0.0,
+        };
+    }
+
+    let energy = window.iter().map(|sample| sample * sample).sum::<f32>() / window.len() as f32;
+    let pairs = window.len() - 1;
+    let crossings = window
+        .windows(2)
+        .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+        .count();
+    let difference_energy = window
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]) * (pair[1] - pair[0]))
+        .sum::<f32>()
+        / pairs as f32;
+
+    let level = energy.sqrt();
+    let ratio = if level > 0.0 {
+        (difference_energy.sqrt() / level / 2.0).min(1.0)
+    } else {
+        0.0
+    };
+
+    FrameFeatures {
+        level,
+        crossing_frequency: crossings as f32 * sample_rate as f32 / (2.0 * pairs as f32),
+        brightness: ratio.asin() * sample_rate as f32 / std::f32::consts::PI,
+    }
+}
+
+fn classify(features: &FrameFeatures, relative_level: f32, threshold: f32) -> Viseme {
+    if relative_level < threshold {
+        Viseme::Rest
+    } else if relative_level < threshold * CLOSED_LEVEL_FACTOR {
+        Viseme::Closed
+    } else if features.crossing_frequency > FRICATIVE_FREQUENCY {
+        Viseme::Fricative
+    } else if features.brightness > WIDE_FREQUENCY {
+        Viseme::Wide
+    } else if features.brightness < ROUND_FREQUENCY {
+        Viseme::Round
+    } else {
+        Viseme::Open
+    }
+}
+
+fn hold_visemes(mut visemes: Vec<Viseme>, min_hold: usize) -> Vec<Viseme> {
+    let mut start = 0;
+    while start < visemes.len() {
+        let viseme = visemes[start];
+        let end = visemes[start..]
+            .iter()
+            .position(|other| *other != viseme)
+            .map_or(visemes.len(), |offset| start + offset);
+        if end - start < min_hold && start > 0 {
+            let previous = visemes[start - 1];
+            visemes[start..end].fill(previous);
+        }
+        start = end;
+    }
+    visemes
+}
+
+pub fn generate_lip_sync(app: &mut AnimateApp, state: &LipSyncState) -> Result<usize, String> {
+    let track = app
+        .project
+        .audio_tracks
+        .get(state.track_index)
+        .ok_or_else(|| "No audio track selected".to_string())?;
+    let layer = app
+        .project
+        .layers
+        .get(state.layer_index)
+        .ok_or_else(|| "No target layer selected".to_string())?;
+    if layer.locked {
+        return Err(format!("Layer {} is locked", layer.name));
+    }
+
+    let start_frame = track.start_frame;
+    let total_frames = app.project.total_frames;
+    if start_frame >= total_frames {
+        return Err(format!(
+            "Audio track {} starts after the last frame",
+            track.name
+        ));
+    }
+
+    let poses = mouth_poses(app, state);
+    let fallback = poses[Viseme::Rest as usize]
+        .clone()
+        .or_else(|| poses.iter().flatten().next().cloned())
+        .ok_or_else(|| "Assign a mouth pose to at least one viseme".to_string())?;
+
+    let visemes = analyze_track(track, app.project.frame_rate, state.threshold)?;
+    let end_frame = (start_frame + visemes.len() as u32).min(total_frames);
+
+    app.history.push(app.project.clone(), "Generate Lip Sync");
+
+    let layer = &mut app.project.layers[state.layer_index];
+    layer
+        .keyframes
+        .retain(|frame, _| !(start_frame..end_frame).contains(frame));
+
+    let mut written = 0;
+    let mut previous = None;
+    for (frame, viseme) in (start_frame..end_frame).zip(visemes) {
+        if previous == Some(viseme) {
+            continue;
+        }
+        previous = Some(viseme);
+        let objects = poses[viseme as usize]
+            .clone()
+            .unwrap_or_else(|| fallback.clone());
+        layer.keyframes.insert(
+            frame,
+            Keyframe {
+                objects,
+                ..Keyframe::default()
+            },
+        );
+        written += 1;
+    }
+
+    if end_frame < total_frames
+        && previous != Some(Viseme::Rest)
+        && !layer.keyframes.contains_key(&end_frame)
+    {
+        let objects = poses[Viseme::Rest as usize].clone().unwrap_or(fallback);
+        layer.keyframes.insert(
+            end_frame,
+            Keyframe {
+                objects,
+                ..Keyframe::default()
+            },
+        );
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+fn mouth_poses(app: &AnimateApp, state: &LipSyncState) -> Vec<Option<Vec<AnimObject>>> {
+    let layer = &app.project.layers[state.layer_index];
+    match state.source {
+        MouthSource::Symbols => {
+            let template = symbol_template(app, state);
+            state
+                .symbols
+                .iter()
+                .map(|symbol_id| {
+                    let symbol_id = (*symbol_id)?;
+                    let mut instance = template.clone();
+                    if let Shape::SymbolInstance {
+                        symbol_id: instance_symbol,
+                        ..
+                    } = &mut instance.shape
+                    {
+                        *instance_symbol = symbol_id;
+                    }
+                    Some(vec![instance])
+                })
+                .collect()
+        }
+        MouthSource::Keyframes => state
+            .frames
+            .iter()
+            .map(|frame| {
+                let keyframe = layer.keyframes.get(&(*frame)?)?;
+                Some(keyframe.objects.clone())
+            })
+            .collect(),
+    }
+}
+
+fn symbol_template(app: &AnimateApp, state: &LipSyncState) -> AnimObject {
+    let layer = &app.project.layers[state.layer_index];
+    let start_frame = app
+        .project
+        .audio_tracks
+        .get(state.track_index)
+        .map_or(0, |track| track.start_frame);
+    tween::resolve_frame(layer, start_frame)
+        .into_iter()
+        .flatten()
+        .find(|object| matches!(object.shape, Shape::SymbolInstance { .. }))
+        .unwrap_or_else(|| {
+            AnimObject::new(
+                Shape::SymbolInstance {
+                    symbol_id: uuid::Uuid::nil(),
+                    play_mode: SymbolPlayMode::Loop,
+                    first_frame: 0,
+                },
+                [
+                    app.project.canvas_width as f32 / 2.0,
+                    app.project.canvas_height as f32 / 2.0,
+                ],
+                Paint::Solid([1.0, 1.0, 1.0, 1.0]),
+                Paint::Solid([0.0, 0.0, 0.0, 0.0]),
+                0.0,
+            )
+        })
+}
+
+pub fn draw_lip_sync_window(app: &mut AnimateApp, ui_context: &egui::Context) {
+    let Some(mut state) = app.lip_sync.take() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut generate = false;
+
+    egui::Window::new("Lip Sync")
+        .open(&mut open)
+        .resizable(false)
+        .default_width(320.0)
+        .show(ui_context, |ui| {
+            egui::Grid::new("lip_sync_settings")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Audio:");
+                    let track_name = app
+                        .project
+                        .audio_tracks
+                        .get(state.track_index)
+                        .map_or("None", |track| track.name.as_str());
+                    egui::ComboBox::from_id_salt("lip_sync_track")
+                        .selected_text(track_name)
+                        .show_ui(ui, |ui| {
+                            for (index, track) in app.project.audio_tracks.iter().enumerate() {
+                                ui.selectable_value(&mut state.track_index, index, &track.name);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Mouth layer:");
+                    let layer_name = app
+                        .project
+                        .layers
+                        .get(state.layer_index)
+                        .map_or("None", |layer| layer.name.as_str());
+                    egui::ComboBox::from_id_salt("lip_sync_layer")
+                        .selected_text(layer_name)
+                        .show_ui(ui, |ui| {
+                            for (index, layer) in app.project.layers.iter().enumerate() {
+                                ui.selectable_value(&mut state.layer_index, index, &layer.name);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Silence:");
+                    ui.add(egui::Slider::new(&mut state.threshold, 0.01..=0.5));
+                    ui.end_row();
+                });
+
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut state.source, MouthSource::Symbols, "Library Symbols");
+                ui.radio_value(&mut state.source, MouthSource::Keyframes, "Layer Keyframes");
+            });
+
+            ui.separator();
+            draw_pose_grid(app, ui, &mut state);
+            ui.separator();
+
+            let ready = state.track_index < app.project.audio_tracks.len()
+                && state.layer_index < app.project.layers.len();
+            if ui
+                .add_enabled(ready, egui::Button::new("Generate"))
+                .clicked()
+            {
+                generate = true;
+            }
+        });
+
+    if generate {
+        match generate_lip_sync(app, &state) {
+            Ok(_) => {
+                app.active_layer = state.layer_index;
+                return;
+            }
+            Err(error) => eprintln!("Lip sync failed: {}", error),
+        }
+    }
+
+    if open {
+        app.lip_sync = Some(state);
+    }
+}
+
+fn draw_pose_grid(app: &AnimateApp, ui: &mut egui::Ui, state: &mut LipSyncState) {
+    let symbols = &app.project.library.symbols;
+    let key_frames: Vec<u32> = app
+        .project
+        .layers
+        .get(state.layer_index)
+        .map(|layer| layer.keyframes.keys().copied().collect())
+        .unwrap_or_default();
+
+    egui::Grid::new("lip_sync_poses")
+        .num_columns(2)
+        .show(ui, |ui| {
+            for viseme in Viseme::ALL {
+                let index = viseme as usize;
+                ui.label(viseme.name());
+                match state.source {
+                    MouthSource::Symbols => {
+                        let selected = state.symbols[index]
+                            .and_then(|id| symbols.iter().find(|symbol| symbol.id == id))
+                            .map_or("None", |symbol| symbol.name.as_str());
+                        egui::ComboBox::from_id_salt(("lip_sync_symbol", index))
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut state.symbols[index], None, "None");
+                                for symbol in symbols {
+                                    ui.selectable_value(
+                                        &mut state.symbols[index],
+                                        Some(symbol.id),
+                                        &symbol.name,
+                                    );
+                                }
+                            });
+                    }
+                    MouthSource::Keyframes => {
+                        let selected = state.frames[index]
+                            .map_or("None".to_string(), |frame| format!("Frame {}", frame + 1));
+                        egui::ComboBox::from_id_salt(("lip_sync_frame", index))
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut state.frames[index], None, "None");
+                                for frame in &key_frames {
+                                    ui.selectable_value(
+                                        &mut state.frames[index],
+                                        Some(*frame),
+                                        format!("Frame {}", frame + 1),
+                                    );
+                                }
+                            });
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
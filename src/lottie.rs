@@ -1,65 +1,373 @@
+use std::collections::{BTreeMap, HashMap};
+
 use nightshade::prelude::*;
 
-use crate::project::{LayerType, Project, Shape, TweenType};
+use crate::export::RenderSettings;
+use crate::library;
+use crate::paint::{GradientStop, Paint};
+use crate::project::{
+    AnimObject, BlendMode, FontFamily, Keyframe, Layer, LayerType, PathPoint, Project, PropertyKey,
+    PropertyTracks, Shape, Symbol, SymbolPlayMode, TweenType,
+};
+use crate::raster;
+use crate::scenes;
+use crate::tween;
 
-pub fn export_lottie(project: &Project, path: &std::path::Path) {
-    let composition = build_lottie_composition(project);
-    let json = serde_json::to_string_pretty(&composition).unwrap_or_default();
-    let _ = std::fs::write(path, json);
+pub fn export_lottie(
+    project: &Project,
+    path: &std::path::Path,
+    settings: &RenderSettings,
+) -> Result<(), String> {
+    project.load_assets()?;
+    let composition = build_lottie_composition(project, settings);
+    let json = serde_json::to_string_pretty(&composition).map_err(|error| error.to_string())?;
+    std::fs::write(path, json)
+        .map_err(|error| format!("Failed to write {}: {}", path.display(), error))
 }
 
-fn build_lottie_composition(project: &Project) -> serde_json::Value {
-    let mut layers = Vec::new();
+#[derive(Default)]
+struct LottieAssets {
+    assets: Vec<serde_json::Value>,
+    fonts: Vec<FontFamily>,
+}
 
-    for (layer_index, layer) in project.layers.iter().enumerate().rev() {
-        if !layer.visible {
-            continue;
-        }
-        if layer.layer_type == LayerType::Guide || layer.layer_type == LayerType::Folder {
-            continue;
-        }
+struct LottieStack<'a> {
+    layers: &'a [Layer],
+    frames: std::ops::Range<u32>,
+    total_frames: u32,
+    offset: f64,
+    scale: f64,
+    depth: usize,
+}
 
-        let lottie_layer = build_lottie_layer(project, layer_index);
-        if let Some(lottie_layer) = lottie_layer {
-            layers.push(lottie_layer);
+fn build_lottie_composition(project: &Project, settings: &RenderSettings) -> serde_json::Value {
+    let mut assets = LottieAssets::default();
+    let layers = match settings.scene {
+        None if project.scenes.len() > 1 => build_scene_layers(project, settings, &mut assets),
+        scene => {
+            let (scene_layers, total_frames) =
+                scenes::scene_layers(project, scene.unwrap_or(project.active_scene));
+            let stack = LottieStack {
+                layers: scene_layers,
+                frames: settings.frames(),
+                total_frames,
+                offset: 0.0,
+                scale: settings.scale as f64 * 100.0,
+                depth: 0,
+            };
+            build_lottie_layers(project, &stack, &mut assets)
         }
-    }
+    };
+
+    let (width, height) = settings.output_size(project);
 
     serde_json::json!({
         "v": "5.7.4",
         "fr": project.frame_rate,
-        "ip": 0,
-        "op": project.total_frames,
-        "w": project.canvas_width,
-        "h": project.canvas_height,
+        "ip": settings.start_frame,
+        "op": settings.end_frame,
+        "w": width,
+        "h": height,
         "nm": project.name,
         "ddd": 0,
-        "assets": [],
+        "assets": assets.assets,
+        "fonts": { "list": assets.fonts.iter().map(|family| lottie_font(*family)).collect::<Vec<_>>() },
         "layers": layers
     })
 }
 
-fn build_lottie_layer(project: &Project, layer_index: usize) -> Option<serde_json::Value> {
-    let layer = &project.layers[layer_index];
+fn lottie_font(family: FontFamily) -> serde_json::Value {
+    let (name, css_family) = lottie_font_names(family);
+    serde_json::json!({
+        "fName": name,
+        "fFamily": css_family,
+        "fStyle": "Regular",
+        "ascent": TEXT_ASCENT * 100.0
+    })
+}
+
+fn lottie_font_names(family: FontFamily) -> (&'static str, &'static str) {
+    match family {
+        FontFamily::SansSerif => ("SansSerif", "sans-serif"),
+        FontFamily::Serif => ("Serif", "serif"),
+        FontFamily::Monospace => ("Monospace", "monospace"),
+    }
+}
 
-    let keyframe_entries: Vec<(u32, &crate::project::Keyframe)> = layer
-        .keyframes
-        .iter()
-        .map(|(frame, keyframe)| (*frame, keyframe))
-        .collect();
+fn build_scene_layers(
+    project: &Project,
+    settings: &RenderSettings,
+    assets: &mut LottieAssets,
+) -> Vec<serde_json::Value> {
+    let layer_scale = settings.scale as f64 * 100.0;
+    let mut layers = Vec::new();
 
-    if keyframe_entries.is_empty() {
-        return None;
+    for (scene_index, scene) in project.scenes.iter().enumerate() {
+        let (scene_layers, total_frames) = scenes::scene_layers(project, scene_index);
+        let start_frame = scenes::scene_start_frame(project, scene_index);
+        let stack = LottieStack {
+            layers: scene_layers,
+            frames: 0..total_frames,
+            total_frames,
+            offset: 0.0,
+            scale: 100.0,
+            depth: 0,
+        };
+        let asset_id = format!("scene_{}", scene.id.simple());
+        let scene_content = build_lottie_layers(project, &stack, assets);
+        assets
+            .assets
+            .push(serde_json::json!({ "id": asset_id, "layers": scene_content }));
+
+        layers.push(serde_json::json!({
+            "ddd": 0,
+            "ind": scene_index,
+            "ty": 0,
+            "nm": scene.name,
+            "refId": asset_id,
+            "sr": 1,
+            "ks": {
+                "o": static_value(vec![100.0]),
+                "r": static_value(vec![0.0]),
+                "p": static_value(vec![0.0, 0.0, 0.0]),
+                "a": static_value(vec![0.0, 0.0, 0.0]),
+                "s": static_value(vec![layer_scale, layer_scale, 100.0])
+            },
+            "ao": 0,
+            "w": project.canvas_width,
+            "h": project.canvas_height,
+            "ip": start_frame,
+            "op": start_frame + total_frames,
+            "st": start_frame,
+            "bm": 0
+        }));
+    }
+
+    layers
+}
+
+enum LayerPart {
+    Shapes(Vec<uuid::Uuid>),
+    Object(AnimObject),
+}
+
+fn layer_parts(layer: &Layer) -> Vec<LayerPart> {
+    let mut parts = Vec::new();
+    for object_id in collect_unique_object_ids(layer).into_iter().rev() {
+        let Some(object) = find_object_in_layer(layer, object_id) else {
+            continue;
+        };
+        match object.shape {
+            Shape::Text { .. } | Shape::RasterImage { .. } | Shape::SymbolInstance { .. } => {
+                parts.push(LayerPart::Object(object));
+            }
+            Shape::Null => {}
+            _ => match parts.last_mut() {
+                Some(LayerPart::Shapes(object_ids)) => object_ids.push(object_id),
+                _ => parts.push(LayerPart::Shapes(vec![object_id])),
+            },
+        }
+    }
+    parts
+}
+
+fn vector_object_ids(layer: &Layer) -> Vec<uuid::Uuid> {
+    layer_parts(layer)
+        .into_iter()
+        .flat_map(|part| match part {
+            LayerPart::Shapes(object_ids) => object_ids,
+            LayerPart::Object(_) => Vec::new(),
+        })
+        .collect()
+}
+
+fn folder_parent_index(layer: &Layer, stack: &LottieStack) -> Option<usize> {
+    let parent_id = layer.parent_id?;
+    stack.layers.iter().position(|candidate| {
+        candidate.id == parent_id && candidate.layer_type == LayerType::Folder
+    })
+}
+
+fn build_lottie_layers(
+    project: &Project,
+    stack: &LottieStack,
+    assets: &mut LottieAssets,
+) -> Vec<serde_json::Value> {
+    let mut layers = Vec::new();
+    let mut next_index = stack.layers.len();
+
+    for (layer_index, layer) in stack.layers.iter().enumerate() {
+        match layer.layer_type {
+            LayerType::Folder => {
+                layers.push(build_null_layer(
+                    layer,
+                    layer_index,
+                    folder_parent_index(layer, stack),
+                    stack,
+                ));
+                continue;
+            }
+            LayerType::Guide | LayerType::Mask => continue,
+            LayerType::Normal => {}
+        }
+        if !layer.visible {
+            continue;
+        }
+
+        let parts = layer_parts(layer);
+        if parts.is_empty() {
+            continue;
+        }
+
+        let matte = layer_index
+            .checked_sub(1)
+            .map(|above_index| &stack.layers[above_index])
+            .filter(|above| above.layer_type == LayerType::Mask && above.visible)
+            .and_then(|mask| build_shape_layer(mask, &vector_object_ids(mask), 0, stack));
+
+        let folder_index = folder_parent_index(layer, stack);
+        let mut primary_index = Some(layer_index);
+        let holder_index = if parts
+            .iter()
+            .any(|part| matches!(part, LayerPart::Object(_)))
+        {
+            layers.push(build_null_layer(layer, layer_index, folder_index, stack));
+            primary_index.take()
+        } else {
+            None
+        };
+
+        for part in parts {
+            let part_index = primary_index.take().unwrap_or_else(|| {
+                next_index += 1;
+                next_index - 1
+            });
+            let part_layer = match part {
+                LayerPart::Shapes(object_ids) => {
+                    build_shape_layer(layer, &object_ids, part_index, stack).map(
+                        |mut shape_layer| {
+                            if let Some(folder_index) = folder_index {
+                                shape_layer["parent"] = serde_json::json!(folder_index);
+                            }
+                            shape_layer
+                        },
+                    )
+                }
+                LayerPart::Object(object) => {
+                    let holder_index = holder_index.unwrap_or(layer_index);
+                    match &object.shape {
+                        Shape::Text { .. } => build_text_layer(
+                            layer,
+                            &object,
+                            holder_index,
+                            part_index,
+                            stack,
+                            assets,
+                        ),
+                        Shape::RasterImage { .. } => build_image_layer(
+                            project,
+                            layer,
+                            &object,
+                            holder_index,
+                            part_index,
+                            stack,
+                            assets,
+                        ),
+                        _ => build_symbol_instance_layer(
+                            project,
+                            layer,
+                            object.id,
+                            holder_index,
+                            part_index,
+                            stack,
+                            assets,
+                        ),
+                    }
+                }
+            };
+            let Some(mut part_layer) = part_layer else {
+                continue;
+            };
+            part_layer["bm"] = serde_json::json!(blend_mode_to_lottie(layer.blend_mode));
+
+            if let Some(matte) = &matte {
+                let mut matte_layer = matte.clone();
+                matte_layer["ind"] = serde_json::json!(next_index);
+                matte_layer["td"] = serde_json::json!(1);
+                next_index += 1;
+                layers.push(matte_layer);
+                part_layer["tt"] = serde_json::json!(1);
+            }
+            layers.push(part_layer);
+        }
+    }
+
+    layers
+}
+
+fn blend_mode_to_lottie(blend_mode: BlendMode) -> u32 {
+    match blend_mode {
+        BlendMode::Normal => 0,
+        BlendMode::Multiply => 1,
+        BlendMode::Screen => 2,
+        BlendMode::Overlay => 3,
+        BlendMode::Darken => 4,
+        BlendMode::Lighten => 5,
+        BlendMode::ColorDodge => 6,
+        BlendMode::ColorBurn => 7,
+        BlendMode::Difference => 10,
+        BlendMode::Exclusion => 11,
+    }
+}
+
+fn stack_transform(stack: &LottieStack, opacity: f64) -> serde_json::Value {
+    serde_json::json!({
+        "o": static_value(vec![opacity]),
+        "r": static_value(vec![0.0]),
+        "p": static_value(vec![stack.offset, stack.offset, 0.0]),
+        "a": static_value(vec![0.0, 0.0, 0.0]),
+        "s": static_value(vec![stack.scale, stack.scale, 100.0])
+    })
+}
+
+fn build_null_layer(
+    layer: &Layer,
+    layer_index: usize,
+    parent_index: Option<usize>,
+    stack: &LottieStack,
+) -> serde_json::Value {
+    let mut null_layer = serde_json::json!({
+        "ddd": 0,
+        "ind": layer_index,
+        "ty": 3,
+        "nm": layer.name,
+        "sr": 1,
+        "ks": stack_transform(stack, 100.0),
+        "ao": 0,
+        "ip": stack.frames.start,
+        "op": stack.frames.end,
+        "st": 0,
+        "bm": 0
+    });
+    if let Some(parent_index) = parent_index {
+        null_layer["parent"] = serde_json::json!(parent_index);
     }
+    null_layer
+}
 
-    let all_object_ids = collect_unique_object_ids(layer);
-    if all_object_ids.is_empty() {
+fn build_shape_layer(
+    layer: &Layer,
+    object_ids: &[uuid::Uuid],
+    layer_index: usize,
+    stack: &LottieStack,
+) -> Option<serde_json::Value> {
+    if layer.keyframes.is_empty() || object_ids.is_empty() {
         return None;
     }
 
     let mut shape_items = Vec::new();
-    for object_id in &all_object_ids {
-        let object_shapes = build_animated_object_shapes(project, layer, *object_id);
+    for object_id in object_ids {
+        let object_shapes = build_animated_object_shapes(layer, *object_id, stack);
         shape_items.extend(object_shapes);
     }
 
@@ -69,22 +377,337 @@ fn build_lottie_layer(project: &Project, layer_index: usize) -> Option<serde_jso
         "ty": 4,
         "nm": layer.name,
         "sr": 1,
+        "ks": stack_transform(stack, (layer.opacity * 100.0) as f64),
+        "ao": 0,
+        "shapes": shape_items,
+        "ip": stack.frames.start,
+        "op": stack.frames.end,
+        "st": 0,
+        "bm": 0
+    }))
+}
+
+fn build_text_layer(
+    layer: &Layer,
+    object: &AnimObject,
+    parent_index: usize,
+    layer_index: usize,
+    stack: &LottieStack,
+    assets: &mut LottieAssets,
+) -> Option<serde_json::Value> {
+    let Shape::Text {
+        font_size,
+        font_family,
+        ..
+    } = &object.shape
+    else {
+        return None;
+    };
+    if !assets.fonts.contains(font_family) {
+        assets.fonts.push(*font_family);
+    }
+
+    let mut documents = Vec::new();
+    for (frame, keyframe) in &layer.keyframes {
+        let Some(keyframe_object) = keyframe
+            .objects
+            .iter()
+            .find(|candidate| candidate.id == object.id)
+        else {
+            continue;
+        };
+        let Shape::Text {
+            content,
+            font_size,
+            font_family,
+        } = &keyframe_object.shape
+        else {
+            continue;
+        };
+        if !assets.fonts.contains(font_family) {
+            assets.fonts.push(*font_family);
+        }
+        let fill = keyframe_object.fill.as_solid();
+        let stroke = keyframe_object.stroke.as_solid();
+        let has_stroke = stroke[3] > 0.001 && keyframe_object.stroke_width > 0.0;
+        documents.push(serde_json::json!({
+            "t": *frame,
+            "s": {
+                "s": font_size,
+                "f": lottie_font_names(*font_family).0,
+                "t": content.replace('\n', "\r"),
+                "j": 0,
+                "tr": 0,
+                "lh": font_size * 1.2,
+                "ls": 0,
+                "fc": [fill[0], fill[1], fill[2]],
+                "sc": [stroke[0], stroke[1], stroke[2]],
+                "sw": if has_stroke { keyframe_object.stroke_width } else { 0.0 },
+                "of": has_stroke
+            }
+        }));
+    }
+
+    let keyframe_frames: Vec<u32> = layer.keyframes.keys().copied().collect();
+    let transform = build_animated_transform(layer, object.id, &keyframe_frames, stack);
+    let opacity = layer.opacity * object.fill.as_solid()[3] * 100.0;
+
+    Some(serde_json::json!({
+        "ddd": 0,
+        "ind": layer_index,
+        "ty": 5,
+        "nm": "Text",
+        "parent": parent_index,
+        "sr": 1,
+        "ks": {
+            "o": static_value(vec![opacity as f64]),
+            "r": transform["r"],
+            "p": transform["p"],
+            "a": static_value(vec![0.0, -(*font_size as f64) * TEXT_ASCENT, 0.0]),
+            "s": transform["s"]
+        },
+        "ao": 0,
+        "t": {
+            "d": { "k": documents },
+            "p": {},
+            "m": { "g": 1, "a": static_value(vec![0.0, 0.0]) },
+            "a": []
+        },
+        "ip": stack.frames.start,
+        "op": stack.frames.end,
+        "st": 0,
+        "bm": 0
+    }))
+}
+
+fn build_image_layer(
+    project: &Project,
+    layer: &Layer,
+    object: &AnimObject,
+    parent_index: usize,
+    layer_index: usize,
+    stack: &LottieStack,
+    assets: &mut LottieAssets,
+) -> Option<serde_json::Value> {
+    use base64::Engine;
+
+    let Shape::RasterImage {
+        image_id,
+        display_width,
+        display_height,
+        ..
+    } = &object.shape
+    else {
+        return None;
+    };
+    let image_asset = project
+        .image_assets
+        .iter()
+        .find(|asset| asset.id == *image_id)?;
+
+    let asset_id = format!("image_{}", image_asset.id.simple());
+    let has_asset = assets
+        .assets
+        .iter()
+        .any(|asset| asset["id"].as_str() == Some(asset_id.as_str()));
+    if !has_asset {
+        let bytes = image_asset.data.bytes().ok()?;
+        let mime_type = image::guess_format(bytes)
+            .map(|format| format.to_mime_type())
+            .unwrap_or("image/png");
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+        assets.assets.push(serde_json::json!({
+            "id": asset_id,
+            "w": image_asset.width,
+            "h": image_asset.height,
+            "u": "",
+            "p": format!("data:{};base64,{}", mime_type, encoded),
+            "e": 1
+        }));
+    }
+
+    let keyframe_frames: Vec<u32> = layer.keyframes.keys().copied().collect();
+    let transform = build_animated_transform(layer, object.id, &keyframe_frames, stack);
+    let mut scale = transform["s"].clone();
+    scale_property(
+        &mut scale,
+        [
+            *display_width as f64 / image_asset.width.max(1) as f64,
+            *display_height as f64 / image_asset.height.max(1) as f64,
+        ],
+    );
+
+    Some(serde_json::json!({
+        "ddd": 0,
+        "ind": layer_index,
+        "ty": 2,
+        "nm": image_asset.name,
+        "refId": asset_id,
+        "parent": parent_index,
+        "sr": 1,
         "ks": {
             "o": static_value(vec![(layer.opacity * 100.0) as f64]),
-            "r": static_value(vec![0.0]),
-            "p": static_value(vec![0.0, 0.0, 0.0]),
-            "a": static_value(vec![0.0, 0.0, 0.0]),
-            "s": static_value(vec![100.0, 100.0, 100.0])
+            "r": transform["r"],
+            "p": transform["p"],
+            "a": static_value(vec![
+                image_asset.width as f64 / 2.0,
+                image_asset.height as f64 / 2.0,
+                0.0
+            ]),
+            "s": scale
         },
         "ao": 0,
-        "shapes": shape_items,
-        "ip": 0,
-        "op": project.total_frames,
+        "ip": stack.frames.start,
+        "op": stack.frames.end,
+        "st": 0,
+        "bm": 0
+    }))
+}
+
+fn scale_property(property: &mut serde_json::Value, factor: [f64; 2]) {
+    let scale_values = |values: &mut serde_json::Value| {
+        for (index, factor) in factor.iter().enumerate() {
+            if let Some(value) = values.get_mut(index)
+                && let Some(number) = value.as_f64()
+            {
+                *value = serde_json::json!(number * factor);
+            }
+        }
+    };
+    if property["a"].as_i64() == Some(1) {
+        if let Some(keys) = property["k"].as_array_mut() {
+            for key in keys {
+                scale_values(&mut key["s"]);
+            }
+        }
+    } else {
+        scale_values(&mut property["k"]);
+    }
+}
+
+fn symbol_precomp_size(project: &Project) -> f64 {
+    project.canvas_width.max(project.canvas_height) as f64 * 2.0
+}
+
+fn build_symbol_instance_layer(
+    project: &Project,
+    layer: &Layer,
+    object_id: uuid::Uuid,
+    parent_index: usize,
+    layer_index: usize,
+    stack: &LottieStack,
+    assets: &mut LottieAssets,
+) -> Option<serde_json::Value> {
+    let instance = find_object_in_layer(layer, object_id)?;
+    let Shape::SymbolInstance { symbol_id, .. } = &instance.shape else {
+        return None;
+    };
+    if stack.depth >= library::MAX_SYMBOL_DEPTH {
+        return None;
+    }
+    let symbol = library::find_symbol(&project.library.symbols, *symbol_id)?;
+
+    let asset_id = format!("symbol_{}", symbol.id.simple());
+    let has_asset = assets
+        .assets
+        .iter()
+        .any(|asset| asset["id"].as_str() == Some(asset_id.as_str()));
+    if !has_asset {
+        assets
+            .assets
+            .push(serde_json::json!({ "id": asset_id, "layers": [] }));
+        let symbol_stack = LottieStack {
+            layers: &symbol.layers,
+            frames: 0..symbol.total_frames.max(1),
+            total_frames: symbol.total_frames.max(1),
+            offset: symbol_precomp_size(project) / 2.0,
+            scale: 100.0,
+            depth: stack.depth + 1,
+        };
+        let symbol_layers = build_lottie_layers(project, &symbol_stack, assets);
+        if let Some(asset) = assets
+            .assets
+            .iter_mut()
+            .find(|asset| asset["id"].as_str() == Some(asset_id.as_str()))
+        {
+            asset["layers"] = serde_json::Value::Array(symbol_layers);
+        }
+    }
+
+    let keyframe_frames: Vec<u32> = layer.keyframes.keys().copied().collect();
+    let transform = build_animated_transform(layer, object_id, &keyframe_frames, stack);
+    let precomp_size = symbol_precomp_size(project);
+
+    Some(serde_json::json!({
+        "ddd": 0,
+        "ind": layer_index,
+        "ty": 0,
+        "nm": symbol.name,
+        "refId": asset_id,
+        "parent": parent_index,
+        "sr": 1,
+        "ks": {
+            "o": static_value(vec![(layer.opacity * 100.0) as f64]),
+            "r": transform["r"],
+            "p": transform["p"],
+            "a": static_value(vec![precomp_size / 2.0, precomp_size / 2.0, 0.0]),
+            "s": transform["s"]
+        },
+        "ao": 0,
+        "tm": build_time_remap(project, layer, &instance, symbol, stack),
+        "w": precomp_size,
+        "h": precomp_size,
+        "ip": stack.frames.start,
+        "op": stack.frames.end,
         "st": 0,
         "bm": 0
     }))
 }
 
+fn build_time_remap(
+    project: &Project,
+    layer: &Layer,
+    instance: &AnimObject,
+    symbol: &Symbol,
+    stack: &LottieStack,
+) -> serde_json::Value {
+    let frame_rate = project.frame_rate.max(1) as f64;
+    let samples: Vec<(u32, u32)> = stack
+        .frames
+        .clone()
+        .map(|frame| {
+            let symbol_frame = tween::resolve_world_frame(layer, frame, stack.layers)
+                .and_then(|objects| objects.into_iter().find(|object| object.id == instance.id))
+                .map(|object| library::resolve_instance_frame(layer, frame, &object, symbol))
+                .unwrap_or(0);
+            (frame, symbol_frame)
+        })
+        .collect();
+
+    let mut keyframes = Vec::new();
+    let easing = tween_to_lottie_easing(TweenType::Linear);
+    for (index, (frame, symbol_frame)) in samples.iter().enumerate() {
+        let is_breakpoint = index == 0
+            || index == samples.len() - 1
+            || samples[index + 1].1 as i64 - *symbol_frame as i64
+                != *symbol_frame as i64 - samples[index - 1].1 as i64;
+        if is_breakpoint {
+            keyframes.push(serde_json::json!({
+                "t": *frame,
+                "s": [*symbol_frame as f64 / frame_rate],
+                "i": easing.0,
+                "o": easing.1
+            }));
+        }
+    }
+
+    serde_json::json!({
+        "a": 1,
+        "k": keyframes,
+        "ix": 2
+    })
+}
+
 fn collect_unique_object_ids(layer: &crate::project::Layer) -> Vec<uuid::Uuid> {
     let mut ids = Vec::new();
     for keyframe in layer.keyframes.values() {
@@ -98,16 +721,23 @@ fn collect_unique_object_ids(layer: &crate::project::Layer) -> Vec<uuid::Uuid> {
 }
 
 fn build_animated_object_shapes(
-    project: &Project,
     layer: &crate::project::Layer,
     object_id: uuid::Uuid,
+    stack: &LottieStack,
 ) -> Vec<serde_json::Value> {
+    let total_frames = stack.total_frames;
     let keyframe_frames: Vec<u32> = layer.keyframes.keys().copied().collect();
 
     let first_object = find_object_in_layer(layer, object_id);
     let Some(first_object) = first_object else {
         return Vec::new();
     };
+    if matches!(first_object.shape, Shape::SymbolInstance { .. }) {
+        return Vec::new();
+    }
+    if matches!(first_object.shape, Shape::Group { .. }) {
+        return build_group_shapes(layer, object_id, &keyframe_frames, stack);
+    }
 
     let mut group_items = Vec::new();
 
@@ -116,18 +746,29 @@ fn build_animated_object_shapes(
         group_items.push(shape_item);
     }
 
-    let fill_item = build_animated_fill(layer, object_id, &keyframe_frames, project.total_frames);
+    let bounds = local_paint_bounds(&first_object.shape);
+    let fill_item = find_gradient_paint(layer, object_id, |object| &object.fill)
+        .and_then(|paint| build_gradient_item("gf", &paint, bounds))
+        .unwrap_or_else(|| build_animated_fill(layer, object_id, &keyframe_frames, total_frames));
     group_items.push(fill_item);
 
     let stroke_color = first_object.stroke.as_solid();
     if stroke_color[3] > 0.001 && first_object.stroke_width > 0.0 {
-        let stroke_item =
-            build_animated_stroke(layer, object_id, &keyframe_frames, project.total_frames);
-        group_items.push(stroke_item);
+        let stroke_item = build_animated_stroke(layer, object_id, &keyframe_frames, total_frames);
+        let gradient_item = find_gradient_paint(layer, object_id, |object| &object.stroke)
+            .and_then(|paint| build_gradient_item("gs", &paint, bounds));
+        match gradient_item {
+            Some(mut gradient_item) => {
+                for key in ["w", "lc", "lj", "ml"] {
+                    gradient_item[key] = stroke_item[key].clone();
+                }
+                group_items.push(gradient_item);
+            }
+            None => group_items.push(stroke_item),
+        }
     }
 
-    let transform_item =
-        build_animated_transform(layer, object_id, &keyframe_frames, project.total_frames);
+    let transform_item = build_animated_transform(layer, object_id, &keyframe_frames, stack);
     group_items.push(transform_item);
 
     vec![serde_json::json!({
@@ -143,6 +784,161 @@ fn build_animated_object_shapes(
     })]
 }
 
+fn build_group_shapes(
+    layer: &Layer,
+    group_id: uuid::Uuid,
+    keyframe_frames: &[u32],
+    stack: &LottieStack,
+) -> Vec<serde_json::Value> {
+    let child_layer = group_child_layer(layer, group_id);
+    let mut group_items: Vec<serde_json::Value> = vector_object_ids(&child_layer)
+        .into_iter()
+        .flat_map(|child_id| build_animated_object_shapes(&child_layer, child_id, stack))
+        .collect();
+    group_items.push(build_animated_transform(
+        layer,
+        group_id,
+        keyframe_frames,
+        stack,
+    ));
+
+    vec![serde_json::json!({
+        "ty": "gr",
+        "it": group_items,
+        "nm": "Group",
+        "np": group_items.len(),
+        "cix": 2,
+        "bm": 0,
+        "ix": 1,
+        "mn": "ADBE Vector Group",
+        "hd": false
+    })]
+}
+
+fn group_child_layer(layer: &Layer, group_id: uuid::Uuid) -> Layer {
+    let mut child_layer = layer.clone();
+    child_layer.property_tracks.clear();
+    child_layer.expressions.clear();
+    child_layer.motion_guide = None;
+    child_layer.transform_parent_id = None;
+    for keyframe in child_layer.keyframes.values_mut() {
+        keyframe.objects = keyframe
+            .objects
+            .iter()
+            .find(|object| object.id == group_id)
+            .and_then(|group| match &group.shape {
+                Shape::Group { children } => Some(children.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+    }
+    child_layer
+}
+
+fn find_gradient_paint(
+    layer: &Layer,
+    object_id: uuid::Uuid,
+    select: fn(&AnimObject) -> &Paint,
+) -> Option<Paint> {
+    layer
+        .keyframes
+        .values()
+        .flat_map(|keyframe| keyframe.objects.iter())
+        .filter(|object| object.id == object_id)
+        .map(select)
+        .find(|paint| !matches!(paint, Paint::Solid(_)))
+        .cloned()
+}
+
+fn local_paint_bounds(shape: &Shape) -> [f32; 4] {
+    let bounds = match shape {
+        Shape::Rectangle { width, height, .. } => {
+            [-width / 2.0, -height / 2.0, width / 2.0, height / 2.0]
+        }
+        Shape::Ellipse { radius_x, radius_y } => [-radius_x, -radius_y, *radius_x, *radius_y],
+        Shape::Path { points, .. } => {
+            let positions: Vec<[f32; 2]> = points.iter().map(|point| point.position).collect();
+            raster::polygon_bounds(&positions)
+        }
+        Shape::Line { end_x, end_y } => raster::polygon_bounds(&[[0.0, 0.0], [*end_x, *end_y]]),
+        Shape::Text { .. }
+        | Shape::RasterImage { .. }
+        | Shape::SymbolInstance { .. }
+        | Shape::Null
+        | Shape::Group { .. } => [0.0, 0.0, 0.0, 0.0],
+    };
+    [
+        bounds[0],
+        bounds[1],
+        bounds[2] - bounds[0],
+        bounds[3] - bounds[1],
+    ]
+}
+
+fn build_gradient_item(
+    item_type: &str,
+    paint: &Paint,
+    bounds: [f32; 4],
+) -> Option<serde_json::Value> {
+    let to_local = |point: [f32; 2]| {
+        vec![
+            (bounds[0] + point[0] * bounds[2]) as f64,
+            (bounds[1] + point[1] * bounds[3]) as f64,
+        ]
+    };
+    let (gradient_type, start, end, stops) = match paint {
+        Paint::Solid(_) => return None,
+        Paint::LinearGradient { start, end, stops } => (1, to_local(*start), to_local(*end), stops),
+        Paint::RadialGradient {
+            center,
+            radius,
+            stops,
+        } => {
+            let start = to_local(*center);
+            let radius = (*radius * (bounds[2] + bounds[3]) / 2.0) as f64;
+            let end = vec![start[0] + radius, start[1]];
+            (2, start, end, stops)
+        }
+    };
+
+    let mut colors = Vec::new();
+    let mut alphas = Vec::new();
+    for stop in stops {
+        colors.extend([
+            stop.offset as f64,
+            stop.color[0] as f64,
+            stop.color[1] as f64,
+            stop.color[2] as f64,
+        ]);
+        alphas.extend([stop.offset as f64, stop.color[3] as f64]);
+    }
+    colors.extend(alphas);
+
+    let (name, match_name) = if item_type == "gs" {
+        ("Gradient Stroke", "ADBE Vector Graphic - G-Stroke")
+    } else {
+        ("Gradient Fill", "ADBE Vector Graphic - G-Fill")
+    };
+    Some(serde_json::json!({
+        "ty": item_type,
+        "o": static_value(vec![100.0]),
+        "r": 1,
+        "bm": 0,
+        "g": {
+            "p": stops.len(),
+            "k": static_value(colors)
+        },
+        "s": static_value(start),
+        "e": static_value(end),
+        "t": gradient_type,
+        "h": static_value(vec![0.0]),
+        "a": static_value(vec![0.0]),
+        "nm": name,
+        "mn": match_name,
+        "hd": false
+    }))
+}
+
 fn find_object_in_layer(
     layer: &crate::project::Layer,
     object_id: uuid::Uuid,
@@ -250,7 +1046,11 @@ fn build_shape_for_object(shape: &Shape) -> Option<serde_json::Value> {
                 "hd": false
             }))
         }
-        Shape::Text { .. } | Shape::RasterImage { .. } | Shape::SymbolInstance { .. } => None,
+        Shape::Text { .. }
+        | Shape::RasterImage { .. }
+        | Shape::SymbolInstance { .. }
+        | Shape::Null
+        | Shape::Group { .. } => None,
     }
 }
 
@@ -278,19 +1078,22 @@ fn build_animated_fill(
             .copied()
             .unwrap_or(total_frames);
         let easing = tween_to_lottie_easing(keyframe.tween);
+        let hold = u8::from(keyframe.tween == TweenType::None);
 
         color_keyframes.push(serde_json::json!({
             "t": *frame,
             "s": [color[0], color[1], color[2], 1.0],
             "i": easing.0,
-            "o": easing.1
+            "o": easing.1,
+            "h": hold
         }));
 
         opacity_keyframes.push(serde_json::json!({
             "t": *frame,
             "s": [color[3] * 100.0],
             "i": easing.0,
-            "o": easing.1
+            "o": easing.1,
+            "h": hold
         }));
 
         if index == keyframe_frames.len() - 1 {
@@ -377,19 +1180,22 @@ fn build_animated_stroke(
             .copied()
             .unwrap_or(total_frames);
         let easing = tween_to_lottie_easing(keyframe.tween);
+        let hold = u8::from(keyframe.tween == TweenType::None);
 
         color_keyframes.push(serde_json::json!({
             "t": *frame,
             "s": [color[0], color[1], color[2], 1.0],
             "i": easing.0,
-            "o": easing.1
+            "o": easing.1,
+            "h": hold
         }));
 
         width_keyframes.push(serde_json::json!({
             "t": *frame,
             "s": [stroke_width],
             "i": easing.0,
-            "o": easing.1
+            "o": easing.1,
+            "h": hold
         }));
 
         if index == keyframe_frames.len() - 1 {
@@ -472,9 +1278,11 @@ fn build_animated_transform(
     layer: &crate::project::Layer,
     object_id: uuid::Uuid,
     keyframe_frames: &[u32],
-    total_frames: u32,
+    stack: &LottieStack,
 ) -> serde_json::Value {
-    let has_animation = keyframe_frames.len() > 1;
+    let total_frames = stack.total_frames;
+    let baked = layer.motion_guide.is_some() || is_parented(layer, object_id);
+    let has_animation = keyframe_frames.len() > 1 || baked;
 
     if !has_animation {
         let object = find_object_in_layer(layer, object_id);
@@ -523,26 +1331,30 @@ fn build_animated_transform(
             .copied()
             .unwrap_or(total_frames);
         let easing = tween_to_lottie_easing(keyframe.tween);
+        let hold = u8::from(keyframe.tween == TweenType::None);
 
         position_keyframes.push(serde_json::json!({
             "t": *frame,
             "s": [position[0], position[1], 0.0],
             "i": easing.0,
-            "o": easing.1
+            "o": easing.1,
+            "h": hold
         }));
 
         rotation_keyframes.push(serde_json::json!({
             "t": *frame,
             "s": [rotation],
             "i": easing.0,
-            "o": easing.1
+            "o": easing.1,
+            "h": hold
         }));
 
         scale_keyframes.push(serde_json::json!({
             "t": *frame,
             "s": [scale[0], scale[1], 100.0],
             "i": easing.0,
-            "o": easing.1
+            "o": easing.1,
+            "h": hold
         }));
 
         if index == keyframe_frames.len() - 1 {
@@ -561,6 +1373,18 @@ fn build_animated_transform(
         }
     }
 
+    if baked {
+        bake_world_keyframes(
+            layer,
+            object_id,
+            keyframe_frames,
+            stack,
+            &mut position_keyframes,
+            &mut rotation_keyframes,
+            &mut scale_keyframes,
+        );
+    }
+
     serde_json::json!({
         "ty": "tr",
         "p": {
@@ -586,6 +1410,81 @@ fn build_animated_transform(
     })
 }
 
+fn is_parented(layer: &Layer, object_id: uuid::Uuid) -> bool {
+    layer
+        .transform_parent_id
+        .is_some_and(|parent_id| parent_id != object_id)
+        || layer
+            .keyframes
+            .values()
+            .flat_map(|keyframe| &keyframe.objects)
+            .any(|object| object.id == object_id && object.parent_id.is_some())
+}
+
+fn bake_world_keyframes(
+    layer: &Layer,
+    object_id: uuid::Uuid,
+    keyframe_frames: &[u32],
+    stack: &LottieStack,
+    position_keyframes: &mut Vec<serde_json::Value>,
+    rotation_keyframes: &mut Vec<serde_json::Value>,
+    scale_keyframes: &mut Vec<serde_json::Value>,
+) {
+    let parented = is_parented(layer, object_id);
+    let oriented = layer.motion_guide.is_some_and(|guide| guide.orient_to_path);
+    let first_frame = keyframe_frames[0];
+    let last_frame = if parented {
+        stack.total_frames.saturating_sub(1).max(first_frame)
+    } else {
+        keyframe_frames[keyframe_frames.len() - 1].min(stack.total_frames)
+    };
+    let easing = tween_to_lottie_easing(TweenType::Linear);
+    let baked_key = |frame: u32, value: Vec<f32>| {
+        serde_json::json!({
+            "t": frame,
+            "s": value,
+            "i": easing.0,
+            "o": easing.1,
+            "h": 0
+        })
+    };
+
+    let mut positions = Vec::new();
+    let mut rotations = Vec::new();
+    let mut scales = Vec::new();
+    for frame in first_frame..=last_frame {
+        let Some(objects) = tween::resolve_world_frame(layer, frame, stack.layers) else {
+            continue;
+        };
+        let Some(object) = objects.iter().find(|object| object.id == object_id) else {
+            continue;
+        };
+        positions.push(baked_key(
+            frame,
+            vec![object.position[0], object.position[1], 0.0],
+        ));
+        rotations.push(baked_key(frame, vec![object.rotation.to_degrees()]));
+        scales.push(baked_key(
+            frame,
+            vec![object.scale[0] * 100.0, object.scale[1] * 100.0, 100.0],
+        ));
+    }
+
+    let hold_last = |mut keys: Vec<serde_json::Value>, target: &mut Vec<serde_json::Value>| {
+        if let Some(last) = keys.last().cloned() {
+            keys.push(serde_json::json!({ "t": stack.total_frames, "s": last["s"] }));
+            *target = keys;
+        }
+    };
+    hold_last(positions, position_keyframes);
+    if parented || oriented {
+        hold_last(rotations, rotation_keyframes);
+    }
+    if parented {
+        hold_last(scales, scale_keyframes);
+    }
+}
+
 fn static_value(value: Vec<f64>) -> serde_json::Value {
     serde_json::json!({
         "a": 0,
@@ -601,16 +1500,16 @@ fn tween_to_lottie_easing(tween: TweenType) -> (serde_json::Value, serde_json::V
             serde_json::json!({"x": [0.833], "y": [0.833]}),
         ),
         TweenType::EaseIn => (
-            serde_json::json!({"x": [0.42], "y": [0.0]}),
-            serde_json::json!({"x": [1.0], "y": [1.0]}),
+            serde_json::json!({"x": [2.0 / 3.0], "y": [1.0 / 3.0]}),
+            serde_json::json!({"x": [1.0 / 3.0], "y": [0.0]}),
         ),
         TweenType::EaseOut => (
-            serde_json::json!({"x": [0.0], "y": [0.0]}),
-            serde_json::json!({"x": [0.58], "y": [1.0]}),
+            serde_json::json!({"x": [2.0 / 3.0], "y": [1.0]}),
+            serde_json::json!({"x": [1.0 / 3.0], "y": [2.0 / 3.0]}),
         ),
         TweenType::EaseInOut => (
-            serde_json::json!({"x": [0.42], "y": [0.0]}),
-            serde_json::json!({"x": [0.58], "y": [1.0]}),
+            serde_json::json!({"x": [0.55], "y": [1.0]}),
+            serde_json::json!({"x": [0.45], "y": [0.0]}),
         ),
         TweenType::CubicBezier { x1, y1, x2, y2 } => (
             serde_json::json!({"x": [x2], "y": [y2]}),
@@ -618,3 +1517,1818 @@ fn tween_to_lottie_easing(tween: TweenType) -> (serde_json::Value, serde_json::V
         ),
     }
 }
+
+pub fn import_lottie(project: &mut Project, path: &std::path::Path) -> Result<Vec<String>, String> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+    let composition: serde_json::Value = serde_json::from_str(&data)
+        .map_err(|error| format!("Failed to parse {}: {}", path.display(), error))?;
+    let layers = composition["layers"]
+        .as_array()
+        .ok_or_else(|| format!("{} is not a Lottie composition", path.display()))?;
+
+    let lottie_frame_rate = composition["fr"]
+        .as_f64()
+        .filter(|frame_rate| *frame_rate > 0.0)
+        .unwrap_or(project.frame_rate as f64);
+    let assets = composition["assets"]
+        .as_array()
+        .map(|assets| {
+            assets
+                .iter()
+                .filter_map(|asset| Some((asset["id"].as_str()?.to_string(), asset)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut importer = LottieImporter {
+        assets,
+        frame_rate: lottie_frame_rate,
+        time_scale: project.frame_rate.max(1) as f64 / lottie_frame_rate,
+        symbols: HashMap::new(),
+        imported_symbols: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let span = LottieTimeSpan {
+        origin: composition["ip"].as_f64().unwrap_or(0.0),
+        end: composition["op"].as_f64().unwrap_or(0.0),
+    };
+    let (imported_layers, end_frame) =
+        importer.import_layers(layers, LottieAffine::identity(), &span, 0);
+    if imported_layers.is_empty() {
+        return Err(format!(
+            "{} has no layers that can be imported",
+            path.display()
+        ));
+    }
+
+    project.layers.splice(0..0, imported_layers);
+    project.library.symbols.extend(importer.imported_symbols);
+    project.total_frames = project.total_frames.max(end_frame);
+    Ok(importer.warnings)
+}
+
+struct LottieImporter<'a> {
+    assets: HashMap<String, &'a serde_json::Value>,
+    frame_rate: f64,
+    time_scale: f64,
+    symbols: HashMap<String, uuid::Uuid>,
+    imported_symbols: Vec<Symbol>,
+    warnings: Vec<String>,
+}
+
+struct LottieTimeSpan {
+    origin: f64,
+    end: f64,
+}
+
+#[derive(Clone, Copy)]
+struct LottieAffine([f64; 6]);
+
+impl LottieAffine {
+    fn identity() -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0])
+    }
+
+    fn translation(x: f64, y: f64) -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, x, y])
+    }
+
+    fn rotation(degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self([cos, sin, -sin, cos, 0.0, 0.0])
+    }
+
+    fn scale(x: f64, y: f64) -> Self {
+        Self([x, 0.0, 0.0, y, 0.0, 0.0])
+    }
+
+    fn then(&self, inner: &Self) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        let [ia, ib, ic, id, ie, iff] = inner.0;
+        Self([
+            a * ia + c * ib,
+            b * ia + d * ib,
+            a * ic + c * id,
+            b * ic + d * id,
+            a * ie + c * iff + e,
+            b * ie + d * iff + f,
+        ])
+    }
+
+    fn apply(&self, point: [f64; 2]) -> [f64; 2] {
+        let [a, b, c, d, e, f] = self.0;
+        [
+            a * point[0] + c * point[1] + e,
+            b * point[0] + d * point[1] + f,
+        ]
+    }
+
+    fn rotation_radians(&self) -> f64 {
+        self.0[1].atan2(self.0[0])
+    }
+
+    fn decompose_scale(&self) -> [f64; 2] {
+        let [a, b, c, d, _, _] = self.0;
+        let scale_x = a.hypot(b);
+        if scale_x < 1e-9 {
+            return [0.0, 0.0];
+        }
+        [scale_x, (a * d - b * c) / scale_x]
+    }
+
+    fn mean_scale(&self) -> f64 {
+        let [a, b, c, d, _, _] = self.0;
+        (a * d - b * c).abs().sqrt()
+    }
+}
+
+trait LottieInterpolate: Clone + PartialEq {
+    fn interpolate(&self, other: &Self, t: f64) -> Self;
+}
+
+impl LottieInterpolate for Vec<f64> {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        self.iter()
+            .zip(other.iter())
+            .map(|(from, to)| from + (to - from) * t)
+            .collect()
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct LottiePath {
+    vertices: Vec<[f64; 2]>,
+    in_tangents: Vec<[f64; 2]>,
+    out_tangents: Vec<[f64; 2]>,
+    closed: bool,
+}
+
+impl LottieInterpolate for LottiePath {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        if self.vertices.len() != other.vertices.len() {
+            return self.clone();
+        }
+        let lerp_points = |from: &[[f64; 2]], to: &[[f64; 2]]| -> Vec<[f64; 2]> {
+            from.iter()
+                .zip(to.iter())
+                .map(|(from, to)| {
+                    [
+                        from[0] + (to[0] - from[0]) * t,
+                        from[1] + (to[1] - from[1]) * t,
+                    ]
+                })
+                .collect()
+        };
+        Self {
+            vertices: lerp_points(&self.vertices, &other.vertices),
+            in_tangents: lerp_points(&self.in_tangents, &other.in_tangents),
+            out_tangents: lerp_points(&self.out_tangents, &other.out_tangents),
+            closed: self.closed,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct AnimatedKey<T> {
+    time: f64,
+    start: T,
+    end: Option<T>,
+    tween: TweenType,
+}
+
+#[derive(Clone)]
+struct Animated<T> {
+    keys: Vec<AnimatedKey<T>>,
+}
+
+enum SegmentEase {
+    Constant,
+    Exact(TweenType),
+    Mixed,
+}
+
+impl<T: LottieInterpolate> Animated<T> {
+    fn constant(value: T) -> Self {
+        Self {
+            keys: vec![AnimatedKey {
+                time: 0.0,
+                start: value,
+                end: None,
+                tween: TweenType::None,
+            }],
+        }
+    }
+
+    fn is_animated(&self) -> bool {
+        let first = &self.keys[0].start;
+        self.keys
+            .iter()
+            .any(|key| key.start != *first || key.end.as_ref().is_some_and(|end| end != first))
+    }
+
+    fn segment_end(&self, index: usize) -> &T {
+        match (&self.keys[index].end, self.keys.get(index + 1)) {
+            (Some(end), _) => end,
+            (None, Some(next)) => &next.start,
+            (None, None) => &self.keys[index].start,
+        }
+    }
+
+    fn value_at(&self, time: f64) -> T {
+        let Some(index) = self.keys.iter().rposition(|key| key.time <= time) else {
+            return self.keys[0].start.clone();
+        };
+        let key = &self.keys[index];
+        let Some(next) = self.keys.get(index + 1) else {
+            return key.start.clone();
+        };
+        if key.tween == TweenType::None || next.time <= key.time {
+            return key.start.clone();
+        }
+        let progress = ((time - key.time) / (next.time - key.time)) as f32;
+        let eased = tween::apply_easing(progress.clamp(0.0, 1.0), key.tween) as f64;
+        key.start.interpolate(self.segment_end(index), eased)
+    }
+}
+
+trait LottieTimeline {
+    fn key_times(&self, offset: f64, times: &mut Vec<f64>);
+    fn segment_ease(&self, from: f64, to: f64) -> SegmentEase;
+}
+
+impl<T: LottieInterpolate> LottieTimeline for Animated<T> {
+    fn key_times(&self, offset: f64, times: &mut Vec<f64>) {
+        if self.is_animated() {
+            times.extend(self.keys.iter().map(|key| key.time + offset));
+        }
+    }
+
+    fn segment_ease(&self, from: f64, to: f64) -> SegmentEase {
+        if !self.is_animated() {
+            return SegmentEase::Constant;
+        }
+        let Some(index) = self
+            .keys
+            .iter()
+            .rposition(|key| key.time <= from + TIME_EPSILON)
+        else {
+            return SegmentEase::Constant;
+        };
+        let key = &self.keys[index];
+        let Some(next) = self.keys.get(index + 1) else {
+            return SegmentEase::Constant;
+        };
+        if key.tween == TweenType::None || key.start == *self.segment_end(index) {
+            return SegmentEase::Constant;
+        }
+        let exact = (key.time - from).abs() < TIME_EPSILON && (next.time - to).abs() < TIME_EPSILON;
+        if exact || key.tween == TweenType::Linear {
+            SegmentEase::Exact(key.tween)
+        } else {
+            SegmentEase::Mixed
+        }
+    }
+}
+
+const TIME_EPSILON: f64 = 1e-4;
+
+#[derive(Clone)]
+struct LottieTransform {
+    anchor: Animated<Vec<f64>>,
+    position: Animated<Vec<f64>>,
+    position_y: Option<Animated<Vec<f64>>>,
+    scale: Animated<Vec<f64>>,
+    rotation: Animated<Vec<f64>>,
+    opacity: Animated<Vec<f64>>,
+}
+
+impl LottieTransform {
+    fn matrix_at(&self, time: f64) -> LottieAffine {
+        let anchor = self.anchor.value_at(time);
+        let position = match &self.position_y {
+            Some(position_y) => vec![
+                component(&self.position.value_at(time), 0, 0.0),
+                component(&position_y.value_at(time), 0, 0.0),
+            ],
+            None => self.position.value_at(time),
+        };
+        let scale = self.scale.value_at(time);
+        LottieAffine::translation(component(&position, 0, 0.0), component(&position, 1, 0.0))
+            .then(&LottieAffine::rotation(component(
+                &self.rotation.value_at(time),
+                0,
+                0.0,
+            )))
+            .then(&LottieAffine::scale(
+                component(&scale, 0, 100.0) / 100.0,
+                component(&scale, 1, 100.0) / 100.0,
+            ))
+            .then(&LottieAffine::translation(
+                -component(&anchor, 0, 0.0),
+                -component(&anchor, 1, 0.0),
+            ))
+    }
+
+    fn has_animated_linear_part(&self) -> bool {
+        self.scale.is_animated() || self.rotation.is_animated()
+    }
+
+    fn timelines(&self) -> Vec<&dyn LottieTimeline> {
+        let mut timelines: Vec<&dyn LottieTimeline> =
+            vec![&self.anchor, &self.position, &self.scale, &self.rotation];
+        if let Some(position_y) = &self.position_y {
+            timelines.push(position_y);
+        }
+        timelines
+    }
+}
+
+#[derive(Clone)]
+struct TimedTransform {
+    transform: LottieTransform,
+    offset: f64,
+}
+
+#[derive(Clone)]
+struct TimedOpacity {
+    opacity: Animated<Vec<f64>>,
+    offset: f64,
+}
+
+#[derive(Clone)]
+enum LottiePaint {
+    Solid {
+        color: Animated<Vec<f64>>,
+        opacity: Animated<Vec<f64>>,
+    },
+    Gradient {
+        radial: bool,
+        start: Animated<Vec<f64>>,
+        end: Animated<Vec<f64>>,
+        stops: Animated<Vec<f64>>,
+        stop_count: usize,
+        opacity: Animated<Vec<f64>>,
+    },
+}
+
+impl LottiePaint {
+    fn is_animated(&self) -> bool {
+        match self {
+            LottiePaint::Solid { color, opacity } => color.is_animated() || opacity.is_animated(),
+            LottiePaint::Gradient {
+                start,
+                end,
+                stops,
+                opacity,
+                ..
+            } => {
+                start.is_animated()
+                    || end.is_animated()
+                    || stops.is_animated()
+                    || opacity.is_animated()
+            }
+        }
+    }
+
+    fn timelines(&self) -> Vec<&dyn LottieTimeline> {
+        match self {
+            LottiePaint::Solid { color, opacity } => vec![color, opacity],
+            LottiePaint::Gradient {
+                start,
+                end,
+                stops,
+                opacity,
+                ..
+            } => vec![start, end, stops, opacity],
+        }
+    }
+
+    fn evaluate(&self, time: f64, opacity_scale: f64, gradient_space: &LottieAffine) -> Paint {
+        match self {
+            LottiePaint::Solid { color, opacity } => {
+                let color = normalize_color(&color.value_at(time));
+                let alpha = component(&opacity.value_at(time), 0, 100.0) / 100.0 * opacity_scale;
+                Paint::Solid([color[0], color[1], color[2], alpha.clamp(0.0, 1.0) as f32])
+            }
+            LottiePaint::Gradient {
+                radial,
+                start,
+                end,
+                stops,
+                stop_count,
+                opacity,
+            } => {
+                let alpha = component(&opacity.value_at(time), 0, 100.0) / 100.0 * opacity_scale;
+                let stops = gradient_stops(&stops.value_at(time), *stop_count, alpha);
+                let start = start.value_at(time);
+                let end = end.value_at(time);
+                let start = gradient_space
+                    .apply([component(&start, 0, 0.0), component(&start, 1, 0.0)])
+                    .map(|value| value as f32);
+                let end = gradient_space
+                    .apply([component(&end, 0, 0.0), component(&end, 1, 0.0)])
+                    .map(|value| value as f32);
+                if *radial {
+                    Paint::RadialGradient {
+                        center: start,
+                        radius: (end[0] - start[0]).hypot(end[1] - start[1]),
+                        stops,
+                    }
+                } else {
+                    Paint::LinearGradient { start, end, stops }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct LottieStroke {
+    paint: LottiePaint,
+    width: Animated<Vec<f64>>,
+}
+
+enum LottieGeometry {
+    Rectangle {
+        center: Animated<Vec<f64>>,
+        size: Animated<Vec<f64>>,
+        roundness: Animated<Vec<f64>>,
+    },
+    Ellipse {
+        center: Animated<Vec<f64>>,
+        size: Animated<Vec<f64>>,
+    },
+    Path {
+        path: Animated<LottiePath>,
+    },
+    Text {
+        content: String,
+        font_size: f64,
+        font_family: FontFamily,
+    },
+    Symbol {
+        symbol_id: uuid::Uuid,
+        size: [f64; 2],
+        play_mode: SymbolPlayMode,
+        first_frame: u32,
+    },
+}
+
+struct ObjectSource {
+    id: uuid::Uuid,
+    geometry: LottieGeometry,
+    root: LottieAffine,
+    transforms: Vec<TimedTransform>,
+    opacities: Vec<TimedOpacity>,
+    fill: Option<LottiePaint>,
+    stroke: Option<LottieStroke>,
+    offset: f64,
+}
+
+impl ObjectSource {
+    fn matrix_at(&self, time: f64) -> LottieAffine {
+        self.transforms.iter().fold(self.root, |matrix, timed| {
+            matrix.then(&timed.transform.matrix_at(time - timed.offset))
+        })
+    }
+
+    fn timelines(&self) -> Vec<(&dyn LottieTimeline, f64)> {
+        let mut timelines = Vec::new();
+        for timed in &self.transforms {
+            timelines.extend(
+                timed
+                    .transform
+                    .timelines()
+                    .into_iter()
+                    .map(|timeline| (timeline, timed.offset)),
+            );
+        }
+        for timed in &self.opacities {
+            timelines.push((&timed.opacity as &dyn LottieTimeline, timed.offset));
+        }
+        match &self.geometry {
+            LottieGeometry::Rectangle {
+                center,
+                size,
+                roundness,
+            } => {
+                timelines.push((center as &dyn LottieTimeline, self.offset));
+                timelines.push((size, self.offset));
+                timelines.push((roundness, self.offset));
+            }
+            LottieGeometry::Ellipse { center, size } => {
+                timelines.push((center as &dyn LottieTimeline, self.offset));
+                timelines.push((size, self.offset));
+            }
+            LottieGeometry::Path { path } => timelines.push((path, self.offset)),
+            LottieGeometry::Text { .. } | LottieGeometry::Symbol { .. } => {}
+        }
+        if let Some(fill) = &self.fill {
+            timelines.extend(
+                fill.timelines()
+                    .into_iter()
+                    .map(|timeline| (timeline, self.offset)),
+            );
+        }
+        if let Some(stroke) = &self.stroke {
+            timelines.extend(
+                stroke
+                    .paint
+                    .timelines()
+                    .into_iter()
+                    .map(|timeline| (timeline, self.offset)),
+            );
+            timelines.push((&stroke.width, self.offset));
+        }
+        timelines
+    }
+
+    fn shape_is_animated(&self) -> bool {
+        match &self.geometry {
+            LottieGeometry::Rectangle {
+                size, roundness, ..
+            } => size.is_animated() || roundness.is_animated(),
+            LottieGeometry::Ellipse { size, .. } => size.is_animated(),
+            LottieGeometry::Path { path } => {
+                path.is_animated()
+                    || self
+                        .transforms
+                        .iter()
+                        .any(|timed| timed.transform.has_animated_linear_part())
+            }
+            LottieGeometry::Text { .. } | LottieGeometry::Symbol { .. } => false,
+        }
+    }
+
+    fn opacity_is_animated(&self) -> bool {
+        self.opacities
+            .iter()
+            .any(|timed| timed.opacity.is_animated())
+    }
+
+    fn fill_is_animated(&self) -> bool {
+        self.opacity_is_animated() || self.fill.as_ref().is_some_and(LottiePaint::is_animated)
+    }
+
+    fn stroke_is_animated(&self) -> bool {
+        self.opacity_is_animated()
+            || self
+                .stroke
+                .as_ref()
+                .is_some_and(|stroke| stroke.paint.is_animated())
+    }
+
+    fn evaluate(&self, time: f64) -> AnimObject {
+        let local_time = time - self.offset;
+        let matrix = self.matrix_at(time);
+        let opacity_scale = self.opacities.iter().fold(1.0, |opacity, timed| {
+            opacity * component(&timed.opacity.value_at(time - timed.offset), 0, 100.0) / 100.0
+        });
+
+        let (shape, position, baked, gradient_space) = match &self.geometry {
+            LottieGeometry::Rectangle {
+                center,
+                size,
+                roundness,
+            } => {
+                let center = point_of(&center.value_at(local_time));
+                let size = point_of(&size.value_at(local_time));
+                (
+                    Shape::Rectangle {
+                        width: size[0] as f32,
+                        height: size[1] as f32,
+                        corner_radius: component(&roundness.value_at(local_time), 0, 0.0) as f32,
+                    },
+                    matrix.apply(center),
+                    false,
+                    bounds_space([center[0] - size[0] / 2.0, center[1] - size[1] / 2.0], size),
+                )
+            }
+            LottieGeometry::Ellipse { center, size } => {
+                let center = point_of(&center.value_at(local_time));
+                let size = point_of(&size.value_at(local_time));
+                (
+                    Shape::Ellipse {
+                        radius_x: (size[0] / 2.0) as f32,
+                        radius_y: (size[1] / 2.0) as f32,
+                    },
+                    matrix.apply(center),
+                    false,
+                    bounds_space([center[0] - size[0] / 2.0, center[1] - size[1] / 2.0], size),
+                )
+            }
+            LottieGeometry::Path { path } => {
+                let path = path.value_at(local_time);
+                let world: Vec<[f64; 2]> = path
+                    .vertices
+                    .iter()
+                    .map(|vertex| matrix.apply(*vertex))
+                    .collect();
+                let mut bounds = [f64::MAX, f64::MAX, f64::MIN, f64::MIN];
+                for point in &world {
+                    bounds[0] = bounds[0].min(point[0]);
+                    bounds[1] = bounds[1].min(point[1]);
+                    bounds[2] = bounds[2].max(point[0]);
+                    bounds[3] = bounds[3].max(point[1]);
+                }
+                if world.is_empty() {
+                    bounds = [0.0, 0.0, 0.0, 0.0];
+                }
+                let position = [(bounds[0] + bounds[2]) / 2.0, (bounds[1] + bounds[3]) / 2.0];
+                let to_object = |point: [f64; 2]| -> [f32; 2] {
+                    let world = matrix.apply(point);
+                    [
+                        (world[0] - position[0]) as f32,
+                        (world[1] - position[1]) as f32,
+                    ]
+                };
+                let control = |vertex: [f64; 2], tangent: [f64; 2]| -> Option<[f32; 2]> {
+                    (tangent[0].abs() > 1e-6 || tangent[1].abs() > 1e-6)
+                        .then(|| to_object([vertex[0] + tangent[0], vertex[1] + tangent[1]]))
+                };
+                let points = path
+                    .vertices
+                    .iter()
+                    .enumerate()
+                    .map(|(index, vertex)| PathPoint {
+                        position: to_object(*vertex),
+                        control_in: control(
+                            *vertex,
+                            path.in_tangents.get(index).copied().unwrap_or([0.0, 0.0]),
+                        ),
+                        control_out: control(
+                            *vertex,
+                            path.out_tangents.get(index).copied().unwrap_or([0.0, 0.0]),
+                        ),
+                        pressure: 1.0,
+                        weights: Vec::new(),
+                    })
+                    .collect();
+                let gradient_space = bounds_space(
+                    [bounds[0], bounds[1]],
+                    [bounds[2] - bounds[0], bounds[3] - bounds[1]],
+                )
+                .then(&matrix);
+                (
+                    Shape::Path {
+                        points,
+                        closed: path.closed,
+                    },
+                    position,
+                    true,
+                    gradient_space,
+                )
+            }
+            LottieGeometry::Text {
+                content,
+                font_size,
+                font_family,
+            } => (
+                Shape::Text {
+                    content: content.clone(),
+                    font_size: *font_size as f32,
+                    font_family: *font_family,
+                },
+                matrix.apply([0.0, -font_size * TEXT_ASCENT]),
+                false,
+                LottieAffine::identity(),
+            ),
+            LottieGeometry::Symbol {
+                symbol_id,
+                size,
+                play_mode,
+                first_frame,
+            } => (
+                Shape::SymbolInstance {
+                    symbol_id: *symbol_id,
+                    play_mode: *play_mode,
+                    first_frame: *first_frame,
+                },
+                matrix.apply([size[0] / 2.0, size[1] / 2.0]),
+                false,
+                LottieAffine::identity(),
+            ),
+        };
+
+        let (rotation, scale) = if baked {
+            (0.0, [1.0, 1.0])
+        } else {
+            let scale = matrix.decompose_scale();
+            (
+                matrix.rotation_radians() as f32,
+                [scale[0] as f32, scale[1] as f32],
+            )
+        };
+        let transparent = Paint::Solid([0.0, 0.0, 0.0, 0.0]);
+        let fill = self
+            .fill
+            .as_ref()
+            .map(|fill| fill.evaluate(local_time, opacity_scale, &gradient_space))
+            .unwrap_or_else(|| transparent.clone());
+        let (stroke, stroke_width) = match &self.stroke {
+            Some(stroke) => (
+                stroke
+                    .paint
+                    .evaluate(local_time, opacity_scale, &gradient_space),
+                (component(&stroke.width.value_at(local_time), 0, 0.0) * matrix.mean_scale())
+                    as f32,
+            ),
+            None => (transparent, 0.0),
+        };
+
+        AnimObject {
+            id: self.id,
+            shape,
+            position: [position[0] as f32, position[1] as f32],
+            rotation,
+            scale,
+            fill,
+            stroke,
+            stroke_width,
+            parent_id: None,
+        }
+    }
+}
+
+const TEXT_ASCENT: f64 = 0.8;
+
+struct ShapeContext {
+    transforms: Vec<TimedTransform>,
+    opacities: Vec<TimedOpacity>,
+    fill: Option<LottiePaint>,
+    stroke: Option<LottieStroke>,
+    root: LottieAffine,
+    offset: f64,
+}
+
+struct ObjectSample {
+    frame: u32,
+    tween: TweenType,
+    object: AnimObject,
+}
+
+impl LottieImporter<'_> {
+    fn warn(&mut self, message: String) {
+        if !self.warnings.contains(&message) {
+            self.warnings.push(message);
+        }
+    }
+
+    fn frame_of(&self, time: f64, origin: f64) -> u32 {
+        ((time - origin) * self.time_scale).round().max(0.0) as u32
+    }
+
+    fn time_of(&self, frame: u32, origin: f64) -> f64 {
+        origin + frame as f64 / self.time_scale
+    }
+
+    fn import_layers(
+        &mut self,
+        layers: &[serde_json::Value],
+        root: LottieAffine,
+        span: &LottieTimeSpan,
+        depth: usize,
+    ) -> (Vec<Layer>, u32) {
+        let by_index: HashMap<i64, &serde_json::Value> = layers
+            .iter()
+            .filter_map(|layer| Some((layer["ind"].as_i64()?, layer)))
+            .collect();
+        let end_frame = self.frame_of(span.end, span.origin);
+        let mut imported = Vec::new();
+
+        for (layer_number, layer_json) in layers.iter().enumerate() {
+            let name = layer_json["nm"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("Layer {}", layer_number + 1));
+            let layer_type = layer_json["ty"].as_i64().unwrap_or(-1);
+            match layer_type {
+                0 | 1 | 4 | 5 => {}
+                3 => continue,
+                2 => {
+                    self.warn(format!("Image layer '{}' was skipped", name));
+                    continue;
+                }
+                6 => {
+                    self.warn(format!("Audio layer '{}' was skipped", name));
+                    continue;
+                }
+                _ => {
+                    self.warn(format!(
+                        "Layer '{}' has unsupported type {} and was skipped",
+                        name, layer_type
+                    ));
+                    continue;
+                }
+            }
+            self.warn_unsupported_layer_features(layer_json, &name);
+
+            let offset = layer_json["st"].as_f64().unwrap_or(0.0);
+            let in_time = layer_json["ip"]
+                .as_f64()
+                .unwrap_or(span.origin)
+                .max(span.origin);
+            let out_time = layer_json["op"].as_f64().unwrap_or(span.end).min(span.end);
+            let mut transforms = self.transform_chain(layer_json, &by_index, &name);
+            let layer_opacity = transforms
+                .last()
+                .map(|timed| timed.transform.opacity.clone())
+                .unwrap_or_else(|| Animated::constant(vec![100.0]));
+            if let Some(own) = transforms.last_mut() {
+                own.transform.opacity = Animated::constant(vec![100.0]);
+            }
+
+            let mut layer = Layer::new(name.clone());
+            layer.visible = !layer_json["hd"].as_bool().unwrap_or(false);
+            layer.blend_mode = self.blend_mode(layer_json["bm"].as_i64().unwrap_or(0), &name);
+            let mut opacities = Vec::new();
+            if layer_opacity.is_animated() {
+                opacities.push(TimedOpacity {
+                    opacity: layer_opacity,
+                    offset,
+                });
+            } else {
+                layer.opacity = (component(&layer_opacity.value_at(0.0), 0, 100.0) / 100.0)
+                    .clamp(0.0, 1.0) as f32;
+            }
+
+            let context = ShapeContext {
+                transforms,
+                opacities,
+                fill: None,
+                stroke: None,
+                root,
+                offset,
+            };
+            let mut sources = Vec::new();
+            match layer_type {
+                0 => self.collect_precomp_instance(
+                    layer_json,
+                    &context,
+                    in_time,
+                    depth,
+                    &mut sources,
+                ),
+                1 => collect_solid(layer_json, &context, &mut sources),
+                4 => {
+                    let items = layer_json["shapes"].as_array().cloned().unwrap_or_default();
+                    self.collect_shape_items(&items, &context, &name, &mut sources);
+                }
+                _ => self.collect_text(layer_json, &context, &name, &mut sources),
+            }
+            if sources.is_empty() {
+                continue;
+            }
+
+            let timing = LottieTimeSpan {
+                origin: in_time,
+                end: out_time,
+            };
+            if self.fill_layer(&mut layer, &sources, &timing, span.origin, end_frame) {
+                imported.push(layer);
+            }
+        }
+
+        (imported, end_frame)
+    }
+
+    fn warn_unsupported_layer_features(&mut self, layer_json: &serde_json::Value, name: &str) {
+        if layer_json["ddd"].as_i64() == Some(1) {
+            self.warn(format!("3D properties of layer '{}' were flattened", name));
+        }
+        if layer_json["masksProperties"]
+            .as_array()
+            .is_some_and(|masks| !masks.is_empty())
+        {
+            self.warn(format!("Masks on layer '{}' were not imported", name));
+        }
+        if layer_json.get("tt").is_some() {
+            self.warn(format!("Track matte on layer '{}' was not imported", name));
+        }
+        if layer_json["ef"]
+            .as_array()
+            .is_some_and(|effects| !effects.is_empty())
+        {
+            self.warn(format!("Effects on layer '{}' were not imported", name));
+        }
+        if layer_json["sr"]
+            .as_f64()
+            .is_some_and(|stretch| (stretch - 1.0).abs() > 1e-6)
+        {
+            self.warn(format!("Time stretch on layer '{}' was ignored", name));
+        }
+    }
+
+    fn transform_chain(
+        &mut self,
+        layer_json: &serde_json::Value,
+        by_index: &HashMap<i64, &serde_json::Value>,
+        name: &str,
+    ) -> Vec<TimedTransform> {
+        let mut chain = vec![TimedTransform {
+            transform: self.parse_transform(&layer_json["ks"], name),
+            offset: layer_json["st"].as_f64().unwrap_or(0.0),
+        }];
+        let mut parent = layer_json["parent"].as_i64();
+        while let Some(parent_index) = parent {
+            if chain.len() > by_index.len() {
+                self.warn(format!("Layer '{}' has a parenting cycle", name));
+                break;
+            }
+            let Some(parent_json) = by_index.get(&parent_index) else {
+                self.warn(format!("Parent of layer '{}' was not found", name));
+                break;
+            };
+            chain.insert(
+                0,
+                TimedTransform {
+                    transform: self.parse_transform(&parent_json["ks"], name),
+                    offset: parent_json["st"].as_f64().unwrap_or(0.0),
+                },
+            );
+            parent = parent_json["parent"].as_i64();
+        }
+        chain
+    }
+
+    fn blend_mode(&mut self, blend_mode: i64, name: &str) -> BlendMode {
+        match blend_mode {
+            0 => BlendMode::Normal,
+            1 => BlendMode::Multiply,
+            2 => BlendMode::Screen,
+            3 => BlendMode::Overlay,
+            4 => BlendMode::Darken,
+            5 => BlendMode::Lighten,
+            6 => BlendMode::ColorDodge,
+            7 => BlendMode::ColorBurn,
+            10 => BlendMode::Difference,
+            11 => BlendMode::Exclusion,
+            _ => {
+                self.warn(format!(
+                    "Blend mode {} on layer '{}' is not supported and was set to Normal",
+                    blend_mode, name
+                ));
+                BlendMode::Normal
+            }
+        }
+    }
+
+    fn collect_precomp_instance(
+        &mut self,
+        layer_json: &serde_json::Value,
+        context: &ShapeContext,
+        in_time: f64,
+        depth: usize,
+        sources: &mut Vec<ObjectSource>,
+    ) {
+        let Some(reference) = layer_json["refId"].as_str() else {
+            return;
+        };
+        let size = [
+            layer_json["w"].as_f64().unwrap_or(0.0),
+            layer_json["h"].as_f64().unwrap_or(0.0),
+        ];
+        let Some(symbol_id) = self.import_symbol(reference, size, depth) else {
+            return;
+        };
+        let (play_mode, first_frame) = match layer_json.get("tm") {
+            Some(time_remap) => {
+                let name = layer_json["nm"].as_str().unwrap_or(reference);
+                let time_remap = self.parse_numbers(time_remap, vec![0.0], name);
+                let play_mode = if time_remap.is_animated() {
+                    self.warn(format!(
+                        "Time remapping on layer '{}' was approximated as playing once",
+                        name
+                    ));
+                    SymbolPlayMode::PlayOnce
+                } else {
+                    SymbolPlayMode::SingleFrame
+                };
+                let seconds = component(&time_remap.value_at(in_time - context.offset), 0, 0.0);
+                (play_mode, self.frame_of(seconds * self.frame_rate, 0.0))
+            }
+            None => (
+                SymbolPlayMode::PlayOnce,
+                self.frame_of(in_time - context.offset, 0.0),
+            ),
+        };
+        sources.push(ObjectSource {
+            id: uuid::Uuid::new_v4(),
+            geometry: LottieGeometry::Symbol {
+                symbol_id,
+                size,
+                play_mode,
+                first_frame,
+            },
+            root: context.root,
+            transforms: context.transforms.clone(),
+            opacities: context.opacities.clone(),
+            fill: None,
+            stroke: None,
+            offset: context.offset,
+        });
+    }
+
+    fn import_symbol(
+        &mut self,
+        reference: &str,
+        size: [f64; 2],
+        depth: usize,
+    ) -> Option<uuid::Uuid> {
+        if let Some(symbol_id) = self.symbols.get(reference) {
+            return Some(*symbol_id);
+        }
+        if depth >= library::MAX_SYMBOL_DEPTH {
+            self.warn(format!(
+                "Precomp '{}' is nested too deeply and was skipped",
+                reference
+            ));
+            return None;
+        }
+        let Some(asset) = self.assets.get(reference).copied() else {
+            self.warn(format!("Precomp asset '{}' was not found", reference));
+            return None;
+        };
+        let Some(layers) = asset["layers"].as_array() else {
+            self.warn(format!(
+                "Asset '{}' is not a precomp and was skipped",
+                reference
+            ));
+            return None;
+        };
+
+        let symbol_id = uuid::Uuid::new_v4();
+        self.symbols.insert(reference.to_string(), symbol_id);
+        let size = [
+            asset["w"].as_f64().unwrap_or(size[0]),
+            asset["h"].as_f64().unwrap_or(size[1]),
+        ];
+        let span = LottieTimeSpan {
+            origin: 0.0,
+            end: layers
+                .iter()
+                .filter_map(|layer| layer["op"].as_f64())
+                .fold(1.0, f64::max),
+        };
+        let (symbol_layers, total_frames) = self.import_layers(
+            layers,
+            LottieAffine::translation(-size[0] / 2.0, -size[1] / 2.0),
+            &span,
+            depth + 1,
+        );
+
+        self.imported_symbols.push(Symbol {
+            id: symbol_id,
+            name: asset["nm"].as_str().unwrap_or(reference).to_string(),
+            layers: symbol_layers,
+            total_frames: total_frames.max(1),
+            width: size[0] as f32,
+            height: size[1] as f32,
+        });
+        Some(symbol_id)
+    }
+
+    fn collect_text(
+        &mut self,
+        layer_json: &serde_json::Value,
+        context: &ShapeContext,
+        name: &str,
+        sources: &mut Vec<ObjectSource>,
+    ) {
+        let documents = layer_json["t"]["d"]["k"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let Some(document) = documents.first().map(|document| &document["s"]) else {
+            return;
+        };
+        if documents.len() > 1 {
+            self.warn(format!(
+                "Animated text on layer '{}' was imported at its first value",
+                name
+            ));
+        }
+        if layer_json["t"]["a"]
+            .as_array()
+            .is_some_and(|animators| !animators.is_empty())
+        {
+            self.warn(format!(
+                "Text animators on layer '{}' were not imported",
+                name
+            ));
+        }
+        let color = numbers_of(&document["fc"]).unwrap_or_else(|| vec![0.0, 0.0, 0.0]);
+        sources.push(ObjectSource {
+            id: uuid::Uuid::new_v4(),
+            geometry: LottieGeometry::Text {
+                content: document["t"].as_str().unwrap_or("").replace('\r', "\n"),
+                font_size: document["s"].as_f64().unwrap_or(16.0),
+                font_family: lottie_font_family(document["f"].as_str().unwrap_or("")),
+            },
+            root: context.root,
+            transforms: context.transforms.clone(),
+            opacities: context.opacities.clone(),
+            fill: Some(LottiePaint::Solid {
+                color: Animated::constant(color),
+                opacity: Animated::constant(vec![100.0]),
+            }),
+            stroke: None,
+            offset: context.offset,
+        });
+    }
+
+    fn collect_shape_items(
+        &mut self,
+        items: &[serde_json::Value],
+        parent: &ShapeContext,
+        layer_name: &str,
+        sources: &mut Vec<ObjectSource>,
+    ) {
+        let visible_items: Vec<&serde_json::Value> = items
+            .iter()
+            .filter(|item| !item["hd"].as_bool().unwrap_or(false))
+            .collect();
+
+        let mut context = ShapeContext {
+            transforms: parent.transforms.clone(),
+            opacities: parent.opacities.clone(),
+            fill: parent.fill.clone(),
+            stroke: parent.stroke.clone(),
+            root: parent.root,
+            offset: parent.offset,
+        };
+        if let Some(fill) = visible_items
+            .iter()
+            .find(|item| matches!(item["ty"].as_str(), Some("fl") | Some("gf")))
+        {
+            context.fill = Some(self.parse_paint(fill, layer_name));
+        }
+        if let Some(stroke) = visible_items
+            .iter()
+            .find(|item| matches!(item["ty"].as_str(), Some("st") | Some("gs")))
+        {
+            context.stroke = Some(LottieStroke {
+                paint: self.parse_paint(stroke, layer_name),
+                width: self.parse_numbers(&stroke["w"], vec![1.0], layer_name),
+            });
+        }
+        if let Some(transform) = visible_items
+            .iter()
+            .find(|item| item["ty"].as_str() == Some("tr"))
+        {
+            let transform = self.parse_transform(transform, layer_name);
+            context.opacities.push(TimedOpacity {
+                opacity: transform.opacity.clone(),
+                offset: context.offset,
+            });
+            context.transforms.push(TimedTransform {
+                transform,
+                offset: context.offset,
+            });
+        }
+
+        for item in visible_items.iter().rev() {
+            let geometry = match item["ty"].as_str().unwrap_or("") {
+                "gr" => {
+                    let children = item["it"].as_array().cloned().unwrap_or_default();
+                    self.collect_shape_items(&children, &context, layer_name, sources);
+                    continue;
+                }
+                "fl" | "gf" | "st" | "gs" | "tr" => continue,
+                "rc" => LottieGeometry::Rectangle {
+                    center: self.parse_numbers(&item["p"], vec![0.0, 0.0], layer_name),
+                    size: self.parse_numbers(&item["s"], vec![0.0, 0.0], layer_name),
+                    roundness: self.parse_numbers(&item["r"], vec![0.0], layer_name),
+                },
+                "el" => LottieGeometry::Ellipse {
+                    center: self.parse_numbers(&item["p"], vec![0.0, 0.0], layer_name),
+                    size: self.parse_numbers(&item["s"], vec![0.0, 0.0], layer_name),
+                },
+                "sh" => LottieGeometry::Path {
+                    path: self.parse_path(&item["ks"], layer_name),
+                },
+                "sr" => LottieGeometry::Path {
+                    path: Animated::constant(self.polystar_path(item, layer_name)),
+                },
+                other => {
+                    let kind = match other {
+                        "tm" => "Trim Paths",
+                        "rp" => "Repeater",
+                        "rd" => "Round Corners",
+                        "mm" => "Merge Paths",
+                        "tw" => "Twist",
+                        "pb" => "Pucker & Bloat",
+                        "zz" => "Zig Zag",
+                        "op" => "Offset Path",
+                        _ => other,
+                    };
+                    self.warn(format!(
+                        "{} in layer '{}' is not supported and was skipped",
+                        kind, layer_name
+                    ));
+                    continue;
+                }
+            };
+            sources.push(ObjectSource {
+                id: uuid::Uuid::new_v4(),
+                geometry,
+                root: context.root,
+                transforms: context.transforms.clone(),
+                opacities: context.opacities.clone(),
+                fill: context.fill.clone(),
+                stroke: context.stroke.clone(),
+                offset: context.offset,
+            });
+        }
+    }
+
+    fn polystar_path(&mut self, item: &serde_json::Value, layer_name: &str) -> LottiePath {
+        let center = self.parse_numbers(&item["p"], vec![0.0, 0.0], layer_name);
+        let points = self.parse_numbers(&item["pt"], vec![5.0], layer_name);
+        let outer_radius = self.parse_numbers(&item["or"], vec![0.0], layer_name);
+        let inner_radius = self.parse_numbers(&item["ir"], vec![0.0], layer_name);
+        let rotation = self.parse_numbers(&item["r"], vec![0.0], layer_name);
+        if [&center, &points, &outer_radius, &inner_radius, &rotation]
+            .iter()
+            .any(|property| property.is_animated())
+        {
+            self.warn(format!(
+                "Animated polystar in layer '{}' was imported at its first frame",
+                layer_name
+            ));
+        }
+
+        let center = point_of(&center.value_at(0.0));
+        let is_star = item["sy"].as_i64().unwrap_or(1) == 1;
+        let point_count = component(&points.value_at(0.0), 0, 5.0).round().max(3.0) as usize;
+        let outer_radius = component(&outer_radius.value_at(0.0), 0, 0.0);
+        let inner_radius = component(&inner_radius.value_at(0.0), 0, 0.0);
+        let start_angle = (component(&rotation.value_at(0.0), 0, 0.0) - 90.0).to_radians();
+        let vertex_count = if is_star {
+            point_count * 2
+        } else {
+            point_count
+        };
+
+        let vertices: Vec<[f64; 2]> = (0..vertex_count)
+            .map(|index| {
+                let angle =
+                    start_angle + std::f64::consts::TAU * index as f64 / vertex_count as f64;
+                let radius = if is_star && index % 2 == 1 {
+                    inner_radius
+                } else {
+                    outer_radius
+                };
+                [
+                    center[0] + angle.cos() * radius,
+                    center[1] + angle.sin() * radius,
+                ]
+            })
+            .collect();
+        LottiePath {
+            in_tangents: vec![[0.0, 0.0]; vertices.len()],
+            out_tangents: vec![[0.0, 0.0]; vertices.len()],
+            vertices,
+            closed: true,
+        }
+    }
+
+    fn parse_transform(&mut self, value: &serde_json::Value, layer_name: &str) -> LottieTransform {
+        let split_position = value["p"]["s"].as_bool().unwrap_or(false);
+        let (position, position_y) = if split_position {
+            (
+                self.parse_numbers(&value["p"]["x"], vec![0.0], layer_name),
+                Some(self.parse_numbers(&value["p"]["y"], vec![0.0], layer_name)),
+            )
+        } else {
+            (
+                self.parse_numbers(&value["p"], vec![0.0, 0.0], layer_name),
+                None,
+            )
+        };
+        let rotation_value = if value.get("r").is_some() {
+            &value["r"]
+        } else {
+            &value["rz"]
+        };
+        let skew = self.parse_numbers(&value["sk"], vec![0.0], layer_name);
+        if skew.is_animated() || component(&skew.value_at(0.0), 0, 0.0).abs() > 1e-6 {
+            self.warn(format!("Skew in layer '{}' was ignored", layer_name));
+        }
+
+        LottieTransform {
+            anchor: self.parse_numbers(&value["a"], vec![0.0, 0.0], layer_name),
+            position,
+            position_y,
+            scale: self.parse_numbers(&value["s"], vec![100.0, 100.0], layer_name),
+            rotation: self.parse_numbers(rotation_value, vec![0.0], layer_name),
+            opacity: self.parse_numbers(&value["o"], vec![100.0], layer_name),
+        }
+    }
+
+    fn parse_paint(&mut self, item: &serde_json::Value, layer_name: &str) -> LottiePaint {
+        let opacity = self.parse_numbers(&item["o"], vec![100.0], layer_name);
+        match item["ty"].as_str() {
+            Some("gf") | Some("gs") => LottiePaint::Gradient {
+                radial: item["t"].as_i64() == Some(2),
+                start: self.parse_numbers(&item["s"], vec![0.0, 0.0], layer_name),
+                end: self.parse_numbers(&item["e"], vec![0.0, 0.0], layer_name),
+                stops: self.parse_numbers(&item["g"]["k"], Vec::new(), layer_name),
+                stop_count: item["g"]["p"].as_u64().unwrap_or(0) as usize,
+                opacity,
+            },
+            _ => LottiePaint::Solid {
+                color: self.parse_numbers(&item["c"], vec![0.0, 0.0, 0.0, 1.0], layer_name),
+                opacity,
+            },
+        }
+    }
+
+    fn parse_numbers(
+        &mut self,
+        property: &serde_json::Value,
+        default: Vec<f64>,
+        layer_name: &str,
+    ) -> Animated<Vec<f64>> {
+        self.parse_animated(property, default, numbers_of, layer_name)
+    }
+
+    fn parse_path(
+        &mut self,
+        property: &serde_json::Value,
+        layer_name: &str,
+    ) -> Animated<LottiePath> {
+        let empty = LottiePath {
+            vertices: Vec::new(),
+            in_tangents: Vec::new(),
+            out_tangents: Vec::new(),
+            closed: false,
+        };
+        self.parse_animated(property, empty, path_of, layer_name)
+    }
+
+    fn parse_animated<T: LottieInterpolate>(
+        &mut self,
+        property: &serde_json::Value,
+        default: T,
+        parse_value: fn(&serde_json::Value) -> Option<T>,
+        layer_name: &str,
+    ) -> Animated<T> {
+        if property["x"].is_string() {
+            self.warn(format!(
+                "Expressions in layer '{}' were ignored",
+                layer_name
+            ));
+        }
+        let value = &property["k"];
+        let keys = value.as_array().filter(|keys| {
+            keys.first()
+                .is_some_and(|key| key.is_object() && key.get("t").is_some())
+        });
+        let Some(keys) = keys else {
+            return Animated::constant(parse_value(value).unwrap_or(default));
+        };
+
+        let mut animated: Vec<AnimatedKey<T>> = Vec::new();
+        for key in keys {
+            let Some(time) = key["t"].as_f64() else {
+                continue;
+            };
+            let start = parse_value(&key["s"]).or_else(|| {
+                animated
+                    .last()
+                    .map(|previous| previous.end.clone().unwrap_or(previous.start.clone()))
+            });
+            let Some(start) = start else {
+                continue;
+            };
+            let has_spatial_tangents = [&key["to"], &key["ti"]].iter().any(|tangent| {
+                numbers_of(tangent).is_some_and(|values| values.iter().any(|v| v.abs() > 1e-6))
+            });
+            if has_spatial_tangents {
+                self.warn(format!(
+                    "Curved motion paths in layer '{}' were imported as straight lines",
+                    layer_name
+                ));
+            }
+            animated.push(AnimatedKey {
+                time,
+                start,
+                end: parse_value(&key["e"]),
+                tween: lottie_easing_to_tween(key),
+            });
+        }
+
+        if animated.is_empty() {
+            return Animated::constant(default);
+        }
+        Animated { keys: animated }
+    }
+
+    fn fill_layer(
+        &mut self,
+        layer: &mut Layer,
+        sources: &[ObjectSource],
+        timing: &LottieTimeSpan,
+        origin: f64,
+        composition_end: u32,
+    ) -> bool {
+        let start_frame = self.frame_of(timing.origin, origin);
+        let end_frame = self.frame_of(timing.end, origin).min(composition_end);
+        if end_frame <= start_frame {
+            return false;
+        }
+
+        let mut keyframe_tweens: BTreeMap<u32, Option<TweenType>> = BTreeMap::new();
+        keyframe_tweens.insert(start_frame, None);
+        let mut shape_tween = false;
+
+        for source in sources {
+            let samples = self.sample_object(source, timing, origin);
+            let tracks = build_property_tracks(source, &samples);
+            if !tracks.is_empty() {
+                layer.property_tracks.insert(source.id, tracks);
+            }
+            if source.shape_is_animated() && samples.len() > 1 {
+                shape_tween = true;
+                for sample in &samples {
+                    let entry = keyframe_tweens.entry(sample.frame).or_insert(None);
+                    *entry = match entry {
+                        Some(existing) if *existing != sample.tween => Some(TweenType::Linear),
+                        _ => Some(sample.tween),
+                    };
+                }
+            }
+        }
+
+        for (frame, tween) in keyframe_tweens {
+            let time = self.time_of(frame, origin);
+            layer.keyframes.insert(
+                frame,
+                Keyframe {
+                    objects: sources.iter().map(|source| source.evaluate(time)).collect(),
+                    tween: tween.unwrap_or(TweenType::None),
+                    label: String::new(),
+                    comment: String::new(),
+                    shape_tween,
+                },
+            );
+        }
+        if end_frame < composition_end {
+            layer.keyframes.insert(end_frame, Keyframe::default());
+        }
+        true
+    }
+
+    fn sample_object(
+        &self,
+        source: &ObjectSource,
+        timing: &LottieTimeSpan,
+        origin: f64,
+    ) -> Vec<ObjectSample> {
+        let timelines = source.timelines();
+        let mut times = vec![timing.origin];
+        for (timeline, offset) in &timelines {
+            timeline.key_times(*offset, &mut times);
+        }
+        if times.iter().any(|time| *time > timing.end - TIME_EPSILON) {
+            times.push(timing.end - 1.0 / self.time_scale);
+        }
+        times.retain(|time| *time >= timing.origin && *time < timing.end - TIME_EPSILON);
+        times.sort_by(f64::total_cmp);
+        times.dedup_by(|next, previous| (*next - *previous).abs() < TIME_EPSILON);
+
+        let mut segments: Vec<(f64, TweenType)> = Vec::new();
+        for (index, time) in times.iter().enumerate() {
+            let Some(next_time) = times.get(index + 1) else {
+                segments.push((*time, TweenType::None));
+                break;
+            };
+            let mut tween = Some(TweenType::None);
+            for (timeline, offset) in &timelines {
+                tween = match (
+                    timeline.segment_ease(time - offset, next_time - offset),
+                    tween,
+                ) {
+                    (SegmentEase::Constant, tween) => tween,
+                    (SegmentEase::Exact(ease), Some(TweenType::None)) => Some(ease),
+                    (SegmentEase::Exact(ease), Some(current)) if ease == current => Some(ease),
+                    _ => None,
+                };
+            }
+            let rotation_change = (source.matrix_at(*next_time).rotation_radians()
+                - source.matrix_at(*time).rotation_radians())
+            .abs();
+            let rotates_path =
+                matches!(source.geometry, LottieGeometry::Path { .. }) && rotation_change > 1e-4;
+            match tween {
+                Some(tween) if rotation_change < std::f64::consts::PI && !rotates_path => {
+                    segments.push((*time, tween));
+                }
+                _ => {
+                    segments.push((*time, TweenType::Linear));
+                    let first = self.frame_of(*time, origin) + 1;
+                    let last = self.frame_of(*next_time, origin);
+                    for frame in first..last {
+                        segments.push((self.time_of(frame, origin), TweenType::Linear));
+                    }
+                }
+            }
+        }
+
+        let mut samples: Vec<ObjectSample> = Vec::new();
+        for (time, tween) in segments {
+            let frame = self.frame_of(time, origin);
+            if samples.last().is_some_and(|sample| sample.frame >= frame) {
+                continue;
+            }
+            samples.push(ObjectSample {
+                frame,
+                tween,
+                object: source.evaluate(time),
+            });
+        }
+        samples
+    }
+}
+
+fn build_property_tracks(source: &ObjectSource, samples: &[ObjectSample]) -> PropertyTracks {
+    let mut tracks = PropertyTracks::default();
+    if samples.len() < 2 {
+        return tracks;
+    }
+    let first = &samples[0].object;
+    let is_path = matches!(source.geometry, LottieGeometry::Path { .. });
+    let varies = |difference: &dyn Fn(&AnimObject) -> f32| {
+        samples
+            .iter()
+            .any(|sample| difference(&sample.object) > 1e-3)
+    };
+
+    let position_varies = varies(&|object| {
+        (object.position[0] - first.position[0]).abs()
+            + (object.position[1] - first.position[1]).abs()
+    });
+    let rotation_varies = !is_path && varies(&|object| (object.rotation - first.rotation).abs());
+    let scale_varies = !is_path
+        && varies(&|object| {
+            (object.scale[0] - first.scale[0]).abs() + (object.scale[1] - first.scale[1]).abs()
+        });
+    let stroke_width_varies = varies(&|object| (object.stroke_width - first.stroke_width).abs());
+
+    for sample in samples {
+        let object = &sample.object;
+        let tween = sample.tween;
+        if position_varies {
+            tracks.position.insert(
+                sample.frame,
+                PropertyKey {
+                    value: object.position,
+                    tween,
+                    tangents: None,
+                },
+            );
+        }
+        if rotation_varies {
+            tracks.rotation.insert(
+                sample.frame,
+                PropertyKey {
+                    value: object.rotation,
+                    tween,
+                    tangents: None,
+                },
+            );
+        }
+        if scale_varies {
+            tracks.scale.insert(
+                sample.frame,
+                PropertyKey {
+                    value: object.scale,
+                    tween,
+                    tangents: None,
+                },
+            );
+        }
+        if source.fill_is_animated() {
+            tracks.fill.insert(
+                sample.frame,
+                PropertyKey {
+                    value: object.fill.clone(),
+                    tween,
+                    tangents: None,
+                },
+            );
+        }
+        if source.stroke_is_animated() {
+            tracks.stroke.insert(
+                sample.frame,
+                PropertyKey {
+                    value: object.stroke.clone(),
+                    tween,
+                    tangents: None,
+                },
+            );
+        }
+        if stroke_width_varies {
+            tracks.stroke_width.insert(
+                sample.frame,
+                PropertyKey {
+                    value: object.stroke_width,
+                    tween,
+                    tangents: None,
+                },
+            );
+        }
+    }
+    tracks
+}
+
+fn collect_solid(
+    layer_json: &serde_json::Value,
+    context: &ShapeContext,
+    sources: &mut Vec<ObjectSource>,
+) {
+    let width = layer_json["sw"].as_f64().unwrap_or(0.0);
+    let height = layer_json["sh"].as_f64().unwrap_or(0.0);
+    let color = layer_json["sc"]
+        .as_str()
+        .and_then(hex_color)
+        .unwrap_or(vec![0.0, 0.0, 0.0]);
+    sources.push(ObjectSource {
+        id: uuid::Uuid::new_v4(),
+        geometry: LottieGeometry::Rectangle {
+            center: Animated::constant(vec![width / 2.0, height / 2.0]),
+            size: Animated::constant(vec![width, height]),
+            roundness: Animated::constant(vec![0.0]),
+        },
+        root: context.root,
+        transforms: context.transforms.clone(),
+        opacities: context.opacities.clone(),
+        fill: Some(LottiePaint::Solid {
+            color: Animated::constant(color),
+            opacity: Animated::constant(vec![100.0]),
+        }),
+        stroke: None,
+        offset: context.offset,
+    });
+}
+
+fn lottie_easing_to_tween(key: &serde_json::Value) -> TweenType {
+    if key["h"].as_i64() == Some(1) {
+        return TweenType::None;
+    }
+    let first =
+        |value: &serde_json::Value| numbers_of(value).and_then(|values| values.first().copied());
+    let (Some(x1), Some(y1), Some(x2), Some(y2)) = (
+        first(&key["o"]["x"]),
+        first(&key["o"]["y"]),
+        first(&key["i"]["x"]),
+        first(&key["i"]["y"]),
+    ) else {
+        return TweenType::Linear;
+    };
+    if (x1 - y1).abs() < 1e-3 && (x2 - y2).abs() < 1e-3 {
+        return TweenType::Linear;
+    }
+    TweenType::CubicBezier {
+        x1: x1.clamp(0.0, 1.0) as f32,
+        y1: y1 as f32,
+        x2: x2.clamp(0.0, 1.0) as f32,
+        y2: y2 as f32,
+    }
+}
+
+fn lottie_font_family(font_name: &str) -> FontFamily {
+    let font_name = font_name.to_ascii_lowercase();
+    if font_name.contains("mono") || font_name.contains("courier") {
+        FontFamily::Monospace
+    } else if font_name.contains("serif") && !font_name.contains("sans") {
+        FontFamily::Serif
+    } else {
+        FontFamily::SansSerif
+    }
+}
+
+fn numbers_of(value: &serde_json::Value) -> Option<Vec<f64>> {
+    match value {
+        serde_json::Value::Number(number) => number.as_f64().map(|number| vec![number]),
+        serde_json::Value::Array(values) => values.iter().map(serde_json::Value::as_f64).collect(),
+        _ => None,
+    }
+}
+
+fn path_of(value: &serde_json::Value) -> Option<LottiePath> {
+    let value = match value {
+        serde_json::Value::Array(values) => values.first()?,
+        value => value,
+    };
+    let points = |key: &str| -> Option<Vec<[f64; 2]>> {
+        value[key]
+            .as_array()?
+            .iter()
+            .map(|point| {
+                let point = numbers_of(point)?;
+                Some([*point.first()?, *point.get(1)?])
+            })
+            .collect()
+    };
+    let vertices = points("v")?;
+    Some(LottiePath {
+        in_tangents: points("i").unwrap_or_else(|| vec![[0.0, 0.0]; vertices.len()]),
+        out_tangents: points("o").unwrap_or_else(|| vec![[0.0, 0.0]; vertices.len()]),
+        closed: value["c"].as_bool().unwrap_or(false),
+        vertices,
+    })
+}
+
+fn component(values: &[f64], index: usize, default: f64) -> f64 {
+    values.get(index).copied().unwrap_or(default)
+}
+
+fn point_of(values: &[f64]) -> [f64; 2] {
+    [component(values, 0, 0.0), component(values, 1, 0.0)]
+}
+
+fn bounds_space(min: [f64; 2], size: [f64; 2]) -> LottieAffine {
+    let scale_x = if size[0].abs() > 1e-9 {
+        1.0 / size[0]
+    } else {
+        0.0
+    };
+    let scale_y = if size[1].abs() > 1e-9 {
+        1.0 / size[1]
+    } else {
+        0.0
+    };
+    LottieAffine::scale(scale_x, scale_y).then(&LottieAffine::translation(-min[0], -min[1]))
+}
+
+fn normalize_color(values: &[f64]) -> [f32; 3] {
+    let color = [
+        component(values, 0, 0.0),
+        component(values, 1, 0.0),
+        component(values, 2, 0.0),
+    ];
+    let divisor = if color.iter().any(|channel| *channel > 1.0) {
+        255.0
+    } else {
+        1.0
+    };
+    color.map(|channel| (channel / divisor).clamp(0.0, 1.0) as f32)
+}
+
+fn hex_color(hex: &str) -> Option<Vec<f64>> {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() < 6 {
+        return None;
+    }
+    (0..3)
+        .map(|index| {
+            u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16)
+                .ok()
+                .map(|channel| channel as f64 / 255.0)
+        })
+        .collect()
+}
+
+fn gradient_stops(values: &[f64], stop_count: usize, opacity: f64) -> Vec<GradientStop> {
+    let alpha_values = values.get(stop_count * 4..).unwrap_or(&[]);
+    let alpha_at = |offset: f64| -> f64 {
+        let pairs: Vec<[f64; 2]> = alpha_values
+            .chunks_exact(2)
+            .map(|pair| [pair[0], pair[1]])
+            .collect();
+        let Some(first) = pairs.first() else {
+            return 1.0;
+        };
+        if offset <= first[0] {
+            return first[1];
+        }
+        for window in pairs.windows(2) {
+            if offset <= window[1][0] {
+                let span = (window[1][0] - window[0][0]).max(1e-9);
+                let t = (offset - window[0][0]) / span;
+                return window[0][1] + (window[1][1] - window[0][1]) * t;
+            }
+        }
+        pairs.last().map(|pair| pair[1]).unwrap_or(1.0)
+    };
+
+    values
+        .chunks_exact(4)
+        .take(stop_count)
+        .map(|stop| {
+            let color = normalize_color(&stop[1..4]);
+            GradientStop {
+                offset: stop[0].clamp(0.0, 1.0) as f32,
+                color: [
+                    color[0],
+                    color[1],
+                    color[2],
+                    (alpha_at(stop[0]) * opacity).clamp(0.0, 1.0) as f32,
+                ],
+            }
+        })
+        .collect()
+}
@@ -5,28 +5,45 @@ mod app;
 mod armature;
 #[cfg(not(target_arch = "wasm32"))]
 mod audio;
+#[cfg(not(target_arch = "wasm32"))]
+mod avi;
 mod boolean;
 mod camera;
 mod canvas;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
 mod clipboard;
 mod easing_editor;
 #[cfg(not(target_arch = "wasm32"))]
 mod export;
+#[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+mod expressions;
+mod graph_editor;
+mod group;
 mod guides;
+mod hierarchy;
 mod history;
 #[cfg(not(target_arch = "wasm32"))]
 mod io;
 mod library;
 #[cfg(not(target_arch = "wasm32"))]
+mod lipsync;
+#[cfg(not(target_arch = "wasm32"))]
 mod lottie;
 mod menu;
+mod migrations;
+mod motion_guide;
 mod node_edit;
 mod onion;
+#[cfg(not(target_arch = "wasm32"))]
+mod package;
 mod paint;
 mod paint_editor;
 mod playback;
 mod project;
 mod properties;
+#[cfg(not(target_arch = "wasm32"))]
+mod raster;
 mod scenes;
 mod selection;
 mod snapping;
@@ -41,6 +58,18 @@ mod tween;
 mod z_order;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if args.first().map(String::as_str) == Some("render") {
+            if let Err(error) = cli::run_render(&args[1..]) {
+                eprintln!("framekey render: {}", error);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+    }
+
     launch(FrameKey::default())?;
     Ok(())
 }
@@ -62,6 +91,8 @@ impl State for FrameKey {
 
     fn ui(&mut self, _world: &mut World, ui_context: &egui::Context) {
         playback::advance_playback(&mut self.app);
+        #[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+        expressions::update_context(&self.app.project);
 
         #[cfg(target_arch = "wasm32")]
         menu::process_pending_load(&mut self.app);
@@ -72,6 +103,13 @@ impl State for FrameKey {
         timeline::draw_timeline(&mut self.app, ui_context);
         canvas::draw_canvas(&mut self.app, ui_context);
         easing_editor::draw_easing_editor(&mut self.app, ui_context);
+        graph_editor::draw_graph_editor(&mut self.app, ui_context);
+        #[cfg(not(target_arch = "wasm32"))]
+        menu::draw_video_export_progress(&mut self.app, ui_context);
+        #[cfg(not(target_arch = "wasm32"))]
+        menu::draw_import_warnings(&mut self.app, ui_context);
+        #[cfg(not(target_arch = "wasm32"))]
+        lipsync::draw_lip_sync_window(&mut self.app, ui_context);
 
         menu::handle_global_shortcuts(&mut self.app, ui_context);
         toolbar::handle_tool_shortcuts(&mut self.app, ui_context);
@@ -17,11 +17,12 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
         egui::MenuBar::new().ui(ui, |ui| {
             ui.menu_button("File", |ui| {
                 if ui.button("New").clicked() {
-                    app.history.push(app.project.clone());
+                    app.history.push(app.project.clone(), "New Project");
                     app.project = Project::default();
                     app.current_frame = 0;
                     app.active_layer = 0;
                     app.selection.selected_objects.clear();
+                    app.selection.entered_group = None;
                     app.save_path = None;
                     ui.close();
                 }
@@ -29,25 +30,19 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                 {
                     if ui.button("Open...").clicked() {
                         if let Some(path) = rfd::FileDialog::new()
-                            .add_filter("Animation", &["anim"])
+                            .add_filter("Animation", &["anim", "animpack"])
                             .add_filter("All Files", &["*"])
                             .set_title("Open Project")
                             .pick_file()
-                            && let Ok(project) = io::load_project(&path)
                         {
-                            app.history.push(app.project.clone());
-                            app.project = project;
-                            app.current_frame = 0;
-                            app.active_layer = 0;
-                            app.selection.selected_objects.clear();
-                            app.save_path = Some(path);
+                            open_project(app, path);
                         }
                         ui.close();
                     }
                     ui.separator();
                     if ui.button("Save").clicked() {
                         if let Some(path) = app.save_path.clone() {
-                            let _ = io::save_project(&app.project, &path);
+                            save_project_to(app, path);
                         } else {
                             save_as(app);
                         }
@@ -58,12 +53,23 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                         ui.close();
                     }
                     ui.separator();
+                    if app.project.scenes.len() > 1 {
+                        ui.checkbox(
+                            &mut app.export_current_scene_only,
+                            "Export Current Scene Only",
+                        );
+                    }
                     if ui.button("Export PNG Sequence...").clicked() {
                         if let Some(folder) = rfd::FileDialog::new()
                             .set_title("Export PNG Sequence")
                             .pick_folder()
+                            && let Err(error) = crate::export::export_png_sequence(
+                                &app.project,
+                                &folder,
+                                &export_settings(app),
+                            )
                         {
-                            crate::export::export_png_sequence(&app.project, &folder);
+                            eprintln!("PNG sequence export failed: {}", error);
                         }
                         ui.close();
                     }
@@ -73,8 +79,13 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                             .set_file_name("animation.gif")
                             .set_title("Export GIF")
                             .save_file()
+                            && let Err(error) = crate::export::export_gif(
+                                &app.project,
+                                &path,
+                                &export_settings(app),
+                            )
                         {
-                            crate::export::export_gif(&app.project, &path);
+                            eprintln!("GIF export failed: {}", error);
                         }
                         ui.close();
                     }
@@ -84,40 +95,72 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                             .set_file_name("spritesheet.png")
                             .set_title("Export Sprite Sheet")
                             .save_file()
-                        {
-                            crate::export::export_sprite_sheet(&app.project, &path);
-                        }
-                        ui.close();
-                    }
-                    if ui.button("Export MP4...").clicked() {
-                        if let Some(path) = rfd::FileDialog::new()
-                            .add_filter("MP4 Video", &["mp4"])
-                            .set_file_name("animation.mp4")
-                            .set_title("Export MP4")
-                            .save_file()
-                            && let Err(error) = crate::export::export_video(
+                            && let Err(error) = crate::export::export_sprite_sheet(
                                 &app.project,
                                 &path,
-                                crate::export::VideoFormat::Mp4,
+                                &export_settings(app),
                             )
                         {
-                            eprintln!("MP4 export failed: {}", error);
+                            eprintln!("Sprite sheet export failed: {}", error);
                         }
                         ui.close();
                     }
-                    if ui.button("Export WebM...").clicked() {
+                    let video_export_idle = app.video_export.is_none();
+                    if ui
+                        .add_enabled(video_export_idle, egui::Button::new("Export AVI..."))
+                        .clicked()
+                    {
+                        start_video_export(
+                            app,
+                            crate::export::VideoFormat::Avi,
+                            "AVI Video (MJPEG)",
+                            "avi",
+                        );
+                        ui.close();
+                    }
+                    if ui
+                        .add_enabled(video_export_idle, egui::Button::new("Export MP4..."))
+                        .clicked()
+                    {
+                        start_video_export(
+                            app,
+                            crate::export::VideoFormat::Mp4,
+                            "MP4 Video",
+                            "mp4",
+                        );
+                        ui.close();
+                    }
+                    if ui
+                        .add_enabled(video_export_idle, egui::Button::new("Export WebM..."))
+                        .clicked()
+                    {
+                        start_video_export(
+                            app,
+                            crate::export::VideoFormat::WebM,
+                            "WebM Video",
+                            "webm",
+                        );
+                        ui.close();
+                    }
+                    if ui
+                        .add_enabled(
+                            !app.project.audio_tracks.is_empty(),
+                            egui::Button::new("Export WAV..."),
+                        )
+                        .clicked()
+                    {
                         if let Some(path) = rfd::FileDialog::new()
-                            .add_filter("WebM Video", &["webm"])
-                            .set_file_name("animation.webm")
-                            .set_title("Export WebM")
+                            .add_filter("WAV Audio", &["wav"])
+                            .set_file_name("soundtrack.wav")
+                            .set_title("Export WAV")
                             .save_file()
-                            && let Err(error) = crate::export::export_video(
+                            && let Err(error) = crate::export::export_wav(
                                 &app.project,
                                 &path,
-                                crate::export::VideoFormat::WebM,
+                                &export_settings(app),
                             )
                         {
-                            eprintln!("WebM export failed: {}", error);
+                            eprintln!("WAV export failed: {}", error);
                         }
                         ui.close();
                     }
@@ -127,8 +170,13 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                             .set_file_name("animation.json")
                             .set_title("Export Lottie")
                             .save_file()
+                            && let Err(error) = crate::lottie::export_lottie(
+                                &app.project,
+                                &path,
+                                &export_settings(app),
+                            )
                         {
-                            crate::lottie::export_lottie(&app.project, &path);
+                            eprintln!("Lottie export failed: {}", error);
                         }
                         ui.close();
                     }
@@ -138,8 +186,33 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                             .set_file_name("frame.svg")
                             .set_title("Export SVG")
                             .save_file()
+                            && let Err(error) = crate::svg::export_svg(
+                                &app.project,
+                                app.current_frame,
+                                &path,
+                                &crate::export::RenderSettings::scene(
+                                    &app.project,
+                                    app.project.active_scene,
+                                ),
+                            )
+                        {
+                            eprintln!("SVG export failed: {}", error);
+                        }
+                        ui.close();
+                    }
+                    if ui.button("Export Animated SVG...").clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("SVG Image", &["svg"])
+                            .set_file_name("animation.svg")
+                            .set_title("Export Animated SVG")
+                            .save_file()
+                            && let Err(error) = crate::svg::export_animated_svg(
+                                &app.project,
+                                &path,
+                                &export_settings(app),
+                            )
                         {
-                            crate::svg::export_svg(&app.project, app.current_frame, &path);
+                            eprintln!("Animated SVG export failed: {}", error);
                         }
                         ui.close();
                     }
@@ -164,7 +237,7 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                             .set_title("Import SVG")
                             .pick_file()
                         {
-                            app.history.push(app.project.clone());
+                            app.history.push(app.project.clone(), "Import SVG");
                             tween::ensure_keyframe_at(
                                 &mut app.project.layers[app.active_layer],
                                 app.current_frame,
@@ -178,6 +251,38 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                         }
                         ui.close();
                     }
+                    if ui.button("Import SVG as Layers...").clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("SVG Files", &["svg"])
+                            .set_title("Import SVG as Layers")
+                            .pick_file()
+                        {
+                            app.history
+                                .push(app.project.clone(), "Import SVG as Layers");
+                            match crate::svg::import_svg_layers(
+                                &mut app.project,
+                                &path,
+                                app.current_frame,
+                            ) {
+                                Ok(_) => {
+                                    app.active_layer = 0;
+                                    app.selection.selected_objects.clear();
+                                }
+                                Err(error) => eprintln!("{}", error),
+                            }
+                        }
+                        ui.close();
+                    }
+                    if ui.button("Import Lottie...").clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Lottie Files", &["json"])
+                            .set_title("Import Lottie")
+                            .pick_file()
+                        {
+                            import_lottie(app, &path);
+                        }
+                        ui.close();
+                    }
                 }
                 #[cfg(target_arch = "wasm32")]
                 {
@@ -194,11 +299,15 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
             });
             ui.menu_button("Edit", |ui| {
                 if ui.button("Undo (Ctrl+Z)").clicked() {
-                    app.history.undo(&mut app.project);
+                    if app.history.undo(&mut app.project) {
+                        crate::group::validate_entered_group(app);
+                    }
                     ui.close();
                 }
                 if ui.button("Redo (Ctrl+Shift+Z)").clicked() {
-                    app.history.redo(&mut app.project);
+                    if app.history.redo(&mut app.project) {
+                        crate::group::validate_entered_group(app);
+                    }
                     ui.close();
                 }
                 ui.separator();
@@ -211,6 +320,15 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                     ui.close();
                 }
                 ui.separator();
+                if ui.button("Group (Ctrl+G)").clicked() {
+                    crate::group::group_selected(app);
+                    ui.close();
+                }
+                if ui.button("Ungroup (Ctrl+Shift+G)").clicked() {
+                    crate::group::ungroup_selected(app);
+                    ui.close();
+                }
+                ui.separator();
                 ui.menu_button("Arrange", |ui| {
                     if ui.button("Bring to Front (Ctrl+Shift+])").clicked() {
                         z_order::bring_to_front(app);
@@ -312,6 +430,18 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                     app.canvas_view.pan = egui::Vec2::ZERO;
                     ui.close();
                 }
+                let graph_label = if app.graph_editor.is_some() {
+                    "Graph Editor [ON]"
+                } else {
+                    "Graph Editor [OFF]"
+                };
+                if ui.button(graph_label).clicked() {
+                    app.graph_editor = match app.graph_editor {
+                        Some(_) => None,
+                        None => Some(crate::graph_editor::GraphEditorState::default()),
+                    };
+                    ui.close();
+                }
                 ui.separator();
                 let snap_grid_label = if app.snap_to_grid {
                     "Snap to Grid [ON]"
@@ -359,22 +489,41 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                     timeline::delete_keyframe(app);
                     ui.close();
                 }
+                if ui.button("Null Object").clicked() {
+                    crate::hierarchy::insert_null_object(app);
+                    ui.close();
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui
+                    .add_enabled(
+                        !app.project.audio_tracks.is_empty(),
+                        egui::Button::new("Lip Sync..."),
+                    )
+                    .clicked()
+                {
+                    app.lip_sync = Some(crate::lipsync::LipSyncState::new(app));
+                    ui.close();
+                }
                 ui.separator();
                 if ui.button("Generate Test Animation").clicked() {
-                    app.history.push(app.project.clone());
+                    app.history
+                        .push(app.project.clone(), "Generate Test Animation");
                     app.project = crate::test_animation::generate_bouncing_ball();
                     app.current_frame = 0;
                     app.active_layer = 0;
                     app.selection.selected_objects.clear();
+                    app.selection.entered_group = None;
                     app.save_path = None;
                     ui.close();
                 }
                 if ui.button("Generate Showcase Animation").clicked() {
-                    app.history.push(app.project.clone());
+                    app.history
+                        .push(app.project.clone(), "Generate Showcase Animation");
                     app.project = crate::test_animation::generate_showcase_animation();
                     app.current_frame = 0;
                     app.active_layer = 0;
                     app.selection.selected_objects.clear();
+                    app.selection.entered_group = None;
                     app.save_path = None;
                     ui.close();
                 }
@@ -397,21 +546,167 @@ pub fn draw_menu_bar(app: &mut AnimateApp, ui_context: &egui::Context) {
                     app.current_frame = app.project.total_frames.saturating_sub(1);
                     ui.close();
                 }
+                if app.project.scenes.len() > 1 {
+                    ui.separator();
+                    ui.checkbox(&mut app.playback.play_through_scenes, "Play Through Scenes");
+                }
             });
         });
     });
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn export_settings(app: &AnimateApp) -> crate::export::RenderSettings {
+    if app.export_current_scene_only && !app.project.scenes.is_empty() {
+        crate::export::RenderSettings::scene(&app.project, app.project.active_scene)
+    } else {
+        crate::export::RenderSettings::full(&app.project)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn start_video_export(
+    app: &mut AnimateApp,
+    format: crate::export::VideoFormat,
+    filter_name: &str,
+    extension: &str,
+) {
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter(filter_name, &[extension])
+        .set_file_name(format!("animation.{}", extension))
+        .set_title(format!("Export {}", extension.to_uppercase()))
+        .save_file()
+    else {
+        return;
+    };
+
+    app.video_export = Some(crate::export::VideoExportJob::spawn(
+        app.project.clone(),
+        path,
+        format,
+        export_settings(app),
+    ));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn draw_video_export_progress(app: &mut AnimateApp, ui_context: &egui::Context) {
+    let Some(job) = &mut app.video_export else {
+        return;
+    };
+
+    if let Some(result) = job.poll() {
+        if let Err(error) = result
+            && !job.is_cancelled()
+        {
+            eprintln!("Video export failed: {}", error);
+        }
+        app.video_export = None;
+        return;
+    }
+
+    let completed = job.completed_frames();
+    let total = job.total_frames.max(1);
+    egui::Window::new("Exporting Video")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ui_context, |ui| {
+            ui.label(job.path.display().to_string());
+            ui.add(
+                egui::ProgressBar::new(completed as f32 / total as f32)
+                    .text(format!("Frame {} / {}", completed, total))
+                    .desired_width(260.0),
+            );
+            ui.add_enabled_ui(!job.is_cancelled(), |ui| {
+                if ui.button("Cancel").clicked() {
+                    job.cancel();
+                }
+            });
+        });
+    ui_context.request_repaint();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn import_lottie(app: &mut AnimateApp, path: &std::path::Path) {
+    let snapshot = app.project.clone();
+    match crate::lottie::import_lottie(&mut app.project, path) {
+        Ok(warnings) => {
+            app.history.push(snapshot, "Import Lottie");
+            app.active_layer = 0;
+            app.selection.selected_objects.clear();
+            app.import_warnings = warnings;
+        }
+        Err(error) => eprintln!("Lottie import failed: {}", error),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn draw_import_warnings(app: &mut AnimateApp, ui_context: &egui::Context) {
+    if app.import_warnings.is_empty() {
+        return;
+    }
+
+    let mut open = true;
+    let mut dismissed = false;
+    egui::Window::new("Lottie Import")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(360.0)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ui_context, |ui| {
+            ui.label("Some features could not be converted:");
+            egui::ScrollArea::vertical()
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    for warning in &app.import_warnings {
+                        ui.label(format!("- {}", warning));
+                    }
+                });
+            ui.separator();
+            if ui.button("OK").clicked() {
+                dismissed = true;
+            }
+        });
+    if !open || dismissed {
+        app.import_warnings.clear();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn open_project(app: &mut AnimateApp, path: std::path::PathBuf) {
+    match io::load_project(&path) {
+        Ok(project) => {
+            app.history.push(app.project.clone(), "Open Project");
+            app.project = project;
+            app.current_frame = 0;
+            app.active_layer = 0;
+            app.selection.selected_objects.clear();
+            app.selection.entered_group = None;
+            app.save_path = Some(path);
+        }
+        Err(error) => eprintln!("Failed to open {}: {}", path.display(), error),
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn save_as(app: &mut AnimateApp) {
     if let Some(path) = rfd::FileDialog::new()
         .add_filter("Animation", &["anim"])
+        .add_filter("Packaged Animation", &["animpack"])
         .set_file_name("project.anim")
         .set_title("Save Project As")
         .save_file()
     {
-        let _ = io::save_project(&app.project, &path);
-        app.save_path = Some(path);
+        save_project_to(app, path);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_project_to(app: &mut AnimateApp, path: std::path::PathBuf) {
+    match io::save_project(&app.project, &path) {
+        Ok(()) => app.save_path = Some(path),
+        Err(error) => eprintln!("Failed to save {}: {}", path.display(), error),
     }
 }
 
@@ -443,12 +738,12 @@ fn import_image(app: &mut AnimateApp, path: &std::path::Path) {
         .map(|os_str| os_str.to_string_lossy().to_string())
         .unwrap_or_else(|| "image".to_string());
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Import Image");
 
     app.project.image_assets.push(crate::project::ImageAsset {
         id: asset_id,
         name,
-        data: png_bytes,
+        data: png_bytes.into(),
         width: source_width,
         height: source_height,
     });
@@ -581,16 +876,23 @@ fn wasm_load_project(app: &AnimateApp) {
 #[cfg(target_arch = "wasm32")]
 pub fn process_pending_load(app: &mut AnimateApp) {
     let data = app.pending_project_load.borrow_mut().take();
-    if let Some(bytes) = data
-        && let Ok(json) = std::str::from_utf8(&bytes)
-        && let Ok(project) = serde_json::from_str::<crate::project::Project>(json)
-    {
-        app.history.push(app.project.clone());
-        app.project = project;
-        app.current_frame = 0;
-        app.active_layer = 0;
-        app.selection.selected_objects.clear();
-        app.save_path = None;
+    let Some(bytes) = data else {
+        return;
+    };
+    let result = std::str::from_utf8(&bytes)
+        .map_err(|error| format!("Failed to read project: {}", error))
+        .and_then(crate::migrations::project_from_json);
+    match result {
+        Ok(project) => {
+            app.history.push(app.project.clone(), "Open Project");
+            app.project = project;
+            app.current_frame = 0;
+            app.active_layer = 0;
+            app.selection.selected_objects.clear();
+            app.selection.entered_group = None;
+            app.save_path = None;
+        }
+        Err(error) => tracing::error!("Failed to open project: {}", error),
     }
 }
 
@@ -601,7 +903,7 @@ pub fn select_all(app: &mut AnimateApp) {
             continue;
         }
         if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
-            for object in &objects {
+            for object in &crate::group::container_objects(objects, app.selection.entered_group) {
                 app.selection.selected_objects.push(object.id);
             }
         }
@@ -613,21 +915,26 @@ pub fn delete_selected(app: &mut AnimateApp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Delete Objects");
     let selected = app.selection.selected_objects.clone();
+    let entered = app.selection.entered_group;
     for layer in &mut app.project.layers {
         let has_selected = tween::resolve_frame(layer, app.current_frame)
-            .map(|objects| objects.iter().any(|object| selected.contains(&object.id)))
+            .map(|objects| {
+                crate::group::container_objects(objects, entered)
+                    .iter()
+                    .any(|object| selected.contains(&object.id))
+            })
             .unwrap_or(false);
 
         if has_selected {
             tween::ensure_keyframe_at(layer, app.current_frame);
         }
 
-        if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame) {
-            keyframe
-                .objects
-                .retain(|object| !selected.contains(&object.id));
+        if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame)
+            && let Some(container) = crate::group::container_mut(&mut keyframe.objects, entered)
+        {
+            container.retain(|object| !selected.contains(&object.id));
         }
     }
     app.selection.selected_objects.clear();
@@ -638,11 +945,19 @@ pub fn handle_global_shortcuts(app: &mut AnimateApp, ui_context: &egui::Context)
         return;
     }
     ui_context.input(|input| {
-        if input.modifiers.ctrl && !input.modifiers.shift && input.key_pressed(egui::Key::Z) {
-            app.history.undo(&mut app.project);
+        if input.modifiers.ctrl
+            && !input.modifiers.shift
+            && input.key_pressed(egui::Key::Z)
+            && app.history.undo(&mut app.project)
+        {
+            crate::group::validate_entered_group(app);
         }
-        if input.modifiers.ctrl && input.modifiers.shift && input.key_pressed(egui::Key::Z) {
-            app.history.redo(&mut app.project);
+        if input.modifiers.ctrl
+            && input.modifiers.shift
+            && input.key_pressed(egui::Key::Z)
+            && app.history.redo(&mut app.project)
+        {
+            crate::group::validate_entered_group(app);
         }
         if input.modifiers.ctrl && input.key_pressed(egui::Key::A) {
             select_all(app);
@@ -662,6 +977,15 @@ pub fn handle_global_shortcuts(app: &mut AnimateApp, ui_context: &egui::Context)
         if input.modifiers.ctrl && input.key_pressed(egui::Key::D) {
             clipboard::duplicate_selected(app);
         }
+        if input.modifiers.ctrl && !input.modifiers.shift && input.key_pressed(egui::Key::G) {
+            crate::group::group_selected(app);
+        }
+        if input.modifiers.ctrl && input.modifiers.shift && input.key_pressed(egui::Key::G) {
+            crate::group::ungroup_selected(app);
+        }
+        if input.key_pressed(egui::Key::Escape) {
+            crate::group::exit_group(app);
+        }
         if input.key_pressed(egui::Key::O) && !input.modifiers.ctrl {
             app.onion.enabled = !app.onion.enabled;
         }
@@ -709,7 +1033,7 @@ pub fn handle_global_shortcuts(app: &mut AnimateApp, ui_context: &egui::Context)
         {
             if input.modifiers.ctrl && !input.modifiers.shift && input.key_pressed(egui::Key::S) {
                 if let Some(path) = app.save_path.clone() {
-                    let _ = io::save_project(&app.project, &path);
+                    save_project_to(app, path);
                 } else {
                     save_as(app);
                 }
@@ -720,18 +1044,12 @@ pub fn handle_global_shortcuts(app: &mut AnimateApp, ui_context: &egui::Context)
             if input.modifiers.ctrl
                 && input.key_pressed(egui::Key::O)
                 && let Some(path) = rfd::FileDialog::new()
-                    .add_filter("Animation", &["anim"])
+                    .add_filter("Animation", &["anim", "animpack"])
                     .add_filter("All Files", &["*"])
                     .set_title("Open Project")
                     .pick_file()
-                && let Ok(project) = io::load_project(&path)
             {
-                app.history.push(app.project.clone());
-                app.project = project;
-                app.current_frame = 0;
-                app.active_layer = 0;
-                app.selection.selected_objects.clear();
-                app.save_path = Some(path);
+                open_project(app, path);
             }
         }
     });
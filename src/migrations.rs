@@ -0,0 +1,396 @@
+use serde_json::Value;
+
+use crate::project::{FORMAT_VERSION, Project};
+
+type Migration = fn(&mut Value);
+
+const MIGRATIONS: [Migration; FORMAT_VERSION as usize] = [
+    migrate_to_v1,
+    migrate_to_v2,
+    migrate_to_v3,
+    migrate_to_v4,
+    migrate_to_v5,
+    migrate_to_v6,
+    migrate_to_v7,
+];
+
+pub fn project_from_json(json: &str) -> Result<Project, String> {
+    let value = serde_json::from_str(json)
+        .map_err(|error| format!("Failed to parse project: {}", error))?;
+    project_from_value(value)
+}
+
+pub fn project_from_value(mut value: Value) -> Result<Project, String> {
+    migrate(&mut value)?;
+    serde_json::from_value(value).map_err(|error| format!("Failed to read project: {}", error))
+}
+
+pub fn migrate(value: &mut Value) -> Result<(), String> {
+    let Some(project) = value.as_object_mut() else {
+        return Err("Failed to read project: expected a JSON object".to_string());
+    };
+    let version = match project.get("format_version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| format!("Invalid project format version {}", version))?,
+    };
+    if version > FORMAT_VERSION as u64 {
+        return Err(format!(
+            "Project uses format version {}, but this build of FrameKey only reads up to version {}. Update FrameKey to open it.",
+            version, FORMAT_VERSION
+        ));
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(value);
+    }
+    if let Some(project) = value.as_object_mut() {
+        project.insert("format_version".to_string(), FORMAT_VERSION.into());
+    }
+    Ok(())
+}
+
+fn migrate_to_v1(project: &mut Value) {
+    for layer in all_layers(project) {
+        insert_missing(layer, "property_tracks", Value::Object(Default::default()));
+        let Some(keyframes) = layer.get_mut("keyframes").and_then(Value::as_object_mut) else {
+            continue;
+        };
+        for keyframe in keyframes.values_mut() {
+            insert_missing(keyframe, "shape_tween", Value::Bool(false));
+            let Some(objects) = keyframe.get_mut("objects").and_then(Value::as_array_mut) else {
+                continue;
+            };
+            for object in objects {
+                let Some(shape) = object.get_mut("shape") else {
+                    continue;
+                };
+                if let Some(instance) = shape.get_mut("SymbolInstance") {
+                    insert_missing(instance, "play_mode", Value::from("Loop"));
+                    insert_missing(instance, "first_frame", Value::from(0));
+                }
+                if let Some(points) = shape
+                    .get_mut("Path")
+                    .and_then(|path| path.get_mut("points"))
+                    .and_then(Value::as_array_mut)
+                {
+                    for point in points {
+                        insert_missing(point, "weights", Value::Array(Vec::new()));
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(armatures) = project.get_mut("armatures").and_then(Value::as_array_mut) {
+        for armature in armatures {
+            if let Some(bones) = armature.get_mut("bones").and_then(Value::as_array_mut) {
+                for bone in bones {
+                    insert_missing(bone, "pose_keys", Value::Object(Default::default()));
+                }
+            }
+        }
+    }
+
+    if let Some(tracks) = project
+        .get_mut("audio_tracks")
+        .and_then(Value::as_array_mut)
+    {
+        for track in tracks {
+            if let Some(data) = track.get_mut("data")
+                && let Some(samples) = data.as_array()
+            {
+                use base64::Engine;
+                let bytes: Vec<u8> = samples
+                    .iter()
+                    .filter_map(|byte| byte.as_u64())
+                    .map(|byte| byte as u8)
+                    .collect();
+                *data = Value::String(base64::engine::general_purpose::STANDARD.encode(bytes));
+            }
+        }
+    }
+}
+
+fn migrate_to_v2(project: &mut Value) {
+    if let Some(tracks) = project
+        .get_mut("audio_tracks")
+        .and_then(Value::as_array_mut)
+    {
+        for track in tracks {
+            insert_missing(track, "muted", Value::Bool(false));
+            insert_missing(track, "solo", Value::Bool(false));
+        }
+    }
+}
+
+fn migrate_to_v3(project: &mut Value) {
+    for layer in all_layers(project) {
+        if let Some(objects) = layer
+            .get_mut("property_tracks")
+            .and_then(Value::as_object_mut)
+        {
+            for object_tracks in objects.values_mut() {
+                if let Some(object_tracks) = object_tracks.as_object_mut() {
+                    for track in object_tracks.values_mut() {
+                        insert_missing_in_keys(track, "tangents", Value::Null);
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(armatures) = project.get_mut("armatures").and_then(Value::as_array_mut) {
+        for armature in armatures {
+            if let Some(bones) = armature.get_mut("bones").and_then(Value::as_array_mut) {
+                for bone in bones {
+                    if let Some(pose_keys) = bone.get_mut("pose_keys") {
+                        insert_missing_in_keys(pose_keys, "tangents", Value::Null);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn migrate_to_v4(project: &mut Value) {
+    for layer in all_layers(project) {
+        insert_missing(layer, "motion_guide", Value::Null);
+    }
+}
+
+fn migrate_to_v5(project: &mut Value) {
+    for layer in all_layers(project) {
+        insert_missing(layer, "expressions", Value::Object(Default::default()));
+    }
+}
+
+fn migrate_to_v6(project: &mut Value) {
+    for layer in all_layers(project) {
+        let Some(objects) = layer
+            .get_mut("property_tracks")
+            .and_then(Value::as_object_mut)
+        else {
+            continue;
+        };
+        for object_tracks in objects.values_mut() {
+            insert_missing(
+                object_tracks,
+                "modifiers",
+                Value::Object(Default::default()),
+            );
+        }
+    }
+}
+
+fn migrate_to_v7(project: &mut Value) {
+    for layer in all_layers(project) {
+        insert_missing(layer, "transform_parent_id", Value::Null);
+        let Some(keyframes) = layer.get_mut("keyframes").and_then(Value::as_object_mut) else {
+            continue;
+        };
+        for keyframe in keyframes.values_mut() {
+            if let Some(objects) = keyframe.get_mut("objects").and_then(Value::as_array_mut) {
+                for object in objects {
+                    insert_missing(object, "parent_id", Value::Null);
+                }
+            }
+        }
+    }
+}
+
+fn all_layers(project: &mut Value) -> Vec<&mut Value> {
+    let Some(project) = project.as_object_mut() else {
+        return Vec::new();
+    };
+    let mut layers = Vec::new();
+    for (field, value) in project.iter_mut() {
+        match field.as_str() {
+            "layers" => layers.extend(value.as_array_mut().into_iter().flatten()),
+            "scenes" => {
+                for scene in value.as_array_mut().into_iter().flatten() {
+                    if let Some(scene_layers) = scene.get_mut("layers") {
+                        layers.extend(scene_layers.as_array_mut().into_iter().flatten());
+                    }
+                }
+            }
+            "library" => {
+                if let Some(symbols) = value.get_mut("symbols").and_then(Value::as_array_mut) {
+                    for symbol in symbols {
+                        if let Some(symbol_layers) = symbol.get_mut("layers") {
+                            layers.extend(symbol_layers.as_array_mut().into_iter().flatten());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    layers
+}
+
+fn insert_missing(object: &mut Value, key: &str, default: Value) {
+    if let Some(object) = object.as_object_mut() {
+        object.entry(key).or_insert(default);
+    }
+}
+
+fn insert_missing_in_keys(track: &mut Value, key: &str, default: Value) {
+    if let Some(keys) = track.as_object_mut() {
+        for property_key in keys.values_mut() {
+            insert_missing(property_key, key, default.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Shape, SymbolPlayMode};
+
+    fn v0_layer(objects: Value) -> Value {
+        serde_json::json!({
+            "id": uuid::Uuid::new_v4(),
+            "name": "Layer 1",
+            "visible": true,
+            "locked": false,
+            "opacity": 1.0,
+            "keyframes": {
+                "0": {
+                    "objects": objects,
+                    "tween": "Linear",
+                    "label": "",
+                    "comment": ""
+                }
+            },
+            "layer_type": "Normal",
+            "blend_mode": "Normal",
+            "parent_id": null,
+            "collapsed": false
+        })
+    }
+
+    fn v0_object(shape: Value) -> Value {
+        serde_json::json!({
+            "id": uuid::Uuid::new_v4(),
+            "shape": shape,
+            "position": [10.0, 20.0],
+            "rotation": 0.0,
+            "scale": [1.0, 1.0],
+            "fill": { "Solid": [1.0, 0.0, 0.0, 1.0] },
+            "stroke": { "Solid": [0.0, 0.0, 0.0, 1.0] },
+            "stroke_width": 2.0
+        })
+    }
+
+    #[test]
+    fn v0_project_migrates_to_current_format() {
+        let symbol_id = uuid::Uuid::new_v4();
+        let path = v0_object(serde_json::json!({
+            "Path": {
+                "points": [
+                    { "position": [0.0, 0.0], "control_in": null, "control_out": null, "pressure": 1.0 },
+                    { "position": [5.0, 5.0], "control_in": null, "control_out": null, "pressure": 1.0 }
+                ],
+                "closed": false
+            }
+        }));
+        let instance =
+            v0_object(serde_json::json!({ "SymbolInstance": { "symbol_id": symbol_id } }));
+        let mut value = serde_json::json!({
+            "name": "Legacy",
+            "canvas_width": 640,
+            "canvas_height": 480,
+            "background_color": [1.0, 1.0, 1.0, 1.0],
+            "frame_rate": 24,
+            "total_frames": 48,
+            "layers": [v0_layer(serde_json::json!([path, instance]))],
+            "guides": [],
+            "camera_keyframes": {},
+            "camera_tween": "None",
+            "scenes": [],
+            "active_scene": 0,
+            "library": {
+                "symbols": [{
+                    "id": symbol_id,
+                    "name": "Symbol",
+                    "layers": [v0_layer(serde_json::json!([]))],
+                    "total_frames": 1,
+                    "width": 100.0,
+                    "height": 100.0
+                }]
+            },
+            "image_assets": [],
+            "loop_start": null,
+            "loop_end": null,
+            "audio_tracks": [{
+                "id": uuid::Uuid::new_v4(),
+                "name": "beep.wav",
+                "data": [82, 73, 70, 70],
+                "start_frame": 0,
+                "volume": 1.0
+            }],
+            "armatures": [{
+                "id": uuid::Uuid::new_v4(),
+                "name": "Armature",
+                "bones": [{
+                    "id": uuid::Uuid::new_v4(),
+                    "name": "Bone",
+                    "parent_bone_id": null,
+                    "position": [0.0, 0.0],
+                    "length": 50.0,
+                    "rotation": 0.0,
+                    "bound_object_ids": []
+                }]
+            }]
+        });
+
+        migrate(&mut value).unwrap();
+        assert_eq!(value["format_version"], FORMAT_VERSION);
+        let project: Project = serde_json::from_value(value).unwrap();
+
+        let layer = &project.layers[0];
+        assert!(layer.property_tracks.is_empty());
+        assert!(layer.motion_guide.is_none());
+        assert!(layer.expressions.is_empty());
+        assert!(layer.transform_parent_id.is_none());
+        let keyframe = &layer.keyframes[&0];
+        assert!(!keyframe.shape_tween);
+        assert!(
+            keyframe
+                .objects
+                .iter()
+                .all(|object| object.parent_id.is_none())
+        );
+        match &keyframe.objects[0].shape {
+            Shape::Path { points, .. } => {
+                assert!(points.iter().all(|point| point.weights.is_empty()))
+            }
+            _ => panic!("expected a path"),
+        }
+        match &keyframe.objects[1].shape {
+            Shape::SymbolInstance {
+                play_mode,
+                first_frame,
+                ..
+            } => {
+                assert!(*play_mode == SymbolPlayMode::Loop);
+                assert_eq!(*first_frame, 0);
+            }
+            _ => panic!("expected a symbol instance"),
+        }
+        assert!(project.library.symbols[0].layers[0].expressions.is_empty());
+
+        let track = &project.audio_tracks[0];
+        assert_eq!(track.data.bytes(), Ok(&b"RIFF"[..]));
+        assert!(!track.muted && !track.solo);
+        assert!(project.armatures[0].bones[0].pose_keys.is_empty());
+    }
+
+    #[test]
+    fn newer_format_is_rejected() {
+        let mut value = serde_json::json!({ "format_version": FORMAT_VERSION + 1 });
+        assert!(migrate(&mut value).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use crate::project::{AnimObject, Layer, LayerType, MotionGuide, PathPoint, Shape, TweenType};
+use crate::tween;
+
+const SAMPLES_PER_SEGMENT: usize = 32;
+
+struct GuideSample {
+    point: [f32; 2],
+    length: f32,
+}
+
+struct GuidePath {
+    samples: Vec<GuideSample>,
+}
+
+impl GuidePath {
+    fn from_object(object: &AnimObject) -> Option<Self> {
+        let Shape::Path { points, closed } = &object.shape else {
+            return None;
+        };
+        if points.len() < 2 {
+            return None;
+        }
+
+        let mut segments: Vec<(&PathPoint, &PathPoint)> =
+            points.windows(2).map(|pair| (&pair[0], &pair[1])).collect();
+        if *closed && points.len() > 2 {
+            segments.push((&points[points.len() - 1], &points[0]));
+        }
+
+        let to_world = |local: [f32; 2]| crate::hierarchy::to_world_point(object, local);
+        let mut samples = vec![GuideSample {
+            point: to_world(segments[0].0.position),
+            length: 0.0,
+        }];
+        for (start, end) in segments {
+            let control_out = start.control_out.unwrap_or(start.position);
+            let control_in = end.control_in.unwrap_or(end.position);
+            for step in 1..=SAMPLES_PER_SEGMENT {
+                let t = step as f32 / SAMPLES_PER_SEGMENT as f32;
+                let point = to_world(cubic_bezier(
+                    start.position,
+                    control_out,
+                    control_in,
+                    end.position,
+                    t,
+                ));
+                let previous = &samples[samples.len() - 1];
+                let length = previous.length + distance(previous.point, point);
+                samples.push(GuideSample { point, length });
+            }
+        }
+
+        (samples[samples.len() - 1].length > f32::EPSILON).then_some(Self { samples })
+    }
+
+    fn travel(
+        &self,
+        from: [f32; 2],
+        to: [f32; 2],
+        t: f32,
+        constant_speed: bool,
+    ) -> ([f32; 2], f32) {
+        let from_index = self.project(from);
+        let to_index = self.project(to);
+        let index = if constant_speed {
+            let from_length = self.length_at(from_index);
+            let to_length = self.length_at(to_index);
+            self.index_at_length(from_length + (to_length - from_length) * t)
+        } else {
+            from_index + (to_index - from_index) * t
+        };
+        (self.point_at(index), self.angle_at(index))
+    }
+
+    fn project(&self, point: [f32; 2]) -> f32 {
+        let mut best_index = 0.0;
+        let mut best_distance = f32::MAX;
+        for (index, pair) in self.samples.windows(2).enumerate() {
+            let start = pair[0].point;
+            let end = pair[1].point;
+            let segment = [end[0] - start[0], end[1] - start[1]];
+            let length_squared = segment[0] * segment[0] + segment[1] * segment[1];
+            let fraction = if length_squared > f32::EPSILON {
+                (((point[0] - start[0]) * segment[0] + (point[1] - start[1]) * segment[1])
+                    / length_squared)
+                    .clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let closest = [
+                start[0] + segment[0] * fraction,
+                start[1] + segment[1] * fraction,
+            ];
+            let closest_distance = distance(point, closest);
+            if closest_distance < best_distance {
+                best_distance = closest_distance;
+                best_index = index as f32 + fraction;
+            }
+        }
+        best_index
+    }
+
+    fn split(&self, index: f32) -> (usize, f32) {
+        let last = self.samples.len() - 2;
+        let clamped = index.clamp(0.0, (last + 1) as f32);
+        let segment = (clamped.floor() as usize).min(last);
+        (segment, clamped - segment as f32)
+    }
+
+    fn length_at(&self, index: f32) -> f32 {
+        let (segment, fraction) = self.split(index);
+        let start = self.samples[segment].length;
+        start + (self.samples[segment + 1].length - start) * fraction
+    }
+
+    fn index_at_length(&self, length: f32) -> f32 {
+        let next = self
+            .samples
+            .partition_point(|sample| sample.length < length)
+            .clamp(1, self.samples.len() - 1);
+        let start = self.samples[next - 1].length;
+        let span = self.samples[next].length - start;
+        let fraction = if span > f32::EPSILON {
+            ((length - start) / span).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        (next - 1) as f32 + fraction
+    }
+
+    fn point_at(&self, index: f32) -> [f32; 2] {
+        let (segment, fraction) = self.split(index);
+        let start = self.samples[segment].point;
+        let end = self.samples[segment + 1].point;
+        [
+            start[0] + (end[0] - start[0]) * fraction,
+            start[1] + (end[1] - start[1]) * fraction,
+        ]
+    }
+
+    fn angle_at(&self, index: f32) -> f32 {
+        let start = self.point_at(index - 0.5);
+        let end = self.point_at(index + 0.5);
+        (end[1] - start[1]).atan2(end[0] - start[0])
+    }
+}
+
+fn guide_path(layer: &Layer, layers: &[Layer], frame: u32) -> Option<(MotionGuide, GuidePath)> {
+    let guide = layer.motion_guide?;
+    let guide_layer = layers.iter().find(|candidate| {
+        candidate.id == guide.guide_layer_id && candidate.layer_type == LayerType::Guide
+    })?;
+    let objects = tween::resolve_frame(guide_layer, frame)?;
+    let path_object = objects.iter().find(|object| object.id == guide.path_id)?;
+    GuidePath::from_object(path_object).map(|path| (guide, path))
+}
+
+pub fn apply_motion_guide(objects: &mut [AnimObject], layer: &Layer, layers: &[Layer], frame: u32) {
+    let Some((guide, path)) = guide_path(layer, layers, frame) else {
+        return;
+    };
+    let Some((prev_frame, prev_keyframe)) = layer.keyframes.range(..=frame).next_back() else {
+        return;
+    };
+    let next = layer
+        .keyframes
+        .range((frame + 1)..)
+        .next()
+        .filter(|_| prev_keyframe.tween != TweenType::None);
+    let t = next
+        .map(|(next_frame, _)| {
+            let raw_t = (frame - prev_frame) as f32 / (next_frame - prev_frame) as f32;
+            tween::apply_easing(raw_t, prev_keyframe.tween)
+        })
+        .unwrap_or(0.0);
+
+    for object in objects {
+        if layer
+            .property_tracks
+            .get(&object.id)
+            .is_some_and(|tracks| !tracks.position.is_empty())
+        {
+            continue;
+        }
+        let Some(from) = prev_keyframe
+            .objects
+            .iter()
+            .find(|candidate| candidate.id == object.id)
+        else {
+            continue;
+        };
+        let to = next
+            .and_then(|(_, next_keyframe)| {
+                next_keyframe
+                    .objects
+                    .iter()
+                    .find(|candidate| candidate.id == object.id)
+            })
+            .unwrap_or(from);
+        let (position, angle) = path.travel(from.position, to.position, t, guide.constant_speed);
+        object.position = position;
+        if guide.orient_to_path {
+            object.rotation += angle;
+        }
+    }
+}
+
+pub fn guide_path_choices(layers: &[Layer]) -> Vec<(uuid::Uuid, uuid::Uuid, String)> {
+    let mut choices = Vec::new();
+    for guide_layer in layers
+        .iter()
+        .filter(|layer| layer.layer_type == LayerType::Guide)
+    {
+        let mut path_ids = Vec::new();
+        for object in guide_layer
+            .keyframes
+            .values()
+            .flat_map(|keyframe| &keyframe.objects)
+        {
+            if matches!(object.shape, Shape::Path { .. }) && !path_ids.contains(&object.id) {
+                path_ids.push(object.id);
+            }
+        }
+        for (index, path_id) in path_ids.into_iter().enumerate() {
+            choices.push((
+                guide_layer.id,
+                path_id,
+                format!("{}: Path {}", guide_layer.name, index + 1),
+            ));
+        }
+    }
+    choices
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt()
+}
+
+fn cubic_bezier(p0: [f32; 2], p1: [f32; 2], p2: [f32; 2], p3: [f32; 2], t: f32) -> [f32; 2] {
+    let omt = 1.0 - t;
+    let omt2 = omt * omt;
+    let omt3 = omt2 * omt;
+    let t2 = t * t;
+    let t3 = t2 * t;
+    [
+        omt3 * p0[0] + 3.0 * omt2 * t * p1[0] + 3.0 * omt * t2 * p2[0] + t3 * p3[0],
+        omt3 * p0[1] + 3.0 * omt2 * t * p1[1] + 3.0 * omt * t2 * p2[1] + t3 * p3[1],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paint::Paint;
+
+    fn point(position: [f32; 2]) -> PathPoint {
+        PathPoint {
+            position,
+            control_in: None,
+            control_out: None,
+            pressure: 1.0,
+            weights: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn guide_samples_match_drawn_path_inside_rotated_group() {
+        let guide = AnimObject::new(
+            Shape::Path {
+                points: vec![point([0.0, 0.0]), point([10.0, 0.0])],
+                closed: false,
+            },
+            [5.0, 0.0],
+            Paint::default(),
+            Paint::default(),
+            1.0,
+        );
+        let mut group = AnimObject::new(
+            Shape::Group {
+                children: vec![guide],
+            },
+            [50.0, 50.0],
+            Paint::default(),
+            Paint::default(),
+            0.0,
+        );
+        group.rotation = std::f32::consts::FRAC_PI_2;
+        let drawn = crate::group::flatten_groups(vec![group]).remove(0);
+
+        let path = GuidePath::from_object(&drawn).unwrap();
+        let close = |sample: [f32; 2], local: [f32; 2]| {
+            let expected = crate::hierarchy::to_world_point(&drawn, local);
+            (sample[0] - expected[0]).abs() < 1e-3 && (sample[1] - expected[1]).abs() < 1e-3
+        };
+        assert!(close(path.samples[0].point, [0.0, 0.0]));
+        assert!(close(
+            path.samples[SAMPLES_PER_SEGMENT / 2].point,
+            [5.0, 0.0]
+        ));
+        assert!(close(path.samples[SAMPLES_PER_SEGMENT].point, [10.0, 0.0]));
+        let end = path.samples[SAMPLES_PER_SEGMENT].point;
+        assert!((end[0] - 50.0).abs() < 1e-3 && (end[1] - 65.0).abs() < 1e-3);
+    }
+}
@@ -2,6 +2,7 @@ use nightshade::prelude::*;
 
 use crate::app::AnimateApp;
 use crate::canvas::CanvasView;
+use crate::hierarchy;
 use crate::project::{PathPoint, Shape};
 use crate::tween;
 
@@ -42,14 +43,14 @@ pub fn handle_node_edit_tool(
 
     let view = app.canvas_view.clone();
 
-    let (path_points, object_position, object_scale) = {
+    let (path_points, transform) = {
         let mut result = None;
         for layer in &app.project.layers {
             if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
                 for object in &objects {
                     if object.id == object_id {
                         if let Shape::Path { ref points, .. } = object.shape {
-                            result = Some((points.clone(), object.position, object.scale));
+                            result = Some((points.clone(), object.clone()));
                         }
                         break;
                     }
@@ -69,36 +70,27 @@ pub fn handle_node_edit_tool(
         && let Some(pos) = response.interact_pointer_pos()
     {
         let canvas_pos = view.screen_to_canvas(pos);
-        let local_x = canvas_pos.x - object_position[0];
-        let local_y = canvas_pos.y - object_position[1];
-
-        let mut hit_target = None;
         let threshold = 8.0 / view.zoom;
+        let hits = |local: [f32; 2]| {
+            let [x, y] = hierarchy::to_world_point(&transform, local);
+            (canvas_pos.x - x).abs() < threshold && (canvas_pos.y - y).abs() < threshold
+        };
 
+        let mut hit_target = None;
         for (index, point) in path_points.iter().enumerate() {
-            if let Some(control_in) = point.control_in {
-                let cx = control_in[0] * object_scale[0];
-                let cy = control_in[1] * object_scale[1];
-                if (local_x - cx).abs() < threshold && (local_y - cy).abs() < threshold {
-                    hit_target = Some(DragTarget::ControlIn(index));
-                    break;
-                }
+            if point.control_in.is_some_and(hits) {
+                hit_target = Some(DragTarget::ControlIn(index));
+                break;
             }
-            if let Some(control_out) = point.control_out {
-                let cx = control_out[0] * object_scale[0];
-                let cy = control_out[1] * object_scale[1];
-                if (local_x - cx).abs() < threshold && (local_y - cy).abs() < threshold {
-                    hit_target = Some(DragTarget::ControlOut(index));
-                    break;
-                }
+            if point.control_out.is_some_and(hits) {
+                hit_target = Some(DragTarget::ControlOut(index));
+                break;
             }
         }
 
         if hit_target.is_none() {
             for (index, point) in path_points.iter().enumerate() {
-                let px = point.position[0] * object_scale[0];
-                let py = point.position[1] * object_scale[1];
-                if (local_x - px).abs() < threshold && (local_y - py).abs() < threshold {
+                if hits(point.position) {
                     hit_target = Some(DragTarget::AnchorPoint(index));
                     break;
                 }
@@ -106,7 +98,7 @@ pub fn handle_node_edit_tool(
         }
 
         if let Some(target) = hit_target {
-            app.history.push(app.project.clone());
+            app.history.push(app.project.clone(), "Edit Path");
             ensure_keyframe_for_object(app, object_id);
 
             if let DragTarget::AnchorPoint(index) = &target {
@@ -137,8 +129,8 @@ pub fn handle_node_edit_tool(
         && let Some(pos) = ui_context.input(|input| input.pointer.latest_pos())
     {
         let canvas_pos = view.screen_to_canvas(pos);
-        let local_x = (canvas_pos.x - object_position[0]) / object_scale[0];
-        let local_y = (canvas_pos.y - object_position[1]) / object_scale[1];
+        let [local_x, local_y] =
+            hierarchy::to_local_point(&transform, [canvas_pos.x, canvas_pos.y]);
 
         for layer in &mut app.project.layers {
             if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame) {
@@ -178,15 +170,12 @@ pub fn handle_node_edit_tool(
         && let Some(pos) = response.interact_pointer_pos()
     {
         let canvas_pos = view.screen_to_canvas(pos);
-        let local_x = canvas_pos.x - object_position[0];
-        let local_y = canvas_pos.y - object_position[1];
         let threshold = 8.0 / view.zoom;
 
         for (index, point) in path_points.iter().enumerate() {
-            let px = point.position[0] * object_scale[0];
-            let py = point.position[1] * object_scale[1];
-            if (local_x - px).abs() < threshold && (local_y - py).abs() < threshold {
-                app.history.push(app.project.clone());
+            let [x, y] = hierarchy::to_world_point(&transform, point.position);
+            if (canvas_pos.x - x).abs() < threshold && (canvas_pos.y - y).abs() < threshold {
+                app.history.push(app.project.clone(), "Delete Node");
                 ensure_keyframe_for_object(app, object_id);
                 delete_node(app, object_id, index);
                 app.selection
@@ -202,8 +191,8 @@ pub fn handle_node_edit_tool(
         && let Some(pos) = response.interact_pointer_pos()
     {
         let canvas_pos = view.screen_to_canvas(pos);
-        let local_x = (canvas_pos.x - object_position[0]) / object_scale[0];
-        let local_y = (canvas_pos.y - object_position[1]) / object_scale[1];
+        let [local_x, local_y] =
+            hierarchy::to_local_point(&transform, [canvas_pos.x, canvas_pos.y]);
 
         let mut best_index = None;
         let mut best_dist = f32::MAX;
@@ -227,7 +216,7 @@ pub fn handle_node_edit_tool(
         }
 
         if let Some(insert_index) = best_index {
-            app.history.push(app.project.clone());
+            app.history.push(app.project.clone(), "Insert Node");
             ensure_keyframe_for_object(app, object_id);
             insert_node(app, object_id, insert_index, [local_x, local_y]);
         }
@@ -246,7 +235,7 @@ pub fn draw_node_edit_overlay(app: &AnimateApp, view: &CanvasView, painter: &egu
             for object in &objects {
                 if object.id == object_id {
                     if let Shape::Path { ref points, .. } = object.shape {
-                        found = Some((points.clone(), object.position, object.scale));
+                        found = Some((points.clone(), object.clone()));
                     }
                     break;
                 }
@@ -257,7 +246,7 @@ pub fn draw_node_edit_overlay(app: &AnimateApp, view: &CanvasView, painter: &egu
         }
     }
 
-    let (points, position, scale) = match found {
+    let (points, transform) = match found {
         Some(data) => data,
         None => return,
     };
@@ -268,10 +257,11 @@ pub fn draw_node_edit_overlay(app: &AnimateApp, view: &CanvasView, painter: &egu
     let line_color = egui::Color32::from_rgba_unmultiplied(150, 150, 150, 180);
 
     for (index, point) in points.iter().enumerate() {
-        let screen_pt = view.canvas_to_screen(egui::pos2(
-            position[0] + point.position[0] * scale[0],
-            position[1] + point.position[1] * scale[1],
-        ));
+        let to_screen = |local: [f32; 2]| {
+            let [x, y] = hierarchy::to_world_point(&transform, local);
+            view.canvas_to_screen(egui::pos2(x, y))
+        };
+        let screen_pt = to_screen(point.position);
 
         let is_selected = app.selection.node_edit.selected_nodes.contains(&index);
         let color = if is_selected {
@@ -283,10 +273,7 @@ pub fn draw_node_edit_overlay(app: &AnimateApp, view: &CanvasView, painter: &egu
         painter.circle_stroke(screen_pt, 4.0, egui::Stroke::new(1.0, egui::Color32::BLACK));
 
         if let Some(control_in) = point.control_in {
-            let screen_control = view.canvas_to_screen(egui::pos2(
-                position[0] + control_in[0] * scale[0],
-                position[1] + control_in[1] * scale[1],
-            ));
+            let screen_control = to_screen(control_in);
             painter.line_segment(
                 [screen_pt, screen_control],
                 egui::Stroke::new(1.0, line_color),
@@ -299,10 +286,7 @@ pub fn draw_node_edit_overlay(app: &AnimateApp, view: &CanvasView, painter: &egu
         }
 
         if let Some(control_out) = point.control_out {
-            let screen_control = view.canvas_to_screen(egui::pos2(
-                position[0] + control_out[0] * scale[0],
-                position[1] + control_out[1] * scale[1],
-            ));
+            let screen_control = to_screen(control_out);
             painter.line_segment(
                 [screen_pt, screen_control],
                 egui::Stroke::new(1.0, line_color),
@@ -357,6 +341,10 @@ fn insert_node(app: &mut AnimateApp, object_id: uuid::Uuid, at_index: usize, pos
                         control_in: None,
                         control_out: None,
                         pressure: 1.0,
+                        weights: points
+                            .get(at_index.saturating_sub(1))
+                            .map(|point| point.weights.clone())
+                            .unwrap_or_default(),
                     };
                     points.insert(at_index, new_point);
                 }
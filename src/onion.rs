@@ -58,7 +58,9 @@ fn draw_ghost_frame(
             continue;
         }
 
-        if let Some(objects) = tween::resolve_frame(layer, frame) {
+        if let Some(objects) =
+            tween::resolve_posed_frame(layer, frame, &app.project.layers, &app.project.armatures)
+        {
             for object in &objects {
                 let mut tinted = object.clone();
                 let original_fill = object.fill.as_solid();
@@ -0,0 +1,536 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use nightshade::prelude::*;
+
+use crate::export;
+use crate::migrations;
+use crate::project::{AssetData, Project};
+
+pub const PACKAGE_EXTENSION: &str = "animpack";
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_OF_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+const LOCAL_HEADER_SIZE: u64 = 30;
+const END_OF_DIRECTORY_SIZE: usize = 22;
+const ZIP_VERSION: u16 = 20;
+const DOS_DATE_1980: u16 = 0x21;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+const PROJECT_ENTRY: &str = "project.json";
+const THUMBNAIL_ENTRY: &str = "thumbnail.png";
+const THUMBNAIL_SIZE: u32 = 256;
+
+#[derive(Clone)]
+struct ZipEntry {
+    name: String,
+    method: u16,
+    crc: u32,
+    compressed_size: u32,
+    size: u32,
+    offset: u32,
+}
+
+struct ZipWriter {
+    file: std::io::BufWriter<std::fs::File>,
+    position: u64,
+    entries: Vec<ZipEntry>,
+}
+
+impl ZipWriter {
+    fn create(path: &std::path::Path) -> Result<Self, String> {
+        let file = std::fs::File::create(path)
+            .map_err(|error| format!("Failed to create {}: {}", path.display(), error))?;
+        Ok(Self {
+            file: std::io::BufWriter::new(file),
+            position: 0,
+            entries: Vec::new(),
+        })
+    }
+
+    fn add(&mut self, name: &str, data: &[u8], compress: bool) -> Result<(), String> {
+        let compressed = if compress {
+            Some(deflate(data)?).filter(|compressed| compressed.len() < data.len() * 9 / 10)
+        } else {
+            None
+        };
+        let (method, payload) = match &compressed {
+            Some(compressed) => (METHOD_DEFLATED, compressed.as_slice()),
+            None => (METHOD_STORED, data),
+        };
+        if self.position + LOCAL_HEADER_SIZE + name.len() as u64 + payload.len() as u64
+            > u32::MAX as u64
+        {
+            return Err("Package exceeds the 4 GB zip limit".to_string());
+        }
+
+        let entry = ZipEntry {
+            name: name.to_string(),
+            method,
+            crc: crc32fast::hash(data),
+            compressed_size: payload.len() as u32,
+            size: data.len() as u32,
+            offset: self.position as u32,
+        };
+        let mut header = Vec::with_capacity(LOCAL_HEADER_SIZE as usize + name.len());
+        push_u32(&mut header, LOCAL_HEADER_SIGNATURE);
+        push_u16(&mut header, ZIP_VERSION);
+        push_entry_fields(&mut header, &entry);
+        push_u16(&mut header, 0);
+        header.extend_from_slice(name.as_bytes());
+
+        self.write(&header)?;
+        self.write(payload)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), String> {
+        let directory_offset = self.position;
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            push_u32(&mut directory, CENTRAL_HEADER_SIGNATURE);
+            push_u16(&mut directory, ZIP_VERSION);
+            push_u16(&mut directory, ZIP_VERSION);
+            push_entry_fields(&mut directory, entry);
+            push_u16(&mut directory, 0);
+            push_u16(&mut directory, 0);
+            push_u16(&mut directory, 0);
+            push_u16(&mut directory, 0);
+            push_u32(&mut directory, 0);
+            push_u32(&mut directory, entry.offset);
+            directory.extend_from_slice(entry.name.as_bytes());
+        }
+        let directory_size = directory.len() as u64;
+        if directory_offset + directory_size > u32::MAX as u64 {
+            return Err("Package exceeds the 4 GB zip limit".to_string());
+        }
+
+        push_u32(&mut directory, END_OF_DIRECTORY_SIGNATURE);
+        push_u16(&mut directory, 0);
+        push_u16(&mut directory, 0);
+        push_u16(&mut directory, self.entries.len() as u16);
+        push_u16(&mut directory, self.entries.len() as u16);
+        push_u32(&mut directory, directory_size as u32);
+        push_u32(&mut directory, directory_offset as u32);
+        push_u16(&mut directory, 0);
+
+        self.write(&directory)?;
+        self.file
+            .flush()
+            .map_err(|error| format!("Failed to finish package: {}", error))
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), String> {
+        self.file
+            .write_all(data)
+            .map_err(|error| format!("Failed to write package: {}", error))?;
+        self.position += data.len() as u64;
+        Ok(())
+    }
+}
+
+pub fn is_package(path: &std::path::Path) -> bool {
+    let mut signature = [0u8; 4];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut signature))
+        .is_ok()
+        && u32::from_le_bytes(signature) == LOCAL_HEADER_SIGNATURE
+}
+
+pub fn save_package(project: &Project, path: &std::path::Path) -> Result<(), String> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| PACKAGE_EXTENSION.to_string());
+    let temporary_path = path.with_file_name(format!("{}.tmp", file_name));
+
+    let result = write_package(project, &temporary_path).and_then(|_| {
+        std::fs::rename(&temporary_path, path)
+            .map_err(|error| format!("Failed to replace {}: {}", path.display(), error))
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temporary_path);
+    }
+    result
+}
+
+fn write_package(project: &Project, path: &std::path::Path) -> Result<(), String> {
+    let mut writer = ZipWriter::create(path)?;
+    let mut asset_files: HashMap<u64, Vec<(String, AssetData)>> = HashMap::new();
+    let mut stored_project = project.clone();
+
+    let mut image_files = Vec::new();
+    for asset in &mut stored_project.image_assets {
+        let data = std::mem::take(&mut asset.data);
+        image_files.push(add_asset(&mut writer, &mut asset_files, data)?);
+    }
+    let mut audio_files = Vec::new();
+    for track in &mut stored_project.audio_tracks {
+        let data = std::mem::take(&mut track.data);
+        audio_files.push(add_asset(&mut writer, &mut asset_files, data)?);
+    }
+
+    let mut value = serde_json::to_value(&stored_project)
+        .map_err(|error| format!("Failed to serialize project: {}", error))?;
+    link_asset_files(&mut value, "image_assets", image_files);
+    link_asset_files(&mut value, "audio_tracks", audio_files);
+    let json = serde_json::to_string_pretty(&value)
+        .map_err(|error| format!("Failed to serialize project: {}", error))?;
+    writer.add(PROJECT_ENTRY, json.as_bytes(), true)?;
+
+    let mut thumbnail = Vec::new();
+    export::render_thumbnail(project, THUMBNAIL_SIZE)
+        .write_to(
+            &mut std::io::Cursor::new(&mut thumbnail),
+            image::ImageFormat::Png,
+        )
+        .map_err(|error| format!("Failed to encode thumbnail: {}", error))?;
+    writer.add(THUMBNAIL_ENTRY, &thumbnail, false)?;
+
+    writer.finish()
+}
+
+fn add_asset(
+    writer: &mut ZipWriter,
+    asset_files: &mut HashMap<u64, Vec<(String, AssetData)>>,
+    data: AssetData,
+) -> Result<String, String> {
+    let bytes = data.bytes()?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    let hash = hasher.finish();
+
+    let candidates = asset_files.entry(hash).or_default();
+    if let Some((name, _)) = candidates
+        .iter()
+        .find(|(_, existing)| existing.bytes() == Ok(bytes))
+    {
+        return Ok(name.clone());
+    }
+
+    let name = format!(
+        "assets/{:016x}{}.{}",
+        hash,
+        if candidates.is_empty() {
+            String::new()
+        } else {
+            format!("-{}", candidates.len())
+        },
+        asset_extension(bytes)
+    );
+    writer.add(&name, bytes, true)?;
+    candidates.push((name.clone(), data));
+    Ok(name)
+}
+
+fn link_asset_files(value: &mut serde_json::Value, field: &str, files: Vec<String>) {
+    let Some(assets) = value
+        .get_mut(field)
+        .and_then(|assets| assets.as_array_mut())
+    else {
+        return;
+    };
+    for (asset, file) in assets.iter_mut().zip(files) {
+        if let Some(asset) = asset.as_object_mut() {
+            asset.insert("file".to_string(), serde_json::Value::String(file));
+        }
+    }
+}
+
+fn asset_extension(data: &[u8]) -> &'static str {
+    match image::guess_format(data) {
+        Ok(image::ImageFormat::Png) => return "png",
+        Ok(image::ImageFormat::Jpeg) => return "jpg",
+        Ok(image::ImageFormat::Gif) => return "gif",
+        Ok(image::ImageFormat::WebP) => return "webp",
+        _ => {}
+    }
+    match data {
+        [b'R', b'I', b'F', b'F', ..] => "wav",
+        [b'O', b'g', b'g', b'S', ..] => "ogg",
+        [b'f', b'L', b'a', b'C', ..] => "flac",
+        [b'I', b'D', b'3', ..] => "mp3",
+        [0xFF, second, ..] if second & 0xE0 == 0xE0 => "mp3",
+        _ => "bin",
+    }
+}
+
+pub fn load_package(path: &std::path::Path) -> Result<Project, String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|error| format!("Failed to open {}: {}", path.display(), error))?;
+    let entries = read_directory(&mut file)
+        .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+
+    let project_entry = entries
+        .get(PROJECT_ENTRY)
+        .ok_or_else(|| format!("{} has no {}", path.display(), PROJECT_ENTRY))?;
+    let json = read_entry(&mut file, project_entry)?;
+    let value: serde_json::Value = serde_json::from_slice(&json)
+        .map_err(|error| format!("Failed to parse {}: {}", PROJECT_ENTRY, error))?;
+    let image_files = asset_files(&value, "image_assets");
+    let audio_files = asset_files(&value, "audio_tracks");
+    let mut project = migrations::project_from_value(value)?;
+
+    let mut loaded: HashMap<String, AssetData> = HashMap::new();
+    let mut lazy_asset = |file: Option<String>| -> Result<Option<AssetData>, String> {
+        let Some(file) = file else {
+            return Ok(None);
+        };
+        if let Some(data) = loaded.get(&file) {
+            return Ok(Some(data.clone()));
+        }
+        let entry = entries
+            .get(&file)
+            .cloned()
+            .ok_or_else(|| format!("{} is missing {}", path.display(), file))?;
+        let package_path = path.to_path_buf();
+        let data = AssetData::lazy(move || {
+            let mut file = std::fs::File::open(&package_path)
+                .map_err(|error| format!("Failed to open {}: {}", package_path.display(), error))?;
+            read_entry(&mut file, &entry)
+        });
+        loaded.insert(file, data.clone());
+        Ok(Some(data))
+    };
+
+    for (asset, file) in project.image_assets.iter_mut().zip(image_files) {
+        if let Some(data) = lazy_asset(file)? {
+            asset.data = data;
+        }
+    }
+    for (track, file) in project.audio_tracks.iter_mut().zip(audio_files) {
+        if let Some(data) = lazy_asset(file)? {
+            track.data = data;
+        }
+    }
+
+    Ok(project)
+}
+
+fn asset_files(value: &serde_json::Value, field: &str) -> Vec<Option<String>> {
+    value
+        .get(field)
+        .and_then(|assets| assets.as_array())
+        .map(|assets| {
+            assets
+                .iter()
+                .map(|asset| {
+                    asset
+                        .get("file")
+                        .and_then(|file| file.as_str())
+                        .map(str::to_string)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn read_directory(file: &mut std::fs::File) -> Result<HashMap<String, ZipEntry>, String> {
+    let length = file
+        .seek(SeekFrom::End(0))
+        .map_err(|error| error.to_string())?;
+    let tail_length = length.min(END_OF_DIRECTORY_SIZE as u64 + u16::MAX as u64);
+    let mut tail = vec![0u8; tail_length as usize];
+    file.seek(SeekFrom::Start(length - tail_length))
+        .and_then(|_| file.read_exact(&mut tail))
+        .map_err(|error| error.to_string())?;
+
+    let end = (0..=tail.len().saturating_sub(END_OF_DIRECTORY_SIZE))
+        .rev()
+        .find(|index| read_u32(&tail, *index) == END_OF_DIRECTORY_SIGNATURE)
+        .ok_or_else(|| "not a zip package".to_string())?;
+    let entry_count = read_u16(&tail, end + 10) as usize;
+    let directory_size = read_u32(&tail, end + 12) as usize;
+    let directory_offset = read_u32(&tail, end + 16) as u64;
+
+    let mut directory = vec![0u8; directory_size];
+    file.seek(SeekFrom::Start(directory_offset))
+        .and_then(|_| file.read_exact(&mut directory))
+        .map_err(|error| error.to_string())?;
+
+    let mut entries = HashMap::new();
+    let mut cursor = 0;
+    for _ in 0..entry_count {
+        if cursor + 46 > directory.len() || read_u32(&directory, cursor) != CENTRAL_HEADER_SIGNATURE
+        {
+            return Err("corrupt zip directory".to_string());
+        }
+        let name_length = read_u16(&directory, cursor + 28) as usize;
+        let extra_length = read_u16(&directory, cursor + 30) as usize;
+        let comment_length = read_u16(&directory, cursor + 32) as usize;
+        let name_end = (cursor + 46 + name_length).min(directory.len());
+        let name = String::from_utf8_lossy(&directory[cursor + 46..name_end]).to_string();
+        entries.insert(
+            name.clone(),
+            ZipEntry {
+                name,
+                method: read_u16(&directory, cursor + 10),
+                crc: read_u32(&directory, cursor + 16),
+                compressed_size: read_u32(&directory, cursor + 20),
+                size: read_u32(&directory, cursor + 24),
+                offset: read_u32(&directory, cursor + 42),
+            },
+        );
+        cursor += 46 + name_length + extra_length + comment_length;
+    }
+    Ok(entries)
+}
+
+fn read_entry(file: &mut std::fs::File, entry: &ZipEntry) -> Result<Vec<u8>, String> {
+    let error_context = |error: String| format!("Failed to read {}: {}", entry.name, error);
+
+    let mut header = [0u8; LOCAL_HEADER_SIZE as usize];
+    file.seek(SeekFrom::Start(entry.offset as u64))
+        .and_then(|_| file.read_exact(&mut header))
+        .map_err(|error| error_context(error.to_string()))?;
+    if read_u32(&header, 0) != LOCAL_HEADER_SIGNATURE {
+        return Err(error_context("corrupt local header".to_string()));
+    }
+    let data_offset = entry.offset as u64
+        + LOCAL_HEADER_SIZE
+        + read_u16(&header, 26) as u64
+        + read_u16(&header, 28) as u64;
+
+    let mut payload = vec![0u8; entry.compressed_size as usize];
+    file.seek(SeekFrom::Start(data_offset))
+        .and_then(|_| file.read_exact(&mut payload))
+        .map_err(|error| error_context(error.to_string()))?;
+
+    let data = match entry.method {
+        METHOD_STORED => payload,
+        METHOD_DEFLATED => {
+            let mut data = Vec::with_capacity(entry.size as usize);
+            flate2::read::DeflateDecoder::new(payload.as_slice())
+                .read_to_end(&mut data)
+                .map_err(|error| error_context(error.to_string()))?;
+            data
+        }
+        method => {
+            return Err(error_context(format!(
+                "unsupported compression method {}",
+                method
+            )));
+        }
+    };
+
+    if data.len() != entry.size as usize || crc32fast::hash(&data) != entry.crc {
+        return Err(error_context("checksum mismatch".to_string()));
+    }
+    Ok(data)
+}
+
+fn deflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut encoder =
+        flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(data)
+        .and_then(|_| encoder.finish())
+        .map_err(|error| format!("Failed to compress package entry: {}", error))
+}
+
+fn push_entry_fields(buffer: &mut Vec<u8>, entry: &ZipEntry) {
+    push_u16(buffer, 0);
+    push_u16(buffer, entry.method);
+    push_u16(buffer, 0);
+    push_u16(buffer, DOS_DATE_1980);
+    push_u32(buffer, entry.crc);
+    push_u32(buffer, entry.compressed_size);
+    push_u32(buffer, entry.size);
+    push_u16(buffer, entry.name.len() as u16);
+}
+
+fn push_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn read_u32(buffer: &[u8], offset: usize) -> u32 {
+    buffer
+        .get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .unwrap_or(0)
+}
+
+fn read_u16(buffer: &[u8], offset: usize) -> u16 {
+    buffer
+        .get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{AudioTrack, ImageAsset};
+
+    fn image_asset(name: &str, bytes: Vec<u8>) -> ImageAsset {
+        ImageAsset {
+            id: uuid::Uuid::new_v4(),
+            name: name.to_string(),
+            data: AssetData::from(bytes),
+            width: 2,
+            height: 2,
+        }
+    }
+
+    #[test]
+    fn package_round_trip_deduplicates_assets() {
+        let mut png = Vec::new();
+        image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let wav = b"RIFF\x04\x00\x00\x00WAVE".to_vec();
+
+        let mut project = Project::default();
+        project
+            .image_assets
+            .push(image_asset("first.png", png.clone()));
+        project
+            .image_assets
+            .push(image_asset("copy.png", png.clone()));
+        project.audio_tracks.push(AudioTrack {
+            id: uuid::Uuid::new_v4(),
+            name: "track.wav".to_string(),
+            data: AssetData::from(wav.clone()),
+            start_frame: 0,
+            volume: 1.0,
+            muted: false,
+            solo: false,
+        });
+
+        let path = std::env::temp_dir().join(format!(
+            "framekey-{}.{}",
+            uuid::Uuid::new_v4(),
+            PACKAGE_EXTENSION
+        ));
+        save_package(&project, &path).unwrap();
+        let loaded = load_package(&path).unwrap();
+        let entries = read_directory(&mut std::fs::File::open(&path).unwrap()).unwrap();
+
+        let mut asset_names: Vec<&str> = entries
+            .keys()
+            .filter(|name| name.starts_with("assets/"))
+            .map(String::as_str)
+            .collect();
+        asset_names.sort_by_key(|name| name.ends_with(".wav"));
+        assert_eq!(asset_names.len(), 2);
+        assert!(asset_names[0].ends_with(".png"));
+        assert!(asset_names[1].ends_with(".wav"));
+
+        assert_eq!(loaded.image_assets.len(), 2);
+        assert_eq!(loaded.image_assets[0].name, "first.png");
+        assert_eq!(loaded.image_assets[1].name, "copy.png");
+        for asset in &loaded.image_assets {
+            assert_eq!(asset.data.bytes(), Ok(png.as_slice()));
+        }
+        assert_eq!(loaded.audio_tracks[0].data.bytes(), Ok(wav.as_slice()));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,11 +1,13 @@
 use nightshade::prelude::*;
 
 use crate::app::AnimateApp;
+use crate::scenes;
 
 pub struct PlaybackState {
     pub playing: bool,
     pub accumulator: f64,
     pub last_instant: Option<Instant>,
+    pub play_through_scenes: bool,
 }
 
 impl Default for PlaybackState {
@@ -14,6 +16,7 @@ impl Default for PlaybackState {
             playing: false,
             accumulator: 0.0,
             last_instant: None,
+            play_through_scenes: false,
         }
     }
 }
@@ -30,6 +33,7 @@ pub fn advance_playback(app: &mut AnimateApp) {
         app.playback.accumulator += delta;
 
         let frame_duration = 1.0 / app.project.frame_rate as f64;
+        let mut wrapped = false;
         while app.playback.accumulator >= frame_duration {
             app.playback.accumulator -= frame_duration;
             app.current_frame += 1;
@@ -39,11 +43,22 @@ pub fn advance_playback(app: &mut AnimateApp) {
             {
                 if app.current_frame > loop_end {
                     app.current_frame = loop_start;
+                    wrapped = true;
                 }
             } else if app.current_frame >= app.project.total_frames {
+                if app.playback.play_through_scenes && app.project.scenes.len() > 1 {
+                    let next_scene = (app.project.active_scene + 1) % app.project.scenes.len();
+                    scenes::switch_scene(app, next_scene);
+                }
                 app.current_frame = 0;
+                wrapped = true;
             }
         }
+
+        if wrapped {
+            #[cfg(not(target_arch = "wasm32"))]
+            crate::audio::start_audio_playback(app);
+        }
     }
     app.playback.last_instant = Some(now);
 }
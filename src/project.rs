@@ -1,9 +1,13 @@
 use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock};
 
 use crate::paint::Paint;
 
+pub const FORMAT_VERSION: u32 = 7;
+
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Project {
+    pub format_version: u32,
     pub name: String,
     pub canvas_width: u32,
     pub canvas_height: u32,
@@ -29,6 +33,7 @@ impl Default for Project {
         let mut layer = Layer::new("Layer 1".to_string());
         layer.keyframes.insert(0, Keyframe::default());
         Self {
+            format_version: FORMAT_VERSION,
             name: "Untitled".to_string(),
             canvas_width: 1920,
             canvas_height: 1080,
@@ -51,7 +56,26 @@ impl Default for Project {
     }
 }
 
-#[derive(Clone, serde::Serialize, serde::Deserialize)]
+impl Project {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_assets(&self) -> Result<(), String> {
+        for asset in &self.image_assets {
+            asset
+                .data
+                .bytes()
+                .map_err(|error| format!("Failed to load image {}: {}", asset.name, error))?;
+        }
+        for track in &self.audio_tracks {
+            track
+                .data
+                .bytes()
+                .map_err(|error| format!("Failed to load audio {}: {}", track.name, error))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Layer {
     pub id: uuid::Uuid,
     pub name: String,
@@ -63,8 +87,10 @@ pub struct Layer {
     pub blend_mode: BlendMode,
     pub parent_id: Option<uuid::Uuid>,
     pub collapsed: bool,
-    #[serde(default)]
     pub property_tracks: HashMap<uuid::Uuid, PropertyTracks>,
+    pub motion_guide: Option<MotionGuide>,
+    pub expressions: HashMap<uuid::Uuid, BTreeMap<PropertyChannel, String>>,
+    pub transform_parent_id: Option<uuid::Uuid>,
 }
 
 impl Layer {
@@ -81,6 +107,73 @@ impl Layer {
             parent_id: None,
             collapsed: false,
             property_tracks: HashMap::new(),
+            motion_guide: None,
+            expressions: HashMap::new(),
+            transform_parent_id: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MotionGuide {
+    pub guide_layer_id: uuid::Uuid,
+    pub path_id: uuid::Uuid,
+    pub orient_to_path: bool,
+    pub constant_speed: bool,
+}
+
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum PropertyChannel {
+    PositionX,
+    PositionY,
+    Rotation,
+    ScaleX,
+    ScaleY,
+    StrokeWidth,
+}
+
+impl PropertyChannel {
+    pub const ALL: [PropertyChannel; 6] = [
+        PropertyChannel::PositionX,
+        PropertyChannel::PositionY,
+        PropertyChannel::Rotation,
+        PropertyChannel::ScaleX,
+        PropertyChannel::ScaleY,
+        PropertyChannel::StrokeWidth,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PropertyChannel::PositionX => "X",
+            PropertyChannel::PositionY => "Y",
+            PropertyChannel::Rotation => "Rotation",
+            PropertyChannel::ScaleX => "Scale X",
+            PropertyChannel::ScaleY => "Scale Y",
+            PropertyChannel::StrokeWidth => "Stroke W",
+        }
+    }
+
+    pub fn value(self, object: &AnimObject) -> f32 {
+        match self {
+            PropertyChannel::PositionX => object.position[0],
+            PropertyChannel::PositionY => object.position[1],
+            PropertyChannel::Rotation => object.rotation.to_degrees(),
+            PropertyChannel::ScaleX => object.scale[0],
+            PropertyChannel::ScaleY => object.scale[1],
+            PropertyChannel::StrokeWidth => object.stroke_width,
+        }
+    }
+
+    pub fn set_value(self, object: &mut AnimObject, value: f32) {
+        match self {
+            PropertyChannel::PositionX => object.position[0] = value,
+            PropertyChannel::PositionY => object.position[1] = value,
+            PropertyChannel::Rotation => object.rotation = value.to_radians(),
+            PropertyChannel::ScaleX => object.scale[0] = value,
+            PropertyChannel::ScaleY => object.scale[1] = value,
+            PropertyChannel::StrokeWidth => object.stroke_width = value.max(0.0),
         }
     }
 }
@@ -107,13 +200,12 @@ pub enum BlendMode {
     Exclusion,
 }
 
-#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Keyframe {
     pub objects: Vec<AnimObject>,
     pub tween: TweenType,
     pub label: String,
     pub comment: String,
-    #[serde(default)]
     pub shape_tween: bool,
 }
 
@@ -129,7 +221,7 @@ impl Default for Keyframe {
     }
 }
 
-#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct AnimObject {
     pub id: uuid::Uuid,
     pub shape: Shape,
@@ -139,6 +231,7 @@ pub struct AnimObject {
     pub fill: Paint,
     pub stroke: Paint,
     pub stroke_width: f32,
+    pub parent_id: Option<uuid::Uuid>,
 }
 
 impl AnimObject {
@@ -158,11 +251,12 @@ impl AnimObject {
             fill,
             stroke,
             stroke_width,
+            parent_id: None,
         }
     }
 }
 
-#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum Shape {
     Rectangle {
         width: f32,
@@ -195,9 +289,39 @@ pub enum Shape {
     },
     SymbolInstance {
         symbol_id: uuid::Uuid,
+        play_mode: SymbolPlayMode,
+        first_frame: u32,
+    },
+    Null,
+    Group {
+        children: Vec<AnimObject>,
     },
 }
 
+impl Shape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Rectangle { .. } => "Rectangle",
+            Shape::Ellipse { .. } => "Ellipse",
+            Shape::Line { .. } => "Line",
+            Shape::Path { .. } => "Path",
+            Shape::Text { .. } => "Text",
+            Shape::RasterImage { .. } => "Image",
+            Shape::SymbolInstance { .. } => "Symbol",
+            Shape::Null => "Null",
+            Shape::Group { .. } => "Group",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum SymbolPlayMode {
+    #[default]
+    Loop,
+    PlayOnce,
+    SingleFrame,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum FontFamily {
     SansSerif,
@@ -205,12 +329,19 @@ pub enum FontFamily {
     Monospace,
 }
 
-#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct PathPoint {
     pub position: [f32; 2],
     pub control_in: Option<[f32; 2]>,
     pub control_out: Option<[f32; 2]>,
     pub pressure: f32,
+    pub weights: Vec<BoneWeight>,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BoneWeight {
+    pub bone_id: uuid::Uuid,
+    pub weight: f32,
 }
 
 #[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -270,32 +401,70 @@ pub struct Symbol {
 pub struct ImageAsset {
     pub id: uuid::Uuid,
     pub name: String,
-    #[serde(
-        serialize_with = "serialize_bytes_as_base64",
-        deserialize_with = "deserialize_bytes_from_base64"
-    )]
-    pub data: Vec<u8>,
+    pub data: AssetData,
     pub width: u32,
     pub height: u32,
 }
 
-fn serialize_bytes_as_base64<S: serde::Serializer>(
-    data: &Vec<u8>,
-    serializer: S,
-) -> Result<S::Ok, S::Error> {
-    use base64::Engine;
-    let encoded = base64::engine::general_purpose::STANDARD.encode(data);
-    serializer.serialize_str(&encoded)
+type AssetLoader = Box<dyn Fn() -> Result<Vec<u8>, String> + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct AssetData(Arc<AssetDataSource>);
+
+#[derive(Default)]
+struct AssetDataSource {
+    bytes: OnceLock<Result<Box<[u8]>, String>>,
+    loader: Option<AssetLoader>,
 }
 
-fn deserialize_bytes_from_base64<'de, D: serde::Deserializer<'de>>(
-    deserializer: D,
-) -> Result<Vec<u8>, D::Error> {
-    use base64::Engine;
-    let encoded: String = serde::Deserialize::deserialize(deserializer)?;
-    base64::engine::general_purpose::STANDARD
-        .decode(&encoded)
-        .map_err(serde::de::Error::custom)
+impl AssetData {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn lazy(loader: impl Fn() -> Result<Vec<u8>, String> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(AssetDataSource {
+            bytes: OnceLock::new(),
+            loader: Some(Box::new(loader)),
+        }))
+    }
+
+    pub fn bytes(&self) -> Result<&[u8], String> {
+        self.0
+            .bytes
+            .get_or_init(|| match &self.0.loader {
+                Some(loader) => loader().map(Vec::into_boxed_slice),
+                None => Ok(Box::default()),
+            })
+            .as_deref()
+            .map_err(Clone::clone)
+    }
+}
+
+impl From<Vec<u8>> for AssetData {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(Arc::new(AssetDataSource {
+            bytes: OnceLock::from(Ok(bytes.into_boxed_slice())),
+            loader: None,
+        }))
+    }
+}
+
+impl serde::Serialize for AssetData {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use base64::Engine;
+        let bytes = self.bytes().map_err(serde::ser::Error::custom)?;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+        serializer.serialize_str(&encoded)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for AssetData {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use base64::Engine;
+        let encoded: String = serde::Deserialize::deserialize(deserializer)?;
+        base64::engine::general_purpose::STANDARD
+            .decode(&encoded)
+            .map(AssetData::from)
+            .map_err(serde::de::Error::custom)
+    }
 }
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
@@ -314,24 +483,51 @@ pub struct Bone {
     pub length: f32,
     pub rotation: f32,
     pub bound_object_ids: Vec<uuid::Uuid>,
+    pub pose_keys: BTreeMap<u32, PropertyKey<BonePose>>,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BonePose {
+    pub translation: [f32; 2],
+    pub rotation: f32,
+    pub scale: [f32; 2],
+}
+
+impl Default for BonePose {
+    fn default() -> Self {
+        Self {
+            translation: [0.0, 0.0],
+            rotation: 0.0,
+            scale: [1.0, 1.0],
+        }
+    }
 }
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct AudioTrack {
     pub id: uuid::Uuid,
     pub name: String,
-    pub data: Vec<u8>,
+    pub data: AssetData,
     pub start_frame: u32,
     pub volume: f32,
+    pub muted: bool,
+    pub solo: bool,
 }
 
-#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct PropertyKey<T> {
     pub value: T,
     pub tween: TweenType,
+    pub tangents: Option<KeyTangents>,
 }
 
-#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct KeyTangents {
+    pub in_slope: [f32; 2],
+    pub out_slope: [f32; 2],
+}
+
+#[derive(Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct PropertyTracks {
     pub position: BTreeMap<u32, PropertyKey<[f32; 2]>>,
     pub rotation: BTreeMap<u32, PropertyKey<f32>>,
@@ -339,6 +535,35 @@ pub struct PropertyTracks {
     pub fill: BTreeMap<u32, PropertyKey<Paint>>,
     pub stroke: BTreeMap<u32, PropertyKey<Paint>>,
     pub stroke_width: BTreeMap<u32, PropertyKey<f32>>,
+    pub modifiers: BTreeMap<PropertyChannel, Vec<PropertyModifier>>,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum PropertyModifier {
+    Wiggle {
+        seed: u32,
+        amplitude: f32,
+        period: f32,
+        octaves: u32,
+    },
+    Oscillator {
+        amplitude: f32,
+        period: f32,
+        phase: f32,
+    },
+    Step {
+        hold: u32,
+    },
+}
+
+impl PropertyModifier {
+    pub fn name(self) -> &'static str {
+        match self {
+            PropertyModifier::Wiggle { .. } => "Wiggle",
+            PropertyModifier::Oscillator { .. } => "Oscillator",
+            PropertyModifier::Step { .. } => "Step",
+        }
+    }
 }
 
 impl PropertyTracks {
@@ -349,6 +574,7 @@ impl PropertyTracks {
             && self.fill.is_empty()
             && self.stroke.is_empty()
             && self.stroke_width.is_empty()
+            && self.modifiers.is_empty()
     }
 
     pub fn active_property_names(&self) -> Vec<&'static str> {
@@ -5,10 +5,16 @@ use nightshade::prelude::*;
 use crate::app::{AnimateApp, PropertiesTab};
 use crate::armature;
 use crate::camera;
+use crate::group;
+use crate::hierarchy;
+use crate::history;
 use crate::library;
 use crate::paint::Paint;
 use crate::paint_editor;
-use crate::project::{BlendMode, LayerType, PropertyKey, Shape, TweenType};
+use crate::project::{
+    BlendMode, LayerType, MotionGuide, PropertyChannel, PropertyKey, PropertyModifier, Shape,
+    SymbolPlayMode, TweenType,
+};
 use crate::tween;
 
 enum ShapeEdit {
@@ -29,6 +35,10 @@ enum ShapeEdit {
         font_size: f32,
         font_family: crate::project::FontFamily,
     },
+    SymbolInstance {
+        play_mode: SymbolPlayMode,
+        first_frame: u32,
+    },
 }
 
 fn apply_shape_edit(shape: &mut Shape, edit: &ShapeEdit) {
@@ -80,6 +90,20 @@ fn apply_shape_edit(shape: &mut Shape, edit: &ShapeEdit) {
                 *f = *font_family;
             }
         }
+        ShapeEdit::SymbolInstance {
+            play_mode,
+            first_frame,
+        } => {
+            if let Shape::SymbolInstance {
+                play_mode: m,
+                first_frame: f,
+                ..
+            } = shape
+            {
+                *m = *play_mode;
+                *f = *first_frame;
+            }
+        }
     }
 }
 
@@ -96,6 +120,7 @@ pub fn draw_properties(app: &mut AnimateApp, ui_context: &egui::Context) {
                     "Properties",
                 );
                 ui.selectable_value(&mut app.properties_tab, PropertiesTab::Library, "Library");
+                ui.selectable_value(&mut app.properties_tab, PropertiesTab::History, "History");
             });
             ui.separator();
 
@@ -116,6 +141,9 @@ pub fn draw_properties(app: &mut AnimateApp, ui_context: &egui::Context) {
                 PropertiesTab::Library => {
                     library::draw_library_panel(app, ui);
                 }
+                PropertiesTab::History => {
+                    history::draw_history_panel(app, ui);
+                }
             }
         });
 }
@@ -285,7 +313,176 @@ fn draw_layer_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
                     .range(0.0..=1.0),
             );
             ui.end_row();
+
+            if app.project.layers[app.active_layer].layer_type == LayerType::Normal {
+                draw_layer_parent_properties(app, ui);
+                draw_motion_guide_properties(app, ui);
+            }
+        });
+}
+
+fn draw_layer_parent_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
+    let layers = &app.project.layers;
+    let frame = app.current_frame;
+    let layer = &layers[app.active_layer];
+    let own_ids: Vec<uuid::Uuid> = layer
+        .keyframes
+        .values()
+        .flat_map(|keyframe| &keyframe.objects)
+        .map(|object| object.id)
+        .collect();
+    let choices: Vec<(uuid::Uuid, String)> = hierarchy::parent_choices(layers, frame)
+        .into_iter()
+        .filter(|(candidate_id, _)| {
+            !own_ids.contains(candidate_id)
+                && !own_ids
+                    .iter()
+                    .any(|own_id| hierarchy::is_ancestor(*own_id, *candidate_id, layers, frame))
+        })
+        .collect();
+
+    ui.label("Parent:");
+    let selected = parent_combo(ui, "layer_parent", &choices, layer.transform_parent_id);
+    ui.end_row();
+
+    if let Some(parent_id) = selected {
+        app.history.push(app.project.clone(), "Set Parent");
+        hierarchy::reparent(&mut app.project.layers, app.active_layer, |layer| {
+            layer.transform_parent_id = parent_id;
         });
+    }
+}
+
+fn draw_object_parent_properties(
+    app: &mut AnimateApp,
+    ui: &mut egui::Ui,
+    layer_index: usize,
+    object_id: uuid::Uuid,
+) {
+    let layers = &app.project.layers;
+    let frame = app.current_frame;
+    let choices: Vec<(uuid::Uuid, String)> = hierarchy::parent_choices(layers, frame)
+        .into_iter()
+        .filter(|(candidate_id, _)| {
+            *candidate_id != object_id
+                && !hierarchy::is_ancestor(object_id, *candidate_id, layers, frame)
+        })
+        .collect();
+    let current = tween::resolve_frame(&layers[layer_index], frame).and_then(|objects| {
+        objects
+            .iter()
+            .find(|object| object.id == object_id)
+            .and_then(|object| object.parent_id)
+    });
+
+    let mut selected = None;
+    egui::Grid::new("object_parent")
+        .num_columns(2)
+        .spacing([8.0, 4.0])
+        .show(ui, |ui| {
+            ui.label("Parent:");
+            selected = parent_combo(ui, "object_parent_combo", &choices, current);
+            ui.end_row();
+        });
+
+    if let Some(parent_id) = selected {
+        app.history.push(app.project.clone(), "Set Parent");
+        hierarchy::reparent(&mut app.project.layers, layer_index, |layer| {
+            for object in layer
+                .keyframes
+                .values_mut()
+                .flat_map(|keyframe| &mut keyframe.objects)
+                .filter(|object| object.id == object_id)
+            {
+                object.parent_id = parent_id;
+            }
+        });
+    }
+}
+
+fn parent_combo(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    choices: &[(uuid::Uuid, String)],
+    current: Option<uuid::Uuid>,
+) -> Option<Option<uuid::Uuid>> {
+    let mut selected = None;
+    let selected_text = match current {
+        None => "None".to_string(),
+        Some(parent_id) => choices
+            .iter()
+            .find(|(candidate_id, _)| *candidate_id == parent_id)
+            .map(|(_, label)| label.clone())
+            .unwrap_or_else(|| "(Not on this frame)".to_string()),
+    };
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            if ui.selectable_label(current.is_none(), "None").clicked() && current.is_some() {
+                selected = Some(None);
+            }
+            for (candidate_id, label) in choices {
+                let is_current = current == Some(*candidate_id);
+                if ui.selectable_label(is_current, label).clicked() && !is_current {
+                    selected = Some(Some(*candidate_id));
+                }
+            }
+        });
+    selected
+}
+
+fn draw_motion_guide_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
+    let choices = crate::motion_guide::guide_path_choices(&app.project.layers);
+    let current = app.project.layers[app.active_layer].motion_guide;
+    let mut selected = current;
+
+    ui.label("Guide:");
+    let selected_text = current
+        .and_then(|guide| {
+            choices
+                .iter()
+                .find(|(layer_id, path_id, _)| {
+                    *layer_id == guide.guide_layer_id && *path_id == guide.path_id
+                })
+                .map(|(_, _, label)| label.clone())
+        })
+        .unwrap_or_else(|| "None".to_string());
+    egui::ComboBox::from_id_salt("motion_guide")
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            if ui.selectable_label(current.is_none(), "None").clicked() {
+                selected = None;
+            }
+            for (guide_layer_id, path_id, label) in &choices {
+                let is_current = current.is_some_and(|guide| {
+                    guide.guide_layer_id == *guide_layer_id && guide.path_id == *path_id
+                });
+                if ui.selectable_label(is_current, label).clicked() {
+                    selected = Some(MotionGuide {
+                        guide_layer_id: *guide_layer_id,
+                        path_id: *path_id,
+                        orient_to_path: current.is_some_and(|guide| guide.orient_to_path),
+                        constant_speed: current.is_none_or(|guide| guide.constant_speed),
+                    });
+                }
+            }
+        });
+    ui.end_row();
+
+    if let Some(guide) = selected.as_mut() {
+        ui.label("");
+        ui.checkbox(&mut guide.orient_to_path, "Orient to Path");
+        ui.end_row();
+
+        ui.label("");
+        ui.checkbox(&mut guide.constant_speed, "Constant Speed");
+        ui.end_row();
+    }
+
+    if selected != current {
+        app.history.push(app.project.clone(), "Motion Guide");
+        app.project.layers[app.active_layer].motion_guide = selected;
+    }
 }
 
 fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
@@ -295,12 +492,13 @@ fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
     ui.separator();
 
     let first_id = selected_ids[0];
+    let entered = app.selection.entered_group;
     let mut found_object = None;
     let mut object_layer_index = None;
 
     for (layer_index, layer) in app.project.layers.iter().enumerate() {
         if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
-            for object in &objects {
+            for object in &group::container_objects(objects, entered) {
                 if object.id == first_id {
                     found_object = Some(object.clone());
                     object_layer_index = Some(layer_index);
@@ -322,9 +520,14 @@ fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
     };
 
     let single_object = selected_ids.len() == 1;
+    let single_top_level = single_object && entered.is_none();
     let current_frame = app.current_frame;
 
-    let tracks = if single_object {
+    if let Some(layer_idx) = object_layer_index.filter(|_| single_top_level) {
+        draw_object_parent_properties(app, ui, layer_idx, first_id);
+    }
+
+    let tracks = if single_top_level {
         object_layer_index.and_then(|idx| {
             app.project.layers[idx]
                 .property_tracks
@@ -431,58 +634,26 @@ fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
             let layer = &mut app.project.layers[layer_idx];
             let obj_tracks = layer.property_tracks.entry(first_id).or_default();
             if pos_tracked && position_changed {
-                obj_tracks.position.insert(
-                    current_frame,
-                    PropertyKey {
-                        value: position,
-                        tween: TweenType::Linear,
-                    },
-                );
+                set_track_value(&mut obj_tracks.position, current_frame, position);
             }
             if rot_tracked && rotation_changed {
-                obj_tracks.rotation.insert(
+                set_track_value(
+                    &mut obj_tracks.rotation,
                     current_frame,
-                    PropertyKey {
-                        value: rotation_deg.to_radians(),
-                        tween: TweenType::Linear,
-                    },
+                    rotation_deg.to_radians(),
                 );
             }
             if scale_tracked && scale_changed {
-                obj_tracks.scale.insert(
-                    current_frame,
-                    PropertyKey {
-                        value: scale,
-                        tween: TweenType::Linear,
-                    },
-                );
+                set_track_value(&mut obj_tracks.scale, current_frame, scale);
             }
             if fill_tracked && fill_changed {
-                obj_tracks.fill.insert(
-                    current_frame,
-                    PropertyKey {
-                        value: fill_paint.clone(),
-                        tween: TweenType::Linear,
-                    },
-                );
+                set_track_value(&mut obj_tracks.fill, current_frame, fill_paint.clone());
             }
             if stroke_tracked && stroke_changed {
-                obj_tracks.stroke.insert(
-                    current_frame,
-                    PropertyKey {
-                        value: stroke_paint.clone(),
-                        tween: TweenType::Linear,
-                    },
-                );
+                set_track_value(&mut obj_tracks.stroke, current_frame, stroke_paint.clone());
             }
             if sw_tracked && stroke_width_changed {
-                obj_tracks.stroke_width.insert(
-                    current_frame,
-                    PropertyKey {
-                        value: stroke_width,
-                        tween: TweenType::Linear,
-                    },
-                );
+                set_track_value(&mut obj_tracks.stroke_width, current_frame, stroke_width);
             }
         }
     }
@@ -499,7 +670,7 @@ fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
         for layer in &mut app.project.layers {
             let has_selected = tween::resolve_frame(layer, app.current_frame)
                 .map(|objects| {
-                    objects
+                    group::container_objects(objects, entered)
                         .iter()
                         .any(|object| selected_ids.contains(&object.id))
                 })
@@ -509,8 +680,10 @@ fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
                 tween::ensure_keyframe_at(layer, app.current_frame);
             }
 
-            if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame) {
-                for object in &mut keyframe.objects {
+            if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame)
+                && let Some(container) = group::container_mut(&mut keyframe.objects, entered)
+            {
+                for object in container {
                     if selected_ids.contains(&object.id) {
                         if !pos_tracked && position_changed {
                             object.position = position;
@@ -536,7 +709,7 @@ fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
         }
     }
 
-    if let Some(layer_idx) = object_layer_index.filter(|_| single_object) {
+    if let Some(layer_idx) = object_layer_index.filter(|_| single_top_level) {
         ui.separator();
         draw_property_keyframe_buttons(
             app,
@@ -553,6 +726,8 @@ fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
                 stroke_width,
             },
         );
+        draw_modifier_properties(app, ui, layer_idx, first_id);
+        draw_expression_properties(app, ui, layer_idx, first_id);
     }
 
     ui.separator();
@@ -715,8 +890,82 @@ fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
         } => {
             ui.label(format!("Image: {}x{}", source_width, source_height));
         }
-        Shape::SymbolInstance { symbol_id } => {
-            ui.label(format!("Symbol: {}", symbol_id));
+        Shape::SymbolInstance {
+            symbol_id,
+            play_mode,
+            first_frame,
+        } => {
+            let symbol = app
+                .project
+                .library
+                .symbols
+                .iter()
+                .find(|symbol| symbol.id == *symbol_id);
+            let symbol_name = symbol
+                .map(|symbol| symbol.name.clone())
+                .unwrap_or_else(|| symbol_id.to_string());
+            let symbol_frames = symbol.map(|symbol| symbol.total_frames.max(1)).unwrap_or(1);
+            ui.label(format!("Symbol: {}", symbol_name));
+
+            let mut instance_mode = *play_mode;
+            let mut instance_first_frame = first_frame + 1;
+            egui::Grid::new("symbol_instance_props")
+                .num_columns(2)
+                .spacing([8.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("Playback:");
+                    let mode_label = match instance_mode {
+                        SymbolPlayMode::Loop => "Loop",
+                        SymbolPlayMode::PlayOnce => "Play Once",
+                        SymbolPlayMode::SingleFrame => "Single Frame",
+                    };
+                    egui::ComboBox::from_id_salt("symbol_play_mode")
+                        .selected_text(mode_label)
+                        .show_ui(ui, |ui| {
+                            for (mode, label) in [
+                                (SymbolPlayMode::Loop, "Loop"),
+                                (SymbolPlayMode::PlayOnce, "Play Once"),
+                                (SymbolPlayMode::SingleFrame, "Single Frame"),
+                            ] {
+                                if ui
+                                    .selectable_value(&mut instance_mode, mode, label)
+                                    .changed()
+                                {
+                                    shape_changed = true;
+                                }
+                            }
+                        });
+                    ui.end_row();
+                    ui.label("First Frame:");
+                    shape_changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut instance_first_frame)
+                                .speed(1.0)
+                                .range(1..=symbol_frames),
+                        )
+                        .changed();
+                    ui.end_row();
+                });
+            if shape_changed {
+                new_shape_data = Some(ShapeEdit::SymbolInstance {
+                    play_mode: instance_mode,
+                    first_frame: instance_first_frame - 1,
+                });
+            }
+        }
+        Shape::Null => {
+            ui.label("Null");
+        }
+        Shape::Group { children } => {
+            ui.label(format!("Group: {} objects", children.len()));
+            ui.horizontal(|ui| {
+                if single_object && ui.button("Enter Group").clicked() {
+                    group::enter_group(app, first_id);
+                }
+                if ui.button("Ungroup").clicked() {
+                    group::ungroup_selected(app);
+                }
+            });
         }
     }
 
@@ -724,7 +973,7 @@ fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
         for layer in &mut app.project.layers {
             let has_selected = tween::resolve_frame(layer, app.current_frame)
                 .map(|objects| {
-                    objects
+                    group::container_objects(objects, entered)
                         .iter()
                         .any(|object| selected_ids.contains(&object.id))
                 })
@@ -734,8 +983,10 @@ fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
                 tween::ensure_keyframe_at(layer, app.current_frame);
             }
 
-            if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame) {
-                for object in &mut keyframe.objects {
+            if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame)
+                && let Some(container) = group::container_mut(&mut keyframe.objects, entered)
+            {
+                for object in container {
                     if selected_ids.contains(&object.id) {
                         apply_shape_edit(&mut object.shape, &edit);
                     }
@@ -744,30 +995,12 @@ fn draw_object_properties(app: &mut AnimateApp, ui: &mut egui::Ui) {
         }
     }
 
+    armature::draw_object_bone_binding(app, ui);
+
     ui.separator();
 
     if ui.button("Delete Selected").clicked() {
-        app.history.push(app.project.clone());
-        for layer in &mut app.project.layers {
-            let has_selected = tween::resolve_frame(layer, app.current_frame)
-                .map(|objects| {
-                    objects
-                        .iter()
-                        .any(|object| selected_ids.contains(&object.id))
-                })
-                .unwrap_or(false);
-
-            if has_selected {
-                tween::ensure_keyframe_at(layer, app.current_frame);
-            }
-
-            if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame) {
-                keyframe
-                    .objects
-                    .retain(|object| !selected_ids.contains(&object.id));
-            }
-        }
-        app.selection.selected_objects.clear();
+        crate::menu::delete_selected(app);
     }
 }
 
@@ -783,6 +1016,254 @@ struct PropertyKeyframeContext {
     stroke_width: f32,
 }
 
+fn draw_modifier_properties(
+    app: &mut AnimateApp,
+    ui: &mut egui::Ui,
+    layer_index: usize,
+    object_id: uuid::Uuid,
+) {
+    let mut modifiers = app.project.layers[layer_index]
+        .property_tracks
+        .get(&object_id)
+        .map(|tracks| tracks.modifiers.clone())
+        .unwrap_or_default();
+    let mut changed = false;
+    let mut added = None;
+    let mut removed = None;
+
+    ui.separator();
+    egui::CollapsingHeader::new("Modifiers")
+        .id_salt("object_modifiers")
+        .default_open(!modifiers.is_empty())
+        .show(ui, |ui| {
+            for (channel, stack) in modifiers.iter_mut() {
+                ui.label(channel.name());
+                for (index, modifier) in stack.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(modifier.name());
+                        changed |= draw_modifier_fields(ui, modifier);
+                        if ui.small_button("x").clicked() {
+                            removed = Some((*channel, index));
+                        }
+                    });
+                }
+            }
+
+            ui.menu_button("Add Modifier", |ui| {
+                for channel in PropertyChannel::ALL {
+                    ui.menu_button(channel.name(), |ui| {
+                        for modifier in default_modifiers(channel) {
+                            if ui.button(modifier.name()).clicked() {
+                                added = Some((channel, modifier));
+                                ui.close();
+                            }
+                        }
+                    });
+                }
+            });
+        });
+
+    if let Some((channel, modifier)) = added {
+        app.history.push(app.project.clone(), "Add Modifier");
+        modifiers.entry(channel).or_default().push(modifier);
+        changed = true;
+    }
+    if let Some((channel, index)) = removed {
+        app.history.push(app.project.clone(), "Remove Modifier");
+        if let Some(stack) = modifiers.get_mut(&channel) {
+            stack.remove(index);
+            if stack.is_empty() {
+                modifiers.remove(&channel);
+            }
+        }
+        changed = true;
+    }
+    if changed {
+        let layer = &mut app.project.layers[layer_index];
+        layer
+            .property_tracks
+            .entry(object_id)
+            .or_default()
+            .modifiers = modifiers;
+        if layer
+            .property_tracks
+            .get(&object_id)
+            .is_some_and(|tracks| tracks.is_empty())
+        {
+            layer.property_tracks.remove(&object_id);
+        }
+    }
+}
+
+fn draw_modifier_fields(ui: &mut egui::Ui, modifier: &mut PropertyModifier) -> bool {
+    let mut changed = false;
+    match modifier {
+        PropertyModifier::Wiggle {
+            seed,
+            amplitude,
+            period,
+            octaves,
+        } => {
+            changed |= ui
+                .add(egui::DragValue::new(amplitude).speed(0.1).prefix("amp "))
+                .changed();
+            changed |= ui
+                .add(
+                    egui::DragValue::new(period)
+                        .speed(0.5)
+                        .range(1.0..=1000.0)
+                        .prefix("every ")
+                        .suffix("f"),
+                )
+                .changed();
+            changed |= ui
+                .add(egui::DragValue::new(octaves).range(1..=6).prefix("oct "))
+                .changed();
+            changed |= ui.add(egui::DragValue::new(seed).prefix("seed ")).changed();
+        }
+        PropertyModifier::Oscillator {
+            amplitude,
+            period,
+            phase,
+        } => {
+            changed |= ui
+                .add(egui::DragValue::new(amplitude).speed(0.1).prefix("amp "))
+                .changed();
+            changed |= ui
+                .add(
+                    egui::DragValue::new(period)
+                        .speed(0.5)
+                        .range(1.0..=1000.0)
+                        .prefix("every ")
+                        .suffix("f"),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::DragValue::new(phase)
+                        .speed(1.0)
+                        .range(-360.0..=360.0)
+                        .suffix("°"),
+                )
+                .changed();
+        }
+        PropertyModifier::Step { hold } => {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(hold)
+                        .range(1..=120)
+                        .prefix("hold ")
+                        .suffix("f"),
+                )
+                .changed();
+        }
+    }
+    changed
+}
+
+fn default_modifiers(channel: PropertyChannel) -> [PropertyModifier; 3] {
+    let amplitude = match channel {
+        PropertyChannel::ScaleX | PropertyChannel::ScaleY => 0.05,
+        _ => 5.0,
+    };
+    [
+        PropertyModifier::Wiggle {
+            seed: uuid::Uuid::new_v4().as_u128() as u32,
+            amplitude,
+            period: 12.0,
+            octaves: 2,
+        },
+        PropertyModifier::Oscillator {
+            amplitude,
+            period: 24.0,
+            phase: 0.0,
+        },
+        PropertyModifier::Step { hold: 2 },
+    ]
+}
+
+fn draw_expression_properties(
+    app: &mut AnimateApp,
+    ui: &mut egui::Ui,
+    layer_index: usize,
+    object_id: uuid::Uuid,
+) {
+    let expressions = app.project.layers[layer_index]
+        .expressions
+        .get(&object_id)
+        .cloned()
+        .unwrap_or_default();
+
+    ui.separator();
+    egui::CollapsingHeader::new("Expressions")
+        .id_salt("object_expressions")
+        .default_open(!expressions.is_empty())
+        .show(ui, |ui| {
+            #[cfg(not(all(feature = "scripting", not(target_arch = "wasm32"))))]
+            ui.weak("Expressions are evaluated in builds with the scripting feature");
+
+            egui::Grid::new("expression_props")
+                .num_columns(2)
+                .spacing([8.0, 4.0])
+                .show(ui, |ui| {
+                    for channel in PropertyChannel::ALL {
+                        let mut source = expressions.get(&channel).cloned().unwrap_or_default();
+                        ui.label(format!("{}:", channel.name()));
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut source)
+                                .code_editor()
+                                .hint_text("value"),
+                        );
+                        let pushed_id = response.id.with("history");
+                        if response.changed() {
+                            let pushed = ui
+                                .ctx()
+                                .data(|data| data.get_temp::<bool>(pushed_id))
+                                .unwrap_or(false);
+                            if !pushed {
+                                app.history.push(app.project.clone(), "Edit Expression");
+                                ui.ctx().data_mut(|data| data.insert_temp(pushed_id, true));
+                            }
+                            set_expression(app, layer_index, object_id, channel, source.clone());
+                        }
+                        if response.lost_focus() {
+                            ui.ctx().data_mut(|data| data.remove::<bool>(pushed_id));
+                        }
+                        ui.end_row();
+
+                        #[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+                        if !source.trim().is_empty()
+                            && let Some(error) =
+                                crate::expressions::expression_error(object_id, channel)
+                        {
+                            ui.label("");
+                            ui.colored_label(egui::Color32::from_rgb(230, 90, 90), error);
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+}
+
+fn set_expression(
+    app: &mut AnimateApp,
+    layer_index: usize,
+    object_id: uuid::Uuid,
+    channel: PropertyChannel,
+    source: String,
+) {
+    let layer = &mut app.project.layers[layer_index];
+    let expressions = layer.expressions.entry(object_id).or_default();
+    if source.trim().is_empty() {
+        expressions.remove(&channel);
+    } else {
+        expressions.insert(channel, source);
+    }
+    if expressions.is_empty() {
+        layer.expressions.remove(&object_id);
+    }
+}
+
 fn draw_property_keyframe_buttons(
     app: &mut AnimateApp,
     ui: &mut egui::Ui,
@@ -871,7 +1352,8 @@ fn draw_property_keyframe_buttons(
     });
 
     if let Some(index) = toggle_index {
-        app.history.push(app.project.clone());
+        app.history
+            .push(app.project.clone(), "Toggle Property Keyframe");
         let layer = &mut app.project.layers[layer_index];
         let obj_tracks = layer.property_tracks.entry(object_id).or_default();
 
@@ -895,6 +1377,19 @@ fn draw_property_keyframe_buttons(
     }
 }
 
+fn set_track_value<T>(track: &mut BTreeMap<u32, PropertyKey<T>>, frame: u32, value: T) {
+    match track.entry(frame) {
+        std::collections::btree_map::Entry::Occupied(mut entry) => entry.get_mut().value = value,
+        std::collections::btree_map::Entry::Vacant(entry) => {
+            entry.insert(PropertyKey {
+                value,
+                tween: TweenType::Linear,
+                tangents: None,
+            });
+        }
+    }
+}
+
 fn toggle_track_key<T: Clone + Copy>(
     track: &mut BTreeMap<u32, PropertyKey<T>>,
     frame: u32,
@@ -904,6 +1399,7 @@ fn toggle_track_key<T: Clone + Copy>(
         entry.insert(PropertyKey {
             value,
             tween: TweenType::Linear,
+            tangents: None,
         });
     } else {
         track.remove(&frame);
@@ -919,6 +1415,7 @@ fn toggle_track_key_clone<T: Clone>(
         entry.insert(PropertyKey {
             value,
             tween: TweenType::Linear,
+            tangents: None,
         });
     } else {
         track.remove(&frame);
@@ -0,0 +1,340 @@
+const MIN_COVERAGE: f32 = 1.0 / 512.0;
+
+pub struct Rasterizer {
+    origin_x: i32,
+    origin_y: i32,
+    width: usize,
+    height: usize,
+    stride: usize,
+    accumulation: Vec<f32>,
+}
+
+impl Rasterizer {
+    pub fn new(image_width: u32, image_height: u32, bounds: [f32; 4]) -> Option<Self> {
+        if !bounds.iter().all(|value| value.is_finite()) {
+            return None;
+        }
+        let min_x = (bounds[0].floor() as i32).max(0);
+        let min_y = (bounds[1].floor() as i32).max(0);
+        let max_x = (bounds[2].ceil() as i32 + 1).min(image_width as i32);
+        let max_y = (bounds[3].ceil() as i32 + 1).min(image_height as i32);
+        if max_x <= min_x || max_y <= min_y {
+            return None;
+        }
+
+        let width = (max_x - min_x) as usize;
+        let height = (max_y - min_y) as usize;
+        let stride = width + 2;
+        Some(Self {
+            origin_x: min_x,
+            origin_y: min_y,
+            width,
+            height,
+            stride,
+            accumulation: vec![0.0; stride * height],
+        })
+    }
+
+    pub fn fill_polygon(&mut self, points: &[[f32; 2]]) {
+        if points.len() < 3 {
+            return;
+        }
+        for index in 0..points.len() {
+            let from = points[index];
+            let to = points[(index + 1) % points.len()];
+            self.add_edge(
+                [
+                    from[0] - self.origin_x as f32,
+                    from[1] - self.origin_y as f32,
+                ],
+                [to[0] - self.origin_x as f32, to[1] - self.origin_y as f32],
+            );
+        }
+    }
+
+    pub fn for_each_pixel(&self, mut visit: impl FnMut(u32, u32, f32)) {
+        for row in 0..self.height {
+            let line = &self.accumulation[row * self.stride..row * self.stride + self.width];
+            let mut winding = 0.0_f32;
+            for (column, value) in line.iter().enumerate() {
+                winding += value;
+                let coverage = winding.abs().min(1.0);
+                if coverage > MIN_COVERAGE {
+                    visit(
+                        (self.origin_x + column as i32) as u32,
+                        (self.origin_y + row as i32) as u32,
+                        coverage,
+                    );
+                }
+            }
+        }
+    }
+
+    fn add_edge(&mut self, from: [f32; 2], to: [f32; 2]) {
+        let right = self.width as f32;
+        let mut splits = [0.0, 1.0, 1.0, 1.0];
+        let mut split_count = 1;
+        let dx = to[0] - from[0];
+        if dx.abs() > f32::EPSILON {
+            for boundary in [0.0, right] {
+                let t = (boundary - from[0]) / dx;
+                if t > 0.0 && t < 1.0 {
+                    splits[split_count] = t;
+                    split_count += 1;
+                }
+            }
+        }
+        splits[split_count] = 1.0;
+        splits[1..split_count].sort_by(f32::total_cmp);
+
+        for index in 0..split_count {
+            let start = lerp_point(from, to, splits[index]);
+            let end = lerp_point(from, to, splits[index + 1]);
+            self.add_line(
+                [start[0].clamp(0.0, right), start[1]],
+                [end[0].clamp(0.0, right), end[1]],
+            );
+        }
+    }
+
+    fn add_line(&mut self, from: [f32; 2], to: [f32; 2]) {
+        if (from[1] - to[1]).abs() <= f32::EPSILON {
+            return;
+        }
+        let (direction, top, bottom) = if from[1] < to[1] {
+            (1.0, from, to)
+        } else {
+            (-1.0, to, from)
+        };
+
+        let height = self.height as f32;
+        if bottom[1] <= 0.0 || top[1] >= height {
+            return;
+        }
+
+        let dxdy = (bottom[0] - top[0]) / (bottom[1] - top[1]);
+        let mut x = top[0];
+        if top[1] < 0.0 {
+            x -= top[1] * dxdy;
+        }
+
+        let first_row = top[1].max(0.0) as usize;
+        let last_row = (bottom[1].ceil() as usize).min(self.height);
+        for row in first_row..last_row {
+            let line_start = row * self.stride;
+            let dy = ((row + 1) as f32).min(bottom[1]) - (row as f32).max(top[1]);
+            let x_next = x + dxdy * dy;
+            let delta = dy * direction;
+            let (x0, x1) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let x0_floor = x0.floor();
+            let x0_index = x0_floor as usize;
+            let x1_ceil = x1.ceil();
+            let x1_index = x1_ceil as usize;
+
+            if x1_index <= x0_index + 1 {
+                let midpoint = 0.5 * (x + x_next) - x0_floor;
+                self.accumulation[line_start + x0_index] += delta - delta * midpoint;
+                self.accumulation[line_start + x0_index + 1] += delta * midpoint;
+            } else {
+                let inverse_width = (x1 - x0).recip();
+                let x0_fraction = x0 - x0_floor;
+                let first_area = 0.5 * inverse_width * (1.0 - x0_fraction) * (1.0 - x0_fraction);
+                let x1_fraction = x1 - x1_ceil + 1.0;
+                let last_area = 0.5 * inverse_width * x1_fraction * x1_fraction;
+
+                self.accumulation[line_start + x0_index] += delta * first_area;
+                if x1_index == x0_index + 2 {
+                    self.accumulation[line_start + x0_index + 1] +=
+                        delta * (1.0 - first_area - last_area);
+                } else {
+                    let second_area = inverse_width * (1.5 - x0_fraction);
+                    self.accumulation[line_start + x0_index + 1] +=
+                        delta * (second_area - first_area);
+                    for column in x0_index + 2..x1_index - 1 {
+                        self.accumulation[line_start + column] += delta * inverse_width;
+                    }
+                    let covered = second_area + (x1_index - x0_index - 3) as f32 * inverse_width;
+                    self.accumulation[line_start + x1_index - 1] +=
+                        delta * (1.0 - covered - last_area);
+                }
+                self.accumulation[line_start + x1_index] += delta * last_area;
+            }
+            x = x_next;
+        }
+    }
+}
+
+fn lerp_point(from: [f32; 2], to: [f32; 2], t: f32) -> [f32; 2] {
+    [
+        from[0] + (to[0] - from[0]) * t,
+        from[1] + (to[1] - from[1]) * t,
+    ]
+}
+
+pub fn polygon_bounds(points: &[[f32; 2]]) -> [f32; 4] {
+    let mut bounds = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+    for point in points {
+        bounds[0] = bounds[0].min(point[0]);
+        bounds[1] = bounds[1].min(point[1]);
+        bounds[2] = bounds[2].max(point[0]);
+        bounds[3] = bounds[3].max(point[1]);
+    }
+    bounds
+}
+
+pub fn expand_bounds(bounds: [f32; 4], amount: f32) -> [f32; 4] {
+    [
+        bounds[0] - amount,
+        bounds[1] - amount,
+        bounds[2] + amount,
+        bounds[3] + amount,
+    ]
+}
+
+fn arc_segments(radius: f32) -> usize {
+    ((radius.max(0.0).sqrt() * 8.0).ceil() as usize).clamp(12, 256)
+}
+
+fn rotate_offset(offset: [f32; 2], rotation: f32) -> [f32; 2] {
+    let (sin_r, cos_r) = rotation.sin_cos();
+    [
+        offset[0] * cos_r - offset[1] * sin_r,
+        offset[0] * sin_r + offset[1] * cos_r,
+    ]
+}
+
+pub fn ellipse_outline(
+    center: [f32; 2],
+    radius_x: f32,
+    radius_y: f32,
+    rotation: f32,
+) -> Vec<[f32; 2]> {
+    let segments = arc_segments(radius_x.max(radius_y));
+    (0..segments)
+        .map(|segment| {
+            let angle = std::f32::consts::TAU * segment as f32 / segments as f32;
+            let offset = rotate_offset([angle.cos() * radius_x, angle.sin() * radius_y], rotation);
+            [center[0] + offset[0], center[1] + offset[1]]
+        })
+        .collect()
+}
+
+pub fn rounded_rect_outline(
+    center: [f32; 2],
+    half_width: f32,
+    half_height: f32,
+    corner_radius: f32,
+    rotation: f32,
+) -> Vec<[f32; 2]> {
+    let radius = corner_radius.max(0.0).min(half_width).min(half_height);
+    let mut local = Vec::new();
+    if radius < 0.01 {
+        local.extend([
+            [-half_width, -half_height],
+            [half_width, -half_height],
+            [half_width, half_height],
+            [-half_width, half_height],
+        ]);
+    } else {
+        let segments = (arc_segments(radius) / 4).max(3);
+        let corners = [
+            (
+                [half_width - radius, -half_height + radius],
+                -std::f32::consts::FRAC_PI_2,
+            ),
+            ([half_width - radius, half_height - radius], 0.0),
+            (
+                [-half_width + radius, half_height - radius],
+                std::f32::consts::FRAC_PI_2,
+            ),
+            (
+                [-half_width + radius, -half_height + radius],
+                std::f32::consts::PI,
+            ),
+        ];
+        for (corner, start_angle) in corners {
+            for step in 0..=segments {
+                let angle =
+                    start_angle + std::f32::consts::FRAC_PI_2 * step as f32 / segments as f32;
+                local.push([
+                    corner[0] + angle.cos() * radius,
+                    corner[1] + angle.sin() * radius,
+                ]);
+            }
+        }
+    }
+
+    local
+        .into_iter()
+        .map(|point| {
+            let offset = rotate_offset(point, rotation);
+            [center[0] + offset[0], center[1] + offset[1]]
+        })
+        .collect()
+}
+
+pub fn stroke_polyline(
+    rasterizer: &mut Rasterizer,
+    points: &[[f32; 2]],
+    half_widths: &[f32],
+    closed: bool,
+) {
+    if points.is_empty() {
+        return;
+    }
+
+    let segment_count = if closed && points.len() > 2 {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+
+    for index in 0..segment_count {
+        let next = (index + 1) % points.len();
+        let start = points[index];
+        let end = points[next];
+        let dx = end[0] - start[0];
+        let dy = end[1] - start[1];
+        let length = (dx * dx + dy * dy).sqrt();
+        if length < 0.001 {
+            continue;
+        }
+        let normal = [-dy / length, dx / length];
+        let start_half = half_widths[index];
+        let end_half = half_widths[next];
+        rasterizer.fill_polygon(&oriented(vec![
+            [
+                start[0] + normal[0] * start_half,
+                start[1] + normal[1] * start_half,
+            ],
+            [end[0] + normal[0] * end_half, end[1] + normal[1] * end_half],
+            [end[0] - normal[0] * end_half, end[1] - normal[1] * end_half],
+            [
+                start[0] - normal[0] * start_half,
+                start[1] - normal[1] * start_half,
+            ],
+        ]));
+    }
+
+    for (point, half_width) in points.iter().zip(half_widths) {
+        rasterizer.fill_polygon(&ellipse_outline(*point, *half_width, *half_width, 0.0));
+    }
+}
+
+pub fn polyline_bounds(points: &[[f32; 2]], half_widths: &[f32]) -> [f32; 4] {
+    let max_half_width = half_widths.iter().copied().fold(0.0, f32::max);
+    expand_bounds(polygon_bounds(points), max_half_width)
+}
+
+fn oriented(mut polygon: Vec<[f32; 2]>) -> Vec<[f32; 2]> {
+    let mut area = 0.0;
+    for index in 0..polygon.len() {
+        let current = polygon[index];
+        let next = polygon[(index + 1) % polygon.len()];
+        area += current[0] * next[1] - next[0] * current[1];
+    }
+    if area < 0.0 {
+        polygon.reverse();
+    }
+    polygon
+}
@@ -1,6 +1,8 @@
 use nightshade::prelude::*;
 
 use crate::app::AnimateApp;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::project::Project;
 use crate::project::{Keyframe, Layer, Scene};
 
 pub fn draw_scene_tabs(app: &mut AnimateApp, ui: &mut egui::Ui) {
@@ -58,7 +60,7 @@ pub fn draw_scene_tabs(app: &mut AnimateApp, ui: &mut egui::Ui) {
     });
 }
 
-fn switch_scene(app: &mut AnimateApp, target_index: usize) {
+pub fn switch_scene(app: &mut AnimateApp, target_index: usize) {
     let current = app.project.active_scene;
 
     app.project.scenes[current].layers = app.project.layers.clone();
@@ -71,10 +73,11 @@ fn switch_scene(app: &mut AnimateApp, target_index: usize) {
     app.current_frame = 0;
     app.active_layer = 0;
     app.selection.selected_objects.clear();
+    app.selection.entered_group = None;
 }
 
 fn add_scene(app: &mut AnimateApp) {
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Add Scene");
 
     app.project.scenes[app.project.active_scene].layers = app.project.layers.clone();
     app.project.scenes[app.project.active_scene].total_frames = app.project.total_frames;
@@ -100,6 +103,7 @@ fn add_scene(app: &mut AnimateApp) {
     app.current_frame = 0;
     app.active_layer = 0;
     app.selection.selected_objects.clear();
+    app.selection.entered_group = None;
 }
 
 fn delete_current_scene(app: &mut AnimateApp) {
@@ -107,7 +111,7 @@ fn delete_current_scene(app: &mut AnimateApp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Delete Scene");
 
     app.project.scenes.remove(app.project.active_scene);
 
@@ -124,10 +128,11 @@ fn delete_current_scene(app: &mut AnimateApp) {
     app.current_frame = 0;
     app.active_layer = 0;
     app.selection.selected_objects.clear();
+    app.selection.entered_group = None;
 }
 
 fn duplicate_current_scene(app: &mut AnimateApp) {
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Duplicate Scene");
 
     app.project.scenes[app.project.active_scene].layers = app.project.layers.clone();
     app.project.scenes[app.project.active_scene].total_frames = app.project.total_frames;
@@ -147,4 +152,63 @@ fn duplicate_current_scene(app: &mut AnimateApp) {
     app.current_frame = 0;
     app.active_layer = 0;
     app.selection.selected_objects.clear();
+    app.selection.entered_group = None;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn scene_count(project: &Project) -> usize {
+    project.scenes.len().max(1)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn scene_layers(project: &Project, scene_index: usize) -> (&[Layer], u32) {
+    if scene_index == project.active_scene || scene_index >= project.scenes.len() {
+        return (&project.layers, project.total_frames);
+    }
+    let scene = &project.scenes[scene_index];
+    (&scene.layers, scene.total_frames)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn scene_start_frame(project: &Project, scene_index: usize) -> u32 {
+    (0..scene_index)
+        .map(|index| scene_layers(project, index).1)
+        .sum()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn sequence_total_frames(project: &Project) -> u32 {
+    (0..scene_count(project))
+        .map(|index| scene_layers(project, index).1)
+        .sum()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn locate_sequence_frame(project: &Project, frame: u32) -> (usize, u32) {
+    let mut start = 0;
+    for scene_index in 0..scene_count(project) {
+        let total_frames = scene_layers(project, scene_index).1;
+        if frame < start + total_frames {
+            return (scene_index, frame - start);
+        }
+        start += total_frames;
+    }
+    let last = scene_count(project) - 1;
+    (last, scene_layers(project, last).1.saturating_sub(1))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn find_scene(project: &Project, name_or_number: &str) -> Option<usize> {
+    if project.scenes.is_empty() {
+        return None;
+    }
+    if let Ok(number) = name_or_number.parse::<usize>() {
+        return (1..=project.scenes.len())
+            .contains(&number)
+            .then_some(number - 1);
+    }
+    project
+        .scenes
+        .iter()
+        .position(|scene| scene.name.eq_ignore_ascii_case(name_or_number))
 }
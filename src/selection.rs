@@ -1,7 +1,9 @@
 use nightshade::prelude::*;
 
 use crate::app::AnimateApp;
-use crate::canvas::CanvasView;
+use crate::canvas::{CanvasView, NULL_SIZE};
+use crate::group;
+use crate::hierarchy;
 use crate::node_edit::NodeEditState;
 use crate::project::{AnimObject, Shape};
 use crate::snapping;
@@ -20,6 +22,7 @@ pub struct Selection {
     pub guide_dragging: Option<usize>,
     pub snap_line_x: Option<f32>,
     pub snap_line_y: Option<f32>,
+    pub entered_group: Option<uuid::Uuid>,
 }
 
 pub fn handle_select_tool(
@@ -48,6 +51,17 @@ pub fn handle_select_tool(
         }
     }
 
+    if response.double_clicked_by(egui::PointerButton::Primary)
+        && let Some(pos) = response.interact_pointer_pos()
+    {
+        let canvas_pos = app.canvas_view.screen_to_canvas(pos);
+        match hit_test(app, canvas_pos) {
+            Some(object_id) if is_group_at(app, object_id) => group::enter_group(app, object_id),
+            Some(_) => {}
+            None => group::exit_group(app),
+        }
+    }
+
     if response.drag_started_by(egui::PointerButton::Primary)
         && let Some(pos) = response.interact_pointer_pos()
     {
@@ -60,7 +74,7 @@ pub fn handle_select_tool(
             app.selection.drag_offset = Some(egui::Vec2::ZERO);
             app.selection.marquee_start = None;
 
-            app.history.push(app.project.clone());
+            app.history.push(app.project.clone(), "Move");
         } else {
             app.selection.marquee_start = Some(canvas_pos);
             app.selection.marquee_current = Some(canvas_pos);
@@ -115,7 +129,13 @@ pub fn handle_select_tool(
                     if !layer.visible || layer.locked {
                         continue;
                     }
-                    if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
+                    if let Some(objects) = tween::resolve_posed_frame(
+                        layer,
+                        app.current_frame,
+                        &app.project.layers,
+                        &app.project.armatures,
+                    ) {
+                        let objects = group::editable_objects(objects, app.selection.entered_group);
                         for object in &objects {
                             let (half_w, half_h, center_offset) = get_object_bounds(object);
                             let obj_min_x = object.position[0] + center_offset[0] - half_w;
@@ -165,7 +185,13 @@ fn hit_test(app: &AnimateApp, canvas_pos: egui::Pos2) -> Option<uuid::Uuid> {
             continue;
         }
 
-        if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
+        if let Some(objects) = tween::resolve_posed_frame(
+            layer,
+            app.current_frame,
+            &app.project.layers,
+            &app.project.armatures,
+        ) {
+            let objects = group::editable_objects(objects, app.selection.entered_group);
             for object in objects.iter().rev() {
                 if point_in_object(canvas_pos, object) {
                     return Some(object.id);
@@ -176,6 +202,16 @@ fn hit_test(app: &AnimateApp, canvas_pos: egui::Pos2) -> Option<uuid::Uuid> {
     None
 }
 
+fn is_group_at(app: &AnimateApp, object_id: uuid::Uuid) -> bool {
+    app.project.layers.iter().any(|layer| {
+        tween::resolve_frame(layer, app.current_frame).is_some_and(|objects| {
+            group::container_objects(objects, app.selection.entered_group)
+                .iter()
+                .any(|object| object.id == object_id && group::is_group(object))
+        })
+    })
+}
+
 fn point_in_object(point: egui::Pos2, object: &AnimObject) -> bool {
     let local_x = point.x - object.position[0];
     let local_y = point.y - object.position[1];
@@ -263,6 +299,13 @@ fn point_in_object(point: egui::Pos2, object: &AnimObject) -> bool {
             unrotated_x.abs() <= half_w && unrotated_y.abs() <= half_h
         }
         Shape::SymbolInstance { .. } => unrotated_x.abs() <= 20.0 && unrotated_y.abs() <= 20.0,
+        Shape::Null => unrotated_x.abs() <= NULL_SIZE && unrotated_y.abs() <= NULL_SIZE,
+        Shape::Group { children } => children.iter().any(|child| {
+            point_in_object(
+                point,
+                &crate::library::compose_instance_transform(object, child),
+            )
+        }),
     }
 }
 
@@ -272,21 +315,58 @@ fn move_selected_objects(app: &mut AnimateApp, delta: egui::Vec2) {
     }
 
     let selected = app.selection.selected_objects.clone();
+    let entered = app.selection.entered_group;
+    let mut offsets = Vec::new();
+    for layer in &app.project.layers {
+        let Some(objects) = tween::resolve_frame(layer, app.current_frame) else {
+            continue;
+        };
+        for object in group::container_objects(objects, entered)
+            .iter()
+            .filter(|object| selected.contains(&object.id))
+        {
+            if hierarchy::has_selected_ancestor(
+                object.id,
+                &selected,
+                &app.project.layers,
+                app.current_frame,
+            ) {
+                continue;
+            }
+            let offset = match group::editing_parent(
+                layer,
+                object,
+                &app.project.layers,
+                app.current_frame,
+                entered,
+            ) {
+                Some(parent) => hierarchy::to_local_offset(&parent, [delta.x, delta.y]),
+                None => [delta.x, delta.y],
+            };
+            offsets.push((object.id, offset));
+        }
+    }
 
     for layer in &mut app.project.layers {
         let has_selected = tween::resolve_frame(layer, app.current_frame)
-            .map(|objects| objects.iter().any(|object| selected.contains(&object.id)))
+            .map(|objects| {
+                group::container_objects(objects, entered)
+                    .iter()
+                    .any(|object| selected.contains(&object.id))
+            })
             .unwrap_or(false);
 
         if has_selected {
             tween::ensure_keyframe_at(layer, app.current_frame);
         }
 
-        if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame) {
-            for object in &mut keyframe.objects {
-                if selected.contains(&object.id) {
-                    object.position[0] += delta.x;
-                    object.position[1] += delta.y;
+        if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame)
+            && let Some(container) = group::container_mut(&mut keyframe.objects, entered)
+        {
+            for object in container {
+                if let Some((_, offset)) = offsets.iter().find(|(id, _)| *id == object.id) {
+                    object.position[0] += offset[0];
+                    object.position[1] += offset[1];
                 }
             }
         }
@@ -330,8 +410,13 @@ pub fn draw_selection_indicators(app: &AnimateApp, view: &CanvasView, painter: &
         if !layer.visible {
             continue;
         }
-        if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
-            for object in &objects {
+        if let Some(objects) = tween::resolve_posed_frame(
+            layer,
+            app.current_frame,
+            &app.project.layers,
+            &app.project.armatures,
+        ) {
+            for object in &group::editable_objects(objects, app.selection.entered_group) {
                 if app.selection.selected_objects.contains(&object.id) {
                     draw_bounding_box(object, view, painter);
                 }
@@ -454,5 +539,43 @@ fn get_object_bounds(object: &AnimObject) -> (f32, f32, [f32; 2]) {
             [0.0, 0.0],
         ),
         Shape::SymbolInstance { .. } => (20.0, 20.0, [0.0, 0.0]),
+        Shape::Null => (NULL_SIZE, NULL_SIZE, [0.0, 0.0]),
+        Shape::Group { children } => {
+            let Some((min, max)) = objects_extent(children) else {
+                return (10.0, 10.0, [0.0, 0.0]);
+            };
+            let half_w = (max[0] - min[0]) / 2.0 * object.scale[0].abs();
+            let half_h = (max[1] - min[1]) / 2.0 * object.scale[1].abs();
+            (
+                half_w.max(5.0),
+                half_h.max(5.0),
+                [
+                    (min[0] + max[0]) / 2.0 * object.scale[0],
+                    (min[1] + max[1]) / 2.0 * object.scale[1],
+                ],
+            )
+        }
+    }
+}
+
+pub fn objects_extent(objects: &[AnimObject]) -> Option<([f32; 2], [f32; 2])> {
+    let mut min = [f32::INFINITY; 2];
+    let mut max = [f32::NEG_INFINITY; 2];
+    for object in objects {
+        let (half_w, half_h, center_offset) = get_object_bounds(object);
+        let cos_r = object.rotation.cos();
+        let sin_r = object.rotation.sin();
+        for [corner_x, corner_y] in [
+            [center_offset[0] - half_w, center_offset[1] - half_h],
+            [center_offset[0] + half_w, center_offset[1] - half_h],
+            [center_offset[0] + half_w, center_offset[1] + half_h],
+            [center_offset[0] - half_w, center_offset[1] + half_h],
+        ] {
+            let x = object.position[0] + corner_x * cos_r - corner_y * sin_r;
+            let y = object.position[1] + corner_x * sin_r + corner_y * cos_r;
+            min = [min[0].min(x), min[1].min(y)];
+            max = [max[0].max(x), max[1].max(y)];
+        }
     }
+    (min[0] <= max[0]).then_some((min, max))
 }
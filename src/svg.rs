@@ -1,58 +1,383 @@
+use std::collections::BTreeMap;
+
+use nightshade::prelude::*;
+
+use crate::camera;
+use crate::export::RenderSettings;
+use crate::library;
 use crate::paint::{GradientStop, Paint};
-use crate::project::{AnimObject, FontFamily, Keyframe, PathPoint, Project, Shape, TweenType};
+use crate::project::{
+    AnimObject, BlendMode, FontFamily, ImageAsset, Keyframe, Layer, LayerType, PathPoint, Project,
+    PropertyKey, Shape, Symbol, SymbolPlayMode, TweenType,
+};
+use crate::raster;
 use crate::tween;
 
-pub fn export_svg(project: &Project, frame: u32, path: &std::path::Path) {
-    let svg_content = render_frame_to_svg(project, frame);
-    let _ = std::fs::write(path, svg_content);
+pub fn export_svg(
+    project: &Project,
+    frame: u32,
+    path: &std::path::Path,
+    settings: &RenderSettings,
+) -> Result<(), String> {
+    project.load_assets()?;
+    let svg_content = render_frame_to_svg(project, frame, settings);
+    std::fs::write(path, svg_content)
+        .map_err(|error| format!("Failed to write {}: {}", path.display(), error))
+}
+
+pub fn export_animated_svg(
+    project: &Project,
+    path: &std::path::Path,
+    settings: &RenderSettings,
+) -> Result<(), String> {
+    project.load_assets()?;
+    let svg_content = render_animated_svg(project, settings);
+    std::fs::write(path, svg_content)
+        .map_err(|error| format!("Failed to write {}: {}", path.display(), error))
 }
 
-fn render_frame_to_svg(project: &Project, frame: u32) -> String {
-    let mut defs = String::new();
+struct SvgDefs<'a> {
+    content: String,
+    gradient_counter: usize,
+    symbol_ids: Vec<String>,
+    image_assets: &'a [ImageAsset],
+    image_ids: Vec<String>,
+}
+
+impl<'a> SvgDefs<'a> {
+    fn new(image_assets: &'a [ImageAsset]) -> Self {
+        Self {
+            content: String::new(),
+            gradient_counter: 0,
+            symbol_ids: Vec::new(),
+            image_assets,
+            image_ids: Vec::new(),
+        }
+    }
+}
+
+fn render_frame_to_svg(project: &Project, frame: u32, settings: &RenderSettings) -> String {
+    let (layers, frame) = settings.resolve_frame(project, frame);
+    let camera = camera::resolve_camera(project, frame);
+    let mask_bounds = canvas_bounds_in_scene(project, &camera);
+    let mut defs = SvgDefs::new(&project.image_assets);
     let mut body = String::new();
-    let mut gradient_counter = 0_usize;
 
-    for layer_index in (0..project.layers.len()).rev() {
-        let layer = &project.layers[layer_index];
-        if !layer.visible {
+    for (layer_index, layer) in layers.iter().enumerate().rev() {
+        if !is_rendered_layer(layer) {
             continue;
         }
 
-        if let Some(objects) = tween::resolve_frame(layer, frame) {
-            let opacity = layer.opacity;
-            for object in &objects {
-                gradient_counter += 1;
-                let element = object_to_svg_element(object, opacity, gradient_counter, &mut defs);
-                body.push_str(&element);
-            }
-        }
+        let content = layer_objects_svg(project, layers, layer, frame, &mut defs);
+        let mask_id = mask_layer_above(layers, layer_index).map(|mask_layer| {
+            let mask_content = layer_objects_svg(project, layers, mask_layer, frame, &mut defs);
+            push_mask_definition(&mut defs, mask_layer, mask_bounds, &mask_content)
+        });
+        body.push_str(&layer_group(layer, mask_id.as_deref(), &content));
     }
 
+    let body = if project.camera_keyframes.is_empty() {
+        body
+    } else {
+        format!(
+            "<g transform=\"{}\">\n{}</g>\n",
+            camera_transform(project, &camera),
+            body
+        )
+    };
+    svg_document(project, settings, &defs, &body)
+}
+
+fn svg_document(
+    project: &Project,
+    settings: &RenderSettings,
+    defs: &SvgDefs,
+    body: &str,
+) -> String {
+    let background = if project.background_color[3] > 0.001 {
+        format!(
+            "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            project.canvas_width,
+            project.canvas_height,
+            rgba_to_svg_color(&project.background_color)
+        )
+    } else {
+        String::new()
+    };
+
     format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
 <svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">
 <defs>
 {}
 </defs>
-{}
+{}{}
 </svg>"#,
+        project.canvas_width as f32 * settings.scale,
+        project.canvas_height as f32 * settings.scale,
         project.canvas_width,
         project.canvas_height,
-        project.canvas_width,
-        project.canvas_height,
-        defs,
+        defs.content,
+        background,
         body
     )
 }
 
-fn object_to_svg_element(
-    object: &AnimObject,
+fn is_rendered_layer(layer: &Layer) -> bool {
+    layer.visible
+        && !matches!(
+            layer.layer_type,
+            LayerType::Guide | LayerType::Folder | LayerType::Mask
+        )
+}
+
+fn mask_layer_above(layers: &[Layer], layer_index: usize) -> Option<&Layer> {
+    let above = layers.get(layer_index.checked_sub(1)?)?;
+    (above.layer_type == LayerType::Mask && above.visible).then_some(above)
+}
+
+fn layer_objects_svg(
+    project: &Project,
+    layers: &[Layer],
+    layer: &Layer,
+    frame: u32,
+    defs: &mut SvgDefs,
+) -> String {
+    match tween::resolve_posed_frame(layer, frame, layers, &project.armatures) {
+        Some(objects) => objects_to_svg(
+            &project.library.symbols,
+            layer,
+            frame,
+            &objects,
+            layer.opacity,
+            0,
+            defs,
+        ),
+        None => String::new(),
+    }
+}
+
+fn layer_group(layer: &Layer, mask_id: Option<&str>, content: &str) -> String {
+    let blend = match blend_mode_name(layer.blend_mode) {
+        Some(mode) => format!(r#" style="mix-blend-mode:{}""#, mode),
+        None => String::new(),
+    };
+    let mask = match mask_id {
+        Some(mask_id) => format!(r#" mask="url(#{})""#, mask_id),
+        None => String::new(),
+    };
+    format!(
+        "<g id=\"layer_{}\"{}{}>\n{}</g>\n",
+        layer.id.simple(),
+        blend,
+        mask,
+        content
+    )
+}
+
+fn push_mask_definition(
+    defs: &mut SvgDefs,
+    mask_layer: &Layer,
+    bounds: [f32; 4],
+    content: &str,
+) -> String {
+    let mask_id = format!("mask_{}", mask_layer.id.simple());
+    defs.content.push_str(&format!(
+        "<mask id=\"{}\" maskUnits=\"userSpaceOnUse\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" style=\"mask-type:alpha\">\n{}</mask>\n",
+        mask_id,
+        bounds[0],
+        bounds[1],
+        bounds[2] - bounds[0],
+        bounds[3] - bounds[1],
+        content
+    ));
+    mask_id
+}
+
+fn blend_mode_name(blend_mode: BlendMode) -> Option<&'static str> {
+    match blend_mode {
+        BlendMode::Normal => None,
+        BlendMode::Multiply => Some("multiply"),
+        BlendMode::Screen => Some("screen"),
+        BlendMode::Overlay => Some("overlay"),
+        BlendMode::Darken => Some("darken"),
+        BlendMode::Lighten => Some("lighten"),
+        BlendMode::ColorDodge => Some("color-dodge"),
+        BlendMode::ColorBurn => Some("color-burn"),
+        BlendMode::Difference => Some("difference"),
+        BlendMode::Exclusion => Some("exclusion"),
+    }
+}
+
+fn camera_transform(project: &Project, camera: &camera::ResolvedCamera) -> String {
+    let center_x = project.canvas_width as f32 / 2.0;
+    let center_y = project.canvas_height as f32 / 2.0;
+    format!(
+        "translate({} {}) scale({}) rotate({}) translate({} {})",
+        center_x,
+        center_y,
+        camera.zoom,
+        camera.rotation.to_degrees(),
+        -camera.position[0] - center_x,
+        -camera.position[1] - center_y
+    )
+}
+
+fn canvas_bounds_in_scene(project: &Project, camera: &camera::ResolvedCamera) -> [f32; 4] {
+    let width = project.canvas_width as f32;
+    let height = project.canvas_height as f32;
+    if project.camera_keyframes.is_empty() {
+        return [0.0, 0.0, width, height];
+    }
+
+    let zoom = if camera.zoom.abs() > 0.0001 {
+        camera.zoom
+    } else {
+        0.0001
+    };
+    let (sin_r, cos_r) = (-camera.rotation).sin_cos();
+    let corners: Vec<[f32; 2]> = [[0.0, 0.0], [width, 0.0], [width, height], [0.0, height]]
+        .iter()
+        .map(|corner| {
+            let relative_x = (corner[0] - width / 2.0) / zoom;
+            let relative_y = (corner[1] - height / 2.0) / zoom;
+            [
+                relative_x * cos_r - relative_y * sin_r + width / 2.0 + camera.position[0],
+                relative_x * sin_r + relative_y * cos_r + height / 2.0 + camera.position[1],
+            ]
+        })
+        .collect();
+    raster::polygon_bounds(&corners)
+}
+
+fn objects_to_svg(
+    symbols: &[Symbol],
+    layer: &Layer,
+    frame: u32,
+    objects: &[AnimObject],
+    opacity: f32,
+    depth: usize,
+    defs: &mut SvgDefs,
+) -> String {
+    let mut elements = String::new();
+    for object in objects {
+        match &object.shape {
+            Shape::SymbolInstance { .. } => {
+                elements.push_str(&symbol_use_element(
+                    symbols, layer, frame, object, opacity, depth, defs,
+                ));
+            }
+            Shape::Group { children } => {
+                let group_opacity = if (opacity - 1.0).abs() > 0.001 {
+                    format!(r#" opacity="{}""#, opacity)
+                } else {
+                    String::new()
+                };
+                let body = objects_to_svg(symbols, layer, frame, children, 1.0, depth, defs);
+                elements.push_str(&format!(
+                    "<g{}{}>\n{}</g>\n",
+                    group_opacity,
+                    build_transform(object),
+                    body
+                ));
+            }
+            _ => elements.push_str(&object_to_svg_element(object, opacity, defs)),
+        }
+    }
+    elements
+}
+
+fn symbol_use_element(
+    symbols: &[Symbol],
+    layer: &Layer,
+    frame: u32,
+    instance: &AnimObject,
     layer_opacity: f32,
-    gradient_id: usize,
-    defs: &mut String,
+    depth: usize,
+    defs: &mut SvgDefs,
 ) -> String {
-    let fill_attr = paint_to_svg_attr(&object.fill, &format!("fill_{}", gradient_id), defs);
-    let stroke_attr = paint_to_svg_attr(&object.stroke, &format!("stroke_{}", gradient_id), defs);
+    if depth >= library::MAX_SYMBOL_DEPTH {
+        return String::new();
+    }
+    let Shape::SymbolInstance { symbol_id, .. } = &instance.shape else {
+        return String::new();
+    };
+    let Some(symbol) = library::find_symbol(symbols, *symbol_id) else {
+        return String::new();
+    };
+
+    let symbol_frame = library::resolve_instance_frame(layer, frame, instance, symbol);
+    let symbol_def_id = symbol_definition(symbols, symbol, symbol_frame, depth, defs);
+
+    let opacity = if (layer_opacity - 1.0).abs() > 0.001 {
+        format!(r#" opacity="{}""#, layer_opacity)
+    } else {
+        String::new()
+    };
+
+    format!(
+        r##"<use href="#{}"{}{}/>"##,
+        symbol_def_id,
+        opacity,
+        build_transform(instance),
+    ) + "\n"
+}
+
+fn symbol_definition(
+    symbols: &[Symbol],
+    symbol: &Symbol,
+    symbol_frame: u32,
+    depth: usize,
+    defs: &mut SvgDefs,
+) -> String {
+    let symbol_def_id = format!("symbol_{}_{}", symbol.id.simple(), symbol_frame);
+    if defs.symbol_ids.contains(&symbol_def_id) {
+        return symbol_def_id;
+    }
+
+    defs.symbol_ids.push(symbol_def_id.clone());
+    let mut symbol_body = String::new();
+    for symbol_layer in symbol.layers.iter().rev() {
+        if !symbol_layer.visible
+            || symbol_layer.layer_type == LayerType::Guide
+            || symbol_layer.layer_type == LayerType::Folder
+        {
+            continue;
+        }
+        if let Some(symbol_objects) =
+            tween::resolve_world_frame(symbol_layer, symbol_frame, &symbol.layers)
+        {
+            symbol_body.push_str(&objects_to_svg(
+                symbols,
+                symbol_layer,
+                symbol_frame,
+                &symbol_objects,
+                symbol_layer.opacity,
+                depth + 1,
+                defs,
+            ));
+        }
+    }
+    defs.content.push_str(&format!(
+        "<symbol id=\"{}\" overflow=\"visible\">\n{}</symbol>\n",
+        symbol_def_id, symbol_body
+    ));
+    symbol_def_id
+}
+
+fn object_to_svg_element(object: &AnimObject, layer_opacity: f32, defs: &mut SvgDefs) -> String {
+    defs.gradient_counter += 1;
+    let gradient_id = defs.gradient_counter;
+    let fill_attr = paint_to_svg_attr(
+        &object.fill,
+        &format!("fill_{}", gradient_id),
+        &mut defs.content,
+    );
+    let stroke_attr = paint_to_svg_attr(
+        &object.stroke,
+        &format!("stroke_{}", gradient_id),
+        &mut defs.content,
+    );
     let stroke_width = object.stroke_width;
     let opacity = if (layer_opacity - 1.0).abs() > 0.001 {
         format!(r#" opacity="{}""#, layer_opacity)
@@ -122,11 +447,7 @@ fn object_to_svg_element(
             font_size,
             font_family,
         } => {
-            let family = match font_family {
-                FontFamily::SansSerif => "sans-serif",
-                FontFamily::Serif => "serif",
-                FontFamily::Monospace => "monospace",
-            };
+            let family = font_family_name(*font_family);
             format!(
                 r#"<text x="0" y="0" font-size="{}" font-family="{}" fill="{}" stroke="{}" stroke-width="{}"{}{} dominant-baseline="hanging">{}</text>"#,
                 font_size,
@@ -139,8 +460,55 @@ fn object_to_svg_element(
                 escape_xml(content),
             ) + "\n"
         }
-        Shape::RasterImage { .. } | Shape::SymbolInstance { .. } => String::new(),
+        Shape::RasterImage {
+            image_id,
+            display_width,
+            display_height,
+            ..
+        } => match image_use_element(defs, *image_id, *display_width, *display_height) {
+            Some(image_use) => {
+                format!("<g{}{}>\n{}</g>\n", opacity, transform, image_use)
+            }
+            None => String::new(),
+        },
+        Shape::SymbolInstance { .. } | Shape::Null | Shape::Group { .. } => String::new(),
+    }
+}
+
+fn image_use_element(
+    defs: &mut SvgDefs,
+    image_id: uuid::Uuid,
+    display_width: f32,
+    display_height: f32,
+) -> Option<String> {
+    use base64::Engine;
+
+    let image_asset = defs
+        .image_assets
+        .iter()
+        .find(|asset| asset.id == image_id)?;
+    let image_def_id = format!("image_{}", image_asset.id.simple());
+    if !defs.image_ids.contains(&image_def_id) {
+        defs.image_ids.push(image_def_id.clone());
+        let bytes = image_asset.data.bytes().ok()?;
+        let mime_type = image::guess_format(bytes)
+            .map(|format| format.to_mime_type())
+            .unwrap_or("image/png");
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+        defs.content.push_str(&format!(
+            "<image id=\"{}\" width=\"{}\" height=\"{}\" preserveAspectRatio=\"none\" href=\"data:{};base64,{}\"/>\n",
+            image_def_id, image_asset.width, image_asset.height, mime_type, encoded
+        ));
     }
+
+    Some(format!(
+        "<use href=\"#{}\" transform=\"translate({} {}) scale({} {})\"/>\n",
+        image_def_id,
+        -display_width / 2.0,
+        -display_height / 2.0,
+        display_width / image_asset.width.max(1) as f32,
+        display_height / image_asset.height.max(1) as f32
+    ))
 }
 
 fn build_transform(object: &AnimObject) -> String {
@@ -275,6 +643,1149 @@ fn path_points_to_svg_d(points: &[PathPoint], closed: bool) -> String {
     d
 }
 
+const TEXT_ASCENT: f32 = 0.8;
+const SPLINE_LINEAR: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+const SPLINE_EASE_IN: [f32; 4] = [1.0 / 3.0, 0.0, 2.0 / 3.0, 1.0 / 3.0];
+const SPLINE_EASE_OUT: [f32; 4] = [1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0, 1.0];
+
+struct TimelinePass {
+    frames: std::ops::Range<u32>,
+    begin: f32,
+    repeat: bool,
+}
+
+struct SvgTimeline {
+    start: u32,
+    frame_rate: f32,
+    passes: Vec<TimelinePass>,
+}
+
+#[derive(Clone, Copy)]
+enum KeySegment {
+    Hold,
+    Spline([f32; 4]),
+}
+
+struct ChannelKey {
+    frame: f32,
+    value: Vec<f32>,
+    segment: KeySegment,
+}
+
+struct LayerTimeline<'a> {
+    layer: &'a Layer,
+    mask_layer_id: Option<uuid::Uuid>,
+    frame_offset: i64,
+    samples: Vec<Option<Vec<AnimObject>>>,
+}
+
+#[derive(Default)]
+struct AnimatedElement {
+    attributes: String,
+    animations: String,
+}
+
+fn render_animated_svg(project: &Project, settings: &RenderSettings) -> String {
+    let timeline = SvgTimeline {
+        start: settings.start_frame,
+        frame_rate: project.frame_rate.max(1) as f32,
+        passes: timeline_passes(project, settings),
+    };
+    let mut defs = SvgDefs::new(&project.image_assets);
+    let layer_timelines = collect_layer_timelines(project, settings);
+    let cameras: Vec<camera::ResolvedCamera> = settings
+        .frames()
+        .map(|frame| camera::resolve_camera(project, settings.resolve_frame(project, frame).1))
+        .collect();
+    let mask_bounds = cameras
+        .iter()
+        .map(|camera| canvas_bounds_in_scene(project, camera))
+        .reduce(|union, bounds| {
+            [
+                union[0].min(bounds[0]),
+                union[1].min(bounds[1]),
+                union[2].max(bounds[2]),
+                union[3].max(bounds[3]),
+            ]
+        })
+        .unwrap_or([0.0; 4]);
+
+    let mut body = String::new();
+    for layer_timeline in &layer_timelines {
+        if layer_timeline.layer.layer_type == LayerType::Mask {
+            continue;
+        }
+        let content = animated_layer_objects(project, &timeline, layer_timeline, &mut defs);
+        let mask_id = layer_timeline
+            .mask_layer_id
+            .and_then(|mask_layer_id| {
+                layer_timelines
+                    .iter()
+                    .find(|mask_timeline| mask_timeline.layer.id == mask_layer_id)
+            })
+            .map(|mask_timeline| {
+                let mask_content =
+                    animated_layer_objects(project, &timeline, mask_timeline, &mut defs);
+                push_mask_definition(&mut defs, mask_timeline.layer, mask_bounds, &mask_content)
+            });
+        body.push_str(&layer_group(
+            layer_timeline.layer,
+            mask_id.as_deref(),
+            &content,
+        ));
+    }
+
+    let body = if project.camera_keyframes.is_empty() {
+        body
+    } else {
+        animated_camera_group(project, settings, &timeline, &cameras, body)
+    };
+    svg_document(project, settings, &defs, &body)
+}
+
+fn animated_layer_objects(
+    project: &Project,
+    timeline: &SvgTimeline,
+    layer_timeline: &LayerTimeline,
+    defs: &mut SvgDefs,
+) -> String {
+    animated_objects(
+        project,
+        timeline,
+        layer_timeline,
+        &layer_timeline.samples,
+        layer_timeline.layer.opacity,
+        defs,
+    )
+}
+
+fn animated_objects(
+    project: &Project,
+    timeline: &SvgTimeline,
+    layer_timeline: &LayerTimeline,
+    samples: &[Option<Vec<AnimObject>>],
+    opacity: f32,
+    defs: &mut SvgDefs,
+) -> String {
+    let mut content = String::new();
+    for object_id in layer_object_order(samples) {
+        let frames: Vec<Option<&AnimObject>> = samples
+            .iter()
+            .map(|sample| {
+                sample
+                    .as_ref()
+                    .and_then(|objects| objects.iter().find(|object| object.id == object_id))
+            })
+            .collect();
+        for run in structure_runs(&frames) {
+            content.push_str(&animated_object_svg(
+                project,
+                timeline,
+                layer_timeline,
+                &run,
+                opacity,
+                defs,
+            ));
+        }
+    }
+    content
+}
+
+fn animated_camera_group(
+    project: &Project,
+    settings: &RenderSettings,
+    timeline: &SvgTimeline,
+    cameras: &[camera::ResolvedCamera],
+    body: String,
+) -> String {
+    let center_x = project.canvas_width as f32 / 2.0;
+    let center_y = project.canvas_height as f32 / 2.0;
+    let breakpoints: Vec<(u32, TweenType)> = settings
+        .frames()
+        .filter(|frame| {
+            let local_frame = settings.resolve_frame(project, *frame).1;
+            project.camera_keyframes.contains_key(&local_frame)
+        })
+        .map(|frame| (frame, project.camera_tween))
+        .collect();
+
+    let offsets: Vec<Vec<f32>> = cameras
+        .iter()
+        .map(|camera| {
+            vec![
+                -camera.position[0] - center_x,
+                -camera.position[1] - center_y,
+            ]
+        })
+        .collect();
+    let body = animated_transform_group(
+        timeline,
+        "translate",
+        &offsets,
+        &breakpoints,
+        &[0.0, 0.0],
+        body,
+    );
+    let mut rotation: Vec<Vec<f32>> = cameras.iter().map(|camera| vec![camera.rotation]).collect();
+    unwrap_angles(&mut rotation);
+    let body = animated_transform_group(timeline, "rotate", &rotation, &breakpoints, &[0.0], body);
+    let zoom: Vec<Vec<f32>> = cameras.iter().map(|camera| vec![camera.zoom]).collect();
+    let body = animated_transform_group(timeline, "scale", &zoom, &breakpoints, &[1.0], body);
+    format!(
+        "<g transform=\"translate({} {})\">\n{}</g>\n",
+        center_x, center_y, body
+    )
+}
+
+fn timeline_passes(project: &Project, settings: &RenderSettings) -> Vec<TimelinePass> {
+    let frames = settings.frames();
+    let loops_active_scene = match settings.scene {
+        Some(scene_index) => scene_index == project.active_scene,
+        None => project.scenes.len() <= 1,
+    };
+
+    if loops_active_scene
+        && let (Some(loop_start), Some(loop_end)) = (project.loop_start, project.loop_end)
+        && loop_start >= frames.start
+        && loop_start <= loop_end
+        && loop_end < frames.end
+    {
+        let mut passes = Vec::new();
+        if loop_start > frames.start {
+            passes.push(TimelinePass {
+                frames: frames.start..loop_start,
+                begin: 0.0,
+                repeat: false,
+            });
+        }
+        passes.push(TimelinePass {
+            frames: loop_start..loop_end + 1,
+            begin: (loop_start - frames.start) as f32 / project.frame_rate.max(1) as f32,
+            repeat: true,
+        });
+        return passes;
+    }
+
+    vec![TimelinePass {
+        frames,
+        begin: 0.0,
+        repeat: true,
+    }]
+}
+
+fn collect_layer_timelines<'a>(
+    project: &'a Project,
+    settings: &RenderSettings,
+) -> Vec<LayerTimeline<'a>> {
+    let frame_count = settings.frames().len();
+    let mut timelines: Vec<LayerTimeline<'a>> = Vec::new();
+
+    for frame in settings.frames() {
+        let (layers, local_frame) = settings.resolve_frame(project, frame);
+        for (layer_index, layer) in layers.iter().enumerate().rev() {
+            let is_mask = layer.layer_type == LayerType::Mask && layer.visible;
+            if !is_rendered_layer(layer) && !is_mask {
+                continue;
+            }
+            let index = match timelines
+                .iter()
+                .position(|timeline| timeline.layer.id == layer.id)
+            {
+                Some(index) => index,
+                None => {
+                    timelines.push(LayerTimeline {
+                        layer,
+                        mask_layer_id: mask_layer_above(layers, layer_index)
+                            .map(|mask_layer| mask_layer.id),
+                        frame_offset: frame as i64 - local_frame as i64,
+                        samples: vec![None; frame_count],
+                    });
+                    timelines.len() - 1
+                }
+            };
+            timelines[index].samples[(frame - settings.start_frame) as usize] =
+                tween::resolve_posed_frame(layer, local_frame, layers, &project.armatures);
+        }
+    }
+
+    timelines
+}
+
+fn layer_object_order(samples: &[Option<Vec<AnimObject>>]) -> Vec<uuid::Uuid> {
+    let mut order: Vec<uuid::Uuid> = Vec::new();
+    for objects in samples.iter().flatten() {
+        for (index, object) in objects.iter().enumerate() {
+            if order.contains(&object.id) {
+                continue;
+            }
+            let insert_at = objects[index + 1..]
+                .iter()
+                .find_map(|above| order.iter().position(|id| *id == above.id))
+                .unwrap_or(order.len());
+            order.insert(insert_at, object.id);
+        }
+    }
+    order
+}
+
+fn structure_runs<'a>(frames: &[Option<&'a AnimObject>]) -> Vec<Vec<Option<&'a AnimObject>>> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut runs: Vec<Vec<Option<&'a AnimObject>>> = Vec::new();
+    for (index, object) in frames.iter().enumerate() {
+        let Some(object) = object else {
+            continue;
+        };
+        let key = shape_structure_key(&object.shape);
+        let run_index = match keys.iter().position(|existing| *existing == key) {
+            Some(run_index) => run_index,
+            None => {
+                keys.push(key);
+                runs.push(vec![None; frames.len()]);
+                runs.len() - 1
+            }
+        };
+        runs[run_index][index] = Some(*object);
+    }
+    runs
+}
+
+fn shape_structure_key(shape: &Shape) -> String {
+    match shape {
+        Shape::Rectangle { .. } => "rect".to_string(),
+        Shape::Ellipse { .. } => "ellipse".to_string(),
+        Shape::Line { .. } => "line".to_string(),
+        Shape::Path { points, closed } => {
+            let layout: String = points
+                .iter()
+                .map(
+                    |point| match (point.control_in.is_some(), point.control_out.is_some()) {
+                        (false, false) => 'p',
+                        (true, false) => 'i',
+                        (false, true) => 'o',
+                        (true, true) => 'b',
+                    },
+                )
+                .collect();
+            format!("path:{}:{}", closed, layout)
+        }
+        Shape::Text {
+            content,
+            font_family,
+            ..
+        } => format!("text:{:?}:{}", font_family, content),
+        Shape::RasterImage { image_id, .. } => format!("image:{}", image_id),
+        Shape::SymbolInstance { symbol_id, .. } => format!("symbol:{}", symbol_id),
+        Shape::Null => "null".to_string(),
+        Shape::Group { .. } => "group".to_string(),
+    }
+}
+
+fn animated_object_svg(
+    project: &Project,
+    timeline: &SvgTimeline,
+    layer_timeline: &LayerTimeline,
+    frames: &[Option<&AnimObject>],
+    opacity: f32,
+    defs: &mut SvgDefs,
+) -> String {
+    let Some(first) = frames.iter().flatten().next().copied() else {
+        return String::new();
+    };
+    let mut current = first;
+    let filled: Vec<&AnimObject> = frames
+        .iter()
+        .map(|object| {
+            if let Some(object) = object {
+                current = object;
+            }
+            current
+        })
+        .collect();
+
+    let layer = layer_timeline.layer;
+    let tracks = layer.property_tracks.get(&first.id);
+    let keyframe_keys = layer_timeline.export_keys(
+        layer
+            .keyframes
+            .iter()
+            .map(|(frame, keyframe)| (*frame, keyframe.tween)),
+    );
+    let track_keys = |track: Option<Vec<(u32, TweenType)>>| match track {
+        Some(keys) if !keys.is_empty() => layer_timeline.export_keys(keys.into_iter()),
+        _ => keyframe_keys.clone(),
+    };
+
+    let content = match &first.shape {
+        Shape::SymbolInstance { symbol_id, .. } => {
+            let Some(symbol) = library::find_symbol(&project.library.symbols, *symbol_id) else {
+                return String::new();
+            };
+            animated_symbol_uses(project, timeline, layer_timeline, &filled, symbol, defs)
+        }
+        Shape::RasterImage {
+            image_id,
+            display_width,
+            display_height,
+            ..
+        } => match image_use_element(defs, *image_id, *display_width, *display_height) {
+            Some(image_use) => image_use,
+            None => return String::new(),
+        },
+        Shape::Group { .. } => {
+            let samples: Vec<Option<Vec<AnimObject>>> = filled
+                .iter()
+                .map(|group| match &group.shape {
+                    Shape::Group { children } => Some(children.clone()),
+                    _ => None,
+                })
+                .collect();
+            animated_objects(project, timeline, layer_timeline, &samples, 1.0, defs)
+        }
+        _ => {
+            let mut element = AnimatedElement::default();
+            if matches!(
+                first.shape,
+                Shape::Line { .. } | Shape::Path { closed: false, .. }
+            ) {
+                element.attribute("fill", "none");
+            } else {
+                element.paint(
+                    timeline,
+                    "fill",
+                    &filled,
+                    |object| &object.fill,
+                    &track_keys(tracks.map(|tracks| track_breakpoints(&tracks.fill))),
+                    defs,
+                );
+            }
+            element.paint(
+                timeline,
+                "stroke",
+                &filled,
+                |object| &object.stroke,
+                &track_keys(tracks.map(|tracks| track_breakpoints(&tracks.stroke))),
+                defs,
+            );
+            element.numeric(
+                timeline,
+                "stroke-width",
+                &channel(&filled, |object| vec![object.stroke_width]),
+                &track_keys(tracks.map(|tracks| track_breakpoints(&tracks.stroke_width))),
+                &format_values,
+            );
+            animated_shape_element(timeline, element, &filled, &keyframe_keys)
+        }
+    };
+
+    let content = animated_transform_group(
+        timeline,
+        "scale",
+        &channel(&filled, |object| object.scale.to_vec()),
+        &track_keys(tracks.map(|tracks| track_breakpoints(&tracks.scale))),
+        &[1.0, 1.0],
+        content,
+    );
+    let mut rotation = channel(&filled, |object| vec![object.rotation]);
+    unwrap_angles(&mut rotation);
+    let content = animated_transform_group(
+        timeline,
+        "rotate",
+        &rotation,
+        &track_keys(tracks.map(|tracks| track_breakpoints(&tracks.rotation))),
+        &[0.0],
+        content,
+    );
+    let content = animated_transform_group(
+        timeline,
+        "translate",
+        &channel(&filled, |object| object.position.to_vec()),
+        &track_keys(tracks.map(|tracks| track_breakpoints(&tracks.position))),
+        &[0.0, 0.0],
+        content,
+    );
+
+    let display: Vec<String> = frames
+        .iter()
+        .map(|object| if object.is_some() { "inline" } else { "none" }.to_string())
+        .collect();
+    let (initial, animation) = timeline.discrete("display", &display);
+    let opacity = if (opacity - 1.0).abs() > 0.001 {
+        format!(r#" opacity="{}""#, opacity)
+    } else {
+        String::new()
+    };
+    match animation {
+        Some(animation) => format!(
+            "<g display=\"{}\"{}>\n{}{}</g>\n",
+            initial, opacity, animation, content
+        ),
+        None if initial == "none" => String::new(),
+        None if opacity.is_empty() => content,
+        None => format!("<g{}>\n{}</g>\n", opacity, content),
+    }
+}
+
+fn animated_shape_element(
+    timeline: &SvgTimeline,
+    mut element: AnimatedElement,
+    filled: &[&AnimObject],
+    keyframe_keys: &[(u32, TweenType)],
+) -> String {
+    match &filled[0].shape {
+        Shape::Rectangle { corner_radius, .. } => {
+            let size = |object: &AnimObject| match object.shape {
+                Shape::Rectangle {
+                    width,
+                    height,
+                    corner_radius,
+                } => [width, height, corner_radius],
+                _ => [0.0; 3],
+            };
+            element.numeric(
+                timeline,
+                "x",
+                &channel(filled, |object| vec![-size(object)[0] / 2.0]),
+                keyframe_keys,
+                &format_values,
+            );
+            element.numeric(
+                timeline,
+                "y",
+                &channel(filled, |object| vec![-size(object)[1] / 2.0]),
+                keyframe_keys,
+                &format_values,
+            );
+            element.numeric(
+                timeline,
+                "width",
+                &channel(filled, |object| vec![size(object)[0]]),
+                keyframe_keys,
+                &format_values,
+            );
+            element.numeric(
+                timeline,
+                "height",
+                &channel(filled, |object| vec![size(object)[1]]),
+                keyframe_keys,
+                &format_values,
+            );
+            let radii = channel(filled, |object| vec![size(object)[2]]);
+            if *corner_radius > 0.0 || radii.iter().any(|radius| radius[0] > 0.0) {
+                element.numeric(timeline, "rx", &radii, keyframe_keys, &format_values);
+            }
+            element.finish("rect", "")
+        }
+        Shape::Ellipse { .. } => {
+            let radii = |object: &AnimObject| match object.shape {
+                Shape::Ellipse { radius_x, radius_y } => [radius_x, radius_y],
+                _ => [0.0; 2],
+            };
+            element.attribute("cx", "0");
+            element.attribute("cy", "0");
+            element.numeric(
+                timeline,
+                "rx",
+                &channel(filled, |object| vec![radii(object)[0]]),
+                keyframe_keys,
+                &format_values,
+            );
+            element.numeric(
+                timeline,
+                "ry",
+                &channel(filled, |object| vec![radii(object)[1]]),
+                keyframe_keys,
+                &format_values,
+            );
+            element.finish("ellipse", "")
+        }
+        Shape::Line { .. } => {
+            let end = |object: &AnimObject| match object.shape {
+                Shape::Line { end_x, end_y } => [end_x, end_y],
+                _ => [0.0; 2],
+            };
+            element.attribute("x1", "0");
+            element.attribute("y1", "0");
+            element.numeric(
+                timeline,
+                "x2",
+                &channel(filled, |object| vec![end(object)[0]]),
+                keyframe_keys,
+                &format_values,
+            );
+            element.numeric(
+                timeline,
+                "y2",
+                &channel(filled, |object| vec![end(object)[1]]),
+                keyframe_keys,
+                &format_values,
+            );
+            element.finish("line", "")
+        }
+        Shape::Path { points, closed } => {
+            let template = points.clone();
+            let closed = *closed;
+            element.numeric(
+                timeline,
+                "d",
+                &channel(filled, path_values),
+                keyframe_keys,
+                &|values| path_from_values(&template, closed, values),
+            );
+            element.finish("path", "")
+        }
+        Shape::Text {
+            content,
+            font_family,
+            ..
+        } => {
+            element.attribute("x", "0");
+            element.attribute("y", "0");
+            element.numeric(
+                timeline,
+                "font-size",
+                &channel(filled, |object| match object.shape {
+                    Shape::Text { font_size, .. } => vec![font_size],
+                    _ => vec![0.0],
+                }),
+                keyframe_keys,
+                &format_values,
+            );
+            element.attribute("font-family", font_family_name(*font_family));
+            element.attribute("dominant-baseline", "hanging");
+            element.finish("text", &escape_xml(content))
+        }
+        Shape::RasterImage { .. }
+        | Shape::SymbolInstance { .. }
+        | Shape::Null
+        | Shape::Group { .. } => String::new(),
+    }
+}
+
+fn animated_symbol_uses(
+    project: &Project,
+    timeline: &SvgTimeline,
+    layer_timeline: &LayerTimeline,
+    filled: &[&AnimObject],
+    symbol: &Symbol,
+    defs: &mut SvgDefs,
+) -> String {
+    let symbol_frames: Vec<u32> = filled
+        .iter()
+        .enumerate()
+        .map(|(index, instance)| {
+            let frame = timeline.start as i64 + index as i64 - layer_timeline.frame_offset;
+            library::resolve_instance_frame(
+                layer_timeline.layer,
+                frame.max(0) as u32,
+                instance,
+                symbol,
+            )
+        })
+        .collect();
+
+    let mut distinct_frames: Vec<u32> = Vec::new();
+    for symbol_frame in &symbol_frames {
+        if !distinct_frames.contains(symbol_frame) {
+            distinct_frames.push(*symbol_frame);
+        }
+    }
+
+    let mut uses = String::new();
+    for symbol_frame in distinct_frames {
+        let symbol_def_id =
+            symbol_definition(&project.library.symbols, symbol, symbol_frame, 0, defs);
+        let display: Vec<String> = symbol_frames
+            .iter()
+            .map(|frame| {
+                if *frame == symbol_frame {
+                    "inline"
+                } else {
+                    "none"
+                }
+                .to_string()
+            })
+            .collect();
+        let (initial, animation) = timeline.discrete("display", &display);
+        match animation {
+            Some(animation) => uses.push_str(&format!(
+                "<use href=\"#{}\" display=\"{}\">{}</use>\n",
+                symbol_def_id, initial, animation
+            )),
+            None if initial == "none" => {}
+            None => uses.push_str(&format!("<use href=\"#{}\"/>\n", symbol_def_id)),
+        }
+    }
+    uses
+}
+
+fn animated_transform_group(
+    timeline: &SvgTimeline,
+    kind: &str,
+    values: &[Vec<f32>],
+    breakpoints: &[(u32, TweenType)],
+    identity: &[f32],
+    content: String,
+) -> String {
+    let (initial, animation) = timeline.numeric(
+        &format!(
+            r#"animateTransform attributeName="transform" type="{}""#,
+            kind
+        ),
+        values,
+        breakpoints,
+        &format_values,
+    );
+    match animation {
+        Some(animation) => format!(
+            "<g transform=\"{}({})\">\n{}{}</g>\n",
+            kind, initial, animation, content
+        ),
+        None if values_match(&values[timeline.first_frame_index()], identity) => content,
+        None => format!("<g transform=\"{}({})\">\n{}</g>\n", kind, initial, content),
+    }
+}
+
+impl AnimatedElement {
+    fn attribute(&mut self, name: &str, value: &str) {
+        self.attributes
+            .push_str(&format!(r#" {}="{}""#, name, escape_xml(value)));
+    }
+
+    fn numeric(
+        &mut self,
+        timeline: &SvgTimeline,
+        name: &str,
+        values: &[Vec<f32>],
+        breakpoints: &[(u32, TweenType)],
+        format: &dyn Fn(&[f32]) -> String,
+    ) {
+        let (initial, animation) = timeline.numeric(
+            &format!(r#"animate attributeName="{}""#, name),
+            values,
+            breakpoints,
+            format,
+        );
+        self.attribute(name, &initial);
+        if let Some(animation) = animation {
+            self.animations.push_str(&animation);
+        }
+    }
+
+    fn paint(
+        &mut self,
+        timeline: &SvgTimeline,
+        name: &str,
+        filled: &[&AnimObject],
+        select: fn(&AnimObject) -> &Paint,
+        breakpoints: &[(u32, TweenType)],
+        defs: &mut SvgDefs,
+    ) {
+        let first = select(filled[0]);
+        if filled
+            .iter()
+            .any(|object| !matches!(select(object), Paint::Solid(_)))
+        {
+            defs.gradient_counter += 1;
+            let value = paint_to_svg_attr(
+                first,
+                &format!("{}_{}", name, defs.gradient_counter),
+                &mut defs.content,
+            );
+            self.attribute(name, &value);
+            return;
+        }
+
+        let colors = channel(filled, |object| match select(object) {
+            Paint::Solid(color) => color.to_vec(),
+            _ => vec![0.0; 4],
+        });
+        let rgb: Vec<Vec<f32>> = colors.iter().map(|color| color[..3].to_vec()).collect();
+        let alpha: Vec<Vec<f32>> = colors.iter().map(|color| vec![color[3]]).collect();
+        if is_constant(timeline, &rgb) && is_constant(timeline, &alpha) {
+            let color = &colors[timeline.first_frame_index()];
+            self.attribute(
+                name,
+                &rgba_to_svg_color(&[color[0], color[1], color[2], color[3]]),
+            );
+            return;
+        }
+        self.numeric(timeline, name, &rgb, breakpoints, &|values| {
+            rgba_to_svg_color_no_alpha(&[values[0], values[1], values[2], 1.0])
+        });
+        self.numeric(
+            timeline,
+            &format!("{}-opacity", name),
+            &alpha,
+            breakpoints,
+            &format_values,
+        );
+    }
+
+    fn finish(self, tag: &str, content: &str) -> String {
+        if self.animations.is_empty() && content.is_empty() {
+            format!("<{}{}/>\n", tag, self.attributes)
+        } else {
+            format!(
+                "<{}{}>\n{}{}</{}>\n",
+                tag, self.attributes, self.animations, content, tag
+            )
+        }
+    }
+}
+
+impl LayerTimeline<'_> {
+    fn export_keys(&self, keys: impl Iterator<Item = (u32, TweenType)>) -> Vec<(u32, TweenType)> {
+        keys.filter_map(|(frame, tween)| {
+            u32::try_from(frame as i64 + self.frame_offset)
+                .ok()
+                .map(|frame| (frame, tween))
+        })
+        .collect()
+    }
+}
+
+impl SvgTimeline {
+    fn first_frame_index(&self) -> usize {
+        (self.passes[0].frames.start - self.start) as usize
+    }
+
+    fn pass_timing(&self, pass: &TimelinePass) -> String {
+        let duration = pass.frames.len() as f32 / self.frame_rate;
+        let ending = if pass.repeat {
+            r#"repeatCount="indefinite""#
+        } else {
+            r#"fill="freeze""#
+        };
+        format!(
+            r#"begin="{}s" dur="{}s" {}"#,
+            format_number(pass.begin),
+            format_number(duration),
+            ending
+        )
+    }
+
+    fn key_time(&self, pass: &TimelinePass, frame: f32) -> f32 {
+        (frame - pass.frames.start as f32) / pass.frames.len() as f32
+    }
+
+    fn numeric(
+        &self,
+        head: &str,
+        values: &[Vec<f32>],
+        breakpoints: &[(u32, TweenType)],
+        format: &dyn Fn(&[f32]) -> String,
+    ) -> (String, Option<String>) {
+        let initial = format(&values[self.first_frame_index()]);
+        if is_constant(self, values) {
+            return (initial, None);
+        }
+
+        let mut animations = String::new();
+        for pass in &self.passes {
+            let keys = fit_channel_keys(values, self.start, pass.frames.clone(), breakpoints);
+            let mut key_times = Vec::new();
+            let mut key_values = Vec::new();
+            let mut key_splines = Vec::new();
+            for (index, key) in keys.iter().enumerate() {
+                if index > 0 {
+                    key_splines.push(match keys[index - 1].segment {
+                        KeySegment::Hold => SPLINE_LINEAR,
+                        KeySegment::Spline(spline) => spline,
+                    });
+                }
+                key_times.push(self.key_time(pass, key.frame));
+                key_values.push(format(&key.value));
+                if let KeySegment::Hold = key.segment {
+                    let next_time = keys
+                        .get(index + 1)
+                        .map_or(1.0, |next| self.key_time(pass, next.frame));
+                    key_splines.push(SPLINE_LINEAR);
+                    key_times.push(next_time);
+                    key_values.push(format(&key.value));
+                }
+            }
+            animations.push_str(&format!(
+                "<{} {} calcMode=\"spline\" keyTimes=\"{}\" keySplines=\"{}\" values=\"{}\"/>\n",
+                head,
+                self.pass_timing(pass),
+                key_times
+                    .iter()
+                    .map(|time| format_number(*time))
+                    .collect::<Vec<_>>()
+                    .join(";"),
+                key_splines
+                    .iter()
+                    .map(|spline| format_values(spline))
+                    .collect::<Vec<_>>()
+                    .join(";"),
+                key_values.join(";"),
+            ));
+        }
+        (initial, Some(animations))
+    }
+
+    fn discrete(&self, name: &str, values: &[String]) -> (String, Option<String>) {
+        let initial = values[self.first_frame_index()].clone();
+        if self.passes.iter().all(|pass| {
+            pass.frames
+                .clone()
+                .all(|frame| values[(frame - self.start) as usize] == initial)
+        }) {
+            return (initial, None);
+        }
+
+        let mut animations = String::new();
+        for pass in &self.passes {
+            let mut key_times = Vec::new();
+            let mut key_values: Vec<&str> = Vec::new();
+            for frame in pass.frames.clone() {
+                let value = &values[(frame - self.start) as usize];
+                if key_values.last() != Some(&value.as_str()) {
+                    key_times.push(format_number(self.key_time(pass, frame as f32)));
+                    key_values.push(value);
+                }
+            }
+            animations.push_str(&format!(
+                "<animate attributeName=\"{}\" {} calcMode=\"discrete\" keyTimes=\"{}\" values=\"{}\"/>\n",
+                name,
+                self.pass_timing(pass),
+                key_times.join(";"),
+                key_values.join(";"),
+            ));
+        }
+        (initial, Some(animations))
+    }
+}
+
+fn fit_channel_keys(
+    values: &[Vec<f32>],
+    start: u32,
+    frames: std::ops::Range<u32>,
+    breakpoints: &[(u32, TweenType)],
+) -> Vec<ChannelKey> {
+    let value = |frame: u32| &values[(frame - start) as usize];
+    let key = |frame: u32, segment: KeySegment| ChannelKey {
+        frame: frame as f32,
+        value: value(frame).clone(),
+        segment,
+    };
+    let first = frames.start;
+    let last = frames.end - 1;
+
+    let mut bounds = vec![first];
+    bounds.extend(
+        breakpoints
+            .iter()
+            .map(|(frame, _)| *frame)
+            .filter(|frame| *frame > first && *frame < last),
+    );
+    if last > first {
+        bounds.push(last);
+    }
+
+    let mut keys: Vec<ChannelKey> = Vec::new();
+    for bound in bounds.windows(2) {
+        let (from, to) = (bound[0], bound[1]);
+        if (from..=to).all(|frame| values_match(value(frame), value(from))) {
+            keys.push(key(from, KeySegment::Spline(SPLINE_LINEAR)));
+            continue;
+        }
+
+        let governing = breakpoints
+            .iter()
+            .filter(|(frame, _)| *frame <= from)
+            .max_by_key(|(frame, _)| *frame);
+        let splines = governing
+            .filter(|(frame, _)| {
+                *frame == from && breakpoints.iter().any(|(frame, _)| *frame == to)
+            })
+            .filter(|(_, tween)| follows_easing(value, from, to, *tween))
+            .and_then(|(_, tween)| tween_splines(*tween));
+        let holds = (from..to).all(|frame| values_match(value(frame), value(from)));
+
+        if matches!(governing, Some((_, TweenType::None))) && holds {
+            keys.push(key(from, KeySegment::Hold));
+        } else if let Some(splines) = splines {
+            if let &[ease_in, ease_out] = splines.as_slice() {
+                let middle: Vec<f32> = value(from)
+                    .iter()
+                    .zip(value(to))
+                    .map(|(from, to)| (from + to) / 2.0)
+                    .collect();
+                keys.push(key(from, KeySegment::Spline(ease_in)));
+                keys.push(ChannelKey {
+                    frame: (from + to) as f32 / 2.0,
+                    value: middle,
+                    segment: KeySegment::Spline(ease_out),
+                });
+            } else {
+                keys.push(key(from, KeySegment::Spline(splines[0])));
+            }
+        } else {
+            keys.extend((from..to).map(|frame| key(frame, KeySegment::Spline(SPLINE_LINEAR))));
+        }
+    }
+    keys.push(key(last, KeySegment::Hold));
+
+    let mut index = 1;
+    while index + 1 < keys.len() {
+        if values_match(&keys[index].value, &keys[index - 1].value)
+            && values_match(&keys[index].value, &keys[index + 1].value)
+        {
+            keys.remove(index);
+        } else {
+            index += 1;
+        }
+    }
+    keys
+}
+
+fn follows_easing<'a>(
+    value: impl Fn(u32) -> &'a Vec<f32>,
+    from: u32,
+    to: u32,
+    tween: TweenType,
+) -> bool {
+    (from..=to).all(|frame| {
+        let t = tween::apply_easing((frame - from) as f32 / (to - from) as f32, tween);
+        let expected: Vec<f32> = value(from)
+            .iter()
+            .zip(value(to))
+            .map(|(from, to)| from + (to - from) * t)
+            .collect();
+        values_match(value(frame), &expected)
+    })
+}
+
+fn tween_splines(tween: TweenType) -> Option<Vec<[f32; 4]>> {
+    match tween {
+        TweenType::None => None,
+        TweenType::Linear => Some(vec![SPLINE_LINEAR]),
+        TweenType::EaseIn => Some(vec![SPLINE_EASE_IN]),
+        TweenType::EaseOut => Some(vec![SPLINE_EASE_OUT]),
+        TweenType::EaseInOut => Some(vec![SPLINE_EASE_IN, SPLINE_EASE_OUT]),
+        TweenType::CubicBezier { x1, y1, x2, y2 } => {
+            let spline = [x1, y1, x2, y2];
+            spline
+                .iter()
+                .all(|value| (0.0..=1.0).contains(value))
+                .then(|| vec![spline])
+        }
+    }
+}
+
+fn track_breakpoints<T>(track: &BTreeMap<u32, PropertyKey<T>>) -> Vec<(u32, TweenType)> {
+    track
+        .iter()
+        .map(|(frame, key)| (*frame, key.tween))
+        .collect()
+}
+
+fn channel(filled: &[&AnimObject], extract: impl Fn(&AnimObject) -> Vec<f32>) -> Vec<Vec<f32>> {
+    filled.iter().map(|object| extract(object)).collect()
+}
+
+fn unwrap_angles(values: &mut [Vec<f32>]) {
+    for index in 1..values.len() {
+        let previous = values[index - 1][0];
+        let mut angle = values[index][0];
+        while angle - previous > std::f32::consts::PI {
+            angle -= std::f32::consts::TAU;
+        }
+        while angle - previous < -std::f32::consts::PI {
+            angle += std::f32::consts::TAU;
+        }
+        values[index][0] = angle;
+    }
+    for value in values.iter_mut() {
+        value[0] = value[0].to_degrees();
+    }
+}
+
+fn is_constant(timeline: &SvgTimeline, values: &[Vec<f32>]) -> bool {
+    let initial = &values[timeline.first_frame_index()];
+    timeline.passes.iter().all(|pass| {
+        pass.frames
+            .clone()
+            .all(|frame| values_match(&values[(frame - timeline.start) as usize], initial))
+    })
+}
+
+fn values_match(a: &[f32], b: &[f32]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| (a - b).abs() <= 1e-3 * (1.0 + a.abs().max(b.abs())))
+}
+
+fn path_values(object: &AnimObject) -> Vec<f32> {
+    let Shape::Path { points, .. } = &object.shape else {
+        return Vec::new();
+    };
+    let mut values = Vec::new();
+    for point in points {
+        values.extend(point.position);
+        if let Some(control_in) = point.control_in {
+            values.extend(control_in);
+        }
+        if let Some(control_out) = point.control_out {
+            values.extend(control_out);
+        }
+    }
+    values
+}
+
+fn path_from_values(template: &[PathPoint], closed: bool, values: &[f32]) -> String {
+    let mut remaining = values.iter().copied();
+    let mut next_pair = || {
+        [
+            remaining.next().unwrap_or(0.0),
+            remaining.next().unwrap_or(0.0),
+        ]
+    };
+    let points: Vec<PathPoint> = template
+        .iter()
+        .map(|point| {
+            let position = next_pair();
+            let control_in = point.control_in.map(|_| next_pair());
+            let control_out = point.control_out.map(|_| next_pair());
+            PathPoint {
+                position,
+                control_in,
+                control_out,
+                pressure: point.pressure,
+                weights: Vec::new(),
+            }
+        })
+        .collect();
+    path_points_to_svg_d(&points, closed)
+}
+
+fn format_number(value: f32) -> String {
+    let rounded = (value * 10000.0).round() / 10000.0;
+    if rounded == 0.0 {
+        "0".to_string()
+    } else {
+        rounded.to_string()
+    }
+}
+
+fn format_values(values: &[f32]) -> String {
+    values
+        .iter()
+        .map(|value| format_number(*value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn font_family_name(font_family: FontFamily) -> &'static str {
+    match font_family {
+        FontFamily::SansSerif => "sans-serif",
+        FontFamily::Serif => "serif",
+        FontFamily::Monospace => "monospace",
+    }
+}
+
 fn escape_xml(text: &str) -> String {
     text.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -283,18 +1794,16 @@ fn escape_xml(text: &str) -> String {
 }
 
 pub fn import_svg(project: &mut Project, path: &std::path::Path, layer_index: usize, frame: u32) {
-    let Ok(svg_data) = std::fs::read(path) else {
+    let Ok(tree) = load_usvg_tree(path) else {
         return;
     };
 
-    let options = usvg::Options::default();
-    let Ok(tree) = usvg::Tree::from_data(&svg_data, &options) else {
-        return;
+    let mut importer = SvgImporter {
+        project,
+        group_prefix: String::new(),
+        group_counter: 0,
     };
-
-    let mut objects = Vec::new();
-
-    import_group(tree.root(), &mut objects, usvg::Transform::identity());
+    let objects = importer.convert_children(tree.root(), usvg::Transform::identity(), 1.0, false);
 
     if layer_index < project.layers.len() {
         let layer = &mut project.layers[layer_index];
@@ -315,57 +1824,385 @@ pub fn import_svg(project: &mut Project, path: &std::path::Path, layer_index: us
     }
 }
 
-fn import_group(
-    group: &usvg::Group,
-    objects: &mut Vec<AnimObject>,
-    parent_transform: usvg::Transform,
-) {
-    for child in group.children() {
+pub fn import_svg_layers(
+    project: &mut Project,
+    path: &std::path::Path,
+    frame: u32,
+) -> Result<usize, String> {
+    let tree = load_usvg_tree(path)?;
+    let file_stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "SVG".to_string());
+
+    let mut importer = SvgImporter {
+        project,
+        group_prefix: file_stem.clone(),
+        group_counter: 0,
+    };
+    let mut layers = Vec::new();
+    let mut loose_objects = Vec::new();
+
+    for child in tree.root().children() {
         match child {
-            usvg::Node::Group(subgroup) => {
-                let combined = combine_transforms(parent_transform, subgroup.transform());
-                import_group(subgroup, objects, combined);
-            }
-            usvg::Node::Path(usvg_path) => {
-                let combined = combine_transforms(parent_transform, usvg_path.abs_transform());
-                if let Some(object) = convert_usvg_path(usvg_path, combined) {
-                    objects.push(object);
+            usvg::Node::Group(group) if is_structural_group(group) => {
+                if !loose_objects.is_empty() {
+                    layers.push(imported_layer(
+                        format!("{} {}", file_stem, layers.len() + 1),
+                        std::mem::take(&mut loose_objects),
+                        frame,
+                    ));
                 }
+                let objects =
+                    importer.convert_children(group, usvg::Transform::identity(), 1.0, true);
+                let name = if group.id().is_empty() {
+                    format!("{} {}", file_stem, layers.len() + 1)
+                } else {
+                    group.id().to_string()
+                };
+                let mut layer = imported_layer(name, objects, frame);
+                layer.opacity = group.opacity().get();
+                layer.blend_mode = blend_mode_from_usvg(group.blend_mode());
+                layers.push(layer);
             }
-            usvg::Node::Text(text_node) => {
-                let combined = combine_transforms(parent_transform, text_node.abs_transform());
-                if let Some(first_chunk) = text_node.chunks().first() {
-                    let text_content = first_chunk.text().to_string();
-                    let mut font_size = 16.0_f32;
-                    let mut fill_color = Paint::Solid([0.0, 0.0, 0.0, 1.0]);
-                    if let Some(first_span) = first_chunk.spans().first() {
-                        font_size = first_span.font_size().get();
-                        fill_color = extract_usvg_fill(first_span.fill());
-                    }
-                    let obj = AnimObject::new(
-                        Shape::Text {
-                            content: text_content,
-                            font_size,
-                            font_family: FontFamily::SansSerif,
-                        },
-                        [combined.tx, combined.ty],
-                        fill_color,
-                        Paint::Solid([0.0, 0.0, 0.0, 0.0]),
-                        0.0,
-                    );
-                    objects.push(obj);
-                }
+            _ => loose_objects.extend(importer.convert_node(
+                child,
+                usvg::Transform::identity(),
+                1.0,
+                true,
+            )),
+        }
+    }
+    if !loose_objects.is_empty() {
+        layers.push(imported_layer(
+            format!("{} {}", file_stem, layers.len() + 1),
+            loose_objects,
+            frame,
+        ));
+    }
+
+    let layer_count = layers.len();
+    for layer in layers {
+        project.layers.insert(0, layer);
+    }
+    Ok(layer_count)
+}
+
+fn load_usvg_tree(path: &std::path::Path) -> Result<usvg::Tree, String> {
+    let svg_data = std::fs::read(path)
+        .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+    let mut options = usvg::Options {
+        resources_dir: path.parent().map(std::path::Path::to_path_buf),
+        ..usvg::Options::default()
+    };
+    options.fontdb_mut().load_system_fonts();
+    usvg::Tree::from_data(&svg_data, &options)
+        .map_err(|error| format!("Failed to parse {}: {}", path.display(), error))
+}
+
+fn imported_layer(name: String, objects: Vec<AnimObject>, frame: u32) -> Layer {
+    let mut layer = Layer::new(name);
+    layer.keyframes.insert(
+        frame,
+        Keyframe {
+            objects,
+            ..Keyframe::default()
+        },
+    );
+    layer
+}
+
+fn is_structural_group(group: &usvg::Group) -> bool {
+    !group.id().is_empty()
+        || group.children().len() != 1
+        || matches!(group.children()[0], usvg::Node::Group(_))
+}
+
+struct SvgImporter<'a> {
+    project: &'a mut Project,
+    group_prefix: String,
+    group_counter: usize,
+}
+
+impl SvgImporter<'_> {
+    fn convert_children(
+        &mut self,
+        group: &usvg::Group,
+        base: usvg::Transform,
+        opacity: f32,
+        nest_symbols: bool,
+    ) -> Vec<AnimObject> {
+        group
+            .children()
+            .iter()
+            .flat_map(|child| self.convert_node(child, base, opacity, nest_symbols))
+            .collect()
+    }
+
+    fn convert_node(
+        &mut self,
+        node: &usvg::Node,
+        base: usvg::Transform,
+        opacity: f32,
+        nest_symbols: bool,
+    ) -> Vec<AnimObject> {
+        match node {
+            usvg::Node::Group(group) if nest_symbols && is_structural_group(group) => {
+                vec![self.convert_symbol_group(group, base, opacity)]
+            }
+            usvg::Node::Group(group) => {
+                self.convert_children(group, base, opacity * group.opacity().get(), nest_symbols)
+            }
+            usvg::Node::Path(usvg_path) => convert_usvg_path(
+                usvg_path,
+                relative_transform(base, usvg_path.abs_transform()),
+                opacity,
+            )
+            .into_iter()
+            .collect(),
+            usvg::Node::Text(text_node) => convert_usvg_text(
+                text_node,
+                relative_transform(base, text_node.abs_transform()),
+                opacity,
+            ),
+            usvg::Node::Image(image_node) => self
+                .convert_usvg_image(
+                    image_node,
+                    relative_transform(base, image_node.abs_transform()),
+                )
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn convert_symbol_group(
+        &mut self,
+        group: &usvg::Group,
+        base: usvg::Transform,
+        opacity: f32,
+    ) -> AnimObject {
+        self.group_counter += 1;
+        let name = if group.id().is_empty() {
+            format!("{} Group {}", self.group_prefix, self.group_counter)
+        } else {
+            group.id().to_string()
+        };
+        let objects = self.convert_children(
+            group,
+            group.abs_transform(),
+            opacity * group.opacity().get(),
+            true,
+        );
+
+        let symbol_id = uuid::Uuid::new_v4();
+        let bounds = group.bounding_box();
+        self.project.library.symbols.push(Symbol {
+            id: symbol_id,
+            name: name.clone(),
+            layers: vec![imported_layer(name, objects, 0)],
+            total_frames: 1,
+            width: bounds.width(),
+            height: bounds.height(),
+        });
+
+        let (position, rotation, scale) =
+            decompose_transform(relative_transform(base, group.abs_transform()));
+        AnimObject {
+            id: uuid::Uuid::new_v4(),
+            shape: Shape::SymbolInstance {
+                symbol_id,
+                play_mode: SymbolPlayMode::default(),
+                first_frame: 0,
+            },
+            position,
+            rotation,
+            scale,
+            fill: Paint::Solid([0.0, 0.0, 0.0, 0.0]),
+            stroke: Paint::Solid([0.0, 0.0, 0.0, 0.0]),
+            stroke_width: 0.0,
+            parent_id: None,
+        }
+    }
+
+    fn convert_usvg_image(
+        &mut self,
+        image_node: &usvg::Image,
+        transform: usvg::Transform,
+    ) -> Option<AnimObject> {
+        let data = match image_node.kind() {
+            usvg::ImageKind::JPEG(data)
+            | usvg::ImageKind::PNG(data)
+            | usvg::ImageKind::GIF(data)
+            | usvg::ImageKind::WEBP(data) => data.as_slice(),
+            usvg::ImageKind::SVG(_) => return None,
+        };
+        if !image_node.is_visible() || image::load_from_memory(data).is_err() {
+            return None;
+        }
+
+        let width = image_node.size().width();
+        let height = image_node.size().height();
+        let image_id = match self
+            .project
+            .image_assets
+            .iter()
+            .find(|asset| asset.data.bytes() == Ok(data))
+        {
+            Some(asset) => asset.id,
+            None => {
+                let asset_id = uuid::Uuid::new_v4();
+                self.project.image_assets.push(ImageAsset {
+                    id: asset_id,
+                    name: if image_node.id().is_empty() {
+                        format!("{} image", self.group_prefix).trim().to_string()
+                    } else {
+                        image_node.id().to_string()
+                    },
+                    data: data.to_vec().into(),
+                    width: width.round() as u32,
+                    height: height.round() as u32,
+                });
+                asset_id
             }
-            usvg::Node::Image(_) => {}
+        };
+
+        let (_, rotation, scale) = decompose_transform(transform);
+        let center = map_usvg_point(transform, width / 2.0, height / 2.0);
+        Some(AnimObject {
+            id: uuid::Uuid::new_v4(),
+            shape: Shape::RasterImage {
+                image_id,
+                source_width: width.round() as u32,
+                source_height: height.round() as u32,
+                display_width: width,
+                display_height: height,
+            },
+            position: center,
+            rotation,
+            scale,
+            fill: Paint::Solid([1.0, 1.0, 1.0, 1.0]),
+            stroke: Paint::Solid([0.0, 0.0, 0.0, 0.0]),
+            stroke_width: 0.0,
+            parent_id: None,
+        })
+    }
+}
+
+fn relative_transform(base: usvg::Transform, absolute: usvg::Transform) -> usvg::Transform {
+    base.invert().unwrap_or_default().pre_concat(absolute)
+}
+
+fn map_usvg_point(transform: usvg::Transform, x: f32, y: f32) -> [f32; 2] {
+    let mut point = usvg::tiny_skia_path::Point::from_xy(x, y);
+    transform.map_point(&mut point);
+    [point.x, point.y]
+}
+
+fn decompose_transform(transform: usvg::Transform) -> ([f32; 2], f32, [f32; 2]) {
+    let scale_x = transform.sx.hypot(transform.ky);
+    let rotation = transform.ky.atan2(transform.sx);
+    let scale_y = if scale_x > f32::EPSILON {
+        (transform.sx * transform.sy - transform.kx * transform.ky) / scale_x
+    } else {
+        transform.kx.hypot(transform.sy)
+    };
+    ([transform.tx, transform.ty], rotation, [scale_x, scale_y])
+}
+
+fn blend_mode_from_usvg(blend_mode: usvg::BlendMode) -> BlendMode {
+    match blend_mode {
+        usvg::BlendMode::Multiply => BlendMode::Multiply,
+        usvg::BlendMode::Screen => BlendMode::Screen,
+        usvg::BlendMode::Overlay => BlendMode::Overlay,
+        usvg::BlendMode::Darken => BlendMode::Darken,
+        usvg::BlendMode::Lighten => BlendMode::Lighten,
+        usvg::BlendMode::ColorDodge => BlendMode::ColorDodge,
+        usvg::BlendMode::ColorBurn => BlendMode::ColorBurn,
+        usvg::BlendMode::Difference => BlendMode::Difference,
+        usvg::BlendMode::Exclusion => BlendMode::Exclusion,
+        _ => BlendMode::Normal,
+    }
+}
+
+fn convert_usvg_text(
+    text_node: &usvg::Text,
+    transform: usvg::Transform,
+    opacity: f32,
+) -> Vec<AnimObject> {
+    let (_, rotation, scale) = decompose_transform(transform);
+    let bounds = transformed_rect_bounds(text_node.bounding_box(), transform);
+    let mut objects: Vec<AnimObject> = Vec::new();
+    let mut line_y = None;
+
+    for chunk in text_node.chunks() {
+        let Some(span) = chunk.spans().first() else {
+            continue;
+        };
+        let starts_line = chunk.y().is_some() && chunk.y() != line_y;
+        if !starts_line
+            && let Some(Shape::Text { content, .. }) =
+                objects.last_mut().map(|object| &mut object.shape)
+        {
+            content.push_str(chunk.text());
+            continue;
         }
+        line_y = chunk.y().or(line_y);
+
+        let font_size = span.font_size().get();
+        let (stroke, stroke_width) = extract_usvg_stroke(span.stroke(), transform, bounds, opacity);
+        let top_left = map_usvg_point(
+            transform,
+            chunk.x().unwrap_or(0.0),
+            line_y.unwrap_or(0.0) - font_size * TEXT_ASCENT,
+        );
+        objects.push(AnimObject {
+            id: uuid::Uuid::new_v4(),
+            shape: Shape::Text {
+                content: chunk.text().to_string(),
+                font_size,
+                font_family: font_family_from_usvg(span.font().families()),
+            },
+            position: top_left,
+            rotation,
+            scale,
+            fill: extract_usvg_fill(span.fill(), transform, bounds, opacity),
+            stroke,
+            stroke_width,
+            parent_id: None,
+        });
     }
+    objects
 }
 
-fn combine_transforms(parent: usvg::Transform, child: usvg::Transform) -> usvg::Transform {
-    parent.pre_concat(child)
+fn font_family_from_usvg(families: &[usvg::FontFamily]) -> FontFamily {
+    for family in families {
+        match family {
+            usvg::FontFamily::Serif => return FontFamily::Serif,
+            usvg::FontFamily::SansSerif => return FontFamily::SansSerif,
+            usvg::FontFamily::Monospace => return FontFamily::Monospace,
+            usvg::FontFamily::Named(name) => {
+                let name = name.to_ascii_lowercase();
+                if name.contains("mono") || name.contains("courier") || name.contains("consol") {
+                    return FontFamily::Monospace;
+                }
+                if name.contains("sans") || name.contains("arial") || name.contains("helvetica") {
+                    return FontFamily::SansSerif;
+                }
+                if name.contains("serif") || name.contains("times") || name.contains("georgia") {
+                    return FontFamily::Serif;
+                }
+            }
+            usvg::FontFamily::Cursive | usvg::FontFamily::Fantasy => {}
+        }
+    }
+    FontFamily::SansSerif
 }
 
-fn convert_usvg_path(usvg_path: &usvg::Path, transform: usvg::Transform) -> Option<AnimObject> {
+fn convert_usvg_path(
+    usvg_path: &usvg::Path,
+    transform: usvg::Transform,
+    opacity: f32,
+) -> Option<AnimObject> {
     let mut path_points = Vec::new();
     let mut closed = false;
     let mut current_pos = [0.0_f32; 2];
@@ -379,6 +2216,7 @@ fn convert_usvg_path(usvg_path: &usvg::Path, transform: usvg::Transform) -> Opti
                     control_in: None,
                     control_out: None,
                     pressure: 1.0,
+                    weights: Vec::new(),
                 });
             }
             usvg::tiny_skia_path::PathSegment::LineTo(point) => {
@@ -388,6 +2226,7 @@ fn convert_usvg_path(usvg_path: &usvg::Path, transform: usvg::Transform) -> Opti
                     control_in: None,
                     control_out: None,
                     pressure: 1.0,
+                    weights: Vec::new(),
                 });
             }
             usvg::tiny_skia_path::PathSegment::CubicTo(point1, point2, point3) => {
@@ -400,6 +2239,7 @@ fn convert_usvg_path(usvg_path: &usvg::Path, transform: usvg::Transform) -> Opti
                     control_in: Some([point2.x, point2.y]),
                     control_out: None,
                     pressure: 1.0,
+                    weights: Vec::new(),
                 });
             }
             usvg::tiny_skia_path::PathSegment::QuadTo(control, end_point) => {
@@ -421,6 +2261,7 @@ fn convert_usvg_path(usvg_path: &usvg::Path, transform: usvg::Transform) -> Opti
                     control_in: Some(ctrl2),
                     control_out: None,
                     pressure: 1.0,
+                    weights: Vec::new(),
                 });
             }
             usvg::tiny_skia_path::PathSegment::Close => {
@@ -433,13 +2274,36 @@ fn convert_usvg_path(usvg_path: &usvg::Path, transform: usvg::Transform) -> Opti
         return None;
     }
 
-    let fill_color = extract_usvg_fill(usvg_path.fill());
-    let (stroke_color, stroke_width) = extract_usvg_stroke(usvg_path.stroke());
+    let linear = usvg::Transform::from_row(
+        transform.sx,
+        transform.ky,
+        transform.kx,
+        transform.sy,
+        0.0,
+        0.0,
+    );
+    let mut outline = Vec::new();
+    for point in &mut path_points {
+        point.position = map_usvg_point(linear, point.position[0], point.position[1]);
+        point.control_in = point
+            .control_in
+            .map(|control| map_usvg_point(linear, control[0], control[1]));
+        point.control_out = point
+            .control_out
+            .map(|control| map_usvg_point(linear, control[0], control[1]));
+        outline.push(point.position);
+        outline.extend(point.control_in);
+        outline.extend(point.control_out);
+    }
+    let outline: Vec<[f32; 2]> = outline
+        .into_iter()
+        .map(|point| [point[0] + transform.tx, point[1] + transform.ty])
+        .collect();
+    let bounds = raster::polygon_bounds(&outline);
 
-    let position = [transform.tx, transform.ty];
-    let scale_x = (transform.sx * transform.sx + transform.kx * transform.kx).sqrt();
-    let scale_y = (transform.sy * transform.sy + transform.ky * transform.ky).sqrt();
-    let rotation = transform.kx.atan2(transform.sx);
+    let fill = extract_usvg_fill(usvg_path.fill(), transform, bounds, opacity);
+    let (stroke, stroke_width) =
+        extract_usvg_stroke(usvg_path.stroke(), transform, bounds, opacity);
 
     Some(AnimObject {
         id: uuid::Uuid::new_v4(),
@@ -447,84 +2311,130 @@ fn convert_usvg_path(usvg_path: &usvg::Path, transform: usvg::Transform) -> Opti
             points: path_points,
             closed,
         },
-        position,
-        rotation,
-        scale: [scale_x, scale_y],
-        fill: fill_color,
-        stroke: stroke_color,
+        position: [transform.tx, transform.ty],
+        rotation: 0.0,
+        scale: [1.0, 1.0],
+        fill,
+        stroke,
         stroke_width,
+        parent_id: None,
     })
 }
 
-fn extract_usvg_fill(fill: Option<&usvg::Fill>) -> Paint {
+fn transformed_rect_bounds(rect: usvg::Rect, transform: usvg::Transform) -> [f32; 4] {
+    let corners: Vec<[f32; 2]> = [
+        (rect.left(), rect.top()),
+        (rect.right(), rect.top()),
+        (rect.right(), rect.bottom()),
+        (rect.left(), rect.bottom()),
+    ]
+    .iter()
+    .map(|(x, y)| map_usvg_point(transform, *x, *y))
+    .collect();
+    raster::polygon_bounds(&corners)
+}
+
+fn extract_usvg_fill(
+    fill: Option<&usvg::Fill>,
+    transform: usvg::Transform,
+    bounds: [f32; 4],
+    opacity: f32,
+) -> Paint {
     match fill {
-        Some(fill) => match fill.paint() {
-            usvg::Paint::Color(color) => Paint::Solid([
-                color.red as f32 / 255.0,
-                color.green as f32 / 255.0,
-                color.blue as f32 / 255.0,
-                fill.opacity().get(),
-            ]),
-            usvg::Paint::LinearGradient(gradient) => {
-                let stops: Vec<GradientStop> = gradient
-                    .stops()
-                    .iter()
-                    .map(|stop| GradientStop {
-                        offset: stop.offset().get(),
-                        color: [
-                            stop.color().red as f32 / 255.0,
-                            stop.color().green as f32 / 255.0,
-                            stop.color().blue as f32 / 255.0,
-                            stop.opacity().get(),
-                        ],
-                    })
-                    .collect();
-                Paint::LinearGradient {
-                    start: [gradient.x1(), gradient.y1()],
-                    end: [gradient.x2(), gradient.y2()],
-                    stops,
-                }
-            }
-            usvg::Paint::RadialGradient(gradient) => {
-                let stops: Vec<GradientStop> = gradient
-                    .stops()
-                    .iter()
-                    .map(|stop| GradientStop {
-                        offset: stop.offset().get(),
-                        color: [
-                            stop.color().red as f32 / 255.0,
-                            stop.color().green as f32 / 255.0,
-                            stop.color().blue as f32 / 255.0,
-                            stop.opacity().get(),
-                        ],
-                    })
-                    .collect();
-                Paint::RadialGradient {
-                    center: [gradient.cx(), gradient.cy()],
-                    radius: gradient.r().get(),
-                    stops,
-                }
-            }
-            usvg::Paint::Pattern(_) => Paint::Solid([0.5, 0.5, 0.5, 1.0]),
-        },
+        Some(fill) => convert_usvg_paint(
+            fill.paint(),
+            fill.opacity().get() * opacity,
+            transform,
+            bounds,
+        ),
         None => Paint::Solid([0.0, 0.0, 0.0, 0.0]),
     }
 }
 
-fn extract_usvg_stroke(stroke: Option<&usvg::Stroke>) -> (Paint, f32) {
+fn extract_usvg_stroke(
+    stroke: Option<&usvg::Stroke>,
+    transform: usvg::Transform,
+    bounds: [f32; 4],
+    opacity: f32,
+) -> (Paint, f32) {
     match stroke {
-        Some(stroke) => {
-            let paint = match stroke.paint() {
-                usvg::Paint::Color(color) => Paint::Solid([
-                    color.red as f32 / 255.0,
-                    color.green as f32 / 255.0,
-                    color.blue as f32 / 255.0,
-                    stroke.opacity().get(),
-                ]),
-                _ => Paint::Solid([0.0, 0.0, 0.0, 1.0]),
-            };
-            (paint, stroke.width().get())
-        }
+        Some(stroke) => (
+            convert_usvg_paint(
+                stroke.paint(),
+                stroke.opacity().get() * opacity,
+                transform,
+                bounds,
+            ),
+            stroke.width().get(),
+        ),
         None => (Paint::Solid([0.0, 0.0, 0.0, 0.0]), 0.0),
     }
 }
+
+fn convert_usvg_paint(
+    paint: &usvg::Paint,
+    opacity: f32,
+    transform: usvg::Transform,
+    bounds: [f32; 4],
+) -> Paint {
+    let normalize = |x: f32, y: f32, gradient_transform: usvg::Transform| {
+        let point = map_usvg_point(transform.pre_concat(gradient_transform), x, y);
+        let width = bounds[2] - bounds[0];
+        let height = bounds[3] - bounds[1];
+        [
+            if width > 0.001 {
+                (point[0] - bounds[0]) / width
+            } else {
+                0.5
+            },
+            if height > 0.001 {
+                (point[1] - bounds[1]) / height
+            } else {
+                0.5
+            },
+        ]
+    };
+
+    match paint {
+        usvg::Paint::Color(color) => Paint::Solid([
+            color.red as f32 / 255.0,
+            color.green as f32 / 255.0,
+            color.blue as f32 / 255.0,
+            opacity,
+        ]),
+        usvg::Paint::LinearGradient(gradient) => Paint::LinearGradient {
+            start: normalize(gradient.x1(), gradient.y1(), gradient.transform()),
+            end: normalize(gradient.x2(), gradient.y2(), gradient.transform()),
+            stops: convert_usvg_stops(gradient.stops(), opacity),
+        },
+        usvg::Paint::RadialGradient(gradient) => {
+            let center = normalize(gradient.cx(), gradient.cy(), gradient.transform());
+            let radius = gradient.r().get();
+            let edge_x = normalize(gradient.cx() + radius, gradient.cy(), gradient.transform());
+            let edge_y = normalize(gradient.cx(), gradient.cy() + radius, gradient.transform());
+            let radius_x = (edge_x[0] - center[0]).hypot(edge_x[1] - center[1]);
+            let radius_y = (edge_y[0] - center[0]).hypot(edge_y[1] - center[1]);
+            Paint::RadialGradient {
+                center,
+                radius: (radius_x + radius_y) / 2.0,
+                stops: convert_usvg_stops(gradient.stops(), opacity),
+            }
+        }
+        usvg::Paint::Pattern(_) => Paint::Solid([0.5, 0.5, 0.5, opacity]),
+    }
+}
+
+fn convert_usvg_stops(stops: &[usvg::Stop], opacity: f32) -> Vec<GradientStop> {
+    stops
+        .iter()
+        .map(|stop| GradientStop {
+            offset: stop.offset().get(),
+            color: [
+                stop.color().red as f32 / 255.0,
+                stop.color().green as f32 / 255.0,
+                stop.color().blue as f32 / 255.0,
+                stop.opacity().get() * opacity,
+            ],
+        })
+        .collect()
+}
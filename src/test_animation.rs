@@ -2,8 +2,8 @@ use std::collections::{BTreeMap, HashMap};
 
 use crate::paint::Paint;
 use crate::project::{
-    AnimObject, BlendMode, Keyframe, Layer, LayerType, Library, PathPoint, Project, Shape,
-    TweenType,
+    AnimObject, BlendMode, FORMAT_VERSION, Keyframe, Layer, LayerType, Library, PathPoint, Project,
+    Shape, TweenType,
 };
 
 pub fn generate_bouncing_ball() -> Project {
@@ -121,6 +121,7 @@ pub fn generate_bouncing_ball() -> Project {
             fill: ball_fill.clone(),
             stroke: ball_stroke.clone(),
             stroke_width: ball_stroke_width,
+            parent_id: None,
         };
 
         ball_keyframes.insert(
@@ -153,6 +154,7 @@ pub fn generate_bouncing_ball() -> Project {
             fill: Paint::Solid([0.0, 0.0, 0.0, shadow_opacity]),
             stroke: Paint::Solid([0.0, 0.0, 0.0, 0.0]),
             stroke_width: 0.0,
+            parent_id: None,
         };
 
         shadow_keyframes.insert(
@@ -179,6 +181,9 @@ pub fn generate_bouncing_ball() -> Project {
         parent_id: None,
         collapsed: false,
         property_tracks: HashMap::new(),
+        motion_guide: None,
+        expressions: HashMap::new(),
+        transform_parent_id: None,
     };
 
     let ball_layer = Layer {
@@ -193,9 +198,13 @@ pub fn generate_bouncing_ball() -> Project {
         parent_id: None,
         collapsed: false,
         property_tracks: HashMap::new(),
+        motion_guide: None,
+        expressions: HashMap::new(),
+        transform_parent_id: None,
     };
 
     Project {
+        format_version: FORMAT_VERSION,
         name: "Bouncing Ball".to_string(),
         canvas_width,
         canvas_height,
@@ -468,6 +477,7 @@ pub fn generate_showcase_animation() -> Project {
                     fill: Paint::Solid([0.0; 4]),
                     stroke: Paint::Solid(stroke),
                     stroke_width,
+                    parent_id: None,
                 }],
                 tween,
                 label: String::new(),
@@ -484,18 +494,21 @@ pub fn generate_showcase_animation() -> Project {
                 control_in: Some([-20.0, 5.0]),
                 control_out: Some([20.0, -5.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [200.0, -50.0],
                 control_in: Some([180.0, -30.0]),
                 control_out: Some([220.0, -70.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [200.0, 50.0],
                 control_in: Some([220.0, 70.0]),
                 control_out: Some([180.0, 30.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
         ],
         closed: true,
@@ -528,6 +541,7 @@ pub fn generate_showcase_animation() -> Project {
                         fill: Paint::Solid([0.65, 0.6, 0.55, 1.0]),
                         stroke: Paint::Solid([0.4, 0.35, 0.3, 0.8]),
                         stroke_width: 1.5,
+                        parent_id: None,
                     },
                     AnimObject {
                         id: lighthouse_tower_id,
@@ -542,6 +556,7 @@ pub fn generate_showcase_animation() -> Project {
                         fill: Paint::Solid([0.85, 0.82, 0.75, 1.0]),
                         stroke: Paint::Solid([0.5, 0.2, 0.15, 0.8]),
                         stroke_width: 2.0,
+                        parent_id: None,
                     },
                     AnimObject {
                         id: lighthouse_beam_id,
@@ -552,6 +567,7 @@ pub fn generate_showcase_animation() -> Project {
                         fill: Paint::Solid([1.0, 0.95, 0.6, light_alpha * 0.85]),
                         stroke: Paint::Solid([0.0; 4]),
                         stroke_width: 0.0,
+                        parent_id: None,
                     },
                     AnimObject {
                         id: lighthouse_lantern_id,
@@ -566,6 +582,7 @@ pub fn generate_showcase_animation() -> Project {
                         fill: Paint::Solid([0.2, 0.18, 0.16, 1.0]),
                         stroke: Paint::Solid([0.12, 0.1, 0.08, 0.9]),
                         stroke_width: 1.5,
+                        parent_id: None,
                     },
                     AnimObject {
                         id: lighthouse_light_id,
@@ -579,6 +596,7 @@ pub fn generate_showcase_animation() -> Project {
                         fill: Paint::Solid([1.0, 0.95, 0.5, light_alpha]),
                         stroke: Paint::Solid([1.0, 0.9, 0.3, light_alpha * 0.6]),
                         stroke_width: 3.0,
+                        parent_id: None,
                     },
                 ],
                 tween,
@@ -596,18 +614,21 @@ pub fn generate_showcase_animation() -> Project {
                 control_in: Some([15.0, 32.0]),
                 control_out: Some([2.0, 15.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [0.0, -40.0],
                 control_in: Some([-2.0, -20.0]),
                 control_out: Some([25.0, -35.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [45.0, 10.0],
                 control_in: Some([40.0, -15.0]),
                 control_out: Some([35.0, 25.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
         ],
         closed: true,
@@ -620,18 +641,21 @@ pub fn generate_showcase_animation() -> Project {
                 control_in: Some([-40.0, -3.0]),
                 control_out: Some([-30.0, 10.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [5.0, 15.0],
                 control_in: Some([-15.0, 14.0]),
                 control_out: Some([25.0, 14.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [45.0, 0.0],
                 control_in: Some([40.0, 8.0]),
                 control_out: Some([42.0, -3.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
         ],
         closed: true,
@@ -675,6 +699,7 @@ pub fn generate_showcase_animation() -> Project {
                         fill: Paint::Solid(hull_fill),
                         stroke: Paint::Solid(hull_stroke),
                         stroke_width: 1.5,
+                        parent_id: None,
                     },
                     AnimObject {
                         id: sailboat_mast_id,
@@ -689,6 +714,7 @@ pub fn generate_showcase_animation() -> Project {
                         fill: Paint::Solid(mast_fill),
                         stroke: Paint::Solid([0.0; 4]),
                         stroke_width: 0.0,
+                        parent_id: None,
                     },
                     AnimObject {
                         id: sailboat_id,
@@ -699,6 +725,7 @@ pub fn generate_showcase_animation() -> Project {
                         fill: Paint::Solid(sail_fill),
                         stroke: Paint::Solid(sail_stroke),
                         stroke_width: 1.5,
+                        parent_id: None,
                     },
                 ],
                 tween,
@@ -716,60 +743,70 @@ pub fn generate_showcase_animation() -> Project {
                 control_in: None,
                 control_out: Some([-100.0, -18.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [40.0, 0.0],
                 control_in: Some([-60.0, 18.0]),
                 control_out: Some([140.0, -18.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [280.0, 0.0],
                 control_in: Some([180.0, 18.0]),
                 control_out: Some([380.0, -18.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [520.0, 0.0],
                 control_in: Some([420.0, 18.0]),
                 control_out: Some([620.0, -18.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [760.0, 0.0],
                 control_in: Some([660.0, 18.0]),
                 control_out: Some([860.0, -18.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [1000.0, 0.0],
                 control_in: Some([900.0, 18.0]),
                 control_out: Some([1100.0, -18.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [1240.0, 0.0],
                 control_in: Some([1140.0, 18.0]),
                 control_out: Some([1340.0, -18.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [1480.0, 0.0],
                 control_in: Some([1380.0, 18.0]),
                 control_out: Some([1580.0, -18.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [1720.0, 0.0],
                 control_in: Some([1620.0, 18.0]),
                 control_out: Some([1820.0, -18.0]),
                 pressure: 1.0,
+                weights: Vec::new(),
             },
             PathPoint {
                 position: [2100.0, 0.0],
                 control_in: Some([1960.0, 18.0]),
                 control_out: None,
                 pressure: 1.0,
+                weights: Vec::new(),
             },
         ],
         closed: false,
@@ -889,6 +926,7 @@ pub fn generate_showcase_animation() -> Project {
                     fill: Paint::Solid(fill),
                     stroke: Paint::Solid(stroke),
                     stroke_width,
+                    parent_id: None,
                 })
                 .collect()
         };
@@ -949,10 +987,14 @@ pub fn generate_showcase_animation() -> Project {
             parent_id: None,
             collapsed: false,
             property_tracks: HashMap::new(),
+            motion_guide: None,
+            expressions: HashMap::new(),
+            transform_parent_id: None,
         }
     };
 
     Project {
+        format_version: FORMAT_VERSION,
         name: "Sunset Showcase".to_string(),
         canvas_width,
         canvas_height,
@@ -1015,6 +1057,7 @@ fn build_full_object_layer(
                     fill: Paint::Solid(spec.fill),
                     stroke: Paint::Solid(spec.stroke),
                     stroke_width: spec.stroke_width,
+                    parent_id: None,
                 }],
                 tween: spec.tween,
                 label: String::new(),
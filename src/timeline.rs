@@ -51,7 +51,7 @@ fn draw_layer_panel(app: &mut AnimateApp, ui: &mut egui::Ui) {
                 && app.project.layers.len() > 1
                 && app.active_layer < app.project.layers.len()
             {
-                app.history.push(app.project.clone());
+                app.history.push(app.project.clone(), "Delete Layer");
                 app.project.layers.remove(app.active_layer);
                 if app.active_layer >= app.project.layers.len() {
                     app.active_layer = app.project.layers.len() - 1;
@@ -282,11 +282,12 @@ fn draw_layer_panel(app: &mut AnimateApp, ui: &mut egui::Ui) {
                     app.active_layer = index;
                 }
                 if let Some(index) = visibility_toggle {
-                    app.history.push(app.project.clone());
+                    app.history
+                        .push(app.project.clone(), "Toggle Layer Visibility");
                     app.project.layers[index].visible = !app.project.layers[index].visible;
                 }
                 if let Some(index) = lock_toggle {
-                    app.history.push(app.project.clone());
+                    app.history.push(app.project.clone(), "Toggle Layer Lock");
                     app.project.layers[index].locked = !app.project.layers[index].locked;
                 }
                 if let Some(index) = collapse_toggle {
@@ -323,7 +324,7 @@ fn draw_layer_panel(app: &mut AnimateApp, ui: &mut egui::Ui) {
                         && source < app.project.layers.len()
                         && target < app.project.layers.len()
                     {
-                        app.history.push(app.project.clone());
+                        app.history.push(app.project.clone(), "Reorder Layers");
                         let layer = app.project.layers.remove(source);
                         let insert_at = if target > source { target } else { target + 1 };
                         let insert_at = insert_at.min(app.project.layers.len());
@@ -664,7 +665,7 @@ fn handle_grid_interaction(
                     .keyframes
                     .contains_key(&frame)
             {
-                app.history.push(app.project.clone());
+                app.history.push(app.project.clone(), "Move Keyframes");
                 app.timeline_drag = Some(crate::app::TimelineDragState {
                     dragging_layer: None,
                     drop_target: None,
@@ -681,7 +682,7 @@ fn handle_grid_interaction(
         }
 
         if pos.y < grid_y {
-            app.current_frame = frame;
+            set_playhead(app, frame);
         }
     }
 
@@ -699,7 +700,7 @@ fn handle_grid_interaction(
             return;
         }
 
-        app.current_frame = frame;
+        set_playhead(app, frame);
     }
 
     if response.drag_stopped() {
@@ -773,11 +774,20 @@ fn handle_grid_interaction(
 
             app.current_frame = frame;
         } else if pos.y >= header_y {
-            app.current_frame = frame;
+            set_playhead(app, frame);
         }
     }
 }
 
+fn set_playhead(app: &mut AnimateApp, frame: u32) {
+    if app.current_frame == frame {
+        return;
+    }
+    app.current_frame = frame;
+    #[cfg(not(target_arch = "wasm32"))]
+    crate::audio::scrub_audio(app);
+}
+
 fn draw_context_menu(
     app: &mut AnimateApp,
     response: &egui::Response,
@@ -821,7 +831,7 @@ fn draw_context_menu(
                             name.to_string()
                         };
                         if ui.button(label).clicked() {
-                            app.history.push(app.project.clone());
+                            app.history.push(app.project.clone(), "Set Tween");
                             if let Some(keyframe) =
                                 app.project.layers[layer_index].keyframes.get_mut(&frame)
                             {
@@ -862,7 +872,7 @@ fn draw_context_menu(
                         .unwrap_or(false);
                     let mut shape_tween = current_shape_tween;
                     if ui.checkbox(&mut shape_tween, "Shape Tween").changed() {
-                        app.history.push(app.project.clone());
+                        app.history.push(app.project.clone(), "Toggle Shape Tween");
                         if let Some(keyframe) =
                             app.project.layers[layer_index].keyframes.get_mut(&frame)
                         {
@@ -913,7 +923,7 @@ fn draw_context_menu(
                     && app.project.layers[layer_index].keyframes.len() > 1
                     && ui.button("Delete Keyframe").clicked()
                 {
-                    app.history.push(app.project.clone());
+                    app.history.push(app.project.clone(), "Delete Keyframe");
                     app.project.layers[layer_index].keyframes.remove(&frame);
                     ui.close();
                 }
@@ -949,13 +959,14 @@ fn draw_context_menu(
 
                 let mut toggle_visible = is_visible;
                 if ui.checkbox(&mut toggle_visible, "Visible").changed() {
-                    app.history.push(app.project.clone());
+                    app.history
+                        .push(app.project.clone(), "Toggle Layer Visibility");
                     app.project.layers[layer_index].visible = toggle_visible;
                 }
 
                 let mut toggle_locked = is_locked;
                 if ui.checkbox(&mut toggle_locked, "Locked").changed() {
-                    app.history.push(app.project.clone());
+                    app.history.push(app.project.clone(), "Toggle Layer Lock");
                     app.project.layers[layer_index].locked = toggle_locked;
                 }
             }
@@ -994,7 +1005,7 @@ pub fn handle_timeline_shortcuts(app: &mut AnimateApp, ui_context: &egui::Contex
 }
 
 fn delete_selected_frames(app: &mut AnimateApp) {
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Delete Frames");
     let selections = app.timeline_selection.selected_frames.clone();
     for (layer_index, frame) in selections {
         if layer_index < app.project.layers.len()
@@ -1101,7 +1112,7 @@ pub fn insert_keyframe(app: &mut AnimateApp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Insert Keyframe");
 
     let layer = &mut app.project.layers[app.active_layer];
     if !layer.keyframes.contains_key(&app.current_frame) {
@@ -1120,7 +1131,8 @@ pub fn insert_blank_keyframe(app: &mut AnimateApp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history
+        .push(app.project.clone(), "Insert Blank Keyframe");
 
     let layer = &mut app.project.layers[app.active_layer];
     layer
@@ -1138,7 +1150,7 @@ pub fn delete_keyframe(app: &mut AnimateApp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Delete Keyframe");
     app.project.layers[app.active_layer]
         .keyframes
         .remove(&app.current_frame);
@@ -196,6 +196,7 @@ fn handle_pen_tool(app: &mut AnimateApp, response: &egui::Response, ui_context:
                             .control_out
                             .map(|control| [control[0] - min_x, control[1] - min_y]),
                         pressure: point.pressure,
+                        weights: Vec::new(),
                     })
                     .collect();
 
@@ -230,6 +231,7 @@ fn handle_pen_tool(app: &mut AnimateApp, response: &egui::Response, ui_context:
                     control_in: None,
                     control_out: None,
                     pressure: 1.0,
+                    weights: Vec::new(),
                 };
                 app.tool_state = ToolState::PenDrawing {
                     points: vec![point],
@@ -266,6 +268,7 @@ fn handle_pen_tool(app: &mut AnimateApp, response: &egui::Response, ui_context:
                     }),
                     control_out: None,
                     pressure: 1.0,
+                    weights: Vec::new(),
                 };
                 points.push(point);
                 app.tool_state = ToolState::PenDrawing {
@@ -292,6 +295,7 @@ fn handle_pen_tool(app: &mut AnimateApp, response: &egui::Response, ui_context:
                     }),
                     control_out: None,
                     pressure: 1.0,
+                    weights: Vec::new(),
                 };
                 points.push(point);
                 app.tool_state = ToolState::PenDrawing {
@@ -391,6 +395,7 @@ fn handle_pencil_tool(app: &mut AnimateApp, response: &egui::Response, ui_contex
                         control_in: None,
                         control_out: None,
                         pressure: 1.0,
+                        weights: Vec::new(),
                     })
                     .collect();
 
@@ -423,7 +428,7 @@ fn handle_eraser_tool(app: &mut AnimateApp, response: &egui::Response, ui_contex
         app.tool_state = ToolState::Erasing {
             points: vec![[canvas_pos.x, canvas_pos.y]],
         };
-        app.history.push(app.project.clone());
+        app.history.push(app.project.clone(), "Erase");
     }
 
     if let ToolState::Erasing { ref mut points } = app.tool_state {
@@ -443,7 +448,9 @@ fn handle_eraser_tool(app: &mut AnimateApp, response: &egui::Response, ui_contex
                 if !layer.visible || layer.locked {
                     continue;
                 }
-                if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
+                if let Some(objects) =
+                    tween::resolve_world_frame(layer, app.current_frame, &app.project.layers)
+                {
                     for object in &objects {
                         if selection::point_in_object_public(
                             egui::pos2(canvas_pos.x, canvas_pos.y),
@@ -492,7 +499,7 @@ fn handle_paint_bucket_tool(app: &mut AnimateApp, response: &egui::Response) {
         let hit_id = selection::hit_test_public(app, canvas_pos);
 
         if let Some(object_id) = hit_id {
-            app.history.push(app.project.clone());
+            app.history.push(app.project.clone(), "Paint Bucket");
             let fill = app.fill_paint.clone();
             for layer in &mut app.project.layers {
                 let has_match = tween::resolve_frame(layer, app.current_frame)
@@ -552,6 +559,7 @@ fn handle_brush_tool(app: &mut AnimateApp, response: &egui::Response, ui_context
                 control_in: None,
                 control_out: None,
                 pressure: 1.0,
+                weights: Vec::new(),
             }],
         };
     }
@@ -573,6 +581,7 @@ fn handle_brush_tool(app: &mut AnimateApp, response: &egui::Response, ui_context
                         control_in: None,
                         control_out: None,
                         pressure,
+                        weights: Vec::new(),
                     });
                 }
             }
@@ -596,6 +605,7 @@ fn handle_brush_tool(app: &mut AnimateApp, response: &egui::Response, ui_context
                         control_in: None,
                         control_out: None,
                         pressure: point.pressure,
+                        weights: Vec::new(),
                     })
                     .collect();
 
@@ -902,7 +912,7 @@ fn insert_object_at_current_frame(app: &mut AnimateApp, object: AnimObject) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Add Object");
 
     let layer = &mut app.project.layers[app.active_layer];
 
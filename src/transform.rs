@@ -2,6 +2,9 @@ use nightshade::prelude::*;
 
 use crate::app::AnimateApp;
 use crate::canvas::CanvasView;
+use crate::group;
+use crate::hierarchy;
+use crate::project::AnimObject;
 use crate::selection;
 use crate::tween;
 
@@ -25,6 +28,7 @@ pub struct TransformState {
     pub initial_positions: Vec<(uuid::Uuid, [f32; 2])>,
     pub initial_scales: Vec<(uuid::Uuid, [f32; 2])>,
     pub initial_rotations: Vec<(uuid::Uuid, f32)>,
+    pub initial_parents: Vec<(uuid::Uuid, AnimObject)>,
     pub bbox_center: [f32; 2],
     pub bbox_half: [f32; 2],
 }
@@ -37,6 +41,7 @@ impl Default for TransformState {
             initial_positions: Vec::new(),
             initial_scales: Vec::new(),
             initial_rotations: Vec::new(),
+            initial_parents: Vec::new(),
             bbox_center: [0.0, 0.0],
             bbox_half: [0.0, 0.0],
         }
@@ -108,20 +113,39 @@ pub fn handle_transform_interaction(
             let mut initial_positions = Vec::new();
             let mut initial_scales = Vec::new();
             let mut initial_rotations = Vec::new();
-
-            for layer in &app.project.layers {
-                if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
-                    for object in &objects {
-                        if selected.contains(&object.id) {
-                            initial_positions.push((object.id, object.position));
-                            initial_scales.push((object.id, object.scale));
-                            initial_rotations.push((object.id, object.rotation));
-                        }
+            let mut initial_parents = Vec::new();
+
+            let entered = app.selection.entered_group;
+            let layers = &app.project.layers;
+            for layer in layers {
+                let Some(objects) = tween::resolve_frame(layer, app.current_frame) else {
+                    continue;
+                };
+                for object in &group::container_objects(objects, entered) {
+                    if !selected.contains(&object.id)
+                        || hierarchy::has_selected_ancestor(
+                            object.id,
+                            &selected,
+                            layers,
+                            app.current_frame,
+                        )
+                    {
+                        continue;
                     }
+                    let mut world = object.clone();
+                    if let Some(parent) =
+                        group::editing_parent(layer, object, layers, app.current_frame, entered)
+                    {
+                        world = crate::library::compose_instance_transform(&parent, object);
+                        initial_parents.push((object.id, parent));
+                    }
+                    initial_positions.push((object.id, world.position));
+                    initial_scales.push((object.id, world.scale));
+                    initial_rotations.push((object.id, world.rotation));
                 }
             }
 
-            app.history.push(app.project.clone());
+            app.history.push(app.project.clone(), "Transform");
             ensure_keyframes_for_selected(app);
 
             app.selection.transform_state = TransformState {
@@ -130,6 +154,7 @@ pub fn handle_transform_interaction(
                 initial_positions,
                 initial_scales,
                 initial_rotations,
+                initial_parents,
                 bbox_center: center,
                 bbox_half: [half_w, half_h],
             };
@@ -163,8 +188,10 @@ fn compute_selection_bbox(app: &AnimateApp) -> ([f32; 2], f32, f32) {
     let mut max_y = f32::NEG_INFINITY;
 
     for layer in &app.project.layers {
-        if let Some(objects) = tween::resolve_frame(layer, app.current_frame) {
-            for object in &objects {
+        if let Some(objects) =
+            tween::resolve_world_frame(layer, app.current_frame, &app.project.layers)
+        {
+            for object in &group::editable_objects(objects, app.selection.entered_group) {
                 if selected.contains(&object.id) {
                     let (half_w, half_h, offset) = selection::get_object_bounds_public(object);
                     let cx = object.position[0] + offset[0];
@@ -255,9 +282,14 @@ fn find_hit_handle(
 
 fn ensure_keyframes_for_selected(app: &mut AnimateApp) {
     let selected = app.selection.selected_objects.clone();
+    let entered = app.selection.entered_group;
     for layer in &mut app.project.layers {
         let has_match = tween::resolve_frame(layer, app.current_frame)
-            .map(|objects| objects.iter().any(|object| selected.contains(&object.id)))
+            .map(|objects| {
+                group::container_objects(objects, entered)
+                    .iter()
+                    .any(|object| selected.contains(&object.id))
+            })
             .unwrap_or(false);
 
         if has_match {
@@ -274,6 +306,7 @@ fn apply_transform(app: &mut AnimateApp, current_mouse: egui::Pos2, shift_held:
     };
 
     let view = app.canvas_view.clone();
+    let entered = app.selection.entered_group;
 
     match handle {
         TransformHandle::Rotation => {
@@ -295,14 +328,23 @@ fn apply_transform(app: &mut AnimateApp, current_mouse: egui::Pos2, shift_held:
             };
 
             for layer in &mut app.project.layers {
-                if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame) {
-                    for object in &mut keyframe.objects {
+                if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame)
+                    && let Some(container) = group::container_mut(&mut keyframe.objects, entered)
+                {
+                    for object in container {
                         if let Some((_, initial_rotation)) = transform
                             .initial_rotations
                             .iter()
                             .find(|(id, _)| *id == object.id)
                         {
                             object.rotation = initial_rotation + snapped_angle;
+                            if let Some((_, initial_scale)) = transform
+                                .initial_scales
+                                .iter()
+                                .find(|(id, _)| *id == object.id)
+                            {
+                                object.scale = *initial_scale;
+                            }
 
                             if let Some((_, initial_pos)) = transform
                                 .initial_positions
@@ -318,6 +360,7 @@ fn apply_transform(app: &mut AnimateApp, current_mouse: egui::Pos2, shift_held:
                                 object.position[1] =
                                     transform.bbox_center[1] + dx * sin_a + dy * cos_a;
                             }
+                            localize(object, &transform.initial_parents);
                         }
                     }
                 }
@@ -340,8 +383,10 @@ fn apply_transform(app: &mut AnimateApp, current_mouse: egui::Pos2, shift_held:
             );
 
             for layer in &mut app.project.layers {
-                if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame) {
-                    for object in &mut keyframe.objects {
+                if let Some(keyframe) = layer.keyframes.get_mut(&app.current_frame)
+                    && let Some(container) = group::container_mut(&mut keyframe.objects, entered)
+                {
+                    for object in container {
                         if let Some((_, initial_scale)) = transform
                             .initial_scales
                             .iter()
@@ -349,6 +394,13 @@ fn apply_transform(app: &mut AnimateApp, current_mouse: egui::Pos2, shift_held:
                         {
                             object.scale[0] = (initial_scale[0] * scale_x).max(0.01);
                             object.scale[1] = (initial_scale[1] * scale_y).max(0.01);
+                            if let Some((_, initial_rotation)) = transform
+                                .initial_rotations
+                                .iter()
+                                .find(|(id, _)| *id == object.id)
+                            {
+                                object.rotation = *initial_rotation;
+                            }
 
                             if let Some((_, initial_pos)) = transform
                                 .initial_positions
@@ -360,6 +412,7 @@ fn apply_transform(app: &mut AnimateApp, current_mouse: egui::Pos2, shift_held:
                                 object.position[0] = transform.bbox_center[0] + dx * scale_x;
                                 object.position[1] = transform.bbox_center[1] + dy * scale_y;
                             }
+                            localize(object, &transform.initial_parents);
                         }
                     }
                 }
@@ -368,6 +421,12 @@ fn apply_transform(app: &mut AnimateApp, current_mouse: egui::Pos2, shift_held:
     }
 }
 
+fn localize(object: &mut AnimObject, parents: &[(uuid::Uuid, AnimObject)]) {
+    if let Some((_, parent)) = parents.iter().find(|(id, _)| *id == object.id) {
+        *object = hierarchy::to_local(parent, object);
+    }
+}
+
 fn compute_scale_factors(
     handle: TransformHandle,
     delta_x: f32,
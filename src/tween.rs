@@ -2,40 +2,72 @@ use std::collections::BTreeMap;
 
 use crate::paint::{Paint, lerp_paint};
 use crate::project::{
-    AnimObject, Keyframe, Layer, PathPoint, PropertyKey, PropertyTracks, Shape, TweenType,
+    AnimObject, Armature, Bone, BonePose, Keyframe, Layer, PathPoint, PropertyChannel, PropertyKey,
+    PropertyModifier, PropertyTracks, Shape, TweenType,
 };
 
 pub fn resolve_frame(layer: &Layer, frame: u32) -> Option<Vec<AnimObject>> {
-    let prev_entry = layer.keyframes.range(..=frame).next_back();
-    let (prev_frame, prev_keyframe) = prev_entry?;
+    let objects = resolve_tracked_frame(layer, frame)?;
+    #[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+    let objects = crate::expressions::apply_expressions(objects, layer, frame);
+    Some(objects)
+}
 
-    let mut objects = if *prev_frame == frame || prev_keyframe.tween == TweenType::None {
-        prev_keyframe.objects.clone()
-    } else {
-        match layer.keyframes.range((frame + 1)..).next() {
-            Some((next_frame, next_keyframe)) => {
-                let raw_t = (frame - prev_frame) as f32 / (next_frame - prev_frame) as f32;
-                let t = apply_easing(raw_t, prev_keyframe.tween);
-                interpolate_objects(
-                    &prev_keyframe.objects,
-                    &next_keyframe.objects,
-                    t,
-                    prev_keyframe.shape_tween,
-                )
-            }
-            None => prev_keyframe.objects.clone(),
-        }
-    };
+pub fn resolve_tracked_frame(layer: &Layer, frame: u32) -> Option<Vec<AnimObject>> {
+    let mut objects = resolve_keyframe_objects(layer, frame)?;
 
     for object in &mut objects {
         if let Some(tracks) = layer.property_tracks.get(&object.id) {
-            apply_property_tracks(object, tracks, frame);
+            apply_property_tracks(object, layer, tracks, frame);
+        }
+    }
+
+    Some(objects)
+}
+
+fn resolve_keyframe_objects(layer: &Layer, frame: u32) -> Option<Vec<AnimObject>> {
+    let (prev_frame, prev_keyframe) = layer.keyframes.range(..=frame).next_back()?;
+
+    if *prev_frame == frame || prev_keyframe.tween == TweenType::None {
+        return Some(prev_keyframe.objects.clone());
+    }
+    match layer.keyframes.range((frame + 1)..).next() {
+        Some((next_frame, next_keyframe)) => {
+            let raw_t = (frame - prev_frame) as f32 / (next_frame - prev_frame) as f32;
+            let t = apply_easing(raw_t, prev_keyframe.tween);
+            Some(interpolate_objects(
+                &prev_keyframe.objects,
+                &next_keyframe.objects,
+                t,
+                prev_keyframe.shape_tween,
+            ))
         }
+        None => Some(prev_keyframe.objects.clone()),
     }
+}
 
+pub fn resolve_posed_frame(
+    layer: &Layer,
+    frame: u32,
+    layers: &[Layer],
+    armatures: &[Armature],
+) -> Option<Vec<AnimObject>> {
+    let mut objects = resolve_world_frame(layer, frame, layers)?;
+    crate::armature::apply_bone_bindings(&mut objects, armatures, frame);
     Some(objects)
 }
 
+pub fn resolve_world_frame(layer: &Layer, frame: u32, layers: &[Layer]) -> Option<Vec<AnimObject>> {
+    let mut objects = resolve_frame(layer, frame)?;
+    crate::motion_guide::apply_motion_guide(&mut objects, layer, layers, frame);
+    crate::hierarchy::apply_parenting(&mut objects, layer, layers, frame);
+    Some(objects)
+}
+
+pub fn resolve_bone_pose(bone: &Bone, frame: u32) -> BonePose {
+    resolve_track_pose(&bone.pose_keys, frame).unwrap_or_default()
+}
+
 pub fn ensure_keyframe_at(layer: &mut Layer, frame: u32) {
     if layer.keyframes.contains_key(&frame) {
         return;
@@ -54,7 +86,7 @@ pub fn ensure_keyframe_at(layer: &mut Layer, frame: u32) {
     layer.keyframes.insert(frame, keyframe);
 }
 
-fn apply_easing(t: f32, tween: TweenType) -> f32 {
+pub fn apply_easing(t: f32, tween: TweenType) -> f32 {
     match tween {
         TweenType::None => t,
         TweenType::Linear => t,
@@ -117,10 +149,19 @@ fn interpolate_objects(
 }
 
 fn interpolate_object(from: &AnimObject, to: &AnimObject, t: f32, shape_tween: bool) -> AnimObject {
-    let shape = if shape_tween {
-        interpolate_shape(&from.shape, &to.shape, t)
-    } else {
-        from.shape.clone()
+    let shape = match (&from.shape, &to.shape) {
+        (
+            Shape::Group {
+                children: from_children,
+            },
+            Shape::Group {
+                children: to_children,
+            },
+        ) => Shape::Group {
+            children: interpolate_objects(from_children, to_children, t, shape_tween),
+        },
+        _ if shape_tween => interpolate_shape(&from.shape, &to.shape, t),
+        _ => from.shape.clone(),
     };
 
     AnimObject {
@@ -132,6 +173,7 @@ fn interpolate_object(from: &AnimObject, to: &AnimObject, t: f32, shape_tween: b
         fill: lerp_paint(&from.fill, &to.fill, t),
         stroke: lerp_paint(&from.stroke, &to.stroke, t),
         stroke_width: lerp_f32(from.stroke_width, to.stroke_width, t),
+        parent_id: from.parent_id,
     }
 }
 
@@ -244,6 +286,11 @@ fn interpolate_path_point(from: &PathPoint, to: &PathPoint, t: f32) -> PathPoint
         control_in: interpolate_optional_point(from.control_in, to.control_in, t),
         control_out: interpolate_optional_point(from.control_out, to.control_out, t),
         pressure: lerp_f32(from.pressure, to.pressure, t),
+        weights: if t < 0.5 {
+            from.weights.clone()
+        } else {
+            to.weights.clone()
+        },
     }
 }
 
@@ -328,24 +375,28 @@ fn shape_to_path(shape: &Shape) -> Shape {
                         control_in: None,
                         control_out: None,
                         pressure: 1.0,
+                        weights: Vec::new(),
                     },
                     PathPoint {
                         position: [half_w, -half_h],
                         control_in: None,
                         control_out: None,
                         pressure: 1.0,
+                        weights: Vec::new(),
                     },
                     PathPoint {
                         position: [half_w, half_h],
                         control_in: None,
                         control_out: None,
                         pressure: 1.0,
+                        weights: Vec::new(),
                     },
                     PathPoint {
                         position: [-half_w, half_h],
                         control_in: None,
                         control_out: None,
                         pressure: 1.0,
+                        weights: Vec::new(),
                     },
                 ],
                 closed: true,
@@ -362,6 +413,7 @@ fn shape_to_path(shape: &Shape) -> Shape {
                         control_in: None,
                         control_out: None,
                         pressure: 1.0,
+                        weights: Vec::new(),
                     }
                 })
                 .collect();
@@ -377,12 +429,14 @@ fn shape_to_path(shape: &Shape) -> Shape {
                     control_in: None,
                     control_out: None,
                     pressure: 1.0,
+                    weights: Vec::new(),
                 },
                 PathPoint {
                     position: [*end_x, *end_y],
                     control_in: None,
                     control_out: None,
                     pressure: 1.0,
+                    weights: Vec::new(),
                 },
             ],
             closed: false,
@@ -411,7 +465,107 @@ fn lerp_angle(a: f32, b: f32, t: f32) -> f32 {
     a + diff * t
 }
 
-fn apply_property_tracks(object: &mut AnimObject, tracks: &PropertyTracks, frame: u32) {
+fn apply_property_tracks(
+    object: &mut AnimObject,
+    layer: &Layer,
+    tracks: &PropertyTracks,
+    frame: u32,
+) {
+    apply_track_values(object, tracks, frame);
+    for (channel, modifiers) in &tracks.modifiers {
+        let value = modified_value(object, layer, tracks, *channel, modifiers, frame);
+        channel.set_value(object, value);
+    }
+}
+
+fn modified_value(
+    object: &AnimObject,
+    layer: &Layer,
+    tracks: &PropertyTracks,
+    channel: PropertyChannel,
+    modifiers: &[PropertyModifier],
+    frame: u32,
+) -> f32 {
+    let Some((modifier, below)) = modifiers.split_last() else {
+        return channel.value(object);
+    };
+    match *modifier {
+        PropertyModifier::Step { hold } => {
+            let held_frame = frame - frame % hold.max(1);
+            let held_object = (held_frame != frame)
+                .then(|| resolve_keyframe_objects(layer, held_frame))
+                .flatten()
+                .and_then(|objects| {
+                    objects
+                        .into_iter()
+                        .find(|candidate| candidate.id == object.id)
+                });
+            match held_object {
+                Some(mut held_object) => {
+                    apply_track_values(&mut held_object, tracks, held_frame);
+                    modified_value(&held_object, layer, tracks, channel, below, held_frame)
+                }
+                None => modified_value(object, layer, tracks, channel, below, frame),
+            }
+        }
+        PropertyModifier::Wiggle {
+            seed,
+            amplitude,
+            period,
+            octaves,
+        } => {
+            let base = modified_value(object, layer, tracks, channel, below, frame);
+            base + amplitude * fractal_noise(seed, frame as f32 / period.max(1.0), octaves)
+        }
+        PropertyModifier::Oscillator {
+            amplitude,
+            period,
+            phase,
+        } => {
+            let base = modified_value(object, layer, tracks, channel, below, frame);
+            let angle = std::f32::consts::TAU * frame as f32 / period.max(1.0) + phase.to_radians();
+            base + amplitude * angle.sin()
+        }
+    }
+}
+
+fn fractal_noise(seed: u32, x: f32, octaves: u32) -> f32 {
+    let mut total = 0.0;
+    let mut range = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    for octave in 0..octaves.max(1) {
+        let octave_seed = seed.wrapping_add(octave.wrapping_mul(0x68e3_1da4));
+        let shift = lattice_gradient(octave_seed, i32::MIN) * 0.5 + 0.5;
+        total += perlin_noise(octave_seed, x * frequency + shift) * amplitude;
+        range += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    total / range
+}
+
+fn perlin_noise(seed: u32, x: f32) -> f32 {
+    let cell = x.floor();
+    let offset = x - cell;
+    let index = cell as i32;
+    let left = lattice_gradient(seed, index) * offset;
+    let right = lattice_gradient(seed, index.wrapping_add(1)) * (offset - 1.0);
+    let fade = offset * offset * offset * (offset * (offset * 6.0 - 15.0) + 10.0);
+    (left + (right - left) * fade) * 2.0
+}
+
+fn lattice_gradient(seed: u32, index: i32) -> f32 {
+    let mut hash = (index as u32).wrapping_mul(0x27d4_eb2d) ^ seed.wrapping_mul(0x9e37_79b9);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^= hash >> 16;
+    hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+fn apply_track_values(object: &mut AnimObject, tracks: &PropertyTracks, frame: u32) {
     if let Some(value) = resolve_track_arr2(&tracks.position, frame) {
         object.position = value;
     }
@@ -432,61 +586,136 @@ fn apply_property_tracks(object: &mut AnimObject, tracks: &PropertyTracks, frame
     }
 }
 
-fn resolve_track_f32(track: &BTreeMap<u32, PropertyKey<f32>>, frame: u32) -> Option<f32> {
-    if track.is_empty() {
-        return None;
+pub trait CurveValue: Copy {
+    const COMPONENTS: usize;
+
+    fn component(&self, index: usize) -> f32;
+
+    fn set_component(&mut self, index: usize, value: f32);
+}
+
+impl CurveValue for f32 {
+    const COMPONENTS: usize = 1;
+
+    fn component(&self, _index: usize) -> f32 {
+        *self
     }
-    let (prev_frame, prev_key) = track.range(..=frame).next_back()?;
-    if *prev_frame == frame || prev_key.tween == TweenType::None {
-        return Some(prev_key.value);
+
+    fn set_component(&mut self, _index: usize, value: f32) {
+        *self = value;
     }
-    let (next_frame, next_key) = track.range((frame + 1)..).next()?;
-    let raw_t = (frame - prev_frame) as f32 / (next_frame - prev_frame) as f32;
-    let t = apply_easing(raw_t, prev_key.tween);
-    Some(lerp_f32(prev_key.value, next_key.value, t))
 }
 
-fn resolve_track_angle(track: &BTreeMap<u32, PropertyKey<f32>>, frame: u32) -> Option<f32> {
-    if track.is_empty() {
+impl CurveValue for [f32; 2] {
+    const COMPONENTS: usize = 2;
+
+    fn component(&self, index: usize) -> f32 {
+        self[index]
+    }
+
+    fn set_component(&mut self, index: usize, value: f32) {
+        self[index] = value;
+    }
+}
+
+pub fn sample_track<T: CurveValue>(
+    track: &BTreeMap<u32, PropertyKey<T>>,
+    frame: f32,
+    angular: bool,
+) -> Option<T> {
+    if frame < 0.0 {
         return None;
     }
-    let (prev_frame, prev_key) = track.range(..=frame).next_back()?;
-    if *prev_frame == frame || prev_key.tween == TweenType::None {
+    let (prev_frame, prev_key) = track.range(..=frame.floor() as u32).next_back()?;
+    if *prev_frame as f32 == frame || prev_key.tween == TweenType::None {
         return Some(prev_key.value);
     }
-    let (next_frame, next_key) = track.range((frame + 1)..).next()?;
-    let raw_t = (frame - prev_frame) as f32 / (next_frame - prev_frame) as f32;
-    let t = apply_easing(raw_t, prev_key.tween);
-    Some(lerp_angle(prev_key.value, next_key.value, t))
+    let (next_frame, next_key) = track.range((prev_frame + 1)..).next()?;
+    let duration = (next_frame - prev_frame) as f32;
+    let raw_t = (frame - *prev_frame as f32) / duration;
+    let has_tangents = prev_key.tangents.is_some() || next_key.tangents.is_some();
+
+    let mut value = prev_key.value;
+    for component in 0..T::COMPONENTS {
+        let from = prev_key.value.component(component);
+        let mut to = next_key.value.component(component);
+        if angular {
+            to = lerp_angle(from, to, 1.0);
+        }
+        let sampled = if has_tangents {
+            let linear_slope = (to - from) / duration;
+            let out_slope = prev_key
+                .tangents
+                .map_or(linear_slope, |tangents| tangents.out_slope[component]);
+            let in_slope = next_key
+                .tangents
+                .map_or(linear_slope, |tangents| tangents.in_slope[component]);
+            hermite(from, to, out_slope * duration, in_slope * duration, raw_t)
+        } else {
+            lerp_f32(from, to, apply_easing(raw_t, prev_key.tween))
+        };
+        value.set_component(component, sampled);
+    }
+    Some(value)
+}
+
+fn hermite(from: f32, to: f32, out_tangent: f32, in_tangent: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (2.0 * t3 - 3.0 * t2 + 1.0) * from
+        + (t3 - 2.0 * t2 + t) * out_tangent
+        + (-2.0 * t3 + 3.0 * t2) * to
+        + (t3 - t2) * in_tangent
+}
+
+fn resolve_track_f32(track: &BTreeMap<u32, PropertyKey<f32>>, frame: u32) -> Option<f32> {
+    sample_track(track, frame as f32, false)
+}
+
+fn resolve_track_angle(track: &BTreeMap<u32, PropertyKey<f32>>, frame: u32) -> Option<f32> {
+    sample_track(track, frame as f32, true)
 }
 
 fn resolve_track_arr2(
     track: &BTreeMap<u32, PropertyKey<[f32; 2]>>,
     frame: u32,
 ) -> Option<[f32; 2]> {
+    sample_track(track, frame as f32, false)
+}
+
+fn resolve_track_paint(track: &BTreeMap<u32, PropertyKey<Paint>>, frame: u32) -> Option<Paint> {
     if track.is_empty() {
         return None;
     }
     let (prev_frame, prev_key) = track.range(..=frame).next_back()?;
     if *prev_frame == frame || prev_key.tween == TweenType::None {
-        return Some(prev_key.value);
+        return Some(prev_key.value.clone());
     }
     let (next_frame, next_key) = track.range((frame + 1)..).next()?;
     let raw_t = (frame - prev_frame) as f32 / (next_frame - prev_frame) as f32;
     let t = apply_easing(raw_t, prev_key.tween);
-    Some(lerp_arr2(prev_key.value, next_key.value, t))
+    Some(lerp_paint(&prev_key.value, &next_key.value, t))
 }
 
-fn resolve_track_paint(track: &BTreeMap<u32, PropertyKey<Paint>>, frame: u32) -> Option<Paint> {
+fn resolve_track_pose(
+    track: &BTreeMap<u32, PropertyKey<BonePose>>,
+    frame: u32,
+) -> Option<BonePose> {
     if track.is_empty() {
         return None;
     }
     let (prev_frame, prev_key) = track.range(..=frame).next_back()?;
     if *prev_frame == frame || prev_key.tween == TweenType::None {
-        return Some(prev_key.value.clone());
+        return Some(prev_key.value);
     }
-    let (next_frame, next_key) = track.range((frame + 1)..).next()?;
+    let Some((next_frame, next_key)) = track.range((frame + 1)..).next() else {
+        return Some(prev_key.value);
+    };
     let raw_t = (frame - prev_frame) as f32 / (next_frame - prev_frame) as f32;
     let t = apply_easing(raw_t, prev_key.tween);
-    Some(lerp_paint(&prev_key.value, &next_key.value, t))
+    Some(BonePose {
+        translation: lerp_arr2(prev_key.value.translation, next_key.value.translation, t),
+        rotation: lerp_angle(prev_key.value.rotation, next_key.value.rotation, t),
+        scale: lerp_arr2(prev_key.value.scale, next_key.value.scale, t),
+    })
 }
@@ -7,7 +7,7 @@ pub fn bring_to_front(app: &mut AnimateApp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Bring to Front");
 
     for layer in &mut app.project.layers {
         let has_match = tween::resolve_frame(layer, app.current_frame)
@@ -40,7 +40,7 @@ pub fn send_to_back(app: &mut AnimateApp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Send to Back");
 
     for layer in &mut app.project.layers {
         let has_match = tween::resolve_frame(layer, app.current_frame)
@@ -73,7 +73,7 @@ pub fn bring_forward(app: &mut AnimateApp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Bring Forward");
 
     for layer in &mut app.project.layers {
         let has_match = tween::resolve_frame(layer, app.current_frame)
@@ -109,7 +109,7 @@ pub fn send_backward(app: &mut AnimateApp) {
         return;
     }
 
-    app.history.push(app.project.clone());
+    app.history.push(app.project.clone(), "Send Backward");
 
     for layer in &mut app.project.layers {
         let has_match = tween::resolve_frame(layer, app.current_frame)